@@ -0,0 +1,370 @@
+use chrono::{DateTime, Duration, Utc};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+
+use crate::features::users::domain::UserIdentity;
+
+/// Default seconds between reminders to acknowledge an announcement
+pub const DEFAULT_REMINDER_INTERVAL_SECS: i64 = 3600;
+
+/// Reminders sent to an identity that does not acknowledge an announcement
+pub const MAX_REMINDER_LEVEL: u32 = 3;
+
+/// Announcement banner domain model
+///
+/// A time-bound banner shown to clients between `starts_at` and `ends_at`.
+/// An announcement without a hospital or department code targets everyone.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct Announcement {
+    pub id: u64,
+    pub title: String,
+    pub message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hospital_code: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub department_code: Option<String>,
+    pub starts_at: DateTime<Utc>,
+    pub ends_at: DateTime<Utc>,
+    /// Recipients must confirm they have read the announcement
+    #[serde(default)]
+    pub requires_acknowledgement: bool,
+    pub created_by: String,
+    pub created_at: DateTime<Utc>,
+}
+
+impl Announcement {
+    /// Check if the announcement is visible at the given time
+    pub fn is_active_at(&self, now: DateTime<Utc>) -> bool {
+        self.starts_at <= now && now < self.ends_at
+    }
+
+    /// Check if the announcement targets the given identity
+    ///
+    /// Untargeted announcements match every client. Targeted announcements
+    /// only match anonymous identities whose hospital (and department, if
+    /// set) codes are equal; verified users carry no hospital information.
+    pub fn targets(&self, identity: Option<&UserIdentity>) -> bool {
+        if self.hospital_code.is_none() && self.department_code.is_none() {
+            return true;
+        }
+
+        let Some(identifier) = identity.and_then(|identity| identity.as_anonymous()) else {
+            return false;
+        };
+
+        let hospital_matches = self
+            .hospital_code
+            .as_ref()
+            .is_none_or(|code| *code == identifier.hospital_code);
+        let department_matches = self
+            .department_code
+            .as_ref()
+            .is_none_or(|code| *code == identifier.department_code);
+
+        hospital_matches && department_matches
+    }
+}
+
+/// Request payload for publishing an announcement
+#[derive(Debug, Deserialize)]
+pub struct CreateAnnouncementRequest {
+    pub title: String,
+    pub message: String,
+    pub hospital_code: Option<String>,
+    pub department_code: Option<String>,
+    pub starts_at: Option<DateTime<Utc>>,
+    pub ends_at: DateTime<Utc>,
+    #[serde(default)]
+    pub requires_acknowledgement: bool,
+}
+
+impl CreateAnnouncementRequest {
+    /// Validate announcement creation request
+    ///
+    /// Enforces business rules:
+    /// - Title and message must not be empty
+    /// - Target codes, when given, must not be empty
+    /// - A department target requires a hospital target
+    /// - The announcement must end after `now` and after it starts, which
+    ///   defaults to `now`
+    pub fn validate(&self, now: DateTime<Utc>) -> Result<(), String> {
+        if self.title.trim().is_empty() {
+            return Err("Title cannot be empty".to_string());
+        }
+        if self.message.trim().is_empty() {
+            return Err("Message cannot be empty".to_string());
+        }
+        if matches!(&self.hospital_code, Some(code) if code.is_empty()) {
+            return Err("Hospital code cannot be empty".to_string());
+        }
+        if matches!(&self.department_code, Some(code) if code.is_empty()) {
+            return Err("Department code cannot be empty".to_string());
+        }
+        if self.department_code.is_some() && self.hospital_code.is_none() {
+            return Err("Department code requires a hospital code".to_string());
+        }
+        if self.ends_at <= now {
+            return Err("Announcement has already ended".to_string());
+        }
+        if self.ends_at <= self.starts_at.unwrap_or(now) {
+            return Err("Announcement must end after it starts".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// Acknowledgements of an announcement that requires them
+///
+/// Tracks who has been shown the announcement, who acknowledged it and the
+/// reminders sent to the others, keyed by stored actor ids so raw composite
+/// keys are never kept.
+#[derive(Debug, Clone, Default)]
+pub struct AcknowledgementTracker {
+    seen: HashMap<String, Reminders>,
+    acknowledged: BTreeMap<String, DateTime<Utc>>,
+}
+
+/// Reminders sent to an identity that was shown an announcement
+#[derive(Debug, Clone)]
+struct Reminders {
+    /// Level of the last reminder, 0 before the first one
+    level: u32,
+    /// When the identity was first shown the announcement or last reminded
+    since: DateTime<Utc>,
+}
+
+impl AcknowledgementTracker {
+    /// Record that the announcement was shown to an identity
+    pub fn record_seen(&mut self, actor_id: &str, at: DateTime<Utc>) {
+        if !self.seen.contains_key(actor_id) {
+            self.seen.insert(
+                actor_id.to_string(),
+                Reminders {
+                    level: 0,
+                    since: at,
+                },
+            );
+        }
+    }
+
+    /// Record an acknowledgement; repeated acknowledgements keep the first time
+    pub fn acknowledge(&mut self, actor_id: &str, at: DateTime<Utc>) {
+        self.record_seen(actor_id, at);
+        self.acknowledged.entry(actor_id.to_string()).or_insert(at);
+    }
+
+    /// Identities due for a reminder at `now`, with the level of their reminder
+    ///
+    /// Identities that have not acknowledged are reminded `interval` after
+    /// they were shown the announcement, and again every `interval` at the
+    /// next level, up to `MAX_REMINDER_LEVEL`. Returned reminders are
+    /// recorded as sent.
+    pub fn due_reminders(&mut self, now: DateTime<Utc>, interval: Duration) -> Vec<(String, u32)> {
+        let mut due = Vec::new();
+        for (actor_id, reminders) in &mut self.seen {
+            if self.acknowledged.contains_key(actor_id)
+                || reminders.level >= MAX_REMINDER_LEVEL
+                || now < reminders.since + interval
+            {
+                continue;
+            }
+            reminders.level += 1;
+            reminders.since = now;
+            due.push((actor_id.clone(), reminders.level));
+        }
+        due
+    }
+
+    /// Check if an identity has acknowledged the announcement
+    pub fn has_acknowledged(&self, actor_id: &str) -> bool {
+        self.acknowledged.contains_key(actor_id)
+    }
+
+    /// Completion statistics for the author
+    pub fn stats(&self, announcement_id: u64) -> AcknowledgementStats {
+        let seen = self.seen.len();
+        let acknowledged = self.acknowledged.len();
+
+        AcknowledgementStats {
+            announcement_id,
+            seen,
+            acknowledged,
+            pending: seen - acknowledged,
+            completion_rate: if seen == 0 {
+                0.0
+            } else {
+                acknowledged as f64 / seen as f64
+            },
+            acknowledgements: self
+                .acknowledged
+                .iter()
+                .map(|(actor_id, acknowledged_at)| Acknowledgement {
+                    actor_id: actor_id.clone(),
+                    acknowledged_at: *acknowledged_at,
+                })
+                .collect(),
+        }
+    }
+}
+
+/// A single acknowledgement of an announcement
+#[derive(Debug, Clone, Serialize)]
+pub struct Acknowledgement {
+    pub actor_id: String,
+    pub acknowledged_at: DateTime<Utc>,
+}
+
+/// Acknowledgement completion of an announcement
+///
+/// `seen` counts identities the announcement was shown to; those that have
+/// not acknowledged it yet are `pending`.
+#[derive(Debug, Clone, Serialize)]
+pub struct AcknowledgementStats {
+    pub announcement_id: u64,
+    pub seen: usize,
+    pub acknowledged: usize,
+    pub pending: usize,
+    pub completion_rate: f64,
+    pub acknowledgements: Vec<Acknowledgement>,
+}
+
+/// Data of `announcements.reminder`
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct AcknowledgementReminder {
+    /// 1 for the first reminder, up to `MAX_REMINDER_LEVEL` for the last
+    pub level: u32,
+    pub announcement: Announcement,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::features::users::domain::{AnonymousUserIdentifier, Role, VerifiedUser};
+    use chrono::NaiveDate;
+
+    fn announcement(hospital: Option<&str>, department: Option<&str>) -> Announcement {
+        let now = Utc::now();
+        Announcement {
+            id: 1,
+            title: "Maintenance".to_string(),
+            message: "EMR downtime tonight".to_string(),
+            hospital_code: hospital.map(str::to_string),
+            department_code: department.map(str::to_string),
+            starts_at: now - Duration::hours(1),
+            ends_at: now + Duration::hours(1),
+            requires_acknowledgement: false,
+            created_by: "1".to_string(),
+            created_at: now,
+        }
+    }
+
+    fn anonymous(hospital: &str, department: &str) -> UserIdentity {
+        UserIdentity::Anonymous(AnonymousUserIdentifier {
+            hospital_code: hospital.to_string(),
+            user_id: "U123".to_string(),
+            user_start_date: NaiveDate::from_ymd_opt(2024, 1, 1).unwrap(),
+            department_code: department.to_string(),
+        })
+    }
+
+    #[test]
+    fn test_is_active_at() {
+        let item = announcement(None, None);
+        assert!(item.is_active_at(Utc::now()));
+        assert!(!item.is_active_at(item.ends_at));
+        assert!(!item.is_active_at(item.starts_at - Duration::seconds(1)));
+    }
+
+    #[test]
+    fn test_untargeted_matches_everyone() {
+        let item = announcement(None, None);
+        assert!(item.targets(None));
+        assert!(item.targets(Some(&anonymous("H001", "D001"))));
+    }
+
+    #[test]
+    fn test_targeted_matches_hospital_and_department() {
+        let hospital_wide = announcement(Some("H001"), None);
+        assert!(hospital_wide.targets(Some(&anonymous("H001", "D002"))));
+        assert!(!hospital_wide.targets(Some(&anonymous("H002", "D001"))));
+        assert!(!hospital_wide.targets(None));
+
+        let department_only = announcement(Some("H001"), Some("D001"));
+        assert!(department_only.targets(Some(&anonymous("H001", "D001"))));
+        assert!(!department_only.targets(Some(&anonymous("H001", "D002"))));
+
+        let verified = UserIdentity::Verified(VerifiedUser {
+            id: 1,
+            username: "john".to_string(),
+            email: "john@example.com".to_string(),
+            role: Role::Member,
+        });
+        assert!(!department_only.targets(Some(&verified)));
+    }
+
+    #[test]
+    fn test_create_request_validation() {
+        let now = Utc::now();
+        let valid = CreateAnnouncementRequest {
+            title: "Notice".to_string(),
+            message: "Hello".to_string(),
+            hospital_code: Some("H001".to_string()),
+            department_code: Some("D001".to_string()),
+            starts_at: Some(now),
+            ends_at: now + Duration::hours(1),
+            requires_acknowledgement: false,
+        };
+        assert!(valid.validate(now).is_ok());
+
+        let department_without_hospital = CreateAnnouncementRequest {
+            hospital_code: None,
+            ..valid
+        };
+        assert!(department_without_hospital.validate(now).is_err());
+
+        let ends_before_start = CreateAnnouncementRequest {
+            title: "Notice".to_string(),
+            message: "Hello".to_string(),
+            hospital_code: None,
+            department_code: None,
+            starts_at: Some(now + Duration::hours(2)),
+            ends_at: now + Duration::hours(1),
+            requires_acknowledgement: false,
+        };
+        assert_eq!(
+            ends_before_start.validate(now),
+            Err("Announcement must end after it starts".to_string())
+        );
+
+        // Starting now by default, so the end must not have passed
+        let ended = CreateAnnouncementRequest {
+            starts_at: None,
+            ends_at: now,
+            ..ends_before_start
+        };
+        assert_eq!(
+            ended.validate(now),
+            Err("Announcement has already ended".to_string())
+        );
+    }
+
+    #[test]
+    fn test_acknowledgement_tracker() {
+        let mut tracker = AcknowledgementTracker::default();
+        let first = Utc::now();
+        tracker.record_seen("user:1", first);
+        tracker.record_seen("user:2", first);
+        tracker.record_seen("user:2", first);
+
+        tracker.acknowledge("user:1", first);
+        tracker.acknowledge("user:1", first + Duration::minutes(5));
+
+        let stats = tracker.stats(7);
+        assert_eq!(stats.seen, 2);
+        assert_eq!(stats.acknowledged, 1);
+        assert_eq!(stats.pending, 1);
+        assert!((stats.completion_rate - 0.5).abs() < f64::EPSILON);
+        assert_eq!(stats.acknowledgements[0].acknowledged_at, first);
+    }
+}
@@ -0,0 +1,152 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Json,
+};
+
+use crate::features::auth::AuthenticatedUser;
+use crate::infrastructure::{AppError, AuditContext, JsonBody};
+
+use super::domain::{AcknowledgementStats, Announcement, CreateAnnouncementRequest};
+use super::service::AnnouncementService;
+
+/// List active announcements handler
+///
+/// Returns the announcements currently visible to the caller. Anonymous
+/// callers see banners targeted at their hospital/department; callers
+/// without a token only see untargeted banners.
+///
+/// # Route
+/// GET /api/v1/announcements/active
+///
+/// # Response
+/// ```json
+/// [
+///   {
+///     "id": 1,
+///     "title": "EMR maintenance",
+///     "message": "The EMR will be unavailable from 22:00 to 23:00",
+///     "hospital_code": "H001",
+///     "starts_at": "2024-01-01T00:00:00Z",
+///     "ends_at": "2024-01-02T00:00:00Z",
+///     "requires_acknowledgement": false,
+///     "created_by": "1",
+///     "created_at": "2024-01-01T00:00:00Z"
+///   }
+/// ]
+/// ```
+pub async fn list_active_announcements(
+    State(announcement_service): State<AnnouncementService>,
+    user: Option<AuthenticatedUser>,
+) -> Json<Vec<Announcement>> {
+    let identity = user.as_ref().map(|user| &user.0);
+    Json(announcement_service.list_active(identity).await)
+}
+
+/// Publish announcement handler
+///
+/// Requires the admin role.
+///
+/// # Route
+/// POST /api/v1/announcements
+///
+/// # Request Body
+/// ```json
+/// {
+///   "title": "EMR maintenance",
+///   "message": "The EMR will be unavailable from 22:00 to 23:00",
+///   "hospital_code": "H001",
+///   "department_code": "D001",
+///   "starts_at": "2024-01-01T00:00:00Z",
+///   "ends_at": "2024-01-02T00:00:00Z",
+///   "requires_acknowledgement": true
+/// }
+/// ```
+///
+/// # Response
+/// 201 Created with the published announcement
+pub async fn create_announcement(
+    State(announcement_service): State<AnnouncementService>,
+    user: AuthenticatedUser,
+    audit: AuditContext,
+    JsonBody(payload): JsonBody<CreateAnnouncementRequest>,
+) -> Result<(StatusCode, Json<Announcement>), AppError> {
+    let announcement = announcement_service
+        .create_announcement(&user.0, payload, &audit)
+        .await?;
+    Ok((StatusCode::CREATED, Json(announcement)))
+}
+
+/// Withdraw announcement handler
+///
+/// Requires the admin role.
+///
+/// # Route
+/// DELETE /api/v1/announcements/:id
+///
+/// # Response
+/// 204 No Content
+pub async fn delete_announcement(
+    State(announcement_service): State<AnnouncementService>,
+    user: AuthenticatedUser,
+    audit: AuditContext,
+    Path(id): Path<u64>,
+) -> Result<StatusCode, AppError> {
+    announcement_service
+        .delete_announcement(&user.0, id, &audit)
+        .await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Acknowledge announcement handler
+///
+/// Requires authentication. Only announcements that are active, target the
+/// caller and require acknowledgement can be acknowledged.
+///
+/// # Route
+/// POST /api/v1/announcements/:id/acknowledge
+///
+/// # Response
+/// 204 No Content
+pub async fn acknowledge_announcement(
+    State(announcement_service): State<AnnouncementService>,
+    user: AuthenticatedUser,
+    audit: AuditContext,
+    Path(id): Path<u64>,
+) -> Result<StatusCode, AppError> {
+    announcement_service
+        .acknowledge(&user.0, id, &audit)
+        .await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Acknowledgement stats handler
+///
+/// Requires authentication as the author of the announcement.
+///
+/// # Route
+/// GET /api/v1/announcements/:id/acknowledgements
+///
+/// # Response
+/// ```json
+/// {
+///   "announcement_id": 1,
+///   "seen": 40,
+///   "acknowledged": 30,
+///   "pending": 10,
+///   "completion_rate": 0.75,
+///   "acknowledgements": [
+///     { "actor_id": "anonymous:9f2c...", "acknowledged_at": "2024-01-01T09:30:00Z" }
+///   ]
+/// }
+/// ```
+pub async fn get_acknowledgement_stats(
+    State(announcement_service): State<AnnouncementService>,
+    user: AuthenticatedUser,
+    Path(id): Path<u64>,
+) -> Result<Json<AcknowledgementStats>, AppError> {
+    announcement_service
+        .acknowledgement_stats(&user.0, id)
+        .await
+        .map(Json)
+}
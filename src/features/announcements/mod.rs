@@ -0,0 +1,44 @@
+//! Announcements Feature Module
+//!
+//! Time-bound announcement banners targeted by hospital and department,
+//! optionally requiring recipients to acknowledge them.
+//!
+//! ## Architecture
+//!
+//! ### Domain Layer (`domain.rs`)
+//! - `Announcement`: Banner entity with visibility window and targeting
+//! - `CreateAnnouncementRequest`: Value object with validation
+//! - `AcknowledgementTracker`: Who has seen, acknowledged and been reminded of
+//!   an announcement
+//!
+//! ### Application Layer (`service.rs`)
+//! - `AnnouncementService`: Publishing, withdrawal, active lookup, acknowledgements
+//!   and escalating reminders
+//!
+//! ### Presentation Layer (`handler.rs`)
+//! - HTTP handlers for the announcement endpoints
+//!
+//! ## Usage
+//! ```rust,ignore
+//! use features::announcements;
+//!
+//! let announcement_service = announcements::AnnouncementService::new(audit_log.clone());
+//!
+//! Router::new()
+//!     .route("/announcements/active", get(announcements::list_active_announcements))
+//!     .with_state(announcement_service)
+//! ```
+
+pub mod domain;
+pub mod handler;
+pub mod service;
+
+// Re-export commonly used items
+pub use domain::{
+    AcknowledgementReminder, AcknowledgementStats, Announcement, CreateAnnouncementRequest,
+};
+pub use handler::{
+    acknowledge_announcement, create_announcement, delete_announcement, get_acknowledgement_stats,
+    list_active_announcements,
+};
+pub use service::AnnouncementService;
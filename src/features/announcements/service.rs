@@ -0,0 +1,756 @@
+use chrono::{DateTime, Duration, Utc};
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use tokio::sync::RwLock;
+
+use crate::features::jsonrpc::{JsonRpcService, ServerEvent};
+use crate::features::users::domain::{Role, UserIdentity};
+use crate::infrastructure::{audit::AuditActor, AppError, AuditContext, AuditLog, AuditOperation};
+
+use super::domain::{
+    AcknowledgementReminder, AcknowledgementStats, AcknowledgementTracker, Announcement,
+    CreateAnnouncementRequest, DEFAULT_REMINDER_INTERVAL_SECS, MAX_REMINDER_LEVEL,
+};
+
+/// Announcement service containing business logic
+///
+/// Application layer service that stores announcement banners, resolves
+/// which of them are visible to a given identity, tracks acknowledgements and
+/// reminds the identities that have not acknowledged.
+/// In a real application, this would interact with a database repository.
+#[derive(Clone)]
+pub struct AnnouncementService {
+    announcements: Arc<RwLock<HashMap<u64, Announcement>>>,
+    acknowledgements: Arc<RwLock<HashMap<u64, AcknowledgementTracker>>>,
+    next_id: Arc<AtomicU64>,
+    audit_log: AuditLog,
+    /// Pushes published and withdrawn banners and reminders to WebSocket
+    /// clients, if set
+    notifications: Option<JsonRpcService>,
+    /// Ids of the announcements pushed as published, once they started
+    published: Arc<RwLock<HashSet<u64>>>,
+    reminder_interval: Duration,
+}
+
+impl AnnouncementService {
+    /// Create a new announcement service recording writes into the given audit log
+    pub fn new(audit_log: AuditLog) -> Self {
+        Self {
+            announcements: Arc::new(RwLock::new(HashMap::new())),
+            acknowledgements: Arc::new(RwLock::new(HashMap::new())),
+            next_id: Arc::new(AtomicU64::new(1)),
+            audit_log,
+            notifications: None,
+            published: Arc::new(RwLock::new(HashSet::new())),
+            reminder_interval: Duration::seconds(DEFAULT_REMINDER_INTERVAL_SECS),
+        }
+    }
+
+    /// Push published and withdrawn announcements to the WebSocket
+    /// connections they target
+    pub fn with_notifications(mut self, jsonrpc_service: JsonRpcService) -> Self {
+        self.notifications = Some(jsonrpc_service);
+        self
+    }
+
+    /// Set the time between reminders to acknowledge an announcement
+    pub fn with_reminder_interval(mut self, reminder_interval: Duration) -> Self {
+        self.reminder_interval = reminder_interval;
+        self
+    }
+
+    /// Publish a new announcement
+    ///
+    /// # Business Logic
+    /// 1. Only admins may publish announcements
+    /// 2. Validate the request
+    /// 3. Default the start time to now
+    /// 4. Store and audit the announcement
+    /// 5. Push it to the connections it targets if it started, otherwise
+    ///    `publish_started` pushes it once it starts
+    pub async fn create_announcement(
+        &self,
+        author: &UserIdentity,
+        request: CreateAnnouncementRequest,
+        audit: &AuditContext,
+    ) -> Result<Announcement, AppError> {
+        let Some(author) = author
+            .as_verified()
+            .filter(|author| author.role == Role::Admin)
+        else {
+            return Err(AppError::Forbidden(
+                "Only admins can publish announcements".to_string(),
+            ));
+        };
+
+        let now = Utc::now();
+        request.validate(now).map_err(AppError::BadRequest)?;
+
+        let announcement = Announcement {
+            id: self.next_id.fetch_add(1, Ordering::SeqCst),
+            title: request.title,
+            message: request.message,
+            hospital_code: request.hospital_code,
+            department_code: request.department_code,
+            starts_at: request.starts_at.unwrap_or(now),
+            ends_at: request.ends_at,
+            requires_acknowledgement: request.requires_acknowledgement,
+            created_by: author.id.to_string(),
+            created_at: now,
+        };
+
+        self.announcements
+            .write()
+            .await
+            .insert(announcement.id, announcement.clone());
+
+        tracing::info!("Published announcement: {:?}", announcement);
+        self.audit_log
+            .record(
+                audit,
+                AuditOperation::Create,
+                "announcement",
+                announcement.id,
+                None,
+                serde_json::to_value(&announcement).ok(),
+            )
+            .await;
+        if announcement.is_active_at(now) {
+            self.publish(&announcement).await;
+        }
+
+        Ok(announcement)
+    }
+
+    /// Push the announcements that started since the last run
+    ///
+    /// Run periodically by the job scheduler, so announcements published
+    /// ahead of their start reach the connections they target once they
+    /// become active. Returns the number of announcements pushed.
+    pub async fn publish_started(&self, now: DateTime<Utc>) -> usize {
+        let started: Vec<Announcement> = self
+            .announcements
+            .read()
+            .await
+            .values()
+            .filter(|announcement| announcement.is_active_at(now))
+            .cloned()
+            .collect();
+
+        let mut published = 0;
+        for announcement in started {
+            if self.publish(&announcement).await {
+                published += 1;
+            }
+        }
+        published
+    }
+
+    /// List announcements currently active for the given identity
+    ///
+    /// Returns announcements ordered by start time, newest first. Announcements
+    /// requiring acknowledgement are recorded as seen by the identity.
+    pub async fn list_active(&self, identity: Option<&UserIdentity>) -> Vec<Announcement> {
+        let now = Utc::now();
+        let announcements = self.announcements.read().await;
+
+        let mut active: Vec<Announcement> = announcements
+            .values()
+            .filter(|announcement| announcement.is_active_at(now))
+            .filter(|announcement| announcement.targets(identity))
+            .cloned()
+            .collect();
+        active.sort_by(|a, b| b.starts_at.cmp(&a.starts_at).then(b.id.cmp(&a.id)));
+
+        if let Some(identity) = identity {
+            let actor_id = self.actor_id(identity);
+            let mut acknowledgements = self.acknowledgements.write().await;
+            for announcement in active.iter().filter(|a| a.requires_acknowledgement) {
+                acknowledgements
+                    .entry(announcement.id)
+                    .or_default()
+                    .record_seen(&actor_id, now);
+            }
+        }
+
+        active
+    }
+
+    /// Acknowledge an announcement on behalf of the given identity
+    ///
+    /// # Business Logic
+    /// 1. The announcement must be active and target the identity
+    /// 2. It must require acknowledgement
+    /// 3. Repeated acknowledgements keep the first time and are audited once
+    pub async fn acknowledge(
+        &self,
+        identity: &UserIdentity,
+        id: u64,
+        audit: &AuditContext,
+    ) -> Result<(), AppError> {
+        let now = Utc::now();
+        let announcement = self
+            .announcements
+            .read()
+            .await
+            .get(&id)
+            .filter(|announcement| announcement.is_active_at(now))
+            .filter(|announcement| announcement.targets(Some(identity)))
+            .cloned()
+            .ok_or_else(|| AppError::NotFound(format!("Announcement {} not found", id)))?;
+
+        if !announcement.requires_acknowledgement {
+            return Err(AppError::BadRequest(format!(
+                "Announcement {} does not require acknowledgement",
+                id
+            )));
+        }
+
+        let actor_id = self.actor_id(identity);
+        let mut acknowledgements = self.acknowledgements.write().await;
+        let tracker = acknowledgements.entry(id).or_default();
+        if tracker.has_acknowledged(&actor_id) {
+            return Ok(());
+        }
+        tracker.acknowledge(&actor_id, now);
+        drop(acknowledgements);
+
+        self.audit_log
+            .record(
+                audit,
+                AuditOperation::Create,
+                "announcement_acknowledgement",
+                id,
+                None,
+                None,
+            )
+            .await;
+
+        Ok(())
+    }
+
+    /// Acknowledgement completion of an announcement
+    ///
+    /// Only the author of the announcement may read it.
+    pub async fn acknowledgement_stats(
+        &self,
+        requester: &UserIdentity,
+        id: u64,
+    ) -> Result<AcknowledgementStats, AppError> {
+        let announcements = self.announcements.read().await;
+        let announcement = announcements
+            .get(&id)
+            .ok_or_else(|| AppError::NotFound(format!("Announcement {} not found", id)))?;
+
+        let is_author = requester
+            .as_verified()
+            .is_some_and(|user| user.id.to_string() == announcement.created_by);
+        if !is_author {
+            return Err(AppError::Forbidden(
+                "Only the author can read acknowledgements".to_string(),
+            ));
+        }
+        if !announcement.requires_acknowledgement {
+            return Err(AppError::BadRequest(format!(
+                "Announcement {} does not require acknowledgement",
+                id
+            )));
+        }
+
+        let acknowledgements = self.acknowledgements.read().await;
+        Ok(acknowledgements
+            .get(&id)
+            .map(|tracker| tracker.stats(id))
+            .unwrap_or_else(|| AcknowledgementTracker::default().stats(id)))
+    }
+
+    /// Remind the identities that have not acknowledged an active announcement
+    ///
+    /// Run periodically by the job scheduler. Each identity is reminded
+    /// `reminder_interval` after it was shown the announcement and again
+    /// every `reminder_interval` at the next level, up to
+    /// `MAX_REMINDER_LEVEL`. Reminders are pushed to the identity's open
+    /// connections; identities without one miss that reminder. Returns the
+    /// number of connections reminded.
+    pub async fn send_reminders(&self, now: DateTime<Utc>) -> usize {
+        let Some(jsonrpc_service) = &self.notifications else {
+            return 0;
+        };
+        let pending: Vec<Announcement> = self
+            .announcements
+            .read()
+            .await
+            .values()
+            .filter(|announcement| announcement.requires_acknowledgement)
+            .filter(|announcement| announcement.is_active_at(now))
+            .cloned()
+            .collect();
+
+        let mut reminded = 0;
+        for announcement in pending {
+            let due = match self
+                .acknowledgements
+                .write()
+                .await
+                .get_mut(&announcement.id)
+            {
+                Some(tracker) => tracker.due_reminders(now, self.reminder_interval),
+                None => continue,
+            };
+            for level in 1..=MAX_REMINDER_LEVEL {
+                let recipients: HashSet<&str> = due
+                    .iter()
+                    .filter(|(_, due_level)| *due_level == level)
+                    .map(|(actor_id, _)| actor_id.as_str())
+                    .collect();
+                if recipients.is_empty() {
+                    continue;
+                }
+                let event = ServerEvent::AcknowledgementReminder(AcknowledgementReminder {
+                    level,
+                    announcement: announcement.clone(),
+                });
+                reminded += jsonrpc_service
+                    .notify_identity(&event, |identity| {
+                        announcement.targets(Some(identity))
+                            && recipients.contains(self.actor_id(identity).as_str())
+                    })
+                    .await;
+            }
+        }
+        reminded
+    }
+
+    /// Id acknowledgements of an identity are stored under
+    fn actor_id(&self, identity: &UserIdentity) -> String {
+        self.audit_log.stored_actor_id(&AuditActor::from(identity))
+    }
+
+    /// Withdraw an announcement before it expires
+    ///
+    /// Only admins may withdraw announcements. The connections it targets
+    /// are told to remove the banner.
+    pub async fn delete_announcement(
+        &self,
+        author: &UserIdentity,
+        id: u64,
+        audit: &AuditContext,
+    ) -> Result<(), AppError> {
+        if !author.has_role(Role::Admin) {
+            return Err(AppError::Forbidden(
+                "Only admins can withdraw announcements".to_string(),
+            ));
+        }
+
+        let removed = self
+            .announcements
+            .write()
+            .await
+            .remove(&id)
+            .ok_or_else(|| AppError::NotFound(format!("Announcement {} not found", id)))?;
+        self.acknowledgements.write().await.remove(&id);
+
+        self.audit_log
+            .record(
+                audit,
+                AuditOperation::Delete,
+                "announcement",
+                id,
+                serde_json::to_value(&removed).ok(),
+                None,
+            )
+            .await;
+        // Connections never got banners that had not started yet
+        if self.published.write().await.remove(&id) {
+            self.notify(&removed, ServerEvent::AnnouncementWithdrawn)
+                .await;
+        }
+
+        Ok(())
+    }
+
+    /// Push an announcement as published unless it already was
+    ///
+    /// Returns whether it was pushed now.
+    async fn publish(&self, announcement: &Announcement) -> bool {
+        if !self.published.write().await.insert(announcement.id) {
+            return false;
+        }
+        self.notify(announcement, ServerEvent::AnnouncementPublished)
+            .await;
+        true
+    }
+
+    /// Push an event about `announcement` to the connections it targets,
+    /// whether or not they subscribed to it
+    async fn notify(
+        &self,
+        announcement: &Announcement,
+        event: impl FnOnce(Announcement) -> ServerEvent,
+    ) {
+        let Some(jsonrpc_service) = &self.notifications else {
+            return;
+        };
+
+        let event = event(announcement.clone());
+        if announcement.hospital_code.is_none() && announcement.department_code.is_none() {
+            jsonrpc_service.broadcast(&event).await;
+        } else {
+            jsonrpc_service
+                .notify_identity(&event, |identity| announcement.targets(Some(identity)))
+                .await;
+        }
+    }
+}
+
+impl Default for AnnouncementService {
+    fn default() -> Self {
+        Self::new(AuditLog::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::features::users::domain::{AnonymousUserIdentifier, Role, VerifiedUser};
+    use chrono::NaiveDate;
+
+    fn admin() -> UserIdentity {
+        UserIdentity::Verified(VerifiedUser {
+            id: 1,
+            username: "admin".to_string(),
+            email: "admin@example.com".to_string(),
+            role: Role::Admin,
+        })
+    }
+
+    fn member() -> UserIdentity {
+        UserIdentity::Verified(VerifiedUser {
+            id: 2,
+            username: "member".to_string(),
+            email: "member@example.com".to_string(),
+            role: Role::Member,
+        })
+    }
+
+    fn anonymous(hospital: &str) -> UserIdentity {
+        anonymous_in(hospital, "D001")
+    }
+
+    fn anonymous_in(hospital: &str, department: &str) -> UserIdentity {
+        UserIdentity::Anonymous(AnonymousUserIdentifier {
+            hospital_code: hospital.to_string(),
+            user_id: "U123".to_string(),
+            user_start_date: NaiveDate::from_ymd_opt(2024, 1, 1).unwrap(),
+            department_code: department.to_string(),
+        })
+    }
+
+    fn request(hospital: Option<&str>) -> CreateAnnouncementRequest {
+        CreateAnnouncementRequest {
+            title: "Notice".to_string(),
+            message: "Fire drill at 3pm".to_string(),
+            hospital_code: hospital.map(str::to_string),
+            department_code: None,
+            starts_at: None,
+            ends_at: Utc::now() + Duration::hours(1),
+            requires_acknowledgement: false,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_create_announcement() {
+        let service = AnnouncementService::default();
+        let result = service
+            .create_announcement(&admin(), request(None), &AuditContext::default())
+            .await;
+        assert!(result.is_ok());
+
+        let announcement = result.unwrap();
+        assert_eq!(announcement.title, "Notice");
+        assert_eq!(announcement.created_by, "1");
+    }
+
+    #[tokio::test]
+    async fn test_only_admins_publish_and_withdraw() {
+        let service = AnnouncementService::default();
+        for author in [anonymous("H001"), member()] {
+            let result = service
+                .create_announcement(&author, request(None), &AuditContext::default())
+                .await;
+            assert!(matches!(result, Err(AppError::Forbidden(_))));
+        }
+
+        let announcement = service
+            .create_announcement(&admin(), request(None), &AuditContext::default())
+            .await
+            .unwrap();
+        assert!(matches!(
+            service
+                .delete_announcement(&member(), announcement.id, &AuditContext::default())
+                .await,
+            Err(AppError::Forbidden(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_banners_are_pushed_to_targeted_connections() {
+        let jsonrpc_service = JsonRpcService::new();
+        let service = AnnouncementService::default().with_notifications(jsonrpc_service.clone());
+        let (_, mut ward) = jsonrpc_service
+            .connect(Some(anonymous_in("H001", "D001")))
+            .await;
+        let (_, mut other_ward) = jsonrpc_service
+            .connect(Some(anonymous_in("H001", "D002")))
+            .await;
+        let (_, mut other_hospital) = jsonrpc_service.connect(Some(anonymous("H002"))).await;
+
+        let announcement = service
+            .create_announcement(
+                &admin(),
+                CreateAnnouncementRequest {
+                    department_code: Some("D001".to_string()),
+                    ..request(Some("H001"))
+                },
+                &AuditContext::default(),
+            )
+            .await
+            .unwrap();
+        service
+            .delete_announcement(&admin(), announcement.id, &AuditContext::default())
+            .await
+            .unwrap();
+
+        for method in ["announcements.published", "announcements.withdrawn"] {
+            let message: serde_json::Value =
+                serde_json::from_str(&ward.recv().await.unwrap()).unwrap();
+            assert_eq!(message["method"], method);
+            assert_eq!(message["params"]["id"], announcement.id);
+        }
+        assert!(other_ward.try_recv().is_err());
+        assert!(other_hospital.try_recv().is_err());
+
+        // Untargeted banners reach every connection
+        service
+            .create_announcement(&admin(), request(None), &AuditContext::default())
+            .await
+            .unwrap();
+        for connection in [&mut ward, &mut other_ward, &mut other_hospital] {
+            let message: serde_json::Value =
+                serde_json::from_str(&connection.recv().await.unwrap()).unwrap();
+            assert_eq!(message["method"], "announcements.published");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_future_banners_are_pushed_once_they_start() {
+        let jsonrpc_service = JsonRpcService::new();
+        let service = AnnouncementService::default().with_notifications(jsonrpc_service.clone());
+        let (_, mut connection) = jsonrpc_service.connect(None).await;
+
+        let now = Utc::now();
+        let announcement = service
+            .create_announcement(
+                &admin(),
+                CreateAnnouncementRequest {
+                    starts_at: Some(now + Duration::hours(1)),
+                    ends_at: now + Duration::hours(2),
+                    ..request(None)
+                },
+                &AuditContext::default(),
+            )
+            .await
+            .unwrap();
+        assert!(connection.try_recv().is_err());
+
+        assert_eq!(service.publish_started(now).await, 0);
+        assert!(connection.try_recv().is_err());
+
+        let started = now + Duration::minutes(90);
+        assert_eq!(service.publish_started(started).await, 1);
+        let message: serde_json::Value =
+            serde_json::from_str(&connection.recv().await.unwrap()).unwrap();
+        assert_eq!(message["method"], "announcements.published");
+        assert_eq!(message["params"]["id"], announcement.id);
+
+        // Pushed only once
+        assert_eq!(service.publish_started(started).await, 0);
+        assert!(connection.try_recv().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_unstarted_banners_are_withdrawn_silently() {
+        let jsonrpc_service = JsonRpcService::new();
+        let service = AnnouncementService::default().with_notifications(jsonrpc_service.clone());
+        let (_, mut connection) = jsonrpc_service.connect(None).await;
+
+        let now = Utc::now();
+        let announcement = service
+            .create_announcement(
+                &admin(),
+                CreateAnnouncementRequest {
+                    starts_at: Some(now + Duration::hours(1)),
+                    ends_at: now + Duration::hours(2),
+                    ..request(None)
+                },
+                &AuditContext::default(),
+            )
+            .await
+            .unwrap();
+        service
+            .delete_announcement(&admin(), announcement.id, &AuditContext::default())
+            .await
+            .unwrap();
+
+        assert!(connection.try_recv().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_list_active_filters_by_target() {
+        let service = AnnouncementService::default();
+        service
+            .create_announcement(&admin(), request(None), &AuditContext::default())
+            .await
+            .unwrap();
+        service
+            .create_announcement(&admin(), request(Some("H001")), &AuditContext::default())
+            .await
+            .unwrap();
+
+        assert_eq!(service.list_active(None).await.len(), 1);
+        assert_eq!(service.list_active(Some(&anonymous("H001"))).await.len(), 2);
+        assert_eq!(service.list_active(Some(&anonymous("H002"))).await.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_delete_announcement() {
+        let service = AnnouncementService::default();
+        let announcement = service
+            .create_announcement(&admin(), request(None), &AuditContext::default())
+            .await
+            .unwrap();
+
+        assert!(service
+            .delete_announcement(&admin(), announcement.id, &AuditContext::default())
+            .await
+            .is_ok());
+        assert!(service.list_active(None).await.is_empty());
+        assert!(matches!(
+            service
+                .delete_announcement(&admin(), announcement.id, &AuditContext::default())
+                .await,
+            Err(AppError::NotFound(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_acknowledgements() {
+        let service = AnnouncementService::default();
+        let announcement = service
+            .create_announcement(
+                &admin(),
+                CreateAnnouncementRequest {
+                    requires_acknowledgement: true,
+                    ..request(Some("H001"))
+                },
+                &AuditContext::default(),
+            )
+            .await
+            .unwrap();
+        let nurse = anonymous("H001");
+
+        // Identities outside the target cannot acknowledge
+        assert!(matches!(
+            service
+                .acknowledge(
+                    &anonymous("H002"),
+                    announcement.id,
+                    &AuditContext::default()
+                )
+                .await,
+            Err(AppError::NotFound(_))
+        ));
+
+        service.list_active(Some(&nurse)).await;
+        let stats = service
+            .acknowledgement_stats(&admin(), announcement.id)
+            .await
+            .unwrap();
+        assert_eq!((stats.seen, stats.acknowledged, stats.pending), (1, 0, 1));
+
+        service
+            .acknowledge(&nurse, announcement.id, &AuditContext::default())
+            .await
+            .unwrap();
+        let stats = service
+            .acknowledgement_stats(&admin(), announcement.id)
+            .await
+            .unwrap();
+        assert_eq!((stats.seen, stats.acknowledged, stats.pending), (1, 1, 0));
+        // Raw composite keys are never stored
+        assert!(!stats.acknowledgements[0].actor_id.contains("U123"));
+
+        assert!(matches!(
+            service.acknowledgement_stats(&nurse, announcement.id).await,
+            Err(AppError::Forbidden(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_reminders_escalate_until_acknowledged() {
+        let jsonrpc_service = JsonRpcService::new();
+        let service = AnnouncementService::default()
+            .with_notifications(jsonrpc_service.clone())
+            .with_reminder_interval(Duration::minutes(30));
+        let nurse = anonymous_in("H001", "D001");
+        let colleague = anonymous_in("H001", "D002");
+        let (_, mut nurse_connection) = jsonrpc_service.connect(Some(nurse.clone())).await;
+        let (_, mut colleague_connection) = jsonrpc_service.connect(Some(colleague.clone())).await;
+
+        let announcement = service
+            .create_announcement(
+                &admin(),
+                CreateAnnouncementRequest {
+                    requires_acknowledgement: true,
+                    ends_at: Utc::now() + Duration::days(1),
+                    ..request(Some("H001"))
+                },
+                &AuditContext::default(),
+            )
+            .await
+            .unwrap();
+        service.list_active(Some(&nurse)).await;
+        service.list_active(Some(&colleague)).await;
+        let now = Utc::now();
+        for connection in [&mut nurse_connection, &mut colleague_connection] {
+            connection.recv().await.unwrap();
+        }
+
+        // Nobody is reminded before the interval has passed
+        assert_eq!(service.send_reminders(now).await, 0);
+
+        service
+            .acknowledge(&colleague, announcement.id, &AuditContext::default())
+            .await
+            .unwrap();
+        for (level, minutes) in [(1, 30), (2, 60), (3, 90)] {
+            let sent = service
+                .send_reminders(now + Duration::minutes(minutes))
+                .await;
+            assert_eq!(sent, 1);
+            let message: serde_json::Value =
+                serde_json::from_str(&nurse_connection.recv().await.unwrap()).unwrap();
+            assert_eq!(message["method"], "announcements.reminder");
+            assert_eq!(message["params"]["level"], level);
+            assert_eq!(message["params"]["announcement"]["id"], announcement.id);
+        }
+
+        // Reminders stop at the last level and never reach acknowledgers
+        assert_eq!(
+            service.send_reminders(now + Duration::minutes(120)).await,
+            0
+        );
+        assert!(colleague_connection.try_recv().is_err());
+    }
+}
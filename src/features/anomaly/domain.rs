@@ -0,0 +1,173 @@
+use chrono::{DateTime, Duration, Utc};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashSet, VecDeque};
+
+use crate::infrastructure::GeoLocation;
+
+/// Kind of suspicious pattern detected
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum AnomalyKind {
+    /// Many failed logins for the same username
+    FailedLogins,
+    /// A refresh token was presented after it had already been exchanged
+    TokenReuse,
+    /// The same identity is used from many different IP addresses
+    IpChurn,
+}
+
+/// Thresholds above which an anomaly is raised within the detection window
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AnomalyThresholds {
+    /// Failed logins per username
+    pub failed_logins: usize,
+    /// Reused refresh tokens per identity
+    pub token_reuse: usize,
+    /// Distinct client IP addresses per identity
+    pub distinct_ips: usize,
+}
+
+impl Default for AnomalyThresholds {
+    fn default() -> Self {
+        Self {
+            failed_logins: 5,
+            token_reuse: 1,
+            distinct_ips: 4,
+        }
+    }
+}
+
+impl AnomalyThresholds {
+    /// Threshold for an anomaly kind
+    pub fn threshold(&self, kind: AnomalyKind) -> usize {
+        match kind {
+            AnomalyKind::FailedLogins => self.failed_logins,
+            AnomalyKind::TokenReuse => self.token_reuse,
+            AnomalyKind::IpChurn => self.distinct_ips,
+        }
+    }
+
+    /// Validate thresholds
+    pub fn validate(&self) -> Result<(), String> {
+        if self.failed_logins == 0 || self.token_reuse == 0 || self.distinct_ips == 0 {
+            return Err("Thresholds must be at least 1".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// Alert raised for admins when a threshold is reached
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct AnomalyAlert {
+    pub id: u64,
+    pub kind: AnomalyKind,
+    /// `username:<name>` for failed logins, otherwise the stored actor id
+    /// of the identity, hashed like in the audit log
+    pub subject: String,
+    /// Hospital code of anonymous identities
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tenant: Option<String>,
+    /// Events counted within the window
+    pub count: usize,
+    pub threshold: usize,
+    /// Location of the client address that triggered the alert
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub location: Option<GeoLocation>,
+    pub detected_at: DateTime<Utc>,
+}
+
+/// Recent events of one kind for one subject
+///
+/// Each event carries a value; IP churn counts distinct values, other
+/// kinds count events.
+#[derive(Debug, Default)]
+pub struct EventWindow {
+    events: VecDeque<(DateTime<Utc>, String)>,
+    last_alert: Option<DateTime<Utc>>,
+}
+
+impl EventWindow {
+    /// Record an event and drop events older than `window`
+    pub fn record(&mut self, now: DateTime<Utc>, value: String, window: Duration) {
+        self.prune(now, window);
+        self.events.push_back((now, value));
+    }
+
+    /// Drop events older than `window`
+    pub fn prune(&mut self, now: DateTime<Utc>, window: Duration) {
+        while self
+            .events
+            .front()
+            .is_some_and(|(at, _)| *at <= now - window)
+        {
+            self.events.pop_front();
+        }
+    }
+
+    /// Number of events counted for `kind`
+    pub fn count(&self, kind: AnomalyKind) -> usize {
+        match kind {
+            AnomalyKind::IpChurn => self
+                .events
+                .iter()
+                .map(|(_, value)| value)
+                .collect::<HashSet<_>>()
+                .len(),
+            _ => self.events.len(),
+        }
+    }
+
+    /// Check if the window has no events left
+    pub fn is_empty(&self) -> bool {
+        self.events.is_empty()
+    }
+
+    /// Mark the window as alerted unless it already was within `window`
+    ///
+    /// Returns true if an alert should be raised, so a burst of events
+    /// raises a single alert.
+    pub fn try_alert(&mut self, now: DateTime<Utc>, window: Duration) -> bool {
+        if self.last_alert.is_some_and(|at| at > now - window) {
+            return false;
+        }
+        self.last_alert = Some(now);
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_event_window() {
+        let now = Utc::now();
+        let window = Duration::minutes(10);
+        let mut events = EventWindow::default();
+
+        events.record(now - Duration::minutes(15), "10.0.0.1".to_string(), window);
+        events.record(now, "10.0.0.1".to_string(), window);
+        events.record(now, "10.0.0.2".to_string(), window);
+
+        // The first event is outside the window
+        assert_eq!(events.count(AnomalyKind::FailedLogins), 2);
+        assert_eq!(events.count(AnomalyKind::IpChurn), 2);
+        events.record(now, "10.0.0.2".to_string(), window);
+        assert_eq!(events.count(AnomalyKind::IpChurn), 2);
+
+        assert!(events.try_alert(now, window));
+        assert!(!events.try_alert(now + Duration::minutes(5), window));
+        assert!(events.try_alert(now + Duration::minutes(11), window));
+    }
+
+    #[test]
+    fn test_thresholds_validate() {
+        assert!(AnomalyThresholds::default().validate().is_ok());
+        let thresholds = AnomalyThresholds {
+            failed_logins: 0,
+            ..AnomalyThresholds::default()
+        };
+        assert!(thresholds.validate().is_err());
+    }
+}
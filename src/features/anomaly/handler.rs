@@ -0,0 +1,102 @@
+use axum::{
+    extract::{Path, Query, State},
+    Json,
+};
+use serde::Deserialize;
+
+use crate::features::auth::AuthenticatedUser;
+use crate::features::users::domain::Role;
+use crate::infrastructure::{AppError, JsonBody};
+
+use super::domain::{AnomalyAlert, AnomalyThresholds};
+use super::service::AnomalyDetector;
+
+/// Query parameters for the list anomalies endpoint
+#[derive(Deserialize)]
+pub struct ListAnomaliesQuery {
+    limit: Option<usize>,
+}
+
+/// List anomaly alerts handler
+///
+/// Recent alerts, newest first. Requires the admin role.
+///
+/// # Route
+/// GET /api/v1/admin/anomalies?limit=50
+///
+/// # Response
+/// ```json
+/// [
+///   {
+///     "id": 3,
+///     "kind": "ip_churn",
+///     "subject": "anonymous:9f2c...",
+///     "tenant": "H001",
+///     "count": 4,
+///     "threshold": 4,
+///     "detected_at": "2024-01-01T09:30:00Z"
+///   }
+/// ]
+/// ```
+pub async fn list_anomalies(
+    State(detector): State<AnomalyDetector>,
+    user: AuthenticatedUser,
+    Query(query): Query<ListAnomaliesQuery>,
+) -> Result<Json<Vec<AnomalyAlert>>, AppError> {
+    require_admin(&user)?;
+    Ok(Json(detector.alerts(query.limit).await))
+}
+
+/// Get anomaly thresholds handler
+///
+/// Thresholds in effect for a tenant. Requires the admin role.
+///
+/// # Route
+/// GET /api/v1/admin/anomalies/thresholds/:hospital_code
+///
+/// # Response
+/// ```json
+/// {"failed_logins": 5, "token_reuse": 1, "distinct_ips": 4}
+/// ```
+pub async fn get_anomaly_thresholds(
+    State(detector): State<AnomalyDetector>,
+    user: AuthenticatedUser,
+    Path(hospital_code): Path<String>,
+) -> Result<Json<AnomalyThresholds>, AppError> {
+    require_admin(&user)?;
+    Ok(Json(detector.thresholds(Some(&hospital_code)).await))
+}
+
+/// Set anomaly thresholds handler
+///
+/// Overrides the thresholds of a tenant. Requires the admin role.
+///
+/// # Route
+/// PUT /api/v1/admin/anomalies/thresholds/:hospital_code
+///
+/// # Request Body
+/// ```json
+/// {"failed_logins": 10, "token_reuse": 1, "distinct_ips": 8}
+/// ```
+pub async fn set_anomaly_thresholds(
+    State(detector): State<AnomalyDetector>,
+    user: AuthenticatedUser,
+    Path(hospital_code): Path<String>,
+    JsonBody(thresholds): JsonBody<AnomalyThresholds>,
+) -> Result<Json<AnomalyThresholds>, AppError> {
+    require_admin(&user)?;
+    detector
+        .set_tenant_thresholds(&hospital_code, thresholds)
+        .await?;
+    Ok(Json(thresholds))
+}
+
+fn require_admin(user: &AuthenticatedUser) -> Result<(), AppError> {
+    if user.0.has_role(Role::Admin) {
+        Ok(())
+    } else {
+        Err(AppError::Forbidden(
+            "Only admins can manage anomaly alerts".to_string(),
+        ))
+    }
+}
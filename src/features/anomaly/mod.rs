@@ -0,0 +1,34 @@
+//! Anomaly Feature Module
+//!
+//! Detects suspicious authentication and traffic patterns and raises
+//! alerts for admins, optionally posted to a webhook.
+//!
+//! ## Architecture
+//!
+//! ### Domain Layer (`domain.rs`)
+//! - `AnomalyKind`: Failed login spikes, token reuse, IP churn per identity
+//! - `AnomalyThresholds`: Per-tenant thresholds within the detection window
+//! - `AnomalyAlert`: Alert raised when a threshold is reached
+//!
+//! ### Application Layer (`service.rs`)
+//! - `AnomalyDetector`: Sliding-window event counting and alerting
+//!
+//! ### Presentation Layer (`handler.rs`)
+//! - Admin handlers listing alerts and managing thresholds
+//!
+//! ## Usage
+//! ```rust,ignore
+//! use features::anomaly;
+//!
+//! let detector = anomaly::AnomalyDetector::new(audit_log.clone());
+//! let auth_service = AuthService::new(secret).with_anomaly_detector(detector.clone());
+//! ```
+
+pub mod domain;
+pub mod handler;
+pub mod service;
+
+// Re-export commonly used items
+pub use domain::{AnomalyAlert, AnomalyKind, AnomalyThresholds};
+pub use handler::{get_anomaly_thresholds, list_anomalies, set_anomaly_thresholds};
+pub use service::AnomalyDetector;
@@ -0,0 +1,289 @@
+use chrono::{Duration, Utc};
+use std::collections::{HashMap, VecDeque};
+use std::net::IpAddr;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use tokio::sync::RwLock;
+
+use crate::features::jsonrpc::ServerEvent;
+use crate::features::users::domain::UserIdentity;
+use crate::infrastructure::audit::AuditActor;
+use crate::infrastructure::{AppError, AuditLog, GeoLocation, Webhook};
+
+use super::domain::{AnomalyAlert, AnomalyKind, AnomalyThresholds, EventWindow};
+
+/// Maximum number of alerts kept in memory
+const MAX_ALERTS: usize = 1000;
+
+/// Default length of the detection window
+const DEFAULT_WINDOW_MINUTES: i64 = 10;
+
+/// Anomaly detector for authentication and traffic patterns
+///
+/// Application layer service that counts suspicious events per subject
+/// within a sliding window and raises an alert once a threshold is reached:
+/// failed logins per username, refresh token reuse and distinct client IPs
+/// per identity. Alerts are kept for admins and optionally posted to a
+/// webhook. Thresholds can be overridden per tenant (hospital code).
+#[derive(Clone)]
+pub struct AnomalyDetector {
+    audit_log: AuditLog,
+    window: Duration,
+    default_thresholds: AnomalyThresholds,
+    tenant_thresholds: Arc<RwLock<HashMap<String, AnomalyThresholds>>>,
+    windows: Arc<RwLock<HashMap<(AnomalyKind, String), EventWindow>>>,
+    alerts: Arc<RwLock<VecDeque<AnomalyAlert>>>,
+    next_alert_id: Arc<AtomicU64>,
+    webhook: Option<Webhook>,
+}
+
+impl AnomalyDetector {
+    /// Create a detector with default thresholds
+    ///
+    /// The audit log is used to hash identities the same way as audit actors.
+    pub fn new(audit_log: AuditLog) -> Self {
+        Self {
+            audit_log,
+            window: Duration::minutes(DEFAULT_WINDOW_MINUTES),
+            default_thresholds: AnomalyThresholds::default(),
+            tenant_thresholds: Arc::new(RwLock::new(HashMap::new())),
+            windows: Arc::new(RwLock::new(HashMap::new())),
+            alerts: Arc::new(RwLock::new(VecDeque::new())),
+            next_alert_id: Arc::new(AtomicU64::new(1)),
+            webhook: None,
+        }
+    }
+
+    /// Set the length of the detection window
+    pub fn with_window(mut self, window: Duration) -> Self {
+        self.window = window;
+        self
+    }
+
+    /// Set the thresholds of tenants without an override
+    pub fn with_default_thresholds(mut self, thresholds: AnomalyThresholds) -> Self {
+        self.default_thresholds = thresholds;
+        self
+    }
+
+    /// Post alerts to `webhook` as `anomaly.detected` events
+    pub fn with_webhook(mut self, webhook: Webhook) -> Self {
+        self.webhook = Some(webhook);
+        self
+    }
+
+    /// Record a failed login for a username
+    pub async fn record_failed_login(&self, username: &str) {
+        self.record(
+            AnomalyKind::FailedLogins,
+            format!("username:{}", username),
+            None,
+            String::new(),
+            None,
+        )
+        .await;
+    }
+
+    /// Record the reuse of an already exchanged refresh token
+    pub async fn record_token_reuse(&self, identity: &UserIdentity) {
+        let (subject, tenant) = self.subject(identity);
+        self.record(
+            AnomalyKind::TokenReuse,
+            subject,
+            tenant,
+            String::new(),
+            None,
+        )
+        .await;
+    }
+
+    /// Record the IP address an identity made a request from
+    ///
+    /// The location of the address, if known, is attached to the alert.
+    pub async fn record_client_ip(
+        &self,
+        identity: &UserIdentity,
+        ip: IpAddr,
+        location: Option<GeoLocation>,
+    ) {
+        let (subject, tenant) = self.subject(identity);
+        self.record(
+            AnomalyKind::IpChurn,
+            subject,
+            tenant,
+            ip.to_string(),
+            location,
+        )
+        .await;
+    }
+
+    /// Recent alerts, newest first
+    pub async fn alerts(&self, limit: Option<usize>) -> Vec<AnomalyAlert> {
+        let alerts = self.alerts.read().await;
+        alerts
+            .iter()
+            .rev()
+            .take(limit.unwrap_or(usize::MAX))
+            .cloned()
+            .collect()
+    }
+
+    /// Thresholds in effect for a tenant
+    pub async fn thresholds(&self, tenant: Option<&str>) -> AnomalyThresholds {
+        let tenant_thresholds = self.tenant_thresholds.read().await;
+        tenant
+            .and_then(|tenant| tenant_thresholds.get(tenant))
+            .copied()
+            .unwrap_or(self.default_thresholds)
+    }
+
+    /// Override the thresholds of a tenant
+    pub async fn set_tenant_thresholds(
+        &self,
+        tenant: &str,
+        thresholds: AnomalyThresholds,
+    ) -> Result<(), AppError> {
+        thresholds.validate().map_err(AppError::BadRequest)?;
+
+        let mut tenant_thresholds = self.tenant_thresholds.write().await;
+        tenant_thresholds.insert(tenant.to_string(), thresholds);
+        Ok(())
+    }
+
+    /// Subject and tenant of an identity
+    fn subject(&self, identity: &UserIdentity) -> (String, Option<String>) {
+        let actor = AuditActor::from(identity);
+        (self.audit_log.stored_actor_id(&actor), actor.tenant)
+    }
+
+    async fn record(
+        &self,
+        kind: AnomalyKind,
+        subject: String,
+        tenant: Option<String>,
+        value: String,
+        location: Option<GeoLocation>,
+    ) {
+        let threshold = self.thresholds(tenant.as_deref()).await.threshold(kind);
+        let now = Utc::now();
+
+        let mut windows = self.windows.write().await;
+        let key = (kind, subject);
+        if !windows.contains_key(&key) {
+            // Drop windows of subjects that have gone quiet
+            windows.retain(|_, events| {
+                events.prune(now, self.window);
+                !events.is_empty()
+            });
+        }
+        let events = windows.entry(key.clone()).or_default();
+        events.record(now, value, self.window);
+
+        let count = events.count(kind);
+        if count < threshold || !events.try_alert(now, self.window) {
+            return;
+        }
+        drop(windows);
+
+        let alert = AnomalyAlert {
+            id: self.next_alert_id.fetch_add(1, Ordering::SeqCst),
+            kind,
+            subject: key.1,
+            tenant,
+            count,
+            threshold,
+            location,
+            detected_at: now,
+        };
+        tracing::warn!(
+            "Anomaly detected: {:?} for {} ({} >= {})",
+            alert.kind,
+            alert.subject,
+            count,
+            threshold
+        );
+
+        if let Some(webhook) = &self.webhook {
+            match serde_json::to_value(ServerEvent::AnomalyDetected(alert.clone()).payload()) {
+                Ok(payload) => {
+                    webhook.deliver(payload);
+                }
+                Err(err) => tracing::error!("Failed to serialize anomaly alert: {}", err),
+            }
+        }
+
+        let mut alerts = self.alerts.write().await;
+        if alerts.len() >= MAX_ALERTS {
+            alerts.pop_front();
+        }
+        alerts.push_back(alert);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::features::users::domain::AnonymousUserIdentifier;
+    use chrono::NaiveDate;
+
+    fn anonymous_user() -> UserIdentity {
+        UserIdentity::Anonymous(AnonymousUserIdentifier {
+            hospital_code: "H001".to_string(),
+            user_id: "U001".to_string(),
+            user_start_date: NaiveDate::from_ymd_opt(2024, 1, 1).unwrap(),
+            department_code: "D001".to_string(),
+        })
+    }
+
+    #[tokio::test]
+    async fn test_failed_logins_raise_single_alert() {
+        let detector = AnomalyDetector::new(AuditLog::new(100, "secret"));
+
+        for _ in 0..4 {
+            detector.record_failed_login("john").await;
+        }
+        assert!(detector.alerts(None).await.is_empty());
+
+        detector.record_failed_login("john").await;
+        detector.record_failed_login("john").await;
+        let alerts = detector.alerts(None).await;
+        assert_eq!(alerts.len(), 1);
+        assert_eq!(alerts[0].kind, AnomalyKind::FailedLogins);
+        assert_eq!(alerts[0].subject, "username:john");
+    }
+
+    #[tokio::test]
+    async fn test_ip_churn_with_tenant_thresholds() {
+        let detector = AnomalyDetector::new(AuditLog::new(100, "secret"));
+        let user = anonymous_user();
+        detector
+            .set_tenant_thresholds(
+                "H001",
+                AnomalyThresholds {
+                    distinct_ips: 2,
+                    ..AnomalyThresholds::default()
+                },
+            )
+            .await
+            .unwrap();
+
+        let ip: IpAddr = "10.0.0.1".parse().unwrap();
+        detector.record_client_ip(&user, ip, None).await;
+        detector.record_client_ip(&user, ip, None).await;
+        assert!(detector.alerts(None).await.is_empty());
+
+        let location = GeoLocation {
+            country: Some("KR".to_string()),
+            region: Some("11".to_string()),
+        };
+        detector
+            .record_client_ip(&user, "10.0.0.2".parse().unwrap(), Some(location.clone()))
+            .await;
+        let alerts = detector.alerts(None).await;
+        assert_eq!(alerts.len(), 1);
+        assert_eq!(alerts[0].tenant.as_deref(), Some("H001"));
+        assert_eq!(alerts[0].location, Some(location));
+        // Raw anonymous composite keys are never exposed
+        assert!(!alerts[0].subject.contains("U001"));
+    }
+}
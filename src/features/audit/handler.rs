@@ -0,0 +1,317 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    Json,
+};
+
+use crate::features::auth::AuthenticatedUser;
+use crate::features::users::domain::Role;
+use crate::infrastructure::audit::{AuditEntry, AuditQuery};
+use crate::infrastructure::dead_letter::DeadLetter;
+use crate::infrastructure::jobs::JobStatus;
+use crate::infrastructure::legal_hold::{LegalHold, PlaceLegalHoldRequest};
+use crate::infrastructure::retention::RetentionReport;
+use crate::infrastructure::sessions::{Session, SessionQuery};
+use crate::infrastructure::{
+    AppError, AuditContext, AuditLog, DeadLetterQueue, HoldKind, JobScheduler, JsonBody,
+    LegalHolds, RetentionJob, SessionStore,
+};
+
+/// Search audit log handler
+///
+/// Presentation layer handler for searching recorded write operations,
+/// newest first. Requires the admin role.
+///
+/// # Route
+/// GET /api/v1/admin/audit?actor=user:1&tenant=H001&operation=delete&resource_type=announcement&limit=50
+///
+/// # Response
+/// ```json
+/// [
+///   {
+///     "id": 12,
+///     "timestamp": "2024-01-01T00:00:00Z",
+///     "actor": {"id": "user:1"},
+///     "operation": "delete",
+///     "resource_type": "announcement",
+///     "resource_id": "3",
+///     "before": {"id": 3, "title": "EMR maintenance", "...": "..."},
+///     "request_id": "7f0c1c4e-5a0e-4a4f-9a4e-1f0d3c2b1a00",
+///     "token_id": "2b9d6f0e-3c4a-4e8b-9f1d-7a6c5b4e3d2f"
+///   }
+/// ]
+/// ```
+pub async fn search_audit_log(
+    State(audit_log): State<AuditLog>,
+    user: AuthenticatedUser,
+    Query(query): Query<AuditQuery>,
+) -> Result<Json<Vec<AuditEntry>>, AppError> {
+    if !user.0.has_role(Role::Admin) {
+        return Err(AppError::Forbidden(
+            "Only admins can read the audit log".to_string(),
+        ));
+    }
+
+    Ok(Json(audit_log.search(&query).await))
+}
+
+/// Retention dry-run report handler
+///
+/// Reports what the retention job would delete or pseudonymize if it ran
+/// now, without modifying any data. Requires the admin role.
+///
+/// # Route
+/// GET /api/v1/admin/retention/report
+///
+/// # Response
+/// ```json
+/// {
+///   "dry_run": true,
+///   "executed_at": "2024-03-01T00:00:00Z",
+///   "rules": [
+///     {"data_class": "anonymous_identifiers", "action": "pseudonymize", "cutoff": "2024-01-31T00:00:00Z", "affected": 42},
+///     {"data_class": "audit_logs", "action": "delete", "cutoff": "2023-12-02T00:00:00Z", "affected": 7}
+///   ]
+/// }
+/// ```
+pub async fn retention_report(
+    State(retention_job): State<RetentionJob>,
+    user: AuthenticatedUser,
+) -> Result<Json<RetentionReport>, AppError> {
+    if !user.0.has_role(Role::Admin) {
+        return Err(AppError::Forbidden(
+            "Only admins can read the retention report".to_string(),
+        ));
+    }
+
+    Ok(Json(retention_job.run_once(true).await))
+}
+
+/// List legal holds handler
+///
+/// Requires the admin role.
+///
+/// # Route
+/// GET /api/v1/admin/legal-holds
+///
+/// # Response
+/// ```json
+/// [
+///   {
+///     "kind": "post",
+///     "id": 7,
+///     "reason": "Case 2024-118",
+///     "placed_by": "user:1",
+///     "placed_at": "2024-01-01T00:00:00Z"
+///   }
+/// ]
+/// ```
+pub async fn list_legal_holds(State(legal_holds): State<LegalHolds>) -> Json<Vec<LegalHold>> {
+    Json(legal_holds.list())
+}
+
+/// Place legal hold handler
+///
+/// Requires the admin role. Held posts and users are exempt from retention
+/// and cannot be deleted until the hold is released. The reason is recorded
+/// in the audit log.
+///
+/// # Route
+/// POST /api/v1/admin/legal-holds
+///
+/// # Request Body
+/// ```json
+/// {
+///   "kind": "user",
+///   "id": 3,
+///   "reason": "Case 2024-118"
+/// }
+/// ```
+///
+/// # Response
+/// 201 Created with the hold
+pub async fn place_legal_hold(
+    State(legal_holds): State<LegalHolds>,
+    audit: AuditContext,
+    JsonBody(payload): JsonBody<PlaceLegalHoldRequest>,
+) -> Result<(StatusCode, Json<LegalHold>), AppError> {
+    let hold = legal_holds.place(payload, &audit).await?;
+    Ok((StatusCode::CREATED, Json(hold)))
+}
+
+/// Release legal hold handler
+///
+/// Requires the admin role.
+///
+/// # Route
+/// DELETE /api/v1/admin/legal-holds/:kind/:id
+///
+/// # Response
+/// 204 No Content
+pub async fn release_legal_hold(
+    State(legal_holds): State<LegalHolds>,
+    audit: AuditContext,
+    Path((kind, id)): Path<(HoldKind, u64)>,
+) -> Result<StatusCode, AppError> {
+    legal_holds.release(kind, id, &audit).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// List background jobs handler
+///
+/// Requires the admin role.
+///
+/// # Route
+/// GET /api/v1/admin/jobs
+///
+/// # Response
+/// ```json
+/// [
+///   {
+///     "name": "backup",
+///     "state": "failed",
+///     "interval_secs": 3600,
+///     "next_run_at": "2024-01-01T01:00:00Z",
+///     "last_started_at": "2024-01-01T00:00:00Z",
+///     "last_finished_at": "2024-01-01T00:00:01Z",
+///     "last_error": "Failed to store backup: No space left on device",
+///     "runs": 12,
+///     "failures": 1
+///   }
+/// ]
+/// ```
+pub async fn list_jobs(State(jobs): State<JobScheduler>) -> Json<Vec<JobStatus>> {
+    Json(jobs.list())
+}
+
+/// Run background job handler
+///
+/// Requires the admin role. Runs the job now instead of waiting for its
+/// next scheduled run, e.g. to retry a failed run.
+///
+/// # Route
+/// POST /api/v1/admin/jobs/:name/run
+///
+/// # Response
+/// 202 Accepted with the status of the job before the run
+pub async fn run_job(
+    State(jobs): State<JobScheduler>,
+    Path(name): Path<String>,
+) -> Result<(StatusCode, Json<JobStatus>), AppError> {
+    let status = jobs.trigger(&name)?;
+    Ok((StatusCode::ACCEPTED, Json(status)))
+}
+
+/// List dead letters handler
+///
+/// Requires the admin role.
+///
+/// # Route
+/// GET /api/v1/admin/dead-letters
+///
+/// # Response
+/// ```json
+/// [
+///   {
+///     "id": 4,
+///     "kind": "webhook",
+///     "source": "anomaly_alert",
+///     "destination": "https://alerts.example.com/webboard",
+///     "payload": {"id": 3, "kind": "ip_churn", "...": "..."},
+///     "attempts": [
+///       {"attempted_at": "2024-01-01T00:00:00Z", "error": "error sending request"}
+///     ],
+///     "dead_lettered_at": "2024-01-01T00:00:03Z"
+///   }
+/// ]
+/// ```
+pub async fn list_dead_letters(
+    State(dead_letters): State<DeadLetterQueue>,
+) -> Json<Vec<DeadLetter>> {
+    Json(dead_letters.list().await)
+}
+
+/// Retry dead letter handler
+///
+/// Requires the admin role. Delivers the payload once more and removes the
+/// entry on success.
+///
+/// # Route
+/// POST /api/v1/admin/dead-letters/:id/retry
+///
+/// # Response
+/// 204 No Content, or 503 Service Unavailable if the delivery failed again
+pub async fn retry_dead_letter(
+    State(dead_letters): State<DeadLetterQueue>,
+    Path(id): Path<u64>,
+) -> Result<StatusCode, AppError> {
+    dead_letters.retry(id).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Discard dead letter handler
+///
+/// Requires the admin role. The discarded entry is recorded in the audit
+/// log.
+///
+/// # Route
+/// DELETE /api/v1/admin/dead-letters/:id
+///
+/// # Response
+/// 204 No Content
+pub async fn discard_dead_letter(
+    State(dead_letters): State<DeadLetterQueue>,
+    audit: AuditContext,
+    Path(id): Path<u64>,
+) -> Result<StatusCode, AppError> {
+    dead_letters.discard(id, &audit).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// List sessions handler
+///
+/// Requires the admin role. Lists the server-side sessions of anonymous
+/// tokens, oldest first, optionally filtered by actor or hospital code.
+/// Composite keys are listed hashed, matching the actor ids of audit entries.
+///
+/// # Route
+/// GET /api/v1/admin/sessions?actor=anonymous:5f2c...&tenant=H001
+///
+/// # Response
+/// ```json
+/// [
+///   {
+///     "id": "0b6f3c2e-8d4a-4f1e-9c7b-2a5d6e8f1b3c",
+///     "actor": "anonymous:5f2c8e1a...",
+///     "tenant": "H001",
+///     "created_at": "2024-01-01T00:00:00Z",
+///     "expires_at": "2024-01-01T12:01:00Z"
+///   }
+/// ]
+/// ```
+pub async fn list_sessions(
+    State(sessions): State<SessionStore>,
+    Query(query): Query<SessionQuery>,
+) -> Result<Json<Vec<Session>>, AppError> {
+    Ok(Json(sessions.list(&query).await?))
+}
+
+/// Revoke session handler
+///
+/// Requires the admin role. The token of the session is rejected with 401
+/// from then on, on other instances after their next sync. The revoked
+/// session is recorded in the audit log.
+///
+/// # Route
+/// DELETE /api/v1/admin/sessions/:id
+///
+/// # Response
+/// 204 No Content
+pub async fn revoke_session(
+    State(sessions): State<SessionStore>,
+    audit: AuditContext,
+    Path(id): Path<String>,
+) -> Result<StatusCode, AppError> {
+    sessions.revoke(&id, &audit).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
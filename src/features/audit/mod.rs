@@ -0,0 +1,28 @@
+//! Audit Feature Module
+//!
+//! Admin-facing access to the audit log kept by the infrastructure layer.
+//! This is a lightweight feature with only a presentation layer; recording
+//! happens inside the services performing writes.
+//!
+//! ## Architecture
+//! - `handler`: HTTP handlers for searching audit entries, reporting on
+//!   data retention, managing legal holds, inspecting background jobs,
+//!   handling dead letters and revoking sessions
+//!
+//! ## Usage
+//! ```rust,ignore
+//! use features::audit;
+//!
+//! Router::new()
+//!     .route("/admin/audit", get(audit::search_audit_log))
+//!     .with_state(audit_log)
+//! ```
+
+pub mod handler;
+
+// Re-export commonly used items
+pub use handler::{
+    discard_dead_letter, list_dead_letters, list_jobs, list_legal_holds, list_sessions,
+    place_legal_hold, release_legal_hold, retention_report, retry_dead_letter, revoke_session,
+    run_job, search_audit_log,
+};
@@ -0,0 +1,180 @@
+use futures::future::BoxFuture;
+use redis::AsyncCommands;
+use std::time::Duration;
+
+use crate::features::health::HealthCheck;
+use crate::infrastructure::{Service, TtlCache};
+
+/// Prefix of the Redis keys revoked token ids are stored under
+pub const REDIS_KEY_PREFIX: &str = "webboard:revoked:";
+
+/// Blacklist of revoked access tokens
+///
+/// Token ids are kept until the token expires. Tokens are checked on every
+/// request while decoding, which is synchronous, so lookups only consult the
+/// in-memory entries. With a Redis backend, revocations are also written to
+/// Redis and `sync` pulls in the tokens revoked by other instances, so a
+/// token logged out on one instance is rejected by all of them.
+/// Clones share the same entries.
+#[derive(Clone, Default)]
+pub struct TokenBlacklist {
+    local: TtlCache<String, ()>,
+    redis: Option<redis::Client>,
+}
+
+impl TokenBlacklist {
+    /// Create an in-memory blacklist
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Share revocations through the Redis server at `url`
+    ///
+    /// Only the URL is checked here; the server is connected to when
+    /// revoking and syncing.
+    pub fn with_redis(mut self, url: &str) -> Result<Self, redis::RedisError> {
+        self.redis = Some(redis::Client::open(url)?);
+        Ok(self)
+    }
+
+    /// Revoke the token with id `jti` for `ttl`, the time until it expires
+    ///
+    /// The token is rejected locally at once. Fails if it could not be
+    /// written to Redis, as other instances then keep accepting it.
+    pub async fn revoke(&self, jti: &str, ttl: Duration) -> Result<(), redis::RedisError> {
+        self.local.insert(jti.to_string(), (), ttl);
+
+        let Some(client) = &self.redis else {
+            return Ok(());
+        };
+        let mut connection = client.get_multiplexed_async_connection().await?;
+        connection
+            .set_ex::<_, _, ()>(
+                format!("{}{}", REDIS_KEY_PREFIX, jti),
+                1,
+                ttl.as_secs().max(1),
+            )
+            .await
+    }
+
+    /// Check if the token with id `jti` has been revoked
+    pub fn is_revoked(&self, jti: &str) -> bool {
+        self.local.get(&jti.to_string()).is_some()
+    }
+
+    /// Pull the tokens revoked on other instances from Redis
+    ///
+    /// Returns the number of revoked tokens in Redis. Does nothing without
+    /// a Redis backend.
+    pub async fn sync(&self) -> Result<usize, redis::RedisError> {
+        let Some(client) = &self.redis else {
+            return Ok(0);
+        };
+        let mut connection = client.get_multiplexed_async_connection().await?;
+
+        let mut keys = Vec::new();
+        {
+            let mut iter = connection
+                .scan_match::<_, String>(format!("{}*", REDIS_KEY_PREFIX))
+                .await?;
+            while let Some(key) = iter.next_item().await {
+                keys.push(key);
+            }
+        }
+        if keys.is_empty() {
+            return Ok(0);
+        }
+
+        let mut pipe = redis::pipe();
+        for key in &keys {
+            pipe.pttl(key);
+        }
+        let ttls: Vec<i64> = pipe.query_async(&mut connection).await?;
+        for (key, ttl) in keys.iter().zip(ttls) {
+            // Negative TTLs mark keys that are gone or never expire
+            if let (Some(jti), Ok(ttl)) = (key.strip_prefix(REDIS_KEY_PREFIX), u64::try_from(ttl)) {
+                self.local
+                    .insert(jti.to_string(), (), Duration::from_millis(ttl));
+            }
+        }
+        Ok(keys.len())
+    }
+
+    /// Check that the Redis server answers
+    ///
+    /// Does nothing without a Redis backend.
+    pub async fn ping(&self) -> Result<(), redis::RedisError> {
+        let Some(client) = &self.redis else {
+            return Ok(());
+        };
+        let mut connection = client.get_multiplexed_async_connection().await?;
+        redis::cmd("PING").query_async(&mut connection).await
+    }
+}
+
+/// Revocations are not shared between instances while Redis is down
+impl HealthCheck for TokenBlacklist {
+    fn name(&self) -> &'static str {
+        "redis"
+    }
+
+    fn check(&self) -> BoxFuture<'_, Result<(), String>> {
+        Box::pin(async move { self.ping().await.map_err(|err| err.to_string()) })
+    }
+}
+
+/// Pulls in the tokens revoked elsewhere before requests are served
+///
+/// With Redis down the instance starts with local revocations only, as it
+/// does when a later sync fails.
+impl Service for TokenBlacklist {
+    fn name(&self) -> &'static str {
+        "token-blacklist"
+    }
+
+    fn start(&self) -> BoxFuture<'_, Result<(), String>> {
+        Box::pin(async move {
+            if let Err(err) = self.sync().await {
+                tracing::warn!("Failed to sync revoked tokens: {}", err);
+            }
+            Ok(())
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_revoked_tokens_expire() {
+        let blacklist = TokenBlacklist::new();
+        blacklist
+            .revoke("a", Duration::from_secs(60))
+            .await
+            .unwrap();
+        blacklist.revoke("b", Duration::ZERO).await.unwrap();
+
+        assert!(blacklist.is_revoked("a"));
+        assert!(!blacklist.is_revoked("b"));
+        assert!(!blacklist.is_revoked("c"));
+        assert_eq!(blacklist.sync().await.unwrap(), 0);
+        assert!(blacklist.ping().await.is_ok());
+
+        assert!(TokenBlacklist::new().with_redis("not a url").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_failed_redis_write_is_reported() {
+        let blacklist = TokenBlacklist::new()
+            .with_redis("redis://127.0.0.1:1")
+            .unwrap();
+
+        // The token is still rejected by this instance
+        assert!(blacklist
+            .revoke("a", Duration::from_secs(60))
+            .await
+            .is_err());
+        assert!(blacklist.is_revoked("a"));
+    }
+}
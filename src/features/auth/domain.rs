@@ -1,7 +1,13 @@
-use chrono::{Duration, NaiveDate, Utc};
+use chrono::{DateTime, Duration, NaiveDate, Utc};
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::net::IpAddr;
+use std::str::FromStr;
 
-use crate::features::users::domain::{AnonymousUserIdentifier, UserIdentity, VerifiedUser};
+use crate::features::users::domain::{
+    validate_username, AnonymousUserIdentifier, Role, UserIdentity, VerifiedUser,
+};
+use crate::infrastructure::validation::{Validate, ValidationErrors};
 
 /// JWT Claims for verified users
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -9,8 +15,16 @@ pub struct VerifiedUserClaims {
     pub sub: String, // user id
     pub username: String,
     pub email: String,
+    #[serde(default)]
+    pub role: Role, // tokens issued before roles existed are members
     pub exp: usize, // expiration timestamp
     pub iat: usize, // issued at timestamp
+    #[serde(default)]
+    pub nbf: usize, // not valid before timestamp
+    #[serde(default)]
+    pub jti: String, // unique token id, empty for tokens issued before ids existed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fpr: Option<String>, // client fingerprint the token is bound to
 }
 
 impl VerifiedUserClaims {
@@ -23,8 +37,12 @@ impl VerifiedUserClaims {
             sub: user.id.to_string(),
             username: user.username.clone(),
             email: user.email.clone(),
+            role: user.role,
             iat: now.timestamp() as usize,
             exp: expiration.timestamp() as usize,
+            nbf: now.timestamp() as usize,
+            jti: uuid::Uuid::new_v4().to_string(),
+            fpr: None,
         }
     }
 }
@@ -39,6 +57,14 @@ pub struct AnonymousUserClaims {
     pub department_code: String,
     pub exp: usize, // expiration timestamp
     pub iat: usize, // issued at timestamp
+    #[serde(default)]
+    pub nbf: usize, // not valid before timestamp
+    #[serde(default)]
+    pub jti: String, // unique token id, empty for tokens issued before ids existed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fpr: Option<String>, // client fingerprint the token is bound to
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sid: Option<String>, // server-side session id, when sessions are enabled
 }
 
 impl AnonymousUserClaims {
@@ -54,6 +80,10 @@ impl AnonymousUserClaims {
             department_code: identifier.department_code.clone(),
             iat: now.timestamp() as usize,
             exp: expiration.timestamp() as usize,
+            nbf: now.timestamp() as usize,
+            jti: uuid::Uuid::new_v4().to_string(),
+            fpr: None,
+            sid: None,
         }
     }
 
@@ -106,6 +136,49 @@ impl TokenClaims {
         }
     }
 
+    /// Get issued at timestamp
+    pub fn iat(&self) -> usize {
+        match self {
+            TokenClaims::Verified(claims) => claims.iat,
+            TokenClaims::Anonymous(claims) => claims.iat,
+        }
+    }
+
+    /// Get the unique token id
+    ///
+    /// Used to revoke the token and to correlate audit entries with it.
+    pub fn jti(&self) -> &str {
+        match self {
+            TokenClaims::Verified(claims) => &claims.jti,
+            TokenClaims::Anonymous(claims) => &claims.jti,
+        }
+    }
+
+    /// Get the client fingerprint the token is bound to, if any
+    pub fn fingerprint(&self) -> Option<&str> {
+        match self {
+            TokenClaims::Verified(claims) => claims.fpr.as_deref(),
+            TokenClaims::Anonymous(claims) => claims.fpr.as_deref(),
+        }
+    }
+
+    /// Get the id of the server-side session the token belongs to, if any
+    pub fn session_id(&self) -> Option<&str> {
+        match self {
+            TokenClaims::Verified(_) => None,
+            TokenClaims::Anonymous(claims) => claims.sid.as_deref(),
+        }
+    }
+
+    /// Bind the token to a client fingerprint
+    pub fn bind_to(&mut self, fingerprint: &ClientFingerprint) {
+        let fpr = Some(fingerprint.as_str().to_string());
+        match self {
+            TokenClaims::Verified(claims) => claims.fpr = fpr,
+            TokenClaims::Anonymous(claims) => claims.fpr = fpr,
+        }
+    }
+
     /// Convert to UserIdentity
     pub fn to_user_identity(&self) -> UserIdentity {
         match self {
@@ -113,6 +186,7 @@ impl TokenClaims {
                 id: claims.sub.parse().unwrap_or(0),
                 username: claims.username.clone(),
                 email: claims.email.clone(),
+                role: claims.role,
             }),
             TokenClaims::Anonymous(claims) => {
                 UserIdentity::Anonymous(claims.to_identifier())
@@ -121,11 +195,84 @@ impl TokenClaims {
     }
 }
 
+/// How strictly tokens are bound to the client they were issued to
+///
+/// - `Off`: tokens carry no fingerprint and are accepted from any client
+/// - `Lenient`: tokens are bound; fingerprint mismatches are logged but accepted
+/// - `Strict`: tokens are bound; mismatching or unbound tokens are rejected
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TokenBinding {
+    #[default]
+    Off,
+    Lenient,
+    Strict,
+}
+
+impl FromStr for TokenBinding {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "off" => Ok(TokenBinding::Off),
+            "lenient" => Ok(TokenBinding::Lenient),
+            "strict" => Ok(TokenBinding::Strict),
+            _ => Err(format!("Unknown token binding mode: {}", s)),
+        }
+    }
+}
+
+/// Fingerprint of the client a request comes from
+///
+/// Derived from a client-generated device id when the client sends one,
+/// otherwise from the User-Agent and the network prefix of the client IP
+/// (/24 for IPv4, /48 for IPv6) so that address changes within a network
+/// do not break the binding. Only a hash is kept.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClientFingerprint(String);
+
+impl ClientFingerprint {
+    /// Compute the fingerprint from the request's client information
+    pub fn new(device_id: Option<&str>, user_agent: Option<&str>, ip: Option<IpAddr>) -> Self {
+        let source = match device_id {
+            Some(device_id) => format!("device:{}", device_id),
+            None => format!(
+                "agent:{}|net:{}",
+                user_agent.unwrap_or_default(),
+                ip.map(network_prefix).unwrap_or_default()
+            ),
+        };
+
+        Self(format!("{:x}", Sha256::digest(source.as_bytes())))
+    }
+
+    /// Hex-encoded fingerprint hash
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+/// Network prefix of an IP address used for fingerprinting
+fn network_prefix(ip: IpAddr) -> String {
+    match ip {
+        IpAddr::V4(ip) => {
+            let [a, b, c, _] = ip.octets();
+            format!("{}.{}.{}.0/24", a, b, c)
+        }
+        IpAddr::V6(ip) => {
+            let segments = ip.segments();
+            format!("{:x}:{:x}:{:x}::/48", segments[0], segments[1], segments[2])
+        }
+    }
+}
+
 /// Authentication token
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AuthToken {
     pub token: String,
     pub token_type: String, // "Bearer"
+    /// Secret to exchange for a new token once this one expires
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub refresh_token: Option<String>,
 }
 
 impl AuthToken {
@@ -134,8 +281,413 @@ impl AuthToken {
         Self {
             token,
             token_type: "Bearer".to_string(),
+            refresh_token: None,
         }
     }
+
+    /// Attach a refresh token
+    pub fn with_refresh_token(mut self, refresh_token: String) -> Self {
+        self.refresh_token = Some(refresh_token);
+        self
+    }
+}
+
+/// Default number of days a refresh token stays valid
+pub const DEFAULT_REFRESH_TOKEN_LIFETIME_DAYS: i64 = 30;
+
+/// Default tolerance for clock skew when validating token timestamps
+pub const DEFAULT_CLOCK_SKEW_LEEWAY_SECS: u64 = 60;
+
+/// Long-lived token exchanged for new access tokens
+///
+/// Only the hash of the secret is stored. Every refresh replaces the token
+/// with a new one of the same family, expiring with the family. Replaced
+/// tokens are kept until they expire, so a replayed token is detected and
+/// revokes its whole family.
+#[derive(Debug, Clone)]
+pub struct RefreshToken {
+    /// Tokens descending from the same login share a family
+    pub family: String,
+    pub identity: UserIdentity,
+    /// Client the token is bound to, if token binding is enabled
+    pub client: Option<ClientFingerprint>,
+    pub expires_at: DateTime<Utc>,
+    /// Set once the token was exchanged for a new one
+    pub replaced: bool,
+}
+
+impl RefreshToken {
+    /// Create a refresh token starting a new family, and its secret
+    pub fn new(
+        identity: UserIdentity,
+        client: Option<ClientFingerprint>,
+        lifetime: Duration,
+    ) -> (String, Self) {
+        let token = Self {
+            family: uuid::Uuid::new_v4().simple().to_string(),
+            identity,
+            client,
+            expires_at: Utc::now() + lifetime,
+            replaced: false,
+        };
+
+        (Self::secret(), token)
+    }
+
+    /// Replace the token with a new one of the same family, and its secret
+    ///
+    /// The new token expires with the family, so refreshing never extends
+    /// a login beyond the refresh token lifetime.
+    pub fn rotate(
+        &mut self,
+        identity: UserIdentity,
+        client: Option<ClientFingerprint>,
+    ) -> (String, Self) {
+        self.replaced = true;
+        let token = Self {
+            family: self.family.clone(),
+            identity,
+            client,
+            expires_at: self.expires_at,
+            replaced: false,
+        };
+
+        (Self::secret(), token)
+    }
+
+    fn secret() -> String {
+        format!(
+            "{}{}",
+            uuid::Uuid::new_v4().simple(),
+            uuid::Uuid::new_v4().simple()
+        )
+    }
+
+    /// Hash under which a refresh token secret is stored
+    pub fn hash(secret: &str) -> String {
+        format!("{:x}", Sha256::digest(secret.as_bytes()))
+    }
+
+    /// Check if the token can no longer be used
+    pub fn is_expired(&self) -> bool {
+        Utc::now() >= self.expires_at
+    }
+}
+
+/// Request carrying a refresh token, used to refresh and to log out
+#[derive(Debug, Deserialize)]
+pub struct RefreshTokenRequest {
+    pub refresh_token: String,
+}
+
+/// Pending login of a shared workstation
+///
+/// Created by the workstation, shown as a QR code, and approved from a
+/// device where the user is already logged in. The workstation then
+/// collects the token by polling with its `poll_token`.
+#[derive(Debug, Clone)]
+pub struct DeviceLogin {
+    pub code: String,
+    pub poll_token: String,
+    pub client: ClientFingerprint,
+    pub expires_at: DateTime<Utc>,
+    pub approved_token: Option<String>,
+}
+
+impl DeviceLogin {
+    /// Create a new device login for the workstation `client`
+    pub fn new(client: ClientFingerprint) -> Self {
+        Self {
+            code: uuid::Uuid::new_v4().simple().to_string(),
+            poll_token: uuid::Uuid::new_v4().simple().to_string(),
+            client,
+            expires_at: Utc::now() + Duration::minutes(5),
+            approved_token: None,
+        }
+    }
+
+    /// Check if the login can no longer be approved or collected
+    pub fn is_expired(&self) -> bool {
+        Utc::now() >= self.expires_at
+    }
+}
+
+/// Response of starting a device login
+#[derive(Debug, Clone, Serialize)]
+pub struct DeviceLoginStart {
+    /// Code shown to the approving device
+    pub code: String,
+    /// Secret the workstation polls with
+    pub poll_token: String,
+    /// Content to encode in the QR code
+    pub qr_payload: String,
+    pub expires_at: DateTime<Utc>,
+}
+
+/// Request to approve a device login
+#[derive(Debug, Deserialize)]
+pub struct ApproveDeviceLoginRequest {
+    pub code: String,
+}
+
+/// Request to poll a device login
+#[derive(Debug, Deserialize)]
+pub struct PollDeviceLoginRequest {
+    pub poll_token: String,
+}
+
+/// State of a device login as seen by the polling workstation
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "status", rename_all = "lowercase")]
+pub enum DeviceLoginStatus {
+    Pending,
+    Approved(AuthToken),
+}
+
+/// Audience of automation tokens, keeping them apart from user tokens
+pub const AUTOMATION_TOKEN_AUDIENCE: &str = "webboard-automation";
+
+/// JWT header type of automation tokens
+pub const AUTOMATION_TOKEN_TYPE: &str = "automation+jwt";
+
+/// JWT Claims for automation tokens
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AutomationTokenClaims {
+    pub sub: String, // automation token id
+    pub aud: String,
+    pub gen: u32,   // rotation generation, older generations are rejected
+    pub exp: usize, // expiration timestamp
+    pub iat: usize, // issued at timestamp
+}
+
+impl AutomationTokenClaims {
+    /// Create new claims for the current generation of an automation token
+    pub fn new(token: &AutomationToken) -> Self {
+        Self {
+            sub: token.id.clone(),
+            aud: AUTOMATION_TOKEN_AUDIENCE.to_string(),
+            gen: token.generation,
+            iat: Utc::now().timestamp() as usize,
+            exp: token.expires_at.timestamp() as usize,
+        }
+    }
+}
+
+/// What an automation token may access
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AutomationTokenScope {
+    /// Path prefixes the token may call, e.g. "/api/v1/admin/usage"
+    pub endpoints: Vec<String>,
+    /// Only allow GET and HEAD requests
+    #[serde(default)]
+    pub read_only: bool,
+    /// Client IPs the token may be used from; empty allows any
+    #[serde(default)]
+    pub allowed_ips: Vec<IpAddr>,
+}
+
+impl AutomationTokenScope {
+    /// Check if a request is within the scope
+    pub fn check(&self, request: &AutomationRequest) -> Result<(), String> {
+        let endpoint_allowed = self.endpoints.iter().any(|endpoint| {
+            let prefix = endpoint.trim_end_matches('/');
+            request.path == prefix
+                || request
+                    .path
+                    .strip_prefix(prefix)
+                    .is_some_and(|rest| rest.starts_with('/'))
+        });
+        if !endpoint_allowed {
+            return Err(format!(
+                "Endpoint {} is not in the token scope",
+                request.path
+            ));
+        }
+
+        if self.read_only && !matches!(request.method, "GET" | "HEAD") {
+            return Err("Token is read-only".to_string());
+        }
+
+        if !self.allowed_ips.is_empty()
+            && !request.ip.is_some_and(|ip| self.allowed_ips.contains(&ip))
+        {
+            return Err("Token is not allowed from this address".to_string());
+        }
+
+        Ok(())
+    }
+}
+
+/// Request an automation token is used for
+#[derive(Debug, Clone, Copy)]
+pub struct AutomationRequest<'a> {
+    pub method: &'a str,
+    pub path: &'a str,
+    pub ip: Option<IpAddr>,
+}
+
+/// Long-lived, narrowly scoped token for scripts and integrations
+///
+/// Acts on behalf of the verified user who created it, limited to its
+/// scope. Rotating the token invalidates all previously issued secrets.
+#[derive(Debug, Clone, Serialize)]
+pub struct AutomationToken {
+    pub id: String,
+    pub name: String,
+    pub scope: AutomationTokenScope,
+    /// Verified user the token acts on behalf of
+    pub owner: VerifiedUser,
+    pub lifetime_days: i64,
+    pub created_at: DateTime<Utc>,
+    pub rotated_at: Option<DateTime<Utc>>,
+    pub expires_at: DateTime<Utc>,
+    #[serde(skip)]
+    pub generation: u32,
+}
+
+impl AutomationToken {
+    /// Create a new automation token owned by `owner`
+    pub fn new(owner: &VerifiedUser, request: CreateAutomationTokenRequest) -> Self {
+        let now = Utc::now();
+
+        Self {
+            id: uuid::Uuid::new_v4().simple().to_string(),
+            name: request.name,
+            scope: request.scope,
+            owner: owner.clone(),
+            lifetime_days: request.lifetime_days,
+            created_at: now,
+            rotated_at: None,
+            expires_at: now + Duration::days(request.lifetime_days),
+            generation: 1,
+        }
+    }
+
+    /// Start a new generation, invalidating previously issued secrets
+    pub fn rotate(&mut self) {
+        let now = Utc::now();
+        self.generation += 1;
+        self.rotated_at = Some(now);
+        self.expires_at = now + Duration::days(self.lifetime_days);
+    }
+}
+
+/// Request to create an automation token
+#[derive(Debug, Deserialize)]
+pub struct CreateAutomationTokenRequest {
+    pub name: String,
+    pub scope: AutomationTokenScope,
+    #[serde(default = "default_automation_token_lifetime_days")]
+    pub lifetime_days: i64,
+}
+
+fn default_automation_token_lifetime_days() -> i64 {
+    365
+}
+
+impl Validate for CreateAutomationTokenRequest {
+    /// Validate create automation token request
+    fn validate(&self) -> Result<(), ValidationErrors> {
+        let mut errors = ValidationErrors::new();
+        errors.check(!self.name.trim().is_empty(), "name", "Name cannot be empty");
+        if self.scope.endpoints.is_empty() {
+            errors.add("scope.endpoints", "At least one endpoint is required");
+        } else if self.scope.endpoints.iter().any(|e| !e.starts_with('/')) {
+            errors.add("scope.endpoints", "Endpoints must be absolute paths");
+        }
+        errors.check(
+            (1..=730).contains(&self.lifetime_days),
+            "lifetime_days",
+            "Lifetime must be between 1 and 730 days",
+        );
+        errors.into_result()
+    }
+}
+
+/// Newly issued automation token; the secret is only returned once
+#[derive(Debug, Clone, Serialize)]
+pub struct IssuedAutomationToken {
+    #[serde(flatten)]
+    pub automation_token: AutomationToken,
+    pub secret: AuthToken,
+}
+
+/// Seconds an action token stays valid
+pub const ACTION_TOKEN_TTL_SECS: u64 = 300;
+
+/// Maximum length of an action name
+pub const MAX_ACTION_LENGTH: usize = 64;
+
+/// Header carrying the action token confirming a sensitive request
+pub const ACTION_TOKEN_HEADER: &str = "X-Action-Token";
+
+/// Action confirmed before deleting a user
+pub const DELETE_USER_ACTION: &str = "users.delete";
+
+/// Action confirmed before deleting a board
+pub const DELETE_BOARD_ACTION: &str = "boards.delete";
+
+/// Single-use token confirming a sensitive operation
+///
+/// Minted for one action and one identity, e.g. "users.delete", and
+/// consumed by the operation on first use.
+#[derive(Debug, Clone)]
+pub struct ActionToken {
+    pub action: String,
+    /// Actor id of the identity the token was minted for
+    pub owner: String,
+    pub expires_at: DateTime<Utc>,
+}
+
+/// Request to mint an action token
+#[derive(Debug, Deserialize)]
+pub struct CreateActionTokenRequest {
+    pub action: String,
+}
+
+impl Validate for CreateActionTokenRequest {
+    /// Validate create action token request
+    ///
+    /// Actions are non-empty names of at most 64 ASCII letters, digits,
+    /// dots, dashes and underscores.
+    fn validate(&self) -> Result<(), ValidationErrors> {
+        let mut errors = ValidationErrors::new();
+        if self.action.is_empty() {
+            errors.add("action", "Action cannot be empty");
+        } else if self.action.len() > MAX_ACTION_LENGTH {
+            errors.add(
+                "action",
+                format!(
+                    "Action cannot be longer than {} characters",
+                    MAX_ACTION_LENGTH
+                ),
+            );
+        } else if !self
+            .action
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '_'))
+        {
+            errors.add("action", "Action contains invalid characters");
+        }
+        errors.into_result()
+    }
+}
+
+/// Newly minted action token
+#[derive(Debug, Clone, Serialize)]
+pub struct IssuedActionToken {
+    pub token: String,
+    pub action: String,
+    pub expires_at: DateTime<Utc>,
+}
+
+/// Stored credentials of a verified user
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UserCredentials {
+    #[serde(flatten)]
+    pub user: VerifiedUser,
+    /// bcrypt hash of the password
+    pub password_hash: String,
 }
 
 /// Login request for verified users
@@ -145,16 +697,21 @@ pub struct LoginRequest {
     pub password: String,
 }
 
-impl LoginRequest {
+impl Validate for LoginRequest {
     /// Validate login request
-    pub fn validate(&self) -> Result<(), String> {
-        if self.username.is_empty() {
-            return Err("Username cannot be empty".to_string());
-        }
-        if self.password.is_empty() {
-            return Err("Password cannot be empty".to_string());
-        }
-        Ok(())
+    fn validate(&self) -> Result<(), ValidationErrors> {
+        let mut errors = ValidationErrors::new();
+        errors.check(
+            !self.username.is_empty(),
+            "username",
+            "Username cannot be empty",
+        );
+        errors.check(
+            !self.password.is_empty(),
+            "password",
+            "Password cannot be empty",
+        );
+        errors.into_result()
     }
 }
 
@@ -166,21 +723,17 @@ pub struct RegisterRequest {
     pub password: String,
 }
 
-impl RegisterRequest {
+impl Validate for RegisterRequest {
     /// Validate register request
-    pub fn validate(&self) -> Result<(), String> {
-        if self.username.is_empty() {
-            return Err("Username cannot be empty".to_string());
-        }
-        if self.username.len() < 3 {
-            return Err("Username must be at least 3 characters".to_string());
-        }
-        if !self.email.contains('@') {
-            return Err("Invalid email format".to_string());
-        }
-        if self.password.len() < 8 {
-            return Err("Password must be at least 8 characters".to_string());
-        }
-        Ok(())
+    fn validate(&self) -> Result<(), ValidationErrors> {
+        let mut errors = ValidationErrors::new();
+        validate_username(&mut errors, &self.username);
+        errors.check(self.email.contains('@'), "email", "Invalid email format");
+        errors.check(
+            self.password.len() >= 8,
+            "password",
+            "Password must be at least 8 characters",
+        );
+        errors.into_result()
     }
 }
@@ -1,11 +1,20 @@
-use axum::{extract::State, http::StatusCode, response::IntoResponse, Json};
-use serde_json::json;
+use axum::{
+    extract::{Path, State},
+    http::{header::AUTHORIZATION, HeaderMap, StatusCode},
+    response::IntoResponse,
+    Extension, Json,
+};
 
-use crate::features::users::domain::AnonymousUserIdentifier;
-use crate::infrastructure::error::AppError;
+use crate::features::users::domain::{AnonymousUserIdentifier, VerifiedUser};
+use crate::infrastructure::{error::AppError, JsonBody};
 
 use super::{
-    domain::{AuthToken, LoginRequest, RegisterRequest},
+    domain::{
+        ApproveDeviceLoginRequest, AuthToken, ClientFingerprint, CreateActionTokenRequest,
+        CreateAutomationTokenRequest, LoginRequest, PollDeviceLoginRequest, RefreshTokenRequest,
+        RegisterRequest,
+    },
+    middleware::{AuthenticatedUser, AutomationCaller},
     service::AuthService,
 };
 
@@ -32,7 +41,7 @@ use super::{
 /// ```
 pub async fn register(
     State(auth_service): State<AuthService>,
-    Json(request): Json<RegisterRequest>,
+    JsonBody(request): JsonBody<RegisterRequest>,
 ) -> Result<impl IntoResponse, AppError> {
     let user = auth_service.register(request).await?;
     Ok((StatusCode::CREATED, Json(user)))
@@ -54,17 +63,80 @@ pub async fn register(
 /// ```json
 /// {
 ///   "token": "eyJhbGciOiJIUzI1NiIsInR5cCI6IkpXVCJ9...",
-///   "token_type": "Bearer"
+///   "token_type": "Bearer",
+///   "refresh_token": "0f4c2d8e9a7b4c3d8e1f2a3b4c5d6e7f..."
 /// }
 /// ```
+///
+/// When token binding is enabled, the tokens are bound to the requesting client.
 pub async fn login(
     State(auth_service): State<AuthService>,
-    Json(request): Json<LoginRequest>,
+    client: ClientFingerprint,
+    JsonBody(request): JsonBody<LoginRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    let token = auth_service.login(request, Some(&client)).await?;
+    Ok(Json(token))
+}
+
+/// Exchange a refresh token for a new access token
+///
+/// POST /api/v1/auth/refresh
+///
+/// Request body:
+/// ```json
+/// {
+///   "refresh_token": "0f4c2d8e9a7b4c3d8e1f2a3b4c5d6e7f..."
+/// }
+/// ```
+///
+/// Response (200 OK); the refresh token is rotated and the old one can no
+/// longer be used:
+/// ```json
+/// {
+///   "token": "eyJhbGciOiJIUzI1NiIsInR5cCI6IkpXVCJ9...",
+///   "token_type": "Bearer",
+///   "refresh_token": "7a1b9c3d5e2f4a6b8c0d1e2f3a4b5c6d..."
+/// }
+/// ```
+pub async fn refresh(
+    State(auth_service): State<AuthService>,
+    client: ClientFingerprint,
+    JsonBody(request): JsonBody<RefreshTokenRequest>,
 ) -> Result<impl IntoResponse, AppError> {
-    let token = auth_service.login(request).await?;
+    let token = auth_service
+        .refresh(&request.refresh_token, &client)
+        .await?;
     Ok(Json(token))
 }
 
+/// Log out by revoking a refresh token
+///
+/// POST /api/v1/auth/logout
+///
+/// Request body:
+/// ```json
+/// {
+///   "refresh_token": "7a1b9c3d5e2f4a6b8c0d1e2f3a4b5c6d..."
+/// }
+/// ```
+///
+/// Response: 204 No Content. All refresh tokens descending from the same
+/// login are revoked. The access token sent in the `Authorization` header,
+/// if any, is revoked too; other access tokens stay valid until they expire.
+/// 503 Service Unavailable if the revocation could not be shared with the
+/// other instances.
+pub async fn logout(
+    State(auth_service): State<AuthService>,
+    headers: HeaderMap,
+    JsonBody(request): JsonBody<RefreshTokenRequest>,
+) -> Result<StatusCode, AppError> {
+    auth_service.logout(&request.refresh_token).await;
+    if let Some(auth_header) = headers.get(AUTHORIZATION).and_then(|h| h.to_str().ok()) {
+        auth_service.revoke_access_token(auth_header).await?;
+    }
+    Ok(StatusCode::NO_CONTENT)
+}
+
 /// Get an authentication token for an anonymous user
 ///
 /// POST /api/v1/auth/anonymous
@@ -86,14 +158,132 @@ pub async fn login(
 ///   "token_type": "Bearer"
 /// }
 /// ```
+///
+/// When token binding is enabled, the token is bound to the requesting client.
 pub async fn anonymous_token(
     State(auth_service): State<AuthService>,
-    Json(identifier): Json<AnonymousUserIdentifier>,
+    client: ClientFingerprint,
+    JsonBody(identifier): JsonBody<AnonymousUserIdentifier>,
 ) -> Result<impl IntoResponse, AppError> {
-    let token = auth_service.generate_anonymous_user_token(&identifier)?;
+    let token = auth_service
+        .generate_anonymous_user_token(&identifier, Some(&client))
+        .await?;
     Ok(Json(AuthToken::bearer(token)))
 }
 
+/// Start a device login on a shared workstation
+///
+/// POST /api/v1/auth/device-login/start
+///
+/// Response (201 Created):
+/// ```json
+/// {
+///   "code": "3f2b9c0e8d7a4b6c9e1f0a2b3c4d5e6f",
+///   "poll_token": "9a8b7c6d5e4f40312a1b2c3d4e5f6a7b",
+///   "qr_payload": "webboard://device-login?code=3f2b9c0e8d7a4b6c9e1f0a2b3c4d5e6f",
+///   "expires_at": "2024-01-01T00:05:00Z"
+/// }
+/// ```
+///
+/// The workstation shows `qr_payload` as a QR code and keeps `poll_token` secret.
+pub async fn device_login_start(
+    State(auth_service): State<AuthService>,
+    client: ClientFingerprint,
+) -> impl IntoResponse {
+    let start = auth_service.start_device_login(client).await;
+    (StatusCode::CREATED, Json(start))
+}
+
+/// Approve a device login from a logged-in device
+///
+/// POST /api/v1/auth/device-login/approve
+///
+/// Requires authentication via Authorization header. The workstation is
+/// logged in as the approving user.
+///
+/// Request body:
+/// ```json
+/// {
+///   "code": "3f2b9c0e8d7a4b6c9e1f0a2b3c4d5e6f"
+/// }
+/// ```
+///
+/// Response: 204 No Content
+pub async fn device_login_approve(
+    State(auth_service): State<AuthService>,
+    user: AuthenticatedUser,
+    JsonBody(request): JsonBody<ApproveDeviceLoginRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    auth_service
+        .approve_device_login(&user.0, &request.code)
+        .await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Poll a device login from the workstation
+///
+/// POST /api/v1/auth/device-login/poll
+///
+/// Request body:
+/// ```json
+/// {
+///   "poll_token": "9a8b7c6d5e4f40312a1b2c3d4e5f6a7b"
+/// }
+/// ```
+///
+/// Response (200 OK) while waiting for approval:
+/// ```json
+/// { "status": "pending" }
+/// ```
+///
+/// Response (200 OK) once approved; the token is returned only once:
+/// ```json
+/// {
+///   "status": "approved",
+///   "token": "eyJhbGciOiJIUzI1NiIsInR5cCI6IkpXVCJ9...",
+///   "token_type": "Bearer"
+/// }
+/// ```
+pub async fn device_login_poll(
+    State(auth_service): State<AuthService>,
+    JsonBody(request): JsonBody<PollDeviceLoginRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    let status = auth_service.poll_device_login(&request.poll_token).await?;
+    Ok(Json(status))
+}
+
+/// Mint a single-use action token
+///
+/// POST /api/v1/auth/action-tokens
+///
+/// Requires authentication via Authorization header. The token confirms one
+/// sensitive operation of the authenticated user, such as deleting their
+/// account, and is consumed by that operation.
+///
+/// Request body:
+/// ```json
+/// {
+///   "action": "account.delete"
+/// }
+/// ```
+///
+/// Response (201 Created):
+/// ```json
+/// {
+///   "token": "7d1e2f3a4b5c46d7e8f9a0b1c2d3e4f5",
+///   "action": "account.delete",
+///   "expires_at": "2024-01-01T00:05:00Z"
+/// }
+/// ```
+pub async fn create_action_token(
+    State(auth_service): State<AuthService>,
+    user: AuthenticatedUser,
+    JsonBody(request): JsonBody<CreateActionTokenRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    let issued = auth_service.issue_action_token(&user.0, request)?;
+    Ok((StatusCode::CREATED, Json(issued)))
+}
+
 /// Get current authenticated user info
 ///
 /// GET /api/v1/auth/me
@@ -121,11 +311,125 @@ pub async fn anonymous_token(
 /// }
 /// ```
 pub async fn me(
-    user: super::middleware::AuthenticatedUser,
+    user: AuthenticatedUser,
 ) -> Result<impl IntoResponse, AppError> {
     Ok(Json(user.0))
 }
 
+/// Create an automation token
+///
+/// POST /api/v1/admin/automation-tokens
+///
+/// Requires the admin role. The token acts on behalf of the creating user,
+/// with the role the user currently has, limited to its scope. Automation
+/// tokens cannot manage automation tokens.
+///
+/// Request body:
+/// ```json
+/// {
+///   "name": "nightly usage export",
+///   "scope": {
+///     "endpoints": ["/api/v1/admin/usage"],
+///     "read_only": true,
+///     "allowed_ips": ["10.0.0.5"]
+///   },
+///   "lifetime_days": 365
+/// }
+/// ```
+///
+/// Response (201 Created); the secret is returned only once:
+/// ```json
+/// {
+///   "id": "5c1e0f3a9b8d4c2e8f7a6b5c4d3e2f1a",
+///   "name": "nightly usage export",
+///   "scope": { "endpoints": ["/api/v1/admin/usage"], "read_only": true, "allowed_ips": ["10.0.0.5"] },
+///   "owner": { "id": 1, "username": "john", "email": "john@example.com" },
+///   "lifetime_days": 365,
+///   "created_at": "2024-01-01T00:00:00Z",
+///   "rotated_at": null,
+///   "expires_at": "2025-01-01T00:00:00Z",
+///   "secret": {
+///     "token": "eyJhbGciOiJIUzI1NiIsInR5cCI6ImF1dG9tYXRpb24rand0In0...",
+///     "token_type": "Bearer"
+///   }
+/// }
+/// ```
+pub async fn create_automation_token(
+    State(auth_service): State<AuthService>,
+    user: AuthenticatedUser,
+    caller: Option<Extension<AutomationCaller>>,
+    JsonBody(request): JsonBody<CreateAutomationTokenRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    let owner = require_token_manager(&user, caller.as_ref())?;
+    let issued = auth_service.create_automation_token(owner, request).await?;
+    Ok((StatusCode::CREATED, Json(issued)))
+}
+
+/// List automation tokens
+///
+/// GET /api/v1/admin/automation-tokens
+///
+/// Requires the admin role. Secrets are never listed.
+pub async fn list_automation_tokens(
+    State(auth_service): State<AuthService>,
+    user: AuthenticatedUser,
+    caller: Option<Extension<AutomationCaller>>,
+) -> Result<impl IntoResponse, AppError> {
+    let manager = require_token_manager(&user, caller.as_ref())?;
+    Ok(Json(auth_service.list_automation_tokens(manager).await))
+}
+
+/// Rotate an automation token
+///
+/// POST /api/v1/admin/automation-tokens/:id/rotate
+///
+/// Requires the admin role. Returns a new secret, in the same format as on
+/// creation; previous secrets stop working immediately.
+pub async fn rotate_automation_token(
+    State(auth_service): State<AuthService>,
+    user: AuthenticatedUser,
+    caller: Option<Extension<AutomationCaller>>,
+    Path(id): Path<String>,
+) -> Result<impl IntoResponse, AppError> {
+    let manager = require_token_manager(&user, caller.as_ref())?;
+    Ok(Json(
+        auth_service.rotate_automation_token(manager, &id).await?,
+    ))
+}
+
+/// Revoke an automation token
+///
+/// DELETE /api/v1/admin/automation-tokens/:id
+///
+/// Requires the admin role.
+///
+/// Response: 204 No Content
+pub async fn revoke_automation_token(
+    State(auth_service): State<AuthService>,
+    user: AuthenticatedUser,
+    caller: Option<Extension<AutomationCaller>>,
+    Path(id): Path<String>,
+) -> Result<impl IntoResponse, AppError> {
+    let manager = require_token_manager(&user, caller.as_ref())?;
+    auth_service.revoke_automation_token(manager, &id).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Check that the caller may manage automation tokens
+fn require_token_manager<'a>(
+    user: &'a AuthenticatedUser,
+    caller: Option<&Extension<AutomationCaller>>,
+) -> Result<&'a VerifiedUser, AppError> {
+    if caller.is_some() {
+        return Err(AppError::Forbidden(
+            "Automation tokens cannot manage automation tokens".to_string(),
+        ));
+    }
+    user.0.as_verified().ok_or_else(|| {
+        AppError::Forbidden("Only verified users can manage automation tokens".to_string())
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -140,11 +444,14 @@ mod tests {
     use tower::util::ServiceExt;
 
     fn create_test_app() -> Router {
-        let auth_service = AuthService::new("test_secret".to_string());
+        // Lowest bcrypt cost, keeps the tests fast
+        let auth_service = AuthService::new("test_secret".to_string()).with_password_hash_cost(4);
 
         Router::new()
             .route("/auth/register", post(register))
             .route("/auth/login", post(login))
+            .route("/auth/refresh", post(refresh))
+            .route("/auth/logout", post(logout))
             .route("/auth/anonymous", post(anonymous_token))
             .route(
                 "/auth/me",
@@ -156,6 +463,21 @@ mod tests {
             .with_state(auth_service)
     }
 
+    /// Register "testuser" / "password123"
+    async fn register_test_user(app: &Router) {
+        let request = Request::builder()
+            .uri("/auth/register")
+            .method("POST")
+            .header("content-type", "application/json")
+            .body(Body::from(
+                r#"{"username":"testuser","email":"test@example.com","password":"password123"}"#,
+            ))
+            .unwrap();
+
+        let response = app.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::CREATED);
+    }
+
     #[tokio::test]
     async fn test_register_endpoint() {
         let app = create_test_app();
@@ -176,6 +498,7 @@ mod tests {
     #[tokio::test]
     async fn test_login_endpoint() {
         let app = create_test_app();
+        register_test_user(&app).await;
 
         let request = Request::builder()
             .uri("/auth/login")
@@ -186,10 +509,100 @@ mod tests {
             ))
             .unwrap();
 
+        let response = app.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let request = Request::builder()
+            .uri("/auth/login")
+            .method("POST")
+            .header("content-type", "application/json")
+            .body(Body::from(
+                r#"{"username":"testuser","password":"wrong-password"}"#,
+            ))
+            .unwrap();
+
+        let response = app.oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    }
+
+    #[tokio::test]
+    async fn test_refresh_endpoint() {
+        let app = create_test_app();
+        register_test_user(&app).await;
+
+        let request = Request::builder()
+            .uri("/auth/login")
+            .method("POST")
+            .header("content-type", "application/json")
+            .body(Body::from(
+                r#"{"username":"testuser","password":"password123"}"#,
+            ))
+            .unwrap();
+        let response = app.clone().oneshot(request).await.unwrap();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let token: AuthToken = serde_json::from_slice(&body).unwrap();
+
+        let request = Request::builder()
+            .uri("/auth/refresh")
+            .method("POST")
+            .header("content-type", "application/json")
+            .body(Body::from(format!(
+                r#"{{"refresh_token":"{}"}}"#,
+                token.refresh_token.unwrap()
+            )))
+            .unwrap();
+
         let response = app.oneshot(request).await.unwrap();
         assert_eq!(response.status(), StatusCode::OK);
     }
 
+    #[tokio::test]
+    async fn test_logout_revokes_access_token() {
+        let app = create_test_app();
+        register_test_user(&app).await;
+
+        let request = Request::builder()
+            .uri("/auth/login")
+            .method("POST")
+            .header("content-type", "application/json")
+            .body(Body::from(
+                r#"{"username":"testuser","password":"password123"}"#,
+            ))
+            .unwrap();
+        let response = app.clone().oneshot(request).await.unwrap();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let token: AuthToken = serde_json::from_slice(&body).unwrap();
+        let me = || {
+            Request::builder()
+                .uri("/auth/me")
+                .header("Authorization", format!("Bearer {}", token.token))
+                .body(Body::empty())
+                .unwrap()
+        };
+        let response = app.clone().oneshot(me()).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let request = Request::builder()
+            .uri("/auth/logout")
+            .method("POST")
+            .header("content-type", "application/json")
+            .header("Authorization", format!("Bearer {}", token.token))
+            .body(Body::from(format!(
+                r#"{{"refresh_token":"{}"}}"#,
+                token.refresh_token.clone().unwrap()
+            )))
+            .unwrap();
+        let response = app.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::NO_CONTENT);
+
+        let response = app.oneshot(me()).await.unwrap();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    }
+
     #[tokio::test]
     async fn test_anonymous_token_endpoint() {
         let app = create_test_app();
@@ -217,7 +630,8 @@ mod tests {
             department_code: "D001".to_string(),
         };
         let token = auth_service
-            .generate_anonymous_user_token(&identifier)
+            .generate_anonymous_user_token(&identifier, None)
+            .await
             .unwrap();
 
         let app = create_test_app();
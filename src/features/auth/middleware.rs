@@ -1,23 +1,61 @@
 use axum::{
-    extract::{Request, State},
-    http::StatusCode,
+    extract::{ConnectInfo, OriginalUri, Query, Request, State},
+    http::{
+        header::{SEC_WEBSOCKET_PROTOCOL, USER_AGENT},
+        Extensions, HeaderMap, StatusCode,
+    },
     middleware::Next,
     response::{IntoResponse, Response},
 };
 use serde_json::json;
+use std::collections::HashMap;
+use std::net::{IpAddr, SocketAddr};
 
-use crate::features::users::domain::UserIdentity;
+use crate::features::users::domain::{Role, UserIdentity};
+use crate::infrastructure::audit::{AuditActor, AuditContext};
+use crate::infrastructure::error::AppError;
+use crate::infrastructure::geoip::GeoLocation;
 
+use super::domain::{AutomationRequest, ClientFingerprint, ACTION_TOKEN_HEADER};
 use super::service::AuthService;
 
+/// Header carrying an optional client-generated device id
+const DEVICE_ID_HEADER: &str = "X-Device-Id";
+
+/// Query parameter carrying the token of a WebSocket upgrade request
+const ACCESS_TOKEN_PARAM: &str = "access_token";
+
+/// Prefix of the `Sec-WebSocket-Protocol` entry carrying the token of a
+/// WebSocket upgrade request
+const TOKEN_PROTOCOL_PREFIX: &str = "bearer.";
+
 /// Extension type for storing authenticated user in request
 #[derive(Clone, Debug)]
 pub struct AuthenticatedUser(pub UserIdentity);
 
+/// Action token sent with a sensitive request, see `AuthService::consume_action_token`
+#[derive(Clone, Debug)]
+pub struct ActionTokenHeader(pub String);
+
+/// Extension type holding the id (`jti`) of the access token a request was
+/// authenticated with
+///
+/// Recorded with audit entries so they can be correlated with the token.
+#[derive(Clone, Debug)]
+pub struct AccessTokenId(pub String);
+
+/// Extension type marking requests authenticated with an automation token
+///
+/// Holds the automation token id. The `AuthenticatedUser` of such requests
+/// is the owner of the token.
+#[derive(Clone, Debug)]
+pub struct AutomationCaller(pub String);
+
 /// Authentication middleware
 ///
 /// Extracts and validates JWT token from Authorization header.
 /// Adds UserIdentity to request extensions if authentication succeeds.
+/// Automation tokens are accepted too, within their scope.
 pub async fn auth_middleware(
     State(auth_service): State<AuthService>,
     mut request: Request,
@@ -40,11 +78,68 @@ pub async fn auth_middleware(
             .into_response();
     };
 
-    // Extract user from header
-    match auth_service.extract_user_from_header(auth_header) {
-        Ok(user_identity) => {
-            // Add user to request extensions
+    // Automation tokens are checked against their scope instead of the token binding
+    if auth_service.is_automation_token(auth_header) {
+        let path = request
+            .extensions()
+            .get::<OriginalUri>()
+            .map(|OriginalUri(uri)| uri.path())
+            .unwrap_or_else(|| request.uri().path());
+        let target = AutomationRequest {
+            method: request.method().as_str(),
+            path,
+            ip: client_ip(request.extensions()),
+        };
+
+        return match auth_service
+            .authenticate_automation(auth_header, &target)
+            .await
+        {
+            Ok(automation_token) => {
+                request
+                    .extensions_mut()
+                    .insert(AuthenticatedUser(UserIdentity::Verified(
+                        automation_token.owner,
+                    )));
+                request
+                    .extensions_mut()
+                    .insert(AutomationCaller(automation_token.id));
+                next.run(request).await
+            }
+            Err(e) => {
+                let status = match e {
+                    AppError::Forbidden(_) => StatusCode::FORBIDDEN,
+                    _ => StatusCode::UNAUTHORIZED,
+                };
+                (
+                    status,
+                    axum::Json(json!({
+                        "error": format!("Authentication failed: {}", e)
+                    })),
+                )
+                    .into_response()
+            }
+        };
+    }
+
+    // Extract user from header, checking the token binding
+    let client = client_fingerprint(request.headers(), request.extensions());
+    match auth_service.authenticate(auth_header, &client) {
+        Ok(claims) => {
+            let user_identity = claims.to_user_identity();
+            auth_service
+                .record_client_ip(
+                    &user_identity,
+                    client_ip(request.extensions()),
+                    client_location(request.extensions()),
+                )
+                .await;
+
+            // Add user and token id to request extensions
             request.extensions_mut().insert(AuthenticatedUser(user_identity));
+            request
+                .extensions_mut()
+                .insert(AccessTokenId(claims.jti().to_string()));
             next.run(request).await
         }
         Err(e) => {
@@ -76,14 +171,179 @@ pub async fn optional_auth_middleware(
 
     // Try to extract user if header is present
     if let Some(auth_header) = auth_header {
-        if let Ok(user_identity) = auth_service.extract_user_from_header(auth_header) {
+        let client = client_fingerprint(request.headers(), request.extensions());
+        if let Ok(claims) = auth_service.authenticate(auth_header, &client) {
+            let user_identity = claims.to_user_identity();
+            auth_service
+                .record_client_ip(
+                    &user_identity,
+                    client_ip(request.extensions()),
+                    client_location(request.extensions()),
+                )
+                .await;
             request.extensions_mut().insert(AuthenticatedUser(user_identity));
+            request
+                .extensions_mut()
+                .insert(AccessTokenId(claims.jti().to_string()));
         }
     }
 
     next.run(request).await
 }
 
+/// Authentication middleware for WebSocket upgrade requests
+///
+/// Browsers cannot set headers on WebSocket upgrade requests, so besides the
+/// `Authorization` header the token is accepted from the `access_token` query
+/// parameter or as a `bearer.<token>` entry of the `Sec-WebSocket-Protocol`
+/// header. Connections without a token stay anonymous; a token that fails
+/// verification is rejected with 401 instead of silently being ignored.
+pub async fn websocket_auth_middleware(
+    State(auth_service): State<AuthService>,
+    mut request: Request,
+    next: Next,
+) -> Response {
+    let Some(auth_header) = websocket_auth_header(&request) else {
+        return next.run(request).await;
+    };
+
+    let client = client_fingerprint(request.headers(), request.extensions());
+    match auth_service.authenticate(&auth_header, &client) {
+        Ok(claims) => {
+            let user_identity = claims.to_user_identity();
+            auth_service
+                .record_client_ip(
+                    &user_identity,
+                    client_ip(request.extensions()),
+                    client_location(request.extensions()),
+                )
+                .await;
+            request
+                .extensions_mut()
+                .insert(AuthenticatedUser(user_identity));
+            request
+                .extensions_mut()
+                .insert(AccessTokenId(claims.jti().to_string()));
+            next.run(request).await
+        }
+        Err(e) => (
+            StatusCode::UNAUTHORIZED,
+            axum::Json(json!({
+                "error": format!("Authentication failed: {}", e)
+            })),
+        )
+            .into_response(),
+    }
+}
+
+/// Authorization header value of a WebSocket upgrade request, wherever the
+/// client put the token
+fn websocket_auth_header(request: &Request) -> Option<String> {
+    if let Some(auth_header) = request
+        .headers()
+        .get("Authorization")
+        .and_then(|h| h.to_str().ok())
+    {
+        return Some(auth_header.to_string());
+    }
+
+    let query_token = Query::<HashMap<String, String>>::try_from_uri(request.uri())
+        .ok()
+        .and_then(|Query(mut params)| params.remove(ACCESS_TOKEN_PARAM));
+    let protocol_token = || {
+        request
+            .headers()
+            .get_all(SEC_WEBSOCKET_PROTOCOL)
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .flat_map(|value| value.split(','))
+            .find_map(|protocol| protocol.trim().strip_prefix(TOKEN_PROTOCOL_PREFIX))
+            .map(str::to_string)
+    };
+
+    query_token
+        .or_else(protocol_token)
+        .map(|token| format!("Bearer {}", token))
+}
+
+/// Role-based authorization middleware
+///
+/// Rejects requests whose authenticated user does not have at least the
+/// role given as state with 403. Must run after `auth_middleware`.
+///
+/// ```rust,ignore
+/// .route_layer(middleware::from_fn_with_state(Role::Admin, require_role))
+/// .route_layer(middleware::from_fn_with_state(auth_service, auth_middleware))
+/// ```
+pub async fn require_role(State(role): State<Role>, request: Request, next: Next) -> Response {
+    let Some(AuthenticatedUser(user_identity)) = request.extensions().get::<AuthenticatedUser>()
+    else {
+        return (
+            StatusCode::UNAUTHORIZED,
+            axum::Json(json!({
+                "error": "Authentication required"
+            })),
+        )
+            .into_response();
+    };
+
+    if !user_identity.has_role(role) {
+        return (
+            StatusCode::FORBIDDEN,
+            axum::Json(json!({
+                "error": "Insufficient role"
+            })),
+        )
+            .into_response();
+    }
+
+    next.run(request).await
+}
+
+/// Compute the fingerprint of the client sending a request
+///
+/// The client IP is only available when the server is started with
+/// `into_make_service_with_connect_info::<SocketAddr>()`.
+fn client_fingerprint(headers: &HeaderMap, extensions: &Extensions) -> ClientFingerprint {
+    let header = |name| headers.get(name).and_then(|value| value.to_str().ok());
+
+    ClientFingerprint::new(
+        header(DEVICE_ID_HEADER),
+        header(USER_AGENT.as_str()),
+        client_ip(extensions),
+    )
+}
+
+/// IP address of the client sending a request, if known
+fn client_ip(extensions: &Extensions) -> Option<IpAddr> {
+    extensions
+        .get::<ConnectInfo<SocketAddr>>()
+        .map(|ConnectInfo(addr)| addr.ip())
+}
+
+/// Location of the client sending a request, if the GeoIP middleware found it
+fn client_location(extensions: &Extensions) -> Option<GeoLocation> {
+    extensions.get::<GeoLocation>().cloned()
+}
+
+/// Extractor for the fingerprint of the requesting client
+///
+/// Used when issuing tokens so they can be bound to the client. Never rejects.
+#[axum::async_trait]
+impl<S> axum::extract::FromRequestParts<S> for ClientFingerprint
+where
+    S: Send + Sync,
+{
+    type Rejection = std::convert::Infallible;
+
+    async fn from_request_parts(
+        parts: &mut axum::http::request::Parts,
+        _state: &S,
+    ) -> Result<Self, Self::Rejection> {
+        Ok(client_fingerprint(&parts.headers, &parts.extensions))
+    }
+}
+
 /// Extractor for authenticated user
 ///
 /// Use this in handlers to get the authenticated user.
@@ -114,6 +374,74 @@ where
     }
 }
 
+/// Extractor for the action token of a request, from the `X-Action-Token`
+/// header
+///
+/// Fails with 403 if the header is missing, like an invalid token.
+#[axum::async_trait]
+impl<S> axum::extract::FromRequestParts<S> for ActionTokenHeader
+where
+    S: Send + Sync,
+{
+    type Rejection = AppError;
+
+    async fn from_request_parts(
+        parts: &mut axum::http::request::Parts,
+        _state: &S,
+    ) -> Result<Self, Self::Rejection> {
+        parts
+            .headers
+            .get(ACTION_TOKEN_HEADER)
+            .and_then(|token| token.to_str().ok())
+            .map(|token| ActionTokenHeader(token.to_string()))
+            .ok_or_else(|| {
+                AppError::Forbidden(format!("{} header is required", ACTION_TOKEN_HEADER))
+            })
+    }
+}
+
+/// Extractor for the audit context of a request
+///
+/// Combines the authenticated user (if any) and the id of their access token
+/// with the request id assigned by the request-id layer and the location
+/// added by the GeoIP middleware.
+/// Never rejects; missing parts are simply `None`.
+#[axum::async_trait]
+impl<S> axum::extract::FromRequestParts<S> for AuditContext
+where
+    S: Send + Sync,
+{
+    type Rejection = std::convert::Infallible;
+
+    async fn from_request_parts(
+        parts: &mut axum::http::request::Parts,
+        _state: &S,
+    ) -> Result<Self, Self::Rejection> {
+        let actor = parts
+            .extensions
+            .get::<AuthenticatedUser>()
+            .map(|user| AuditActor::from(&user.0));
+        let request_id = parts
+            .extensions
+            .get::<tower_http::request_id::RequestId>()
+            .and_then(|id| id.header_value().to_str().ok())
+            .map(str::to_string);
+        let location = client_location(&parts.extensions);
+        let token_id = parts
+            .extensions
+            .get::<AccessTokenId>()
+            .map(|AccessTokenId(jti)| jti.clone())
+            .filter(|jti| !jti.is_empty());
+
+        Ok(AuditContext {
+            actor,
+            request_id,
+            location,
+            token_id,
+        })
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -124,7 +452,8 @@ mod tests {
         Router,
     };
     use tower::util::ServiceExt;
-    use crate::features::users::domain::VerifiedUser;
+    use crate::features::users::domain::{AnonymousUserIdentifier, VerifiedUser};
+    use chrono::NaiveDate;
 
     async fn test_handler(user: AuthenticatedUser) -> impl IntoResponse {
         axum::Json(json!({
@@ -153,8 +482,11 @@ mod tests {
             id: 1,
             username: "testuser".to_string(),
             email: "test@example.com".to_string(),
+            role: Role::Member,
         };
-        let token = auth_service.generate_verified_user_token(&user).unwrap();
+        let token = auth_service
+            .generate_verified_user_token(&user, None)
+            .unwrap();
 
         let app = Router::new()
             .route("/protected", get(test_handler))
@@ -174,6 +506,45 @@ mod tests {
         assert_eq!(response.status(), StatusCode::OK);
     }
 
+    #[tokio::test]
+    async fn test_auth_middleware_rejects_token_from_other_device() {
+        let auth_service = AuthService::new("test_secret".to_string())
+            .with_token_binding(super::super::domain::TokenBinding::Strict);
+        let user = VerifiedUser {
+            id: 1,
+            username: "testuser".to_string(),
+            email: "test@example.com".to_string(),
+            role: Role::Member,
+        };
+        let device = ClientFingerprint::new(Some("device-1"), None, None);
+        let token = auth_service
+            .generate_verified_user_token(&user, Some(&device))
+            .unwrap();
+
+        let app = Router::new()
+            .route("/protected", get(test_handler))
+            .layer(middleware::from_fn_with_state(
+                auth_service.clone(),
+                auth_middleware,
+            ))
+            .with_state(auth_service);
+
+        for (device_id, expected) in [
+            ("device-1", StatusCode::OK),
+            ("device-2", StatusCode::UNAUTHORIZED),
+        ] {
+            let request = Request::builder()
+                .uri("/protected")
+                .header("Authorization", format!("Bearer {}", token))
+                .header(DEVICE_ID_HEADER, device_id)
+                .body(Body::empty())
+                .unwrap();
+
+            let response = app.clone().oneshot(request).await.unwrap();
+            assert_eq!(response.status(), expected);
+        }
+    }
+
     #[tokio::test]
     async fn test_auth_middleware_without_token() {
         let auth_service = AuthService::new("test_secret".to_string());
@@ -195,6 +566,131 @@ mod tests {
         assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
     }
 
+    #[tokio::test]
+    async fn test_require_role() {
+        let auth_service = AuthService::new("test_secret".to_string());
+        let member = VerifiedUser {
+            id: 1,
+            username: "member".to_string(),
+            email: "member@example.com".to_string(),
+            role: Role::Member,
+        };
+        let admin = VerifiedUser {
+            id: 2,
+            username: "admin".to_string(),
+            email: "admin@example.com".to_string(),
+            role: Role::Admin,
+        };
+        let anonymous = AnonymousUserIdentifier {
+            hospital_code: "H001".to_string(),
+            user_id: "U001".to_string(),
+            user_start_date: NaiveDate::from_ymd_opt(2024, 1, 1).unwrap(),
+            department_code: "D001".to_string(),
+        };
+
+        let app = Router::new()
+            .route("/members", get(test_handler))
+            .route_layer(middleware::from_fn_with_state(Role::Member, require_role))
+            .merge(
+                Router::new()
+                    .route("/admins", get(test_handler))
+                    .route_layer(middleware::from_fn_with_state(Role::Admin, require_role)),
+            )
+            .layer(middleware::from_fn_with_state(
+                auth_service.clone(),
+                auth_middleware,
+            ))
+            .with_state(auth_service.clone());
+
+        let member_token = auth_service
+            .generate_verified_user_token(&member, None)
+            .unwrap();
+        let admin_token = auth_service
+            .generate_verified_user_token(&admin, None)
+            .unwrap();
+        let anonymous_token = auth_service
+            .generate_anonymous_user_token(&anonymous, None)
+            .await
+            .unwrap();
+
+        for (uri, token, expected) in [
+            ("/members", &anonymous_token, StatusCode::FORBIDDEN),
+            ("/members", &member_token, StatusCode::OK),
+            ("/admins", &member_token, StatusCode::FORBIDDEN),
+            ("/admins", &admin_token, StatusCode::OK),
+        ] {
+            let request = Request::builder()
+                .uri(uri)
+                .header("Authorization", format!("Bearer {}", token))
+                .body(Body::empty())
+                .unwrap();
+
+            let response = app.clone().oneshot(request).await.unwrap();
+            assert_eq!(response.status(), expected, "{} {}", uri, token);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_websocket_auth_middleware() {
+        let auth_service = AuthService::new("test_secret".to_string());
+        let user = VerifiedUser {
+            id: 1,
+            username: "testuser".to_string(),
+            email: "test@example.com".to_string(),
+            role: Role::Member,
+        };
+        let token = auth_service
+            .generate_verified_user_token(&user, None)
+            .unwrap();
+
+        let app = Router::new()
+            .route("/live", get(test_optional_handler))
+            .layer(middleware::from_fn_with_state(
+                auth_service.clone(),
+                websocket_auth_middleware,
+            ))
+            .with_state(auth_service);
+        let call = |request: Request| {
+            let app = app.clone();
+            async move {
+                let response = app.oneshot(request).await.unwrap();
+                let status = response.status();
+                let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+                    .await
+                    .unwrap();
+                let body: serde_json::Value = serde_json::from_slice(&body).unwrap_or_default();
+                (status, body["authenticated"] == true)
+            }
+        };
+
+        // Token in the query string
+        let request = Request::builder()
+            .uri(format!("/live?access_token={}", token))
+            .body(Body::empty())
+            .unwrap();
+        assert_eq!(call(request).await, (StatusCode::OK, true));
+
+        // Token offered as a subprotocol
+        let request = Request::builder()
+            .uri("/live")
+            .header(
+                "Sec-WebSocket-Protocol",
+                format!("jsonrpc, bearer.{}", token),
+            )
+            .body(Body::empty())
+            .unwrap();
+        assert_eq!(call(request).await, (StatusCode::OK, true));
+
+        // No token is anonymous, an invalid one is rejected
+        let request = Request::builder().uri("/live").body(Body::empty()).unwrap();
+        assert_eq!(call(request).await, (StatusCode::OK, false));
+        let request = Request::builder()
+            .uri("/live?access_token=invalid")
+            .body(Body::empty())
+            .unwrap();
+        assert_eq!(call(request).await.0, StatusCode::UNAUTHORIZED);
+    }
+
     #[tokio::test]
     async fn test_optional_auth_middleware_without_token() {
         let auth_service = AuthService::new("test_secret".to_string());
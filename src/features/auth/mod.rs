@@ -1,42 +1,64 @@
-/// Authentication feature module
-///
-/// Provides authentication and authorization functionality for both
-/// verified and anonymous users.
-///
-/// ## Features
-///
-/// - JWT-based authentication
-/// - Support for verified users (with credentials)
-/// - Support for anonymous users (identified by composite key)
-/// - Authentication middleware for request validation
-/// - Token generation and verification
-///
-/// ## Usage
-///
-/// ```rust,ignore
-/// use crate::features::auth::{AuthService, middleware::auth_middleware};
-///
-/// // Create auth service
-/// let auth_service = AuthService::new("your-secret-key".to_string());
-///
-/// // Generate token for verified user
-/// let token = auth_service.generate_verified_user_token(&user)?;
-///
-/// // Apply authentication middleware to routes
-/// let protected_routes = Router::new()
-///     .route("/protected", get(handler))
-///     .layer(middleware::from_fn_with_state(
-///         auth_service.clone(),
-///         auth_middleware,
-///     ));
-/// ```
+//! Authentication feature module
+//!
+//! Provides authentication and authorization functionality for both
+//! verified and anonymous users.
+//!
+//! ## Features
+//!
+//! - JWT-based authentication
+//! - Support for verified users (with credentials)
+//! - Support for anonymous users (identified by composite key)
+//! - Authentication middleware for request validation
+//! - Role-based authorization (admin, member, anonymous)
+//! - Token generation and verification
+//! - HS256, RS256 or ES256 signing keys, rotated without invalidating tokens
+//! - Rotating refresh tokens, revoked on logout
+//! - Blacklist of revoked access tokens, optionally shared through Redis
+//! - User repository shared with the users feature
+//! - Optional binding of tokens to the client they were issued to
+//! - QR-code device login for shared workstations
+//! - Scoped, long-lived automation tokens for scripts and integrations
+//! - Single-use action tokens confirming sensitive operations
+//!
+//! ## Usage
+//!
+//! ```rust,ignore
+//! use crate::features::auth::{AuthService, middleware::auth_middleware};
+//!
+//! // Create auth service
+//! let auth_service = AuthService::new("your-secret-key".to_string());
+//!
+//! // Generate token for verified user
+//! let token = auth_service.generate_verified_user_token(&user, None)?;
+//!
+//! // Apply authentication middleware to routes
+//! let protected_routes = Router::new()
+//!     .route("/protected", get(handler))
+//!     .layer(middleware::from_fn_with_state(
+//!         auth_service.clone(),
+//!         auth_middleware,
+//!     ));
+//! ```
 
+pub mod blacklist;
 pub mod domain;
 pub mod handler;
+pub mod keys;
 pub mod middleware;
+pub mod repository;
 pub mod service;
 
+pub use blacklist::TokenBlacklist;
 pub use domain::*;
-pub use handler::{anonymous_token, login, me, register};
-pub use middleware::{auth_middleware, optional_auth_middleware, AuthenticatedUser};
+pub use handler::{
+    anonymous_token, create_action_token, create_automation_token, device_login_approve,
+    device_login_poll, device_login_start, list_automation_tokens, login, logout, me, refresh,
+    register, revoke_automation_token, rotate_automation_token,
+};
+pub use keys::JwtKeys;
+pub use middleware::{
+    auth_middleware, optional_auth_middleware, require_role, websocket_auth_middleware,
+    AccessTokenId, ActionTokenHeader, AuthenticatedUser, AutomationCaller,
+};
+pub use repository::UserRepository;
 pub use service::AuthService;
@@ -0,0 +1,159 @@
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use tokio::sync::RwLock;
+
+use crate::features::users::domain::{Role, VerifiedUser};
+use crate::infrastructure::error::AppError;
+
+use super::domain::UserCredentials;
+
+/// Registered users with their credentials
+///
+/// Shared by the auth service, which registers and logs in users, and the
+/// user service, which manages them. Usernames and emails are unique;
+/// emails are compared ignoring case. Clones share the same users.
+#[derive(Clone)]
+pub struct UserRepository {
+    /// Credentials by username
+    credentials: Arc<RwLock<HashMap<String, UserCredentials>>>,
+    next_id: Arc<AtomicU64>,
+}
+
+impl UserRepository {
+    /// Create a repository without users
+    pub fn new() -> Self {
+        Self {
+            credentials: Arc::new(RwLock::new(HashMap::new())),
+            next_id: Arc::new(AtomicU64::new(1)),
+        }
+    }
+
+    /// Credentials of the user with the given username
+    pub async fn find_by_username(&self, username: &str) -> Option<UserCredentials> {
+        self.credentials.read().await.get(username).cloned()
+    }
+
+    /// The user with the given id
+    pub async fn find(&self, id: u64) -> Option<VerifiedUser> {
+        self.credentials
+            .read()
+            .await
+            .values()
+            .find(|credentials| credentials.user.id == id)
+            .map(|credentials| credentials.user.clone())
+    }
+
+    /// Credentials of all users, by id
+    pub async fn list(&self) -> Vec<UserCredentials> {
+        let mut credentials: Vec<UserCredentials> =
+            self.credentials.read().await.values().cloned().collect();
+        credentials.sort_by_key(|credentials| credentials.user.id);
+        credentials
+    }
+
+    /// Reject a username or email that is already taken
+    pub async fn ensure_unique(&self, username: &str, email: &str) -> Result<(), AppError> {
+        Self::check_unique(&*self.credentials.read().await, username, email)
+    }
+
+    /// Add a user with the next free id
+    ///
+    /// Fails with `Conflict` if the username or email is already taken.
+    pub async fn insert(
+        &self,
+        username: String,
+        email: String,
+        role: Role,
+        password_hash: String,
+    ) -> Result<VerifiedUser, AppError> {
+        let mut credentials = self.credentials.write().await;
+        Self::check_unique(&credentials, &username, &email)?;
+
+        let user = VerifiedUser {
+            id: self.next_id.fetch_add(1, Ordering::SeqCst),
+            username,
+            email,
+            role,
+        };
+        credentials.insert(
+            user.username.clone(),
+            UserCredentials {
+                user: user.clone(),
+                password_hash,
+            },
+        );
+        Ok(user)
+    }
+
+    /// Remove the user with the given id, returning it if it existed
+    pub async fn remove(&self, id: u64) -> Option<VerifiedUser> {
+        let mut credentials = self.credentials.write().await;
+        let username = credentials
+            .values()
+            .find(|credentials| credentials.user.id == id)?
+            .user
+            .username
+            .clone();
+        credentials
+            .remove(&username)
+            .map(|credentials| credentials.user)
+    }
+
+    /// Replace all users
+    ///
+    /// New users get ids after the highest given one.
+    pub async fn replace(&self, credentials: Vec<UserCredentials>) {
+        let next_id = credentials
+            .iter()
+            .map(|credentials| credentials.user.id + 1)
+            .max()
+            .unwrap_or(1);
+        *self.credentials.write().await = credentials
+            .into_iter()
+            .map(|credentials| (credentials.user.username.clone(), credentials))
+            .collect();
+        self.next_id.store(next_id, Ordering::SeqCst);
+    }
+
+    /// Add users, replacing the users with the same username
+    ///
+    /// New users get ids after the highest given one.
+    pub async fn upsert(&self, upserted: Vec<UserCredentials>) {
+        let mut credentials = self.credentials.write().await;
+        for upserted in upserted {
+            self.next_id
+                .fetch_max(upserted.user.id + 1, Ordering::SeqCst);
+            credentials.insert(upserted.user.username.clone(), upserted);
+        }
+    }
+
+    fn check_unique(
+        credentials: &HashMap<String, UserCredentials>,
+        username: &str,
+        email: &str,
+    ) -> Result<(), AppError> {
+        if credentials.contains_key(username) {
+            return Err(AppError::Conflict {
+                field: "username".to_string(),
+                message: "Username is already taken".to_string(),
+            });
+        }
+        if credentials
+            .values()
+            .any(|existing| existing.user.email.eq_ignore_ascii_case(email))
+        {
+            return Err(AppError::Conflict {
+                field: "email".to_string(),
+                message: "Email is already registered".to_string(),
+            });
+        }
+        Ok(())
+    }
+}
+
+impl Default for UserRepository {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -1,151 +1,908 @@
-use jsonwebtoken::{decode, encode, DecodingKey, EncodingKey, Header, Validation};
-use std::sync::atomic::{AtomicU64, Ordering};
+use chrono::{Duration, Utc};
+use jsonwebtoken::{decode_header, encode, Validation};
+use std::collections::HashMap;
 use std::sync::Arc;
+use tokio::sync::RwLock;
 
-use crate::features::users::domain::{AnonymousUserIdentifier, UserIdentity, VerifiedUser};
+use std::net::IpAddr;
+
+use crate::features::anomaly::AnomalyDetector;
+use crate::features::users::domain::{AnonymousUserIdentifier, Role, UserIdentity, VerifiedUser};
+use crate::infrastructure::audit::AuditActor;
+use crate::infrastructure::cache::TtlCache;
 use crate::infrastructure::error::AppError;
+use crate::infrastructure::geoip::GeoLocation;
+use crate::infrastructure::sessions::SessionStore;
+use crate::infrastructure::validation::Validate;
 
+use super::blacklist::TokenBlacklist;
 use super::domain::{
-    AnonymousUserClaims, AuthToken, LoginRequest, RegisterRequest, TokenClaims,
-    VerifiedUserClaims,
+    ActionToken, AnonymousUserClaims, AuthToken, AutomationRequest, AutomationToken,
+    AutomationTokenClaims, ClientFingerprint, CreateActionTokenRequest,
+    CreateAutomationTokenRequest, DeviceLogin, DeviceLoginStart, DeviceLoginStatus,
+    IssuedActionToken, IssuedAutomationToken, LoginRequest, RefreshToken, RegisterRequest,
+    TokenBinding, TokenClaims, UserCredentials, VerifiedUserClaims, ACTION_TOKEN_TTL_SECS,
+    AUTOMATION_TOKEN_AUDIENCE, AUTOMATION_TOKEN_TYPE, DEFAULT_CLOCK_SKEW_LEEWAY_SECS,
+    DEFAULT_REFRESH_TOKEN_LIFETIME_DAYS,
 };
+use super::keys::JwtKeys;
+use super::repository::UserRepository;
 
 /// Authentication Service
 ///
 /// Handles authentication and token management for both verified and anonymous users.
 #[derive(Clone)]
 pub struct AuthService {
-    jwt_secret: String,
-    user_id_counter: Arc<AtomicU64>,
+    jwt_keys: JwtKeys,
+    /// Registered users with their credentials
+    users: UserRepository,
+    password_hash_cost: u32,
+    token_binding: TokenBinding,
+    device_logins: Arc<RwLock<HashMap<String, DeviceLogin>>>,
+    automation_tokens: Arc<RwLock<HashMap<String, AutomationToken>>>,
+    /// Refresh tokens by hash of their secret
+    refresh_tokens: Arc<RwLock<HashMap<String, RefreshToken>>>,
+    /// Ids of revoked access tokens, kept until the tokens expire
+    blacklist: TokenBlacklist,
+    /// Sessions anonymous tokens refer to, if enabled
+    sessions: Option<SessionStore>,
+    /// Unused action tokens by token
+    action_tokens: TtlCache<String, ActionToken>,
+    refresh_token_lifetime: Duration,
+    /// Seconds of clock skew tolerated when checking token timestamps
+    clock_skew_leeway: u64,
+    anomaly_detector: Option<AnomalyDetector>,
 }
 
 impl AuthService {
-    /// Create a new AuthService
+    /// Create a new AuthService signing tokens with an HS256 secret
     pub fn new(jwt_secret: String) -> Self {
         Self {
-            jwt_secret,
-            user_id_counter: Arc::new(AtomicU64::new(1)),
+            jwt_keys: JwtKeys::hmac(&jwt_secret),
+            users: UserRepository::new(),
+            password_hash_cost: bcrypt::DEFAULT_COST,
+            token_binding: TokenBinding::default(),
+            device_logins: Arc::new(RwLock::new(HashMap::new())),
+            automation_tokens: Arc::new(RwLock::new(HashMap::new())),
+            refresh_tokens: Arc::new(RwLock::new(HashMap::new())),
+            blacklist: TokenBlacklist::new(),
+            sessions: None,
+            action_tokens: TtlCache::new(),
+            refresh_token_lifetime: Duration::days(DEFAULT_REFRESH_TOKEN_LIFETIME_DAYS),
+            clock_skew_leeway: DEFAULT_CLOCK_SKEW_LEEWAY_SECS,
+            anomaly_detector: None,
         }
     }
 
-    /// Register a new verified user (mock implementation)
+    /// Sign and verify tokens with the given keys, e.g. RS256 or ES256 keys
+    /// or a rotated secret
+    pub fn with_jwt_keys(mut self, jwt_keys: JwtKeys) -> Self {
+        self.jwt_keys = jwt_keys;
+        self
+    }
+
+    /// Bind issued tokens to the requesting client with the given strictness
+    pub fn with_token_binding(mut self, token_binding: TokenBinding) -> Self {
+        self.token_binding = token_binding;
+        self
+    }
+
+    /// Set the bcrypt cost used to hash passwords
+    pub fn with_password_hash_cost(mut self, password_hash_cost: u32) -> Self {
+        self.password_hash_cost = password_hash_cost;
+        self
+    }
+
+    /// Set how long refresh tokens stay valid
+    pub fn with_refresh_token_lifetime(mut self, refresh_token_lifetime: Duration) -> Self {
+        self.refresh_token_lifetime = refresh_token_lifetime;
+        self
+    }
+
+    /// Set how many seconds of clock skew are tolerated when checking the
+    /// expiry, not-before and issued-at times of tokens
+    pub fn with_clock_skew_leeway(mut self, clock_skew_leeway: u64) -> Self {
+        self.clock_skew_leeway = clock_skew_leeway;
+        self
+    }
+
+    /// Keep revoked access tokens in the given blacklist, e.g. one shared
+    /// through Redis
+    pub fn with_token_blacklist(mut self, blacklist: TokenBlacklist) -> Self {
+        self.blacklist = blacklist;
+        self
+    }
+
+    /// Start a server-side session for every anonymous token, so it can be
+    /// revoked before it expires
+    pub fn with_sessions(mut self, sessions: SessionStore) -> Self {
+        self.sessions = Some(sessions);
+        self
+    }
+
+    /// Report failed logins, token reuse and client IPs to an anomaly detector
+    pub fn with_anomaly_detector(mut self, anomaly_detector: AnomalyDetector) -> Self {
+        self.anomaly_detector = Some(anomaly_detector);
+        self
+    }
+
+    /// Keep registered users in the given repository, e.g. one shared with
+    /// the user service
+    pub fn with_user_repository(mut self, users: UserRepository) -> Self {
+        self.users = users;
+        self
+    }
+
+    /// Repository of the registered users
+    pub fn user_repository(&self) -> UserRepository {
+        self.users.clone()
+    }
+
+    /// Credentials of all registered users, by id
+    pub async fn export_credentials(&self) -> Vec<UserCredentials> {
+        self.users.list().await
+    }
+
+    /// Replace the registered users, e.g. when restoring a backup
     ///
-    /// In production, this would:
-    /// 1. Hash the password with bcrypt
-    /// 2. Save the user to the database
-    /// 3. Return the created user
+    /// New users get ids after the highest restored one. Refresh tokens of
+    /// users that were removed or changed are revoked; access tokens issued
+    /// before stay valid until they expire.
+    pub async fn restore_credentials(&self, credentials: Vec<UserCredentials>) {
+        let replaced = self.users.list().await;
+        self.users.replace(credentials.clone()).await;
+        for stored in replaced {
+            if !credentials.contains(&stored) {
+                self.revoke_refresh_tokens(stored.user.id).await;
+            }
+        }
+    }
+
+    /// Add users with their roles from a trusted source, e.g. a seed file
+    ///
+    /// This is the only way to create admins and moderators; registered
+    /// users are always members. Seeded users replace registered users with
+    /// the same username, so seed before serving requests. New users get
+    /// ids after the highest seeded one. Refresh tokens of replaced users
+    /// whose role or password changed are revoked.
+    pub async fn seed_credentials(&self, seeded: Vec<UserCredentials>) {
+        for seeded in &seeded {
+            if let Some(stored) = self.users.find_by_username(&seeded.user.username).await {
+                if stored != *seeded {
+                    self.revoke_refresh_tokens(stored.user.id).await;
+                }
+            }
+        }
+        self.users.upsert(seeded).await;
+    }
+
+    /// Register a new verified user
+    ///
+    /// 1. Validate the request
+    /// 2. Hash the password with bcrypt
+    /// 3. Store the user with the password hash
+    /// 4. Return the created user
+    ///
+    /// Usernames and emails must be unique; emails are compared ignoring
+    /// case. Registered users are always members, whatever their username;
+    /// other roles are only given to seeded users.
     pub async fn register(&self, request: RegisterRequest) -> Result<VerifiedUser, AppError> {
         // Validate request
-        request
-            .validate()
-            .map_err(|e| AppError::BadRequest(e))?;
+        request.validate()?;
 
-        // In production, hash the password:
-        // let password_hash = bcrypt::hash(&request.password, bcrypt::DEFAULT_COST)
-        //     .map_err(|e| AppError::InternalError(format!("Failed to hash password: {}", e)))?;
+        self.users
+            .ensure_unique(&request.username, &request.email)
+            .await?;
 
-        // Create user (mock implementation)
-        let user = VerifiedUser {
-            id: self.user_id_counter.fetch_add(1, Ordering::SeqCst),
-            username: request.username,
-            email: request.email,
-        };
+        // Hashing is CPU-bound, keep it off the async workers
+        let cost = self.password_hash_cost;
+        let password = request.password;
+        let password_hash = tokio::task::spawn_blocking(move || bcrypt::hash(password, cost))
+            .await
+            .map_err(|e| AppError::InternalError(format!("Failed to hash password: {}", e)))?
+            .map_err(|e| AppError::InternalError(format!("Failed to hash password: {}", e)))?;
 
-        Ok(user)
+        // The username or email may have been taken while hashing
+        self.users
+            .insert(request.username, request.email, Role::Member, password_hash)
+            .await
     }
 
-    /// Login a verified user (mock implementation)
+    /// Login a verified user
     ///
-    /// In production, this would:
-    /// 1. Query the database for the user by username
-    /// 2. Verify the password against the stored hash
-    /// 3. Generate and return a JWT token
-    pub async fn login(&self, request: LoginRequest) -> Result<AuthToken, AppError> {
+    /// 1. Validate the request
+    /// 2. Look up the user by username
+    /// 3. Verify the password against the stored hash
+    /// 4. Generate and return a JWT token
+    ///
+    /// Unknown usernames and wrong passwords are both rejected with
+    /// `Unauthorized` and reported to the anomaly detector. The returned
+    /// token carries a refresh token.
+    pub async fn login(
+        &self,
+        request: LoginRequest,
+        client: Option<&ClientFingerprint>,
+    ) -> Result<AuthToken, AppError> {
         // Validate request
-        request
-            .validate()
-            .map_err(|e| AppError::BadRequest(e))?;
+        request.validate()?;
 
-        // Mock user lookup and password verification
-        // In production, query database and verify password:
-        // let user = user_repository.find_by_username(&request.username).await?;
-        // bcrypt::verify(&request.password, &user.password_hash)
-        //     .map_err(|_| AppError::Unauthorized("Invalid credentials".to_string()))?;
+        let stored = self.users.find_by_username(&request.username).await;
+        let verified = match &stored {
+            Some(stored) => {
+                let password = request.password;
+                let password_hash = stored.password_hash.clone();
+                tokio::task::spawn_blocking(move || bcrypt::verify(password, &password_hash))
+                    .await
+                    .map_err(|e| {
+                        AppError::InternalError(format!("Failed to verify password: {}", e))
+                    })?
+                    .unwrap_or(false)
+            }
+            None => false,
+        };
 
-        let mock_user = VerifiedUser {
-            id: 1,
-            username: request.username.clone(),
-            email: format!("{}@example.com", request.username),
+        let Some(UserCredentials { user, .. }) = stored.filter(|_| verified) else {
+            if let Some(anomaly_detector) = &self.anomaly_detector {
+                anomaly_detector
+                    .record_failed_login(&request.username)
+                    .await;
+            }
+            return Err(AppError::Unauthorized("Invalid credentials".to_string()));
         };
 
         // Generate token
-        let token = self.generate_verified_user_token(&mock_user)?;
-        Ok(AuthToken::bearer(token))
+        let token = self.generate_verified_user_token(&user, client)?;
+        let refresh_token = self
+            .issue_refresh_token(UserIdentity::Verified(user), client)
+            .await;
+        Ok(AuthToken::bearer(token).with_refresh_token(refresh_token))
+    }
+
+    /// Exchange a refresh token for a new access token
+    ///
+    /// The refresh token is rotated: the returned token carries a new one
+    /// and the presented one can no longer be used. Presenting a refresh
+    /// token that was already exchanged revokes all tokens of its family,
+    /// since either the client or an attacker holds a stolen copy.
+    ///
+    /// Verified users are looked up again, so the new access token carries
+    /// their current role, and the family of a deleted user is revoked. The
+    /// family expires with the login it started from, however often it is
+    /// refreshed.
+    pub async fn refresh(
+        &self,
+        refresh_token: &str,
+        client: &ClientFingerprint,
+    ) -> Result<AuthToken, AppError> {
+        let mut refresh_tokens = self.refresh_tokens.write().await;
+        refresh_tokens.retain(|_, token| !token.is_expired());
+
+        let stored = refresh_tokens
+            .get_mut(&RefreshToken::hash(refresh_token))
+            .ok_or_else(|| AppError::Unauthorized("Invalid refresh token".to_string()))?;
+
+        if stored.replaced {
+            let family = stored.family.clone();
+            let identity = stored.identity.clone();
+            refresh_tokens.retain(|_, token| token.family != family);
+            drop(refresh_tokens);
+            tracing::warn!("Refresh token reused, revoked token family {}", family);
+            if let Some(anomaly_detector) = &self.anomaly_detector {
+                anomaly_detector.record_token_reuse(&identity).await;
+            }
+            return Err(AppError::Unauthorized(
+                "Refresh token has already been used".to_string(),
+            ));
+        }
+
+        self.check_binding(stored.client.as_ref().map(|c| c.as_str()), client)?;
+        let Some(identity) = self.current_identity(&stored.identity).await else {
+            let family = stored.family.clone();
+            refresh_tokens.retain(|_, token| token.family != family);
+            return Err(AppError::Unauthorized("User no longer exists".to_string()));
+        };
+
+        let (secret, refreshed) = stored.rotate(identity.clone(), self.bound_client(Some(client)));
+        refresh_tokens.insert(RefreshToken::hash(&secret), refreshed);
+        drop(refresh_tokens);
+
+        let token = self.generate_token_for(&identity, Some(client)).await?;
+        Ok(AuthToken::bearer(token).with_refresh_token(secret))
+    }
+
+    /// Log out by revoking a refresh token and all tokens of its family
+    ///
+    /// Already issued access tokens stay valid until they expire. Unknown
+    /// refresh tokens are ignored, so logging out twice is not an error.
+    pub async fn logout(&self, refresh_token: &str) {
+        let mut refresh_tokens = self.refresh_tokens.write().await;
+        let family = refresh_tokens
+            .get(&RefreshToken::hash(refresh_token))
+            .map(|token| token.family.clone());
+
+        if let Some(family) = family {
+            refresh_tokens.retain(|_, token| token.family != family);
+        }
+    }
+
+    /// Revoke the refresh tokens of a verified user, e.g. once deleted
+    ///
+    /// Already issued access tokens stay valid until they expire.
+    pub async fn revoke_refresh_tokens(&self, user_id: u64) {
+        self.refresh_tokens.write().await.retain(|_, token| {
+            !matches!(&token.identity, UserIdentity::Verified(user) if user.id == user_id)
+        });
+    }
+
+    /// Identity as currently registered, `None` if the user was deleted
+    ///
+    /// Anonymous identities are not registered and are returned as is.
+    async fn current_identity(&self, identity: &UserIdentity) -> Option<UserIdentity> {
+        match identity {
+            UserIdentity::Verified(user) => self
+                .users
+                .find(user.id)
+                .await
+                .filter(|current| current.username == user.username)
+                .map(UserIdentity::Verified),
+            UserIdentity::Anonymous(_) => Some(identity.clone()),
+        }
+    }
+
+    /// Revoke the access token of an Authorization header
+    ///
+    /// The token id is blacklisted until the token expires. Invalid, expired
+    /// and already revoked tokens are ignored, like unknown refresh tokens.
+    /// Fails with `ServiceUnavailable` if the revocation could not be shared
+    /// with other instances; the token is rejected by this one anyway.
+    pub async fn revoke_access_token(&self, auth_header: &str) -> Result<(), AppError> {
+        let Ok(claims) = Self::bearer_token(auth_header).and_then(|t| self.decode_token(t)) else {
+            return Ok(());
+        };
+        if claims.jti().is_empty() {
+            return Ok(());
+        }
+
+        // Tokens are accepted up to the leeway past their expiry
+        let now = Utc::now().timestamp().max(0) as u64;
+        let ttl = (claims.exp() as u64 + self.clock_skew_leeway).saturating_sub(now);
+        self.blacklist
+            .revoke(claims.jti(), std::time::Duration::from_secs(ttl))
+            .await
+            .map_err(|err| {
+                AppError::ServiceUnavailable(format!("Token blacklist unavailable: {}", err))
+            })?;
+        tracing::info!("Revoked access token {}", claims.jti());
+        Ok(())
+    }
+
+    /// Check if the access token with the given id has been revoked
+    fn is_revoked(&self, jti: &str) -> bool {
+        !jti.is_empty() && self.blacklist.is_revoked(jti)
+    }
+
+    /// Record the IP address (and its location) an authenticated identity
+    /// made a request from
+    pub async fn record_client_ip(
+        &self,
+        identity: &UserIdentity,
+        ip: Option<IpAddr>,
+        location: Option<GeoLocation>,
+    ) {
+        if let (Some(anomaly_detector), Some(ip)) = (&self.anomaly_detector, ip) {
+            anomaly_detector
+                .record_client_ip(identity, ip, location)
+                .await;
+        }
+    }
+
+    /// Issue a refresh token for `identity`, starting a new token family
+    async fn issue_refresh_token(
+        &self,
+        identity: UserIdentity,
+        client: Option<&ClientFingerprint>,
+    ) -> String {
+        let (secret, token) = RefreshToken::new(
+            identity,
+            self.bound_client(client),
+            self.refresh_token_lifetime,
+        );
+
+        let mut refresh_tokens = self.refresh_tokens.write().await;
+        refresh_tokens.retain(|_, token| !token.is_expired());
+        refresh_tokens.insert(RefreshToken::hash(&secret), token);
+
+        secret
+    }
+
+    /// Client a new token is bound to, according to the token binding
+    fn bound_client(&self, client: Option<&ClientFingerprint>) -> Option<ClientFingerprint> {
+        if self.token_binding == TokenBinding::Off {
+            return None;
+        }
+        client.cloned()
     }
 
     /// Generate a token for a verified user
-    pub fn generate_verified_user_token(&self, user: &VerifiedUser) -> Result<String, AppError> {
+    ///
+    /// The token is bound to `client` unless token binding is off.
+    pub fn generate_verified_user_token(
+        &self,
+        user: &VerifiedUser,
+        client: Option<&ClientFingerprint>,
+    ) -> Result<String, AppError> {
         let claims = VerifiedUserClaims::new(user);
-
-        encode(
-            &Header::default(),
-            &TokenClaims::Verified(claims),
-            &EncodingKey::from_secret(self.jwt_secret.as_bytes()),
-        )
-        .map_err(|e| AppError::InternalError(format!("Failed to generate token: {}", e)))
+        self.encode_token(TokenClaims::Verified(claims), client)
     }
 
     /// Generate a token for an anonymous user
-    pub fn generate_anonymous_user_token(
+    ///
+    /// The token is bound to `client` unless token binding is off. With
+    /// sessions enabled, it refers to a new session lasting as long as the
+    /// token is accepted.
+    pub async fn generate_anonymous_user_token(
         &self,
         identifier: &AnonymousUserIdentifier,
+        client: Option<&ClientFingerprint>,
     ) -> Result<String, AppError> {
         // Validate identifier
-        identifier
-            .validate()
-            .map_err(|e| AppError::BadRequest(e))?;
+        identifier.validate()?;
 
-        let claims = AnonymousUserClaims::new(identifier);
+        let mut claims = AnonymousUserClaims::new(identifier);
+        if let Some(sessions) = &self.sessions {
+            // Tokens are accepted up to the leeway past their expiry
+            let now = Utc::now().timestamp().max(0) as u64;
+            let ttl = (claims.exp as u64 + self.clock_skew_leeway).saturating_sub(now);
+            let actor = AuditActor::from(&UserIdentity::Anonymous(identifier.clone()));
+            let session = sessions
+                .create(&actor, std::time::Duration::from_secs(ttl))
+                .await?;
+            claims.sid = Some(session.id);
+        }
+        self.encode_token(TokenClaims::Anonymous(claims), client)
+    }
+
+    /// Generate a token for an already authenticated identity
+    pub async fn generate_token_for(
+        &self,
+        identity: &UserIdentity,
+        client: Option<&ClientFingerprint>,
+    ) -> Result<String, AppError> {
+        match identity {
+            UserIdentity::Verified(user) => self.generate_verified_user_token(user, client),
+            UserIdentity::Anonymous(identifier) => {
+                self.generate_anonymous_user_token(identifier, client).await
+            }
+        }
+    }
+
+    /// Start a device login for a shared workstation
+    ///
+    /// The returned code is shown as a QR code on the workstation and
+    /// approved from a device where the user is logged in.
+    pub async fn start_device_login(&self, client: ClientFingerprint) -> DeviceLoginStart {
+        let login = DeviceLogin::new(client);
+        let start = DeviceLoginStart {
+            code: login.code.clone(),
+            poll_token: login.poll_token.clone(),
+            qr_payload: format!("webboard://device-login?code={}", login.code),
+            expires_at: login.expires_at,
+        };
+
+        let mut device_logins = self.device_logins.write().await;
+        device_logins.retain(|_, login| !login.is_expired());
+        device_logins.insert(login.code.clone(), login);
+
+        start
+    }
+
+    /// Approve a pending device login
+    ///
+    /// Issues a token for the approving identity, bound to the workstation
+    /// that started the login.
+    pub async fn approve_device_login(
+        &self,
+        approver: &UserIdentity,
+        code: &str,
+    ) -> Result<(), AppError> {
+        let mut device_logins = self.device_logins.write().await;
+        let login = device_logins
+            .get_mut(code)
+            .filter(|login| !login.is_expired())
+            .ok_or_else(|| AppError::NotFound("Device login not found or expired".to_string()))?;
+
+        if login.approved_token.is_some() {
+            return Err(AppError::BadRequest(
+                "Device login already approved".to_string(),
+            ));
+        }
+
+        login.approved_token = Some(
+            self.generate_token_for(approver, Some(&login.client))
+                .await?,
+        );
+        Ok(())
+    }
+
+    /// Poll a device login from the workstation
+    ///
+    /// Once approved, the token is handed out exactly once.
+    pub async fn poll_device_login(&self, poll_token: &str) -> Result<DeviceLoginStatus, AppError> {
+        let mut device_logins = self.device_logins.write().await;
+        let code = device_logins
+            .values()
+            .find(|login| login.poll_token == poll_token && !login.is_expired())
+            .map(|login| login.code.clone())
+            .ok_or_else(|| AppError::NotFound("Device login not found or expired".to_string()))?;
+
+        if device_logins[&code].approved_token.is_none() {
+            return Ok(DeviceLoginStatus::Pending);
+        }
+
+        let token = device_logins
+            .remove(&code)
+            .and_then(|login| login.approved_token)
+            .unwrap_or_default();
+        Ok(DeviceLoginStatus::Approved(AuthToken::bearer(token)))
+    }
+
+    /// Create an automation token acting on behalf of `owner`
+    pub async fn create_automation_token(
+        &self,
+        owner: &VerifiedUser,
+        request: CreateAutomationTokenRequest,
+    ) -> Result<IssuedAutomationToken, AppError> {
+        // Validate request
+        request.validate()?;
+
+        let automation_token = AutomationToken::new(owner, request);
+        let issued = self.issue_automation_token(automation_token.clone())?;
+
+        let mut automation_tokens = self.automation_tokens.write().await;
+        automation_tokens.insert(automation_token.id.clone(), automation_token);
+
+        Ok(issued)
+    }
+
+    /// List the automation tokens `caller` may manage, oldest first
+    ///
+    /// Admins manage all tokens, other users only the tokens they own.
+    pub async fn list_automation_tokens(&self, caller: &VerifiedUser) -> Vec<AutomationToken> {
+        let automation_tokens = self.automation_tokens.read().await;
+        let mut tokens: Vec<_> = automation_tokens
+            .values()
+            .filter(|token| Self::manages(caller, token))
+            .cloned()
+            .collect();
+        tokens.sort_by_key(|token| token.created_at);
+        tokens
+    }
+
+    /// Rotate an automation token
+    ///
+    /// Issues a new secret and invalidates all previous ones. Only admins
+    /// and the owner of the token may rotate it.
+    pub async fn rotate_automation_token(
+        &self,
+        caller: &VerifiedUser,
+        id: &str,
+    ) -> Result<IssuedAutomationToken, AppError> {
+        let mut automation_tokens = self.automation_tokens.write().await;
+        let automation_token = automation_tokens
+            .get_mut(id)
+            .ok_or_else(|| AppError::NotFound(format!("Automation token {} not found", id)))?;
+        Self::ensure_manages(caller, automation_token)?;
+
+        automation_token.rotate();
+        self.issue_automation_token(automation_token.clone())
+    }
+
+    /// Revoke an automation token
+    ///
+    /// Only admins and the owner of the token may revoke it.
+    pub async fn revoke_automation_token(
+        &self,
+        caller: &VerifiedUser,
+        id: &str,
+    ) -> Result<(), AppError> {
+        let mut automation_tokens = self.automation_tokens.write().await;
+        let automation_token = automation_tokens
+            .get(id)
+            .ok_or_else(|| AppError::NotFound(format!("Automation token {} not found", id)))?;
+        Self::ensure_manages(caller, automation_token)?;
+
+        automation_tokens.remove(id);
+        Ok(())
+    }
+
+    /// Check if `caller` may manage `automation_token`
+    fn manages(caller: &VerifiedUser, automation_token: &AutomationToken) -> bool {
+        caller.role == Role::Admin || automation_token.owner.id == caller.id
+    }
+
+    fn ensure_manages(
+        caller: &VerifiedUser,
+        automation_token: &AutomationToken,
+    ) -> Result<(), AppError> {
+        if Self::manages(caller, automation_token) {
+            Ok(())
+        } else {
+            Err(AppError::Forbidden(
+                "Only admins and the owner can manage this automation token".to_string(),
+            ))
+        }
+    }
+
+    /// Mint a single-use token confirming `request.action` for `identity`
+    ///
+    /// The token expires after five minutes unless consumed before.
+    pub fn issue_action_token(
+        &self,
+        identity: &UserIdentity,
+        request: CreateActionTokenRequest,
+    ) -> Result<IssuedActionToken, AppError> {
+        request.validate()?;
+
+        let ttl = std::time::Duration::from_secs(ACTION_TOKEN_TTL_SECS);
+        let token = uuid::Uuid::new_v4().simple().to_string();
+        let action_token = ActionToken {
+            action: request.action,
+            owner: AuditActor::from(identity).id,
+            expires_at: Utc::now() + Duration::seconds(ACTION_TOKEN_TTL_SECS as i64),
+        };
+        let issued = IssuedActionToken {
+            token: token.clone(),
+            action: action_token.action.clone(),
+            expires_at: action_token.expires_at,
+        };
+
+        self.action_tokens.insert(token, action_token, ttl);
+        Ok(issued)
+    }
+
+    /// Consume an action token before performing `action` for `identity`
+    ///
+    /// Called by sensitive operations, e.g. deleting users or boards, over
+    /// REST and JSON-RPC alike. Each token is accepted at most once, even by
+    /// concurrent requests. A token presented for another action or
+    /// identity is used up as well.
+    pub fn consume_action_token(
+        &self,
+        identity: &UserIdentity,
+        action: &str,
+        token: &str,
+    ) -> Result<(), AppError> {
+        self.action_tokens
+            .take(&token.to_string())
+            .filter(|action_token| {
+                action_token.action == action && action_token.owner == AuditActor::from(identity).id
+            })
+            .map(|_| ())
+            .ok_or_else(|| AppError::Forbidden("Invalid or expired action token".to_string()))
+    }
+
+    /// Check if an Authorization header carries an automation token
+    pub fn is_automation_token(&self, auth_header: &str) -> bool {
+        Self::bearer_token(auth_header)
+            .ok()
+            .and_then(|token| decode_header(token).ok())
+            .is_some_and(|header| header.typ.as_deref() == Some(AUTOMATION_TOKEN_TYPE))
+    }
+
+    /// Authenticate a request made with an automation token
+    ///
+    /// Checks the token signature, audience and generation, and that the
+    /// request is within the token's scope. Scope violations are `Forbidden`.
+    /// The returned token acts as its owner currently is, so demoting or
+    /// removing the owner takes effect on the token immediately.
+    pub async fn authenticate_automation(
+        &self,
+        auth_header: &str,
+        request: &AutomationRequest<'_>,
+    ) -> Result<AutomationToken, AppError> {
+        let mut validation = self.validation();
+        validation.set_audience(&[AUTOMATION_TOKEN_AUDIENCE]);
+        validation.set_required_spec_claims(&["exp", "aud", "sub"]);
+
+        let claims = self
+            .jwt_keys
+            .decode::<AutomationTokenClaims>(Self::bearer_token(auth_header)?, &validation)
+            .map_err(|e| AppError::Unauthorized(format!("Invalid token: {}", e)))?
+            .claims;
+        self.check_issued_at(claims.iat)?;
+
+        let mut automation_token = self
+            .automation_tokens
+            .read()
+            .await
+            .get(&claims.sub)
+            .filter(|token| token.generation == claims.gen)
+            .cloned()
+            .ok_or_else(|| {
+                AppError::Unauthorized("Token has been revoked or rotated".to_string())
+            })?;
+
+        automation_token
+            .scope
+            .check(request)
+            .map_err(AppError::Forbidden)?;
+
+        automation_token.owner = self
+            .users
+            .find(automation_token.owner.id)
+            .await
+            .ok_or_else(|| AppError::Unauthorized("Token owner no longer exists".to_string()))?;
+
+        Ok(automation_token)
+    }
+
+    fn issue_automation_token(
+        &self,
+        automation_token: AutomationToken,
+    ) -> Result<IssuedAutomationToken, AppError> {
+        let mut header = self.jwt_keys.header();
+        header.typ = Some(AUTOMATION_TOKEN_TYPE.to_string());
+        let token = encode(
+            &header,
+            &AutomationTokenClaims::new(&automation_token),
+            self.jwt_keys.encoding_key(),
+        )
+        .map_err(|e| AppError::InternalError(format!("Failed to generate token: {}", e)))?;
+
+        Ok(IssuedAutomationToken {
+            automation_token,
+            secret: AuthToken::bearer(token),
+        })
+    }
+
+    fn encode_token(
+        &self,
+        mut claims: TokenClaims,
+        client: Option<&ClientFingerprint>,
+    ) -> Result<String, AppError> {
+        if self.token_binding != TokenBinding::Off {
+            if let Some(client) = client {
+                claims.bind_to(client);
+            }
+        }
 
         encode(
-            &Header::default(),
-            &TokenClaims::Anonymous(claims),
-            &EncodingKey::from_secret(self.jwt_secret.as_bytes()),
+            &self.jwt_keys.header(),
+            &claims,
+            self.jwt_keys.encoding_key(),
         )
         .map_err(|e| AppError::InternalError(format!("Failed to generate token: {}", e)))
     }
 
     /// Verify and decode a token
+    ///
+    /// Does not check the token binding; use `authenticate` for requests.
     pub fn verify_token(&self, token: &str) -> Result<UserIdentity, AppError> {
-        let token_data = decode::<TokenClaims>(
-            token,
-            &DecodingKey::from_secret(self.jwt_secret.as_bytes()),
-            &Validation::default(),
-        )
-        .map_err(|e| AppError::Unauthorized(format!("Invalid token: {}", e)))?;
+        Ok(self.decode_token(token)?.to_user_identity())
+    }
+
+    fn decode_token(&self, token: &str) -> Result<TokenClaims, AppError> {
+        let token_data = self
+            .jwt_keys
+            .decode::<TokenClaims>(token, &self.validation())
+            .map_err(|e| AppError::Unauthorized(format!("Invalid token: {}", e)))?;
+        self.check_issued_at(token_data.claims.iat())?;
+        if self.is_revoked(token_data.claims.jti()) {
+            return Err(AppError::Unauthorized("Token has been revoked".to_string()));
+        }
+        if let (Some(sessions), Some(sid)) = (&self.sessions, token_data.claims.session_id()) {
+            if sessions.is_revoked(sid) {
+                return Err(AppError::Unauthorized(
+                    "Session has been revoked".to_string(),
+                ));
+            }
+        }
 
-        Ok(token_data.claims.to_user_identity())
+        Ok(token_data.claims)
+    }
+
+    /// Token validation tolerating the configured clock skew
+    fn validation(&self) -> Validation {
+        let mut validation = self.jwt_keys.validation();
+        validation.leeway = self.clock_skew_leeway;
+        validation.validate_nbf = true;
+        validation
+    }
+
+    /// Reject tokens issued further in the future than the clock skew leeway
+    fn check_issued_at(&self, iat: usize) -> Result<(), AppError> {
+        let now = Utc::now().timestamp().max(0) as u64;
+        if iat as u64 > now + self.clock_skew_leeway {
+            return Err(AppError::Unauthorized(
+                "Invalid token: issued in the future".to_string(),
+            ));
+        }
+        Ok(())
     }
 
     /// Extract user identity from Authorization header
+    ///
+    /// Does not check the token binding; use `authenticate` for requests.
     pub fn extract_user_from_header(&self, auth_header: &str) -> Result<UserIdentity, AppError> {
+        self.verify_token(Self::bearer_token(auth_header)?)
+    }
+
+    /// Authenticate a request from its Authorization header and client fingerprint
+    ///
+    /// In addition to verifying the token, checks that it is used by the client
+    /// it was issued to, according to the configured token binding. Returns
+    /// the claims of the token.
+    pub fn authenticate(
+        &self,
+        auth_header: &str,
+        client: &ClientFingerprint,
+    ) -> Result<TokenClaims, AppError> {
+        let claims = self.decode_token(Self::bearer_token(auth_header)?)?;
+        self.check_binding(claims.fingerprint(), client)?;
+
+        Ok(claims)
+    }
+
+    /// Check that a token bound to `bound` is used by `client`
+    fn check_binding(
+        &self,
+        bound: Option<&str>,
+        client: &ClientFingerprint,
+    ) -> Result<(), AppError> {
+        match (self.token_binding, bound) {
+            (TokenBinding::Off, _) => {}
+            (_, Some(bound)) if bound == client.as_str() => {}
+            (TokenBinding::Lenient, _) => {
+                tracing::warn!("Token used from a different client than it was issued to");
+            }
+            (TokenBinding::Strict, _) => {
+                return Err(AppError::Unauthorized(
+                    "Token is not bound to this client".to_string(),
+                ));
+            }
+        }
+
+        Ok(())
+    }
+
+    fn bearer_token(auth_header: &str) -> Result<&str, AppError> {
         // Check if header starts with "Bearer "
-        let token = auth_header
+        auth_header
             .strip_prefix("Bearer ")
-            .ok_or_else(|| AppError::Unauthorized("Invalid authorization header".to_string()))?;
-
-        self.verify_token(token)
+            .ok_or_else(|| AppError::Unauthorized("Invalid authorization header".to_string()))
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::features::auth::domain::AutomationTokenScope;
+    use crate::infrastructure::{AuditContext, AuditLog};
+    use axum::{http::StatusCode, response::IntoResponse};
     use chrono::NaiveDate;
 
+    /// Lowest bcrypt cost, keeps the tests fast
+    const TEST_HASH_COST: u32 = 4;
+
+    /// Service with a registered "testuser" / "password123"
+    async fn service_with_user() -> AuthService {
+        let service =
+            AuthService::new("test_secret".to_string()).with_password_hash_cost(TEST_HASH_COST);
+        service
+            .register(RegisterRequest {
+                username: "testuser".to_string(),
+                email: "test@example.com".to_string(),
+                password: "password123".to_string(),
+            })
+            .await
+            .unwrap();
+        service
+    }
+
     #[tokio::test]
     async fn test_register_valid_user() {
-        let service = AuthService::new("test_secret".to_string());
+        let service =
+            AuthService::new("test_secret".to_string()).with_password_hash_cost(TEST_HASH_COST);
         let request = RegisterRequest {
             username: "testuser".to_string(),
             email: "test@example.com".to_string(),
@@ -158,6 +915,72 @@ mod tests {
         let user = result.unwrap();
         assert_eq!(user.username, "testuser");
         assert_eq!(user.email, "test@example.com");
+
+        // Usernames and emails are unique
+        let request = RegisterRequest {
+            username: "testuser".to_string(),
+            email: "other@example.com".to_string(),
+            password: "password456".to_string(),
+        };
+        assert!(matches!(
+            service.register(request).await,
+            Err(AppError::Conflict { field, .. }) if field == "username"
+        ));
+        let request = RegisterRequest {
+            username: "otheruser".to_string(),
+            email: "Test@Example.com".to_string(),
+            password: "password456".to_string(),
+        };
+        assert!(matches!(
+            service.register(request).await,
+            Err(AppError::Conflict { field, .. }) if field == "email"
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_roles_are_only_seeded() {
+        let service =
+            AuthService::new("test_secret".to_string()).with_password_hash_cost(TEST_HASH_COST);
+        let admin = VerifiedUser {
+            id: 7,
+            username: "admin".to_string(),
+            email: "admin@example.com".to_string(),
+            role: Role::Admin,
+        };
+        service
+            .seed_credentials(vec![UserCredentials {
+                user: admin.clone(),
+                password_hash: bcrypt::hash("password123", TEST_HASH_COST).unwrap(),
+            }])
+            .await;
+
+        // Seeded usernames cannot be registered again
+        let register = |username: &str| RegisterRequest {
+            username: username.to_string(),
+            email: format!("{}@example.org", username),
+            password: "password123".to_string(),
+        };
+        assert!(matches!(
+            service.register(register("admin")).await,
+            Err(AppError::Conflict { field, .. }) if field == "username"
+        ));
+        let member = service.register(register("moderator")).await.unwrap();
+        assert_eq!(member.role, Role::Member);
+        assert_eq!(member.id, 8);
+
+        // The role survives the round trip through the token
+        let token = service
+            .login(
+                LoginRequest {
+                    username: "admin".to_string(),
+                    password: "password123".to_string(),
+                },
+                None,
+            )
+            .await
+            .unwrap();
+        let identity = service.verify_token(&token.token).unwrap();
+        assert_eq!(identity.role(), Role::Admin);
     }
 
     #[tokio::test]
@@ -175,18 +998,36 @@ mod tests {
 
     #[tokio::test]
     async fn test_login() {
-        let service = AuthService::new("test_secret".to_string());
+        let service = service_with_user().await;
         let request = LoginRequest {
             username: "testuser".to_string(),
             password: "password123".to_string(),
         };
 
-        let result = service.login(request).await;
+        let result = service.login(request, None).await;
         assert!(result.is_ok());
 
         let token = result.unwrap();
         assert_eq!(token.token_type, "Bearer");
         assert!(!token.token.is_empty());
+        let identity = service.verify_token(&token.token).unwrap();
+        assert_eq!(identity.as_verified().unwrap().email, "test@example.com");
+    }
+
+    #[tokio::test]
+    async fn test_login_invalid_credentials() {
+        let service = service_with_user().await;
+
+        for (username, password) in [("testuser", "wrong-password"), ("nobody", "password123")] {
+            let request = LoginRequest {
+                username: username.to_string(),
+                password: password.to_string(),
+            };
+            assert!(matches!(
+                service.login(request, None).await,
+                Err(AppError::Unauthorized(_))
+            ));
+        }
     }
 
     #[test]
@@ -196,9 +1037,10 @@ mod tests {
             id: 1,
             username: "testuser".to_string(),
             email: "test@example.com".to_string(),
+            role: Role::Member,
         };
 
-        let token = service.generate_verified_user_token(&user).unwrap();
+        let token = service.generate_verified_user_token(&user, None).unwrap();
         let identity = service.verify_token(&token).unwrap();
 
         assert!(identity.is_verified());
@@ -207,7 +1049,35 @@ mod tests {
     }
 
     #[test]
-    fn test_generate_and_verify_anonymous_user_token() {
+    fn test_tokens_survive_key_rotation() {
+        let user = VerifiedUser {
+            id: 1,
+            username: "testuser".to_string(),
+            email: "test@example.com".to_string(),
+            role: Role::Member,
+        };
+        let before = AuthService::new("test_secret".to_string())
+            .with_jwt_keys(JwtKeys::hmac("old_secret").with_key_id("1"));
+        let token = before.generate_verified_user_token(&user, None).unwrap();
+
+        let rotated = JwtKeys::hmac("new_secret").with_key_id("2");
+        let after = AuthService::new("test_secret".to_string()).with_jwt_keys(
+            rotated
+                .clone()
+                .with_previous_key("1", b"old_secret")
+                .unwrap(),
+        );
+        assert!(after.verify_token(&token).is_ok());
+        let fresh = after.generate_verified_user_token(&user, None).unwrap();
+        assert!(after.verify_token(&fresh).is_ok());
+
+        // Dropping the previous key invalidates its tokens
+        let dropped = AuthService::new("test_secret".to_string()).with_jwt_keys(rotated);
+        assert!(dropped.verify_token(&token).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_generate_and_verify_anonymous_user_token() {
         let service = AuthService::new("test_secret".to_string());
         let identifier = AnonymousUserIdentifier {
             hospital_code: "H001".to_string(),
@@ -216,7 +1086,10 @@ mod tests {
             department_code: "D001".to_string(),
         };
 
-        let token = service.generate_anonymous_user_token(&identifier).unwrap();
+        let token = service
+            .generate_anonymous_user_token(&identifier, None)
+            .await
+            .unwrap();
         let identity = service.verify_token(&token).unwrap();
 
         assert!(identity.is_anonymous());
@@ -225,6 +1098,158 @@ mod tests {
         assert_eq!(anonymous_id.user_id, "U123");
     }
 
+    #[tokio::test]
+    async fn test_revoked_session_rejects_anonymous_token() {
+        let sessions = SessionStore::new(AuditLog::new(100, "secret"));
+        let service = AuthService::new("test_secret".to_string()).with_sessions(sessions.clone());
+        let identifier = AnonymousUserIdentifier {
+            hospital_code: "H001".to_string(),
+            user_id: "U123".to_string(),
+            user_start_date: NaiveDate::from_ymd_opt(2024, 1, 1).unwrap(),
+            department_code: "D001".to_string(),
+        };
+
+        let token = service
+            .generate_anonymous_user_token(&identifier, None)
+            .await
+            .unwrap();
+        assert!(service.verify_token(&token).is_ok());
+
+        let session = sessions.list(&Default::default()).await.unwrap().remove(0);
+        sessions
+            .revoke(&session.id, &AuditContext::default())
+            .await
+            .unwrap();
+        assert!(matches!(
+            service.verify_token(&token),
+            Err(AppError::Unauthorized(_))
+        ));
+
+        // New sessions of the same user are not affected
+        let token = service
+            .generate_anonymous_user_token(&identifier, None)
+            .await
+            .unwrap();
+        assert!(service.verify_token(&token).is_ok());
+    }
+
+    #[test]
+    fn test_clock_skew_leeway() {
+        let service = AuthService::new("test_secret".to_string()).with_clock_skew_leeway(60);
+        let user = VerifiedUser {
+            id: 1,
+            username: "testuser".to_string(),
+            email: "test@example.com".to_string(),
+            role: Role::Member,
+        };
+        let now = Utc::now().timestamp() as usize;
+        let token = |iat: usize, exp: usize| {
+            let mut claims = VerifiedUserClaims::new(&user);
+            claims.iat = iat;
+            claims.exp = exp;
+            service
+                .encode_token(TokenClaims::Verified(claims), None)
+                .unwrap()
+        };
+
+        // Expired or issued ahead by less than the leeway
+        assert!(service.verify_token(&token(now - 3600, now - 30)).is_ok());
+        assert!(service.verify_token(&token(now + 30, now + 3600)).is_ok());
+
+        // Beyond the leeway
+        assert!(service.verify_token(&token(now - 3600, now - 120)).is_err());
+        assert!(service.verify_token(&token(now + 120, now + 3600)).is_err());
+
+        let strict = service.clone().with_clock_skew_leeway(0);
+        assert!(strict.verify_token(&token(now - 3600, now - 30)).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_token_ids_and_revocation() {
+        let service = AuthService::new("test_secret".to_string());
+        let user = VerifiedUser {
+            id: 1,
+            username: "testuser".to_string(),
+            email: "test@example.com".to_string(),
+            role: Role::Member,
+        };
+        let first = format!(
+            "Bearer {}",
+            service.generate_verified_user_token(&user, None).unwrap()
+        );
+        let second = format!(
+            "Bearer {}",
+            service.generate_verified_user_token(&user, None).unwrap()
+        );
+        let client = ClientFingerprint::new(None, None, None);
+
+        let claims = service.authenticate(&first, &client).unwrap();
+        assert!(!claims.jti().is_empty());
+        assert_ne!(
+            claims.jti(),
+            service.authenticate(&second, &client).unwrap().jti()
+        );
+
+        // Only the revoked token is rejected
+        service.revoke_access_token(&first).await.unwrap();
+        assert!(service.authenticate(&first, &client).is_err());
+        assert!(service.authenticate(&second, &client).is_ok());
+
+        // Tokens are not valid before their nbf
+        let now = Utc::now().timestamp() as usize;
+        let mut claims = VerifiedUserClaims::new(&user);
+        claims.nbf = now + 3600;
+        let immature = service
+            .encode_token(TokenClaims::Verified(claims), None)
+            .unwrap();
+        assert!(service.verify_token(&immature).is_err());
+    }
+
+    #[test]
+    fn test_action_tokens_are_single_use() {
+        let service = AuthService::new("test_secret".to_string());
+        let user = UserIdentity::Verified(VerifiedUser {
+            id: 1,
+            username: "testuser".to_string(),
+            email: "test@example.com".to_string(),
+            role: Role::Member,
+        });
+        let other = UserIdentity::Verified(VerifiedUser {
+            id: 2,
+            username: "other".to_string(),
+            email: "other@example.com".to_string(),
+            role: Role::Member,
+        });
+        let issue = |action: &str| {
+            let request = CreateActionTokenRequest {
+                action: action.to_string(),
+            };
+            service.issue_action_token(&user, request)
+        };
+
+        assert!(issue("").is_err());
+        assert!(issue("account delete").is_err());
+
+        let issued = issue("account.delete").unwrap();
+        assert_eq!(issued.action, "account.delete");
+        assert!(service
+            .consume_action_token(&user, "account.delete", &issued.token)
+            .is_ok());
+        assert!(service
+            .consume_action_token(&user, "account.delete", &issued.token)
+            .is_err());
+
+        // Tokens are bound to their action and identity
+        let issued = issue("account.delete").unwrap();
+        assert!(service
+            .consume_action_token(&user, "moderation.approve", &issued.token)
+            .is_err());
+        let issued = issue("account.delete").unwrap();
+        assert!(service
+            .consume_action_token(&other, "account.delete", &issued.token)
+            .is_err());
+    }
+
     #[test]
     fn test_extract_user_from_header() {
         let service = AuthService::new("test_secret".to_string());
@@ -232,15 +1257,397 @@ mod tests {
             id: 1,
             username: "testuser".to_string(),
             email: "test@example.com".to_string(),
+            role: Role::Member,
         };
 
-        let token = service.generate_verified_user_token(&user).unwrap();
+        let token = service.generate_verified_user_token(&user, None).unwrap();
         let header = format!("Bearer {}", token);
 
         let identity = service.extract_user_from_header(&header).unwrap();
         assert!(identity.is_verified());
     }
 
+    #[test]
+    fn test_token_binding() {
+        let user = VerifiedUser {
+            id: 1,
+            username: "testuser".to_string(),
+            email: "test@example.com".to_string(),
+            role: Role::Member,
+        };
+        let laptop = ClientFingerprint::new(None, Some("Firefox"), "10.0.0.5".parse().ok());
+        let same_network = ClientFingerprint::new(None, Some("Firefox"), "10.0.0.9".parse().ok());
+        let phone = ClientFingerprint::new(None, Some("Safari"), "10.0.0.5".parse().ok());
+
+        let strict =
+            AuthService::new("test_secret".to_string()).with_token_binding(TokenBinding::Strict);
+        let token = strict
+            .generate_verified_user_token(&user, Some(&laptop))
+            .unwrap();
+        let header = format!("Bearer {}", token);
+        assert!(strict.authenticate(&header, &same_network).is_ok());
+        assert!(strict.authenticate(&header, &phone).is_err());
+
+        let lenient =
+            AuthService::new("test_secret".to_string()).with_token_binding(TokenBinding::Lenient);
+        assert!(lenient.authenticate(&header, &phone).is_ok());
+
+        let unbound = AuthService::new("test_secret".to_string())
+            .generate_verified_user_token(&user, Some(&laptop))
+            .unwrap();
+        assert!(strict
+            .authenticate(&format!("Bearer {}", unbound), &laptop)
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_refresh_token_rotation() {
+        let service = service_with_user().await;
+        let client = ClientFingerprint::new(Some("laptop"), None, None);
+        let login = LoginRequest {
+            username: "testuser".to_string(),
+            password: "password123".to_string(),
+        };
+
+        let token = service.login(login, Some(&client)).await.unwrap();
+        let first = token.refresh_token.unwrap();
+
+        let refreshed = service.refresh(&first, &client).await.unwrap();
+        assert!(service
+            .verify_token(&refreshed.token)
+            .unwrap()
+            .is_verified());
+        let second = refreshed.refresh_token.unwrap();
+        assert_ne!(first, second);
+
+        // Reusing a replaced token revokes the whole family
+        assert!(service.refresh(&first, &client).await.is_err());
+        assert!(service.refresh(&second, &client).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_refresh_reads_the_current_user() {
+        let service = service_with_user().await;
+        let client = ClientFingerprint::new(Some("laptop"), None, None);
+        let login = LoginRequest {
+            username: "testuser".to_string(),
+            password: "password123".to_string(),
+        };
+        let first = service
+            .login(login, Some(&client))
+            .await
+            .unwrap()
+            .refresh_token
+            .unwrap();
+
+        // Refreshing never extends the family
+        let second = service
+            .refresh(&first, &client)
+            .await
+            .unwrap()
+            .refresh_token
+            .unwrap();
+        let refresh_tokens = service.refresh_tokens.read().await;
+        assert_eq!(
+            refresh_tokens[&RefreshToken::hash(&second)].expires_at,
+            refresh_tokens[&RefreshToken::hash(&first)].expires_at
+        );
+        drop(refresh_tokens);
+
+        // The new access token carries the current role
+        let mut stored = service.users.find_by_username("testuser").await.unwrap();
+        stored.user.role = Role::Moderator;
+        service.users.upsert(vec![stored.clone()]).await;
+        let refreshed = service.refresh(&second, &client).await.unwrap();
+        let identity = service.verify_token(&refreshed.token).unwrap();
+        assert_eq!(identity.role(), Role::Moderator);
+
+        // Deleted users cannot refresh and lose the whole family
+        service.users.remove(stored.user.id).await;
+        let third = refreshed.refresh_token.unwrap();
+        assert!(service.refresh(&third, &client).await.is_err());
+        assert!(service.refresh_tokens.read().await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_changed_users_lose_refresh_tokens() {
+        let service = service_with_user().await;
+        let client = ClientFingerprint::new(Some("laptop"), None, None);
+        let login = || async {
+            let login = LoginRequest {
+                username: "testuser".to_string(),
+                password: "password123".to_string(),
+            };
+            service
+                .login(login, Some(&client))
+                .await
+                .unwrap()
+                .refresh_token
+                .unwrap()
+        };
+
+        // Seeding the user unchanged keeps its refresh tokens
+        let refresh_token = login().await;
+        let stored = service.export_credentials().await;
+        service.seed_credentials(stored.clone()).await;
+        service.restore_credentials(stored.clone()).await;
+        let refresh_token = service
+            .refresh(&refresh_token, &client)
+            .await
+            .unwrap()
+            .refresh_token
+            .unwrap();
+
+        // A new role revokes them
+        let mut promoted = stored.clone();
+        promoted[0].user.role = Role::Admin;
+        service.seed_credentials(promoted).await;
+        assert!(service.refresh(&refresh_token, &client).await.is_err());
+
+        // So does restoring a backup without the user
+        let refresh_token = login().await;
+        service.restore_credentials(Vec::new()).await;
+        assert!(service.refresh_tokens.read().await.is_empty());
+        assert!(service.refresh(&refresh_token, &client).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_logout_revokes_refresh_token() {
+        let service = service_with_user().await;
+        let client = ClientFingerprint::new(Some("laptop"), None, None);
+        let login = LoginRequest {
+            username: "testuser".to_string(),
+            password: "password123".to_string(),
+        };
+
+        let token = service.login(login, Some(&client)).await.unwrap();
+        let refresh_token = token.refresh_token.unwrap();
+
+        service.logout(&refresh_token).await;
+        service.logout(&refresh_token).await;
+        assert!(service.refresh(&refresh_token, &client).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_device_login() {
+        let service = AuthService::new("test_secret".to_string());
+        let workstation = ClientFingerprint::new(Some("workstation"), None, None);
+        let phone_user = UserIdentity::Verified(VerifiedUser {
+            id: 1,
+            username: "testuser".to_string(),
+            email: "test@example.com".to_string(),
+            role: Role::Member,
+        });
+
+        let start = service.start_device_login(workstation).await;
+        assert!(start.qr_payload.contains(&start.code));
+        assert!(matches!(
+            service.poll_device_login(&start.poll_token).await,
+            Ok(DeviceLoginStatus::Pending)
+        ));
+
+        service
+            .approve_device_login(&phone_user, &start.code)
+            .await
+            .unwrap();
+        let Ok(DeviceLoginStatus::Approved(token)) =
+            service.poll_device_login(&start.poll_token).await
+        else {
+            panic!("device login should be approved");
+        };
+        assert!(service.verify_token(&token.token).unwrap().is_verified());
+
+        // The token is handed out only once
+        assert!(service.poll_device_login(&start.poll_token).await.is_err());
+        assert!(service
+            .approve_device_login(&phone_user, &start.code)
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_automation_token() {
+        let service = AuthService::new("test_secret".to_string());
+        let owner = VerifiedUser {
+            id: 1,
+            username: "admin".to_string(),
+            email: "admin@example.com".to_string(),
+            role: Role::Admin,
+        };
+        service
+            .restore_credentials(vec![UserCredentials {
+                user: owner.clone(),
+                password_hash: String::new(),
+            }])
+            .await;
+        let request = CreateAutomationTokenRequest {
+            name: "usage export".to_string(),
+            scope: AutomationTokenScope {
+                endpoints: vec!["/api/v1/admin/usage".to_string()],
+                read_only: true,
+                allowed_ips: vec!["10.0.0.5".parse().unwrap()],
+            },
+            lifetime_days: 30,
+        };
+        let issued = service
+            .create_automation_token(&owner, request)
+            .await
+            .unwrap();
+        let header = format!("Bearer {}", issued.secret.token);
+        let usage = AutomationRequest {
+            method: "GET",
+            path: "/api/v1/admin/usage/H001",
+            ip: "10.0.0.5".parse().ok(),
+        };
+
+        assert!(service.is_automation_token(&header));
+        assert!(service.verify_token(&issued.secret.token).is_err());
+        let token = service
+            .authenticate_automation(&header, &usage)
+            .await
+            .unwrap();
+        assert_eq!(token.owner.username, "admin");
+
+        // Requests outside the scope are forbidden
+        for request in [
+            AutomationRequest {
+                path: "/api/v1/admin/audit",
+                ..usage
+            },
+            AutomationRequest {
+                path: "/api/v1/admin/usage-export",
+                ..usage
+            },
+            AutomationRequest {
+                method: "POST",
+                ..usage
+            },
+            AutomationRequest {
+                ip: "10.0.0.6".parse().ok(),
+                ..usage
+            },
+        ] {
+            assert!(matches!(
+                service.authenticate_automation(&header, &request).await,
+                Err(AppError::Forbidden(_))
+            ));
+        }
+
+        // Rotation invalidates the previous secret
+        let rotated = service
+            .rotate_automation_token(&owner, &issued.automation_token.id)
+            .await
+            .unwrap();
+        assert!(service
+            .authenticate_automation(&header, &usage)
+            .await
+            .is_err());
+        let header = format!("Bearer {}", rotated.secret.token);
+        assert!(service
+            .authenticate_automation(&header, &usage)
+            .await
+            .is_ok());
+
+        service
+            .revoke_automation_token(&owner, &issued.automation_token.id)
+            .await
+            .unwrap();
+        assert!(service
+            .authenticate_automation(&header, &usage)
+            .await
+            .is_err());
+        assert!(service.list_automation_tokens(&owner).await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_automation_tokens_are_managed_by_owners_and_admins() {
+        let service = AuthService::new("test_secret".to_string());
+        let user = |id, username: &str, role| VerifiedUser {
+            id,
+            username: username.to_string(),
+            email: format!("{}@example.com", username),
+            role,
+        };
+        let admin = user(1, "admin", Role::Admin);
+        let member = user(2, "member", Role::Member);
+        service
+            .restore_credentials(
+                [&admin, &member]
+                    .into_iter()
+                    .map(|user| UserCredentials {
+                        user: user.clone(),
+                        password_hash: String::new(),
+                    })
+                    .collect(),
+            )
+            .await;
+        let request = || CreateAutomationTokenRequest {
+            name: "usage export".to_string(),
+            scope: AutomationTokenScope {
+                endpoints: vec!["/api/v1/admin/usage".to_string()],
+                read_only: true,
+                allowed_ips: Vec::new(),
+            },
+            lifetime_days: 30,
+        };
+        let admin_token = service
+            .create_automation_token(&admin, request())
+            .await
+            .unwrap();
+        let member_token = service
+            .create_automation_token(&member, request())
+            .await
+            .unwrap();
+        let id = &admin_token.automation_token.id;
+
+        // Members neither see nor take over the tokens of others
+        let listed = service.list_automation_tokens(&member).await;
+        assert_eq!(listed.len(), 1);
+        assert_eq!(listed[0].id, member_token.automation_token.id);
+        assert_eq!(service.list_automation_tokens(&admin).await.len(), 2);
+        let rotated = service.rotate_automation_token(&member, id).await;
+        assert!(matches!(rotated, Err(AppError::Forbidden(_))));
+        assert_eq!(
+            rotated.unwrap_err().into_response().status(),
+            StatusCode::FORBIDDEN
+        );
+        assert!(matches!(
+            service.revoke_automation_token(&member, id).await,
+            Err(AppError::Forbidden(_))
+        ));
+
+        // The token acts with the role its owner currently has
+        let header = format!("Bearer {}", admin_token.secret.token);
+        let usage = AutomationRequest {
+            method: "GET",
+            path: "/api/v1/admin/usage",
+            ip: None,
+        };
+        let token = service
+            .authenticate_automation(&header, &usage)
+            .await
+            .unwrap();
+        assert_eq!(token.owner.role, Role::Admin);
+        service
+            .restore_credentials(vec![UserCredentials {
+                user: user(1, "admin", Role::Member),
+                password_hash: String::new(),
+            }])
+            .await;
+        let token = service
+            .authenticate_automation(&header, &usage)
+            .await
+            .unwrap();
+        assert_eq!(token.owner.role, Role::Member);
+
+        // Tokens of removed users stop working
+        service.restore_credentials(Vec::new()).await;
+        assert!(matches!(
+            service.authenticate_automation(&header, &usage).await,
+            Err(AppError::Unauthorized(_))
+        ));
+    }
+
     #[test]
     fn test_extract_user_from_invalid_header() {
         let service = AuthService::new("test_secret".to_string());
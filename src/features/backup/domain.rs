@@ -0,0 +1,97 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::features::auth::UserCredentials;
+use crate::features::board::domain::BoardArchive;
+
+/// Version of the snapshot format written by this server
+///
+/// Bumped whenever a change to the format would stop older servers from
+/// restoring a snapshot.
+pub const SNAPSHOT_FORMAT_VERSION: u32 = 1;
+
+/// Prefix of the keys backups are stored under
+pub const BACKUP_KEY_PREFIX: &str = "backups/";
+
+/// Default number of backups kept in the storage
+pub const DEFAULT_BACKUPS_KEPT: usize = 24;
+
+/// Snapshot of the repositories, as stored in a backup
+///
+/// Stored as a single JSON object:
+/// ```json
+/// {
+///   "format_version": 1,
+///   "created_at": "2024-01-01T00:00:00Z",
+///   "users": [{"id": 1, "username": "alice", "email": "...", "role": "admin", "password_hash": "$2b$12$..."}],
+///   "boards": [{"id": 1, "name": "Ward 3", ...}],
+///   "threads": [{"id": 1, "board_id": 1, ...}],
+///   "posts": [{"id": 1, "thread_id": 1, ...}]
+/// }
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Snapshot {
+    pub format_version: u32,
+    pub created_at: DateTime<Utc>,
+    /// Registered users with their password hashes
+    pub users: Vec<UserCredentials>,
+    #[serde(flatten)]
+    pub boards: BoardArchive,
+}
+
+/// Backup stored under a key
+#[derive(Debug, Clone, Serialize)]
+pub struct BackupRecord {
+    pub key: String,
+    pub created_at: DateTime<Utc>,
+    /// Size of the stored snapshot in bytes
+    pub size: u64,
+    pub users: usize,
+    pub boards: usize,
+    pub threads: usize,
+    pub posts: usize,
+}
+
+impl BackupRecord {
+    /// Record of a snapshot stored under `key`
+    pub fn new(key: String, snapshot: &Snapshot, size: u64) -> Self {
+        Self {
+            key,
+            created_at: snapshot.created_at,
+            size,
+            users: snapshot.users.len(),
+            boards: snapshot.boards.boards.len(),
+            threads: snapshot.boards.threads.len(),
+            posts: snapshot.boards.posts.len(),
+        }
+    }
+}
+
+/// Failed backup attempt
+#[derive(Debug, Clone, Serialize)]
+pub struct BackupFailure {
+    pub failed_at: DateTime<Utc>,
+    pub error: String,
+}
+
+/// Backup status reported to admins
+#[derive(Debug, Clone, Serialize)]
+pub struct BackupStatus {
+    /// Most recent successful backup since the server started
+    pub last_backup: Option<BackupRecord>,
+    /// Most recent failure, if it happened after the last successful backup
+    pub last_failure: Option<BackupFailure>,
+    /// Keys of the backups in the storage, oldest first
+    pub stored: Vec<String>,
+}
+
+/// Key a snapshot taken at `created_at` is stored under
+///
+/// Keys sort in the order the snapshots were taken.
+pub fn backup_key(created_at: DateTime<Utc>) -> String {
+    format!(
+        "{}{}.json",
+        BACKUP_KEY_PREFIX,
+        created_at.format("%Y%m%dT%H%M%S%.6fZ")
+    )
+}
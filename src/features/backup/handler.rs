@@ -0,0 +1,35 @@
+use axum::{extract::State, Json};
+
+use crate::infrastructure::AppError;
+
+use super::domain::BackupStatus;
+use super::service::BackupService;
+
+/// Backup status handler
+///
+/// Requires the admin role.
+///
+/// # Route
+/// GET /api/v1/admin/backups
+///
+/// # Response
+/// ```json
+/// {
+///   "last_backup": {
+///     "key": "backups/20240101T000000.000000Z.json",
+///     "created_at": "2024-01-01T00:00:00Z",
+///     "size": 5120,
+///     "users": 12,
+///     "boards": 3,
+///     "threads": 40,
+///     "posts": 215
+///   },
+///   "last_failure": null,
+///   "stored": ["backups/20240101T000000.000000Z.json"]
+/// }
+/// ```
+pub async fn backup_status(
+    State(backup_service): State<BackupService>,
+) -> Result<Json<BackupStatus>, AppError> {
+    backup_service.status().await.map(Json)
+}
@@ -0,0 +1,52 @@
+//! Backup Feature Module
+//!
+//! Scheduled backups of the registered users and the boards with their
+//! threads and posts into object storage. Backups are versioned JSON
+//! snapshots and are restored with `webboard restore`.
+//!
+//! ## Architecture
+//!
+//! ### Domain Layer (`domain.rs`)
+//! - `Snapshot`: Documented, versioned format of a backup
+//! - `BackupRecord`, `BackupStatus`: Outcome of backups
+//!
+//! ### Application Layer (`service.rs`)
+//! - `BackupService`: Taking, pruning and restoring backups
+//!
+//! ### Presentation Layer (`handler.rs`)
+//! - Admin handler reporting the backup status
+//!
+//! ## Usage
+//! ```rust,ignore
+//! use features::backup;
+//!
+//! let backup_service = backup::BackupService::new(
+//!     ObjectStorage::new("/var/backups/webboard"),
+//!     auth_service.clone(),
+//!     board_service.clone(),
+//! );
+//! let interval = Duration::from_secs(3600);
+//! jobs.schedule_after("backup", interval, interval, {
+//!     let backup_service = backup_service.clone();
+//!     move || {
+//!         let backup_service = backup_service.clone();
+//!         async move {
+//!             backup_service.backup().await.map_err(|err| err.to_string())?;
+//!             Ok(())
+//!         }
+//!     }
+//! });
+//!
+//! Router::new()
+//!     .route("/backups", get(backup::backup_status))
+//!     .with_state(backup_service)
+//! ```
+
+pub mod domain;
+pub mod handler;
+pub mod service;
+
+// Re-export commonly used items
+pub use domain::{BackupRecord, BackupStatus, Snapshot};
+pub use handler::backup_status;
+pub use service::BackupService;
@@ -0,0 +1,262 @@
+use chrono::Utc;
+use futures::future::BoxFuture;
+use std::io;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+
+use crate::features::auth::AuthService;
+use crate::features::board::BoardService;
+use crate::features::health::HealthCheck;
+use crate::infrastructure::{AppError, ObjectStorage};
+
+use super::domain::{
+    backup_key, BackupFailure, BackupRecord, BackupStatus, Snapshot, BACKUP_KEY_PREFIX,
+    DEFAULT_BACKUPS_KEPT, SNAPSHOT_FORMAT_VERSION,
+};
+
+/// Outcome of the backups taken since the server started
+#[derive(Default)]
+struct BackupState {
+    last_backup: Option<BackupRecord>,
+    last_failure: Option<BackupFailure>,
+}
+
+/// Backup service containing business logic
+///
+/// Application layer service that snapshots the registered users and the
+/// boards with their threads and posts into object storage, and restores
+/// them from there. Only the most recent backups are kept.
+#[derive(Clone)]
+pub struct BackupService {
+    storage: ObjectStorage,
+    auth_service: AuthService,
+    board_service: BoardService,
+    /// Number of backups kept in the storage
+    keep: usize,
+    state: Arc<RwLock<BackupState>>,
+}
+
+impl BackupService {
+    /// Create a new backup service snapshotting the given services into `storage`
+    pub fn new(
+        storage: ObjectStorage,
+        auth_service: AuthService,
+        board_service: BoardService,
+    ) -> Self {
+        Self {
+            storage,
+            auth_service,
+            board_service,
+            keep: DEFAULT_BACKUPS_KEPT,
+            state: Arc::new(RwLock::new(BackupState::default())),
+        }
+    }
+
+    /// Set how many backups are kept in the storage, older ones are removed
+    pub fn with_keep(mut self, keep: usize) -> Self {
+        self.keep = keep.max(1);
+        self
+    }
+
+    /// Take a backup
+    ///
+    /// # Business Logic
+    /// 1. Snapshot users, boards, threads and posts
+    /// 2. Store the snapshot under a key ordered by time
+    /// 3. Remove backups beyond the number kept
+    ///
+    /// The outcome is reported in the backup status.
+    pub async fn backup(&self) -> Result<BackupRecord, AppError> {
+        let result = self.store_snapshot().await;
+        let mut state = self.state.write().await;
+        match &result {
+            Ok(record) => {
+                state.last_backup = Some(record.clone());
+                state.last_failure = None;
+            }
+            Err(err) => {
+                state.last_failure = Some(BackupFailure {
+                    failed_at: Utc::now(),
+                    error: err.to_string(),
+                });
+            }
+        }
+        result
+    }
+
+    /// Restore the backup stored under `key`, or the latest one
+    ///
+    /// Replaces all registered users, boards, threads and posts. Snapshots
+    /// written in a newer format than this server knows are refused.
+    pub async fn restore(&self, key: Option<&str>) -> Result<BackupRecord, AppError> {
+        let key = match key {
+            Some(key) => key.to_string(),
+            None => self
+                .list()
+                .await?
+                .pop()
+                .ok_or_else(|| AppError::NotFound("No backup found".to_string()))?,
+        };
+        let data = self
+            .storage
+            .get(&key)
+            .await
+            .map_err(|err| match err.kind() {
+                io::ErrorKind::NotFound => AppError::NotFound(format!("Backup {} not found", key)),
+                _ => storage_error(err),
+            })?;
+        let snapshot: Snapshot = serde_json::from_slice(&data).map_err(|err| {
+            AppError::BadRequest(format!("Backup {} is not a valid snapshot: {}", key, err))
+        })?;
+        if snapshot.format_version > SNAPSHOT_FORMAT_VERSION {
+            return Err(AppError::BadRequest(format!(
+                "Backup {} has format version {}, this server reads up to {}",
+                key, snapshot.format_version, SNAPSHOT_FORMAT_VERSION
+            )));
+        }
+
+        let record = BackupRecord::new(key, &snapshot, data.len() as u64);
+        self.auth_service.restore_credentials(snapshot.users).await;
+        self.board_service.restore(snapshot.boards).await;
+        tracing::info!("Restored backup {}", record.key);
+        Ok(record)
+    }
+
+    /// Status of the backups
+    pub async fn status(&self) -> Result<BackupStatus, AppError> {
+        let stored = self.list().await?;
+        let state = self.state.read().await;
+        Ok(BackupStatus {
+            last_backup: state.last_backup.clone(),
+            last_failure: state.last_failure.clone(),
+            stored,
+        })
+    }
+
+    async fn store_snapshot(&self) -> Result<BackupRecord, AppError> {
+        let snapshot = Snapshot {
+            format_version: SNAPSHOT_FORMAT_VERSION,
+            created_at: Utc::now(),
+            users: self.auth_service.export_credentials().await,
+            boards: self.board_service.export().await,
+        };
+        let data = serde_json::to_vec(&snapshot).map_err(|err| {
+            AppError::InternalError(format!("Failed to serialize snapshot: {}", err))
+        })?;
+        let key = backup_key(snapshot.created_at);
+        self.storage.put(&key, &data).await.map_err(storage_error)?;
+
+        let keys = self.list().await?;
+        for key in keys.iter().take(keys.len().saturating_sub(self.keep)) {
+            if let Err(err) = self.storage.delete(key).await {
+                tracing::warn!("Failed to remove old backup {}: {}", key, err);
+            }
+        }
+
+        Ok(BackupRecord::new(key, &snapshot, data.len() as u64))
+    }
+
+    /// Keys of the stored backups, oldest first
+    async fn list(&self) -> Result<Vec<String>, AppError> {
+        self.storage
+            .list(BACKUP_KEY_PREFIX)
+            .await
+            .map_err(storage_error)
+    }
+}
+
+/// Backups fail while their storage is not writable
+impl HealthCheck for BackupService {
+    fn name(&self) -> &'static str {
+        "backup_storage"
+    }
+
+    fn check(&self) -> BoxFuture<'_, Result<(), String>> {
+        Box::pin(async move { self.storage.check().await.map_err(|err| err.to_string()) })
+    }
+}
+
+fn storage_error(err: io::Error) -> AppError {
+    AppError::InternalError(format!("Backup storage error: {}", err))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::features::auth::RegisterRequest;
+    use crate::features::board::domain::CreateBoardRequest;
+    use crate::features::users::domain::{Role, UserIdentity, VerifiedUser};
+    use crate::infrastructure::AuditContext;
+
+    #[tokio::test]
+    async fn test_backup_and_restore() {
+        let root = std::env::temp_dir().join(format!("webboard-backup-{}", uuid::Uuid::new_v4()));
+        let auth_service = AuthService::new("secret".to_string()).with_password_hash_cost(4);
+        let board_service = BoardService::default();
+        let service = BackupService::new(
+            ObjectStorage::new(&root),
+            auth_service.clone(),
+            board_service.clone(),
+        )
+        .with_keep(2);
+
+        assert!(matches!(
+            service.restore(None).await,
+            Err(AppError::NotFound(_))
+        ));
+
+        auth_service
+            .register(RegisterRequest {
+                username: "alice".to_string(),
+                email: "alice@example.com".to_string(),
+                password: "password123".to_string(),
+            })
+            .await
+            .unwrap();
+        let admin = UserIdentity::Verified(VerifiedUser {
+            id: 1,
+            username: "alice".to_string(),
+            email: "alice@example.com".to_string(),
+            role: Role::Admin,
+        });
+        board_service
+            .create_board(
+                &admin,
+                CreateBoardRequest {
+                    name: "Ward 3".to_string(),
+                    description: None,
+                },
+                &AuditContext::default(),
+            )
+            .await
+            .unwrap();
+
+        let record = service.backup().await.unwrap();
+        assert_eq!((record.users, record.boards), (1, 1));
+
+        // Restoring brings back the content of the backup
+        let restored_auth = AuthService::new("secret".to_string());
+        let restored_boards = BoardService::default();
+        let restored = BackupService::new(
+            ObjectStorage::new(&root),
+            restored_auth.clone(),
+            restored_boards.clone(),
+        )
+        .restore(None)
+        .await
+        .unwrap();
+        assert_eq!(restored.key, record.key);
+        assert_eq!(restored_auth.export_credentials().await.len(), 1);
+        assert_eq!(restored_boards.list_boards().await[0].name, "Ward 3");
+
+        // Only the most recent backups are kept
+        service.backup().await.unwrap();
+        service.backup().await.unwrap();
+        let status = service.status().await.unwrap();
+        assert_eq!(status.stored.len(), 2);
+        assert!(!status.stored.contains(&record.key));
+        assert!(status.last_failure.is_none());
+
+        tokio::fs::remove_dir_all(root).await.unwrap();
+    }
+}
@@ -0,0 +1,209 @@
+use chrono::{DateTime, Utc};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// Maximum length of board names and thread titles in characters
+pub const MAX_TITLE_LENGTH: usize = 200;
+
+/// Maximum length of a post body in characters
+pub const MAX_BODY_LENGTH: usize = 10_000;
+
+/// Default number of seconds a deleted thread or post can be restored
+pub const DEFAULT_UNDO_WINDOW_SECS: i64 = 30;
+
+/// Message board domain model
+///
+/// Boards group threads by topic and are managed by admins.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Board {
+    pub id: u64,
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    pub created_by: String,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Discussion thread on a board
+///
+/// Opened with a first post; replies are appended as further posts.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Thread {
+    pub id: u64,
+    pub board_id: u64,
+    pub title: String,
+    /// Stored actor id of the author
+    pub author_id: String,
+    /// Username of the author, for verified users
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub author_name: Option<String>,
+    pub post_count: usize,
+    pub created_at: DateTime<Utc>,
+    pub last_post_at: DateTime<Utc>,
+}
+
+/// Post in a thread
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct Post {
+    pub id: u64,
+    pub thread_id: u64,
+    pub body: String,
+    /// Stored actor id of the author
+    pub author_id: String,
+    /// Username of the author, for verified users
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub author_name: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Thread together with its posts, oldest first
+#[derive(Debug, Clone, Serialize)]
+pub struct ThreadDetail {
+    #[serde(flatten)]
+    pub thread: Thread,
+    pub posts: Vec<Post>,
+}
+
+/// All boards with their threads and posts, as kept in backups
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct BoardArchive {
+    pub boards: Vec<Board>,
+    pub threads: Vec<Thread>,
+    pub posts: Vec<Post>,
+}
+
+/// Kind of content a pending deletion removes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DeletedContent {
+    Thread,
+    Post,
+}
+
+/// Deletion of a thread or post that can still be undone
+///
+/// Deleted content is hidden right away and removed for good once the undo
+/// window has passed.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PendingDeletion {
+    /// Id to pass to the undo endpoint
+    pub id: String,
+    pub content: DeletedContent,
+    pub board_id: u64,
+    pub thread_id: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub post_id: Option<u64>,
+    /// Stored actor id of the identity that deleted the content
+    pub deleted_by: String,
+    pub undo_until: DateTime<Utc>,
+}
+
+/// Request payload for undoing a deletion
+#[derive(Debug, Deserialize)]
+pub struct UndoRequest {
+    pub deletion_id: String,
+}
+
+/// Request payload for creating a board
+#[derive(Debug, Deserialize)]
+pub struct CreateBoardRequest {
+    pub name: String,
+    pub description: Option<String>,
+}
+
+impl CreateBoardRequest {
+    /// Validate board creation request
+    ///
+    /// Enforces business rules:
+    /// - Name must not be empty or longer than 200 characters
+    pub fn validate(&self) -> Result<(), String> {
+        validate_title("Name", &self.name)
+    }
+}
+
+/// Request payload for opening a thread
+#[derive(Debug, Deserialize)]
+pub struct CreateThreadRequest {
+    pub title: String,
+    /// Body of the first post
+    pub body: String,
+}
+
+impl CreateThreadRequest {
+    /// Validate thread creation request
+    ///
+    /// Enforces business rules:
+    /// - Title must not be empty or longer than 200 characters
+    /// - Body must not be empty or longer than 10000 characters
+    pub fn validate(&self) -> Result<(), String> {
+        validate_title("Title", &self.title)?;
+        validate_body(&self.body)
+    }
+}
+
+/// Request payload for replying to a thread
+#[derive(Debug, Deserialize)]
+pub struct ReplyRequest {
+    pub body: String,
+}
+
+impl ReplyRequest {
+    /// Validate reply request
+    ///
+    /// Enforces business rules:
+    /// - Body must not be empty or longer than 10000 characters
+    pub fn validate(&self) -> Result<(), String> {
+        validate_body(&self.body)
+    }
+}
+
+fn validate_title(field: &str, title: &str) -> Result<(), String> {
+    if title.trim().is_empty() {
+        return Err(format!("{} cannot be empty", field));
+    }
+    if title.chars().count() > MAX_TITLE_LENGTH {
+        return Err(format!(
+            "{} cannot be longer than {} characters",
+            field, MAX_TITLE_LENGTH
+        ));
+    }
+    Ok(())
+}
+
+fn validate_body(body: &str) -> Result<(), String> {
+    if body.trim().is_empty() {
+        return Err("Body cannot be empty".to_string());
+    }
+    if body.chars().count() > MAX_BODY_LENGTH {
+        return Err(format!(
+            "Body cannot be longer than {} characters",
+            MAX_BODY_LENGTH
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_thread_request() {
+        let request = CreateThreadRequest {
+            title: "Night shift handover".to_string(),
+            body: "Please use the new checklist.".to_string(),
+        };
+        assert!(request.validate().is_ok());
+
+        let empty_title = CreateThreadRequest {
+            title: "  ".to_string(),
+            ..request
+        };
+        assert!(empty_title.validate().is_err());
+
+        let long_body = ReplyRequest {
+            body: "x".repeat(MAX_BODY_LENGTH + 1),
+        };
+        assert!(long_body.validate().is_err());
+    }
+}
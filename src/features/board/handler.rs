@@ -0,0 +1,453 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Json,
+};
+
+use serde::Deserialize;
+
+use crate::features::auth::{
+    ActionTokenHeader, AuthService, AuthenticatedUser, DELETE_BOARD_ACTION,
+};
+use crate::features::jsonrpc::{JsonRpcService, MethodPolicy, RpcContext};
+use crate::infrastructure::{AppError, AuditContext, JsonBody};
+
+use super::domain::{
+    Board, CreateBoardRequest, CreateThreadRequest, PendingDeletion, Post, ReplyRequest, Thread,
+    ThreadDetail, UndoRequest,
+};
+use super::service::BoardService;
+
+/// List boards handler
+///
+/// Requires authentication.
+///
+/// # Route
+/// GET /api/v1/boards
+///
+/// # Response
+/// ```json
+/// [
+///   {
+///     "id": 1,
+///     "name": "Ward 3",
+///     "description": "Handover notes and questions",
+///     "created_by": "user:1",
+///     "created_at": "2024-01-01T00:00:00Z"
+///   }
+/// ]
+/// ```
+pub async fn list_boards(State(board_service): State<BoardService>) -> Json<Vec<Board>> {
+    Json(board_service.list_boards().await)
+}
+
+/// Create board handler
+///
+/// Requires authentication as an admin.
+///
+/// # Route
+/// POST /api/v1/boards
+///
+/// # Request Body
+/// ```json
+/// {
+///   "name": "Ward 3",
+///   "description": "Handover notes and questions"
+/// }
+/// ```
+///
+/// # Response
+/// 201 Created with the board
+pub async fn create_board(
+    State(board_service): State<BoardService>,
+    user: AuthenticatedUser,
+    audit: AuditContext,
+    JsonBody(payload): JsonBody<CreateBoardRequest>,
+) -> Result<(StatusCode, Json<Board>), AppError> {
+    let board = board_service.create_board(&user.0, payload, &audit).await?;
+    Ok((StatusCode::CREATED, Json(board)))
+}
+
+/// Delete board handler
+///
+/// Requires authentication as an admin and an action token minted for
+/// `boards.delete`. Deletes all threads of the board.
+///
+/// # Route
+/// DELETE /api/v1/boards/:board_id
+/// X-Action-Token: <token>
+///
+/// # Response
+/// 204 No Content
+pub async fn delete_board(
+    State(board_service): State<BoardService>,
+    State(auth_service): State<AuthService>,
+    user: AuthenticatedUser,
+    ActionTokenHeader(action_token): ActionTokenHeader,
+    audit: AuditContext,
+    Path(board_id): Path<u64>,
+) -> Result<StatusCode, AppError> {
+    auth_service.consume_action_token(&user.0, DELETE_BOARD_ACTION, &action_token)?;
+    board_service
+        .delete_board(&user.0, board_id, &audit)
+        .await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// List threads handler
+///
+/// Requires authentication. Threads are ordered by their last post, newest first.
+/// Threads and posts of shadow-banned authors are left out, except for the
+/// authors themselves and moderators.
+///
+/// # Route
+/// GET /api/v1/boards/:board_id/threads
+///
+/// # Response
+/// ```json
+/// [
+///   {
+///     "id": 7,
+///     "board_id": 1,
+///     "title": "Night shift handover",
+///     "author_id": "anonymous:9f2c...",
+///     "post_count": 3,
+///     "created_at": "2024-01-01T21:00:00Z",
+///     "last_post_at": "2024-01-01T22:15:00Z"
+///   }
+/// ]
+/// ```
+pub async fn list_threads(
+    State(board_service): State<BoardService>,
+    user: AuthenticatedUser,
+    Path(board_id): Path<u64>,
+) -> Result<Json<Vec<Thread>>, AppError> {
+    board_service
+        .list_threads(&user.0, board_id)
+        .await
+        .map(Json)
+}
+
+/// Create thread handler
+///
+/// Requires authentication.
+///
+/// # Route
+/// POST /api/v1/boards/:board_id/threads
+///
+/// # Request Body
+/// ```json
+/// {
+///   "title": "Night shift handover",
+///   "body": "Please use the new checklist."
+/// }
+/// ```
+///
+/// # Response
+/// 201 Created with the thread and its first post
+pub async fn create_thread(
+    State(board_service): State<BoardService>,
+    user: AuthenticatedUser,
+    audit: AuditContext,
+    Path(board_id): Path<u64>,
+    JsonBody(payload): JsonBody<CreateThreadRequest>,
+) -> Result<(StatusCode, Json<ThreadDetail>), AppError> {
+    let thread = board_service
+        .create_thread(&user.0, board_id, payload, &audit)
+        .await?;
+    Ok((StatusCode::CREATED, Json(thread)))
+}
+
+/// Get thread handler
+///
+/// Requires authentication. Posts of shadow-banned authors are left out,
+/// except for the authors themselves and moderators.
+///
+/// # Route
+/// GET /api/v1/boards/:board_id/threads/:thread_id
+///
+/// # Response
+/// The thread with a `posts` array, oldest first
+pub async fn get_thread(
+    State(board_service): State<BoardService>,
+    user: AuthenticatedUser,
+    Path((board_id, thread_id)): Path<(u64, u64)>,
+) -> Result<Json<ThreadDetail>, AppError> {
+    board_service
+        .get_thread(&user.0, board_id, thread_id)
+        .await
+        .map(Json)
+}
+
+/// Delete thread handler
+///
+/// Requires authentication as the author of the thread or an admin. The
+/// thread is hidden at once and can be restored until `undo_until`.
+///
+/// # Route
+/// DELETE /api/v1/boards/:board_id/threads/:thread_id
+///
+/// # Response
+/// ```json
+/// {
+///   "id": "0b6f2d1e-8c1a-4d4b-9f5e-3a7c2e1d9b40",
+///   "content": "thread",
+///   "board_id": 1,
+///   "thread_id": 1,
+///   "deleted_by": "user:1",
+///   "undo_until": "2024-01-01T00:00:30Z"
+/// }
+/// ```
+pub async fn delete_thread(
+    State(board_service): State<BoardService>,
+    user: AuthenticatedUser,
+    audit: AuditContext,
+    Path((board_id, thread_id)): Path<(u64, u64)>,
+) -> Result<(StatusCode, Json<PendingDeletion>), AppError> {
+    let deletion = board_service
+        .delete_thread(&user.0, board_id, thread_id, &audit)
+        .await?;
+    Ok((StatusCode::ACCEPTED, Json(deletion)))
+}
+
+/// Reply handler
+///
+/// Requires authentication.
+///
+/// # Route
+/// POST /api/v1/boards/:board_id/threads/:thread_id/posts
+///
+/// # Request Body
+/// ```json
+/// {
+///   "body": "Noted, thanks."
+/// }
+/// ```
+///
+/// # Response
+/// 201 Created with the post
+pub async fn reply_to_thread(
+    State(board_service): State<BoardService>,
+    user: AuthenticatedUser,
+    audit: AuditContext,
+    Path((board_id, thread_id)): Path<(u64, u64)>,
+    JsonBody(payload): JsonBody<ReplyRequest>,
+) -> Result<(StatusCode, Json<Post>), AppError> {
+    let post = board_service
+        .reply(&user.0, board_id, thread_id, payload, &audit)
+        .await?;
+    Ok((StatusCode::CREATED, Json(post)))
+}
+
+/// Delete post handler
+///
+/// Requires authentication as the author of the post or an admin. The first
+/// post of a thread is deleted with the thread. The post is hidden at once
+/// and can be restored until `undo_until`.
+///
+/// # Route
+/// DELETE /api/v1/boards/:board_id/threads/:thread_id/posts/:post_id
+///
+/// # Response
+/// 202 Accepted with the pending deletion, see `delete_thread`
+pub async fn delete_post(
+    State(board_service): State<BoardService>,
+    user: AuthenticatedUser,
+    audit: AuditContext,
+    Path((board_id, thread_id, post_id)): Path<(u64, u64, u64)>,
+) -> Result<(StatusCode, Json<PendingDeletion>), AppError> {
+    let deletion = board_service
+        .delete_post(&user.0, board_id, thread_id, post_id, &audit)
+        .await?;
+    Ok((StatusCode::ACCEPTED, Json(deletion)))
+}
+
+/// Undo deletion handler
+///
+/// Requires authentication as the identity that deleted the content or an
+/// admin. Only works until the undo window of the deletion has passed.
+///
+/// # Route
+/// POST /api/v1/boards/undo
+///
+/// # Request Body
+/// ```json
+/// {
+///   "deletion_id": "0b6f2d1e-8c1a-4d4b-9f5e-3a7c2e1d9b40"
+/// }
+/// ```
+///
+/// # Response
+/// 204 No Content
+pub async fn undo_deletion(
+    State(board_service): State<BoardService>,
+    user: AuthenticatedUser,
+    audit: AuditContext,
+    JsonBody(payload): JsonBody<UndoRequest>,
+) -> Result<StatusCode, AppError> {
+    board_service
+        .undo_deletion(&user.0, &payload.deletion_id, &audit)
+        .await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Parameters of the `boards.delete` method
+#[derive(Deserialize)]
+pub struct DeleteBoardParams {
+    board_id: u64,
+    /// Action token minted for `boards.delete`
+    action_token: String,
+}
+
+/// Register the JSON-RPC methods of the board feature
+///
+/// All methods require an authenticated connection.
+///
+/// # Methods
+/// - `boards.delete` `{"board_id": 1, "action_token": "<token>"}`: delete a
+///   board as an admin, confirmed with an action token like
+///   `DELETE /api/v1/boards/:board_id`
+pub fn register_rpc_methods(
+    jsonrpc_service: &JsonRpcService,
+    board_service: BoardService,
+    auth_service: AuthService,
+) {
+    let boards = jsonrpc_service
+        .namespace("boards")
+        .with_policy(MethodPolicy::Authenticated);
+    boards.register_typed_method(
+        "delete",
+        move |params: DeleteBoardParams, context: RpcContext| {
+            let board_service = board_service.clone();
+            let auth_service = auth_service.clone();
+            async move {
+                let identity = context.authenticated()?;
+                auth_service.consume_action_token(
+                    identity,
+                    DELETE_BOARD_ACTION,
+                    &params.action_token,
+                )?;
+                board_service
+                    .delete_board(identity, params.board_id, &context.audit_context())
+                    .await?;
+                Ok(())
+            }
+        },
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::features::auth::{auth_middleware, CreateActionTokenRequest};
+    use crate::features::jsonrpc::JsonRpcRequest;
+    use crate::features::users::domain::{Role, UserIdentity, VerifiedUser};
+    use axum::{body::Body, extract::FromRef, http::Request, middleware, routing::delete, Router};
+    use serde_json::json;
+    use tower::util::ServiceExt;
+
+    #[derive(Clone)]
+    struct TestState {
+        board_service: BoardService,
+        auth_service: AuthService,
+    }
+
+    impl FromRef<TestState> for BoardService {
+        fn from_ref(state: &TestState) -> Self {
+            state.board_service.clone()
+        }
+    }
+
+    impl FromRef<TestState> for AuthService {
+        fn from_ref(state: &TestState) -> Self {
+            state.auth_service.clone()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_delete_board_requires_action_token() {
+        let admin = VerifiedUser {
+            id: 1,
+            username: "admin".to_string(),
+            email: "admin@example.com".to_string(),
+            role: Role::Admin,
+        };
+        let auth_service = AuthService::new("secret".to_string());
+        let board_service = BoardService::default();
+        let identity = UserIdentity::Verified(admin.clone());
+        let mut boards = Vec::new();
+        for name in ["Ward 3", "Ward 4"] {
+            let request = CreateBoardRequest {
+                name: name.to_string(),
+                description: None,
+            };
+            let board = board_service
+                .create_board(&identity, request, &AuditContext::default())
+                .await
+                .unwrap();
+            boards.push(board.id);
+        }
+        let action_token = || {
+            let request = CreateActionTokenRequest {
+                action: DELETE_BOARD_ACTION.to_string(),
+            };
+            auth_service
+                .issue_action_token(&identity, request)
+                .unwrap()
+                .token
+        };
+
+        let app = Router::new()
+            .route(
+                "/boards/:board_id",
+                delete(delete_board).layer(middleware::from_fn_with_state(
+                    auth_service.clone(),
+                    auth_middleware,
+                )),
+            )
+            .with_state(TestState {
+                board_service: board_service.clone(),
+                auth_service: auth_service.clone(),
+            });
+        let bearer = format!(
+            "Bearer {}",
+            auth_service
+                .generate_verified_user_token(&admin, None)
+                .unwrap()
+        );
+        let request = |action_token: Option<String>| {
+            let mut request = Request::builder()
+                .uri(format!("/boards/{}", boards[0]))
+                .method("DELETE")
+                .header("authorization", &bearer);
+            if let Some(action_token) = action_token {
+                request = request.header("x-action-token", action_token);
+            }
+            request.body(Body::empty()).unwrap()
+        };
+        let response = app.clone().oneshot(request(None)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+        let response = app.oneshot(request(Some(action_token()))).await.unwrap();
+        assert_eq!(response.status(), StatusCode::NO_CONTENT);
+
+        // The JSON-RPC method consumes tokens the same way
+        let jsonrpc_service = JsonRpcService::new();
+        register_rpc_methods(
+            &jsonrpc_service,
+            board_service.clone(),
+            auth_service.clone(),
+        );
+        let context = RpcContext::new(Some(identity.clone()));
+        let token = action_token();
+        for expected_ok in [true, false] {
+            let request = JsonRpcRequest::new(
+                "boards.delete".to_string(),
+                Some(json!({"board_id": boards[1], "action_token": token})),
+                Some(json!(1)),
+            );
+            let response = jsonrpc_service.handle_request(request, &context).await;
+            assert_eq!(matches!(response, Some(Ok(_))), expected_ok);
+        }
+        assert!(board_service.list_boards().await.is_empty());
+    }
+}
@@ -0,0 +1,42 @@
+//! Board Feature Module
+//!
+//! Message boards with discussion threads and posts. Admins manage boards;
+//! verified and anonymous users open threads and reply.
+//!
+//! ## Architecture
+//!
+//! ### Domain Layer (`domain.rs`)
+//! - `Board`, `Thread`, `Post`: Board entities
+//! - `CreateBoardRequest`, `CreateThreadRequest`, `ReplyRequest`: Value objects with validation
+//! - `PendingDeletion`: Deleted thread or post that can still be restored
+//! - `BoardArchive`: All boards, threads and posts, as kept in backups
+//!
+//! ### Application Layer (`service.rs`)
+//! - `BoardService`: Board management, threads, replies, deletion with an undo window
+//!
+//! ### Presentation Layer (`handler.rs`)
+//! - HTTP handlers for the board endpoints
+//! - JSON-RPC methods of the `boards` namespace
+//!
+//! ## Usage
+//! ```rust,ignore
+//! use features::board;
+//!
+//! let board_service = board::BoardService::new(audit_log.clone());
+//!
+//! Router::new()
+//!     .route("/boards", get(board::list_boards).post(board::create_board))
+//!     .with_state(board_service)
+//! ```
+
+pub mod domain;
+pub mod handler;
+pub mod service;
+
+// Re-export commonly used items
+pub use domain::{Board, BoardArchive, PendingDeletion, Post, Thread, ThreadDetail};
+pub use handler::{
+    create_board, create_thread, delete_board, delete_post, delete_thread, get_thread, list_boards,
+    list_threads, register_rpc_methods, reply_to_thread, undo_deletion,
+};
+pub use service::BoardService;
@@ -0,0 +1,1314 @@
+use chrono::{DateTime, Duration, Utc};
+use std::collections::{BTreeMap, HashSet};
+use std::sync::Arc;
+use tokio::sync::RwLock;
+
+use crate::features::jsonrpc::{JsonRpcService, PostCreated, ServerEvent};
+use crate::features::moderation::ModerationService;
+use crate::features::notifications::{NotificationKind, NotificationService};
+use crate::features::users::domain::{Role, UserIdentity};
+use crate::infrastructure::{
+    audit::AuditActor, AppError, AuditContext, AuditLog, AuditOperation, HoldKind, LegalHolds,
+};
+
+use super::domain::{
+    Board, BoardArchive, CreateBoardRequest, CreateThreadRequest, DeletedContent, PendingDeletion,
+    Post, ReplyRequest, Thread, ThreadDetail, DEFAULT_UNDO_WINDOW_SECS,
+};
+
+/// Boards, threads and posts, kept under a single lock so a thread and its
+/// posts are always updated together
+#[derive(Default)]
+struct BoardStore {
+    boards: BTreeMap<u64, Board>,
+    threads: BTreeMap<u64, Thread>,
+    posts: BTreeMap<u64, Post>,
+    /// Deletions that can still be undone, by id
+    pending_deletions: BTreeMap<String, PendingDeletion>,
+    /// Threads and posts of pending deletions, hidden from everyone
+    deleted: HashSet<(DeletedContent, u64)>,
+    last_board_id: u64,
+    last_thread_id: u64,
+    last_post_id: u64,
+}
+
+impl BoardStore {
+    /// Thread `thread_id` if it belongs to board `board_id`
+    fn thread(&self, board_id: u64, thread_id: u64) -> Result<&Thread, AppError> {
+        self.threads
+            .get(&thread_id)
+            .filter(|thread| thread.board_id == board_id)
+            .ok_or_else(|| AppError::NotFound(format!("Thread {} not found", thread_id)))
+    }
+
+    /// Thread `thread_id` of board `board_id`, unless it is deleted or its
+    /// author is hidden
+    fn visible_thread(
+        &self,
+        board_id: u64,
+        thread_id: u64,
+        hidden: &HashSet<String>,
+    ) -> Result<&Thread, AppError> {
+        self.thread(board_id, thread_id)
+            .ok()
+            .filter(|thread| self.is_thread_visible(thread, hidden))
+            .ok_or_else(|| AppError::NotFound(format!("Thread {} not found", thread_id)))
+    }
+
+    fn is_thread_visible(&self, thread: &Thread, hidden: &HashSet<String>) -> bool {
+        !hidden.contains(&thread.author_id)
+            && !self.deleted.contains(&(DeletedContent::Thread, thread.id))
+    }
+
+    fn ensure_board(&self, board_id: u64) -> Result<(), AppError> {
+        if !self.boards.contains_key(&board_id) {
+            return Err(AppError::NotFound(format!("Board {} not found", board_id)));
+        }
+        Ok(())
+    }
+
+    /// Posts of a thread that are not deleted and whose author is not
+    /// hidden, oldest first
+    fn visible_posts<'a>(
+        &'a self,
+        thread_id: u64,
+        hidden: &'a HashSet<String>,
+    ) -> impl Iterator<Item = &'a Post> {
+        self.posts.values().filter(move |post| {
+            post.thread_id == thread_id
+                && !hidden.contains(&post.author_id)
+                && !self.deleted.contains(&(DeletedContent::Post, post.id))
+        })
+    }
+
+    /// Thread as seen by a viewer that hidden posts are not shown to
+    ///
+    /// The post count and last activity only cover visible posts, so they
+    /// don't give hidden or deleted posts away.
+    fn thread_as_seen(&self, thread: &Thread, hidden: &HashSet<String>) -> Thread {
+        let mut thread = thread.clone();
+        if hidden.is_empty() && self.deleted.is_empty() {
+            return thread;
+        }
+
+        let visible: Vec<&Post> = self.visible_posts(thread.id, hidden).collect();
+        thread.post_count = visible.len();
+        thread.last_post_at = visible
+            .iter()
+            .map(|post| post.created_at)
+            .max()
+            .unwrap_or(thread.created_at);
+        thread
+    }
+
+    /// Hide content until the deletion is undone or carried out
+    fn mark_deleted(&mut self, deletion: PendingDeletion) {
+        self.deleted.insert(deletion.target());
+        self.pending_deletions.insert(deletion.id.clone(), deletion);
+    }
+
+    /// Drop a pending deletion, making its content visible again unless it
+    /// has been removed
+    fn unmark_deleted(&mut self, deletion_id: &str) -> Option<PendingDeletion> {
+        let deletion = self.pending_deletions.remove(deletion_id)?;
+        self.deleted.remove(&deletion.target());
+        Some(deletion)
+    }
+}
+
+impl PendingDeletion {
+    /// Thread or post the deletion hides
+    fn target(&self) -> (DeletedContent, u64) {
+        match self.content {
+            DeletedContent::Thread => (DeletedContent::Thread, self.thread_id),
+            DeletedContent::Post => (DeletedContent::Post, self.post_id.unwrap_or_default()),
+        }
+    }
+}
+
+/// Board service containing business logic
+///
+/// Application layer service that stores message boards, their threads and
+/// posts. Boards are managed by admins; any authenticated identity can open
+/// threads and reply. Authors are stored under their audit actor id, so the
+/// composite keys of anonymous users are never kept. Content of shadow-banned
+/// authors is only shown to themselves and to moderators. Deleted threads and
+/// posts are hidden at once but only removed after an undo window.
+/// In a real application, this would interact with a database repository.
+#[derive(Clone)]
+pub struct BoardService {
+    store: Arc<RwLock<BoardStore>>,
+    audit_log: AuditLog,
+    legal_holds: LegalHolds,
+    moderation: ModerationService,
+    /// Pushes new posts to subscribed WebSocket connections, if set
+    notifications: Option<JsonRpcService>,
+    /// Notifies thread authors of replies, if set
+    reply_notifications: Option<NotificationService>,
+    /// How long deleted threads and posts can be restored
+    undo_window: Duration,
+}
+
+impl BoardService {
+    /// Create a new board service recording writes into the given audit log
+    pub fn new(audit_log: AuditLog) -> Self {
+        Self {
+            store: Arc::new(RwLock::new(BoardStore::default())),
+            legal_holds: LegalHolds::new(audit_log.clone()),
+            moderation: ModerationService::new(audit_log.clone()),
+            notifications: None,
+            reply_notifications: None,
+            undo_window: Duration::seconds(DEFAULT_UNDO_WINDOW_SECS),
+            audit_log,
+        }
+    }
+
+    /// Set how long deleted threads and posts can be restored
+    pub fn with_undo_window(mut self, undo_window: Duration) -> Self {
+        self.undo_window = undo_window;
+        self
+    }
+
+    /// Hide content of identities shadow-banned by this moderation service
+    pub fn with_moderation(mut self, moderation: ModerationService) -> Self {
+        self.moderation = moderation;
+        self
+    }
+
+    /// Push new posts to WebSocket connections subscribed to `boards.post_created`
+    pub fn with_notifications(mut self, jsonrpc_service: JsonRpcService) -> Self {
+        self.notifications = Some(jsonrpc_service);
+        self
+    }
+
+    /// Notify thread authors of replies through this notification service
+    pub fn with_reply_notifications(mut self, notification_service: NotificationService) -> Self {
+        self.reply_notifications = Some(notification_service);
+        self
+    }
+
+    /// Refuse to delete posts under these legal holds, and the threads and
+    /// boards containing them
+    pub fn with_legal_holds(mut self, legal_holds: LegalHolds) -> Self {
+        self.legal_holds = legal_holds;
+        self
+    }
+
+    /// Create a board
+    ///
+    /// # Business Logic
+    /// 1. Only admins may create boards
+    /// 2. Validate the request
+    /// 3. Store and audit the board
+    pub async fn create_board(
+        &self,
+        author: &UserIdentity,
+        request: CreateBoardRequest,
+        audit: &AuditContext,
+    ) -> Result<Board, AppError> {
+        if !author.has_role(Role::Admin) {
+            return Err(AppError::Forbidden(
+                "Only admins can create boards".to_string(),
+            ));
+        }
+        request.validate().map_err(AppError::BadRequest)?;
+
+        let mut store = self.store.write().await;
+        store.last_board_id += 1;
+        let board = Board {
+            id: store.last_board_id,
+            name: request.name,
+            description: request.description.filter(|d| !d.trim().is_empty()),
+            created_by: self.actor_id(author),
+            created_at: Utc::now(),
+        };
+        store.boards.insert(board.id, board.clone());
+        drop(store);
+
+        tracing::info!("Created board: {:?}", board);
+        self.audit_log
+            .record(
+                audit,
+                AuditOperation::Create,
+                "board",
+                board.id,
+                None,
+                serde_json::to_value(&board).ok(),
+            )
+            .await;
+
+        Ok(board)
+    }
+
+    /// List all boards, oldest first
+    pub async fn list_boards(&self) -> Vec<Board> {
+        self.store.read().await.boards.values().cloned().collect()
+    }
+
+    /// All boards, threads and posts
+    ///
+    /// Content whose deletion can still be undone is included.
+    pub async fn export(&self) -> BoardArchive {
+        let store = self.store.read().await;
+        BoardArchive {
+            boards: store.boards.values().cloned().collect(),
+            threads: store.threads.values().cloned().collect(),
+            posts: store.posts.values().cloned().collect(),
+        }
+    }
+
+    /// Replace all boards, threads and posts, e.g. when restoring a backup
+    ///
+    /// Pending deletions are dropped, so their content is visible again.
+    pub async fn restore(&self, archive: BoardArchive) {
+        let mut store = self.store.write().await;
+        *store = BoardStore {
+            last_board_id: archive
+                .boards
+                .iter()
+                .map(|board| board.id)
+                .max()
+                .unwrap_or(0),
+            last_thread_id: archive
+                .threads
+                .iter()
+                .map(|thread| thread.id)
+                .max()
+                .unwrap_or(0),
+            last_post_id: archive.posts.iter().map(|post| post.id).max().unwrap_or(0),
+            boards: archive
+                .boards
+                .into_iter()
+                .map(|board| (board.id, board))
+                .collect(),
+            threads: archive
+                .threads
+                .into_iter()
+                .map(|thread| (thread.id, thread))
+                .collect(),
+            posts: archive
+                .posts
+                .into_iter()
+                .map(|post| (post.id, post))
+                .collect(),
+            ..BoardStore::default()
+        };
+    }
+
+    /// Delete a board with all its threads and posts
+    ///
+    /// Only admins may delete boards.
+    pub async fn delete_board(
+        &self,
+        requester: &UserIdentity,
+        board_id: u64,
+        audit: &AuditContext,
+    ) -> Result<(), AppError> {
+        if !requester.has_role(Role::Admin) {
+            return Err(AppError::Forbidden(
+                "Only admins can delete boards".to_string(),
+            ));
+        }
+
+        let mut store = self.store.write().await;
+        store.ensure_board(board_id)?;
+        let thread_ids: Vec<u64> = store
+            .threads
+            .values()
+            .filter(|thread| thread.board_id == board_id)
+            .map(|thread| thread.id)
+            .collect();
+        self.check_legal_holds(
+            store
+                .posts
+                .values()
+                .filter(|post| thread_ids.contains(&post.thread_id)),
+        )?;
+
+        let board = store
+            .boards
+            .remove(&board_id)
+            .ok_or_else(|| AppError::NotFound(format!("Board {} not found", board_id)))?;
+        for thread_id in &thread_ids {
+            store.threads.remove(thread_id);
+        }
+        store
+            .posts
+            .retain(|_, post| !thread_ids.contains(&post.thread_id));
+        let deletion_ids: Vec<String> = store
+            .pending_deletions
+            .values()
+            .filter(|deletion| deletion.board_id == board_id)
+            .map(|deletion| deletion.id.clone())
+            .collect();
+        for deletion_id in &deletion_ids {
+            store.unmark_deleted(deletion_id);
+        }
+        drop(store);
+
+        self.audit_log
+            .record(
+                audit,
+                AuditOperation::Delete,
+                "board",
+                board_id,
+                serde_json::to_value(&board).ok(),
+                None,
+            )
+            .await;
+
+        Ok(())
+    }
+
+    /// Open a thread on a board with its first post
+    pub async fn create_thread(
+        &self,
+        author: &UserIdentity,
+        board_id: u64,
+        request: CreateThreadRequest,
+        audit: &AuditContext,
+    ) -> Result<ThreadDetail, AppError> {
+        request.validate().map_err(AppError::BadRequest)?;
+
+        let author_id = self.actor_id(author);
+        let author_name = author.as_verified().map(|user| user.username.clone());
+        let now = Utc::now();
+
+        let mut store = self.store.write().await;
+        store.ensure_board(board_id)?;
+        store.last_thread_id += 1;
+        store.last_post_id += 1;
+        let thread = Thread {
+            id: store.last_thread_id,
+            board_id,
+            title: request.title,
+            author_id: author_id.clone(),
+            author_name: author_name.clone(),
+            post_count: 1,
+            created_at: now,
+            last_post_at: now,
+        };
+        let post = Post {
+            id: store.last_post_id,
+            thread_id: thread.id,
+            body: request.body,
+            author_id,
+            author_name,
+            created_at: now,
+        };
+        store.threads.insert(thread.id, thread.clone());
+        store.posts.insert(post.id, post.clone());
+        drop(store);
+
+        tracing::info!("Created thread: {:?}", thread);
+        self.audit_log
+            .record(
+                audit,
+                AuditOperation::Create,
+                "thread",
+                thread.id,
+                None,
+                serde_json::to_value(&thread).ok(),
+            )
+            .await;
+        self.notify_post_created(board_id, &thread.author_id, &post)
+            .await;
+
+        Ok(ThreadDetail {
+            thread,
+            posts: vec![post],
+        })
+    }
+
+    /// List the threads of a board visible to `viewer`, most recently active first
+    pub async fn list_threads(
+        &self,
+        viewer: &UserIdentity,
+        board_id: u64,
+    ) -> Result<Vec<Thread>, AppError> {
+        let hidden = self.moderation.hidden_authors(viewer);
+        let store = self.store.read().await;
+        store.ensure_board(board_id)?;
+
+        let mut threads: Vec<Thread> = store
+            .threads
+            .values()
+            .filter(|thread| {
+                thread.board_id == board_id && store.is_thread_visible(thread, &hidden)
+            })
+            .map(|thread| store.thread_as_seen(thread, &hidden))
+            .collect();
+        threads.sort_by(|a, b| b.last_post_at.cmp(&a.last_post_at).then(b.id.cmp(&a.id)));
+        Ok(threads)
+    }
+
+    /// Get a thread with the posts visible to `viewer`, oldest first
+    pub async fn get_thread(
+        &self,
+        viewer: &UserIdentity,
+        board_id: u64,
+        thread_id: u64,
+    ) -> Result<ThreadDetail, AppError> {
+        let hidden = self.moderation.hidden_authors(viewer);
+        let store = self.store.read().await;
+        let thread = store.visible_thread(board_id, thread_id, &hidden)?;
+
+        Ok(ThreadDetail {
+            thread: store.thread_as_seen(thread, &hidden),
+            posts: store.visible_posts(thread_id, &hidden).cloned().collect(),
+        })
+    }
+
+    /// Reply to a thread
+    pub async fn reply(
+        &self,
+        author: &UserIdentity,
+        board_id: u64,
+        thread_id: u64,
+        request: ReplyRequest,
+        audit: &AuditContext,
+    ) -> Result<Post, AppError> {
+        request.validate().map_err(AppError::BadRequest)?;
+
+        let author_id = self.actor_id(author);
+        let hidden = self.moderation.hidden_authors(author);
+        let now = Utc::now();
+
+        let mut store = self.store.write().await;
+        let thread_author_id = store
+            .visible_thread(board_id, thread_id, &hidden)?
+            .author_id
+            .clone();
+        store.last_post_id += 1;
+        let post = Post {
+            id: store.last_post_id,
+            thread_id,
+            body: request.body,
+            author_id,
+            author_name: author.as_verified().map(|user| user.username.clone()),
+            created_at: now,
+        };
+        store.posts.insert(post.id, post.clone());
+        if let Some(thread) = store.threads.get_mut(&thread_id) {
+            thread.post_count += 1;
+            thread.last_post_at = now;
+        }
+        drop(store);
+
+        self.audit_log
+            .record(
+                audit,
+                AuditOperation::Create,
+                "post",
+                post.id,
+                None,
+                serde_json::to_value(&post).ok(),
+            )
+            .await;
+        self.notify_post_created(board_id, &thread_author_id, &post)
+            .await;
+        self.notify_reply(board_id, &thread_author_id, &post).await;
+
+        Ok(post)
+    }
+
+    /// Delete a thread with all its posts
+    ///
+    /// Only the author of the thread or an admin may delete it. The thread is
+    /// hidden at once and removed once the undo window has passed.
+    pub async fn delete_thread(
+        &self,
+        requester: &UserIdentity,
+        board_id: u64,
+        thread_id: u64,
+        audit: &AuditContext,
+    ) -> Result<PendingDeletion, AppError> {
+        let hidden = self.moderation.hidden_authors(requester);
+        let mut store = self.store.write().await;
+        let thread = store.visible_thread(board_id, thread_id, &hidden)?.clone();
+        self.check_author(requester, &thread.author_id)?;
+        self.check_legal_holds(
+            store
+                .posts
+                .values()
+                .filter(|post| post.thread_id == thread_id),
+        )?;
+
+        let deletion = self.pending_deletion(requester, DeletedContent::Thread, &thread, None);
+        store.mark_deleted(deletion.clone());
+        drop(store);
+
+        self.audit_log
+            .record(
+                audit,
+                AuditOperation::Delete,
+                "thread",
+                thread_id,
+                serde_json::to_value(&thread).ok(),
+                None,
+            )
+            .await;
+
+        Ok(deletion)
+    }
+
+    /// Delete a reply
+    ///
+    /// Only the author of the post or an admin may delete it. The first post
+    /// of a thread is removed by deleting the thread. The post is hidden at
+    /// once and removed once the undo window has passed.
+    pub async fn delete_post(
+        &self,
+        requester: &UserIdentity,
+        board_id: u64,
+        thread_id: u64,
+        post_id: u64,
+        audit: &AuditContext,
+    ) -> Result<PendingDeletion, AppError> {
+        let hidden = self.moderation.hidden_authors(requester);
+        let mut store = self.store.write().await;
+        let thread = store.visible_thread(board_id, thread_id, &hidden)?.clone();
+
+        let post = store
+            .visible_posts(thread_id, &hidden)
+            .find(|post| post.id == post_id)
+            .cloned()
+            .ok_or_else(|| AppError::NotFound(format!("Post {} not found", post_id)))?;
+        self.check_author(requester, &post.author_id)?;
+        self.legal_holds.ensure_not_held(HoldKind::Post, post_id)?;
+
+        let is_first_post = store
+            .posts
+            .values()
+            .find(|post| post.thread_id == thread_id)
+            .is_some_and(|first| first.id == post_id);
+        if is_first_post {
+            return Err(AppError::BadRequest(
+                "The first post of a thread cannot be deleted; delete the thread instead"
+                    .to_string(),
+            ));
+        }
+
+        let deletion =
+            self.pending_deletion(requester, DeletedContent::Post, &thread, Some(post_id));
+        store.mark_deleted(deletion.clone());
+        drop(store);
+
+        self.audit_log
+            .record(
+                audit,
+                AuditOperation::Delete,
+                "post",
+                post_id,
+                serde_json::to_value(&post).ok(),
+                None,
+            )
+            .await;
+
+        Ok(deletion)
+    }
+
+    /// Undo the deletion of a thread or post within the undo window
+    ///
+    /// Only the identity that deleted the content or an admin may undo it.
+    /// Restoring is recorded in the audit log like a creation.
+    pub async fn undo_deletion(
+        &self,
+        requester: &UserIdentity,
+        deletion_id: &str,
+        audit: &AuditContext,
+    ) -> Result<PendingDeletion, AppError> {
+        let not_found = || {
+            AppError::NotFound(format!(
+                "Deletion {} not found or can no longer be undone",
+                deletion_id
+            ))
+        };
+
+        let mut store = self.store.write().await;
+        let deletion = store
+            .pending_deletions
+            .get(deletion_id)
+            .filter(|deletion| deletion.undo_until > Utc::now())
+            .ok_or_else(not_found)?;
+        if !requester.has_role(Role::Admin) && self.actor_id(requester) != deletion.deleted_by {
+            return Err(not_found());
+        }
+        let deletion = store.unmark_deleted(deletion_id).ok_or_else(not_found)?;
+        let (resource_type, restored) = match deletion.content {
+            DeletedContent::Thread => (
+                "thread",
+                serde_json::to_value(store.threads.get(&deletion.thread_id)).ok(),
+            ),
+            DeletedContent::Post => (
+                "post",
+                deletion
+                    .post_id
+                    .and_then(|post_id| serde_json::to_value(store.posts.get(&post_id)).ok()),
+            ),
+        };
+        drop(store);
+
+        let (_, resource_id) = deletion.target();
+        tracing::info!("Restored {} {}", resource_type, resource_id);
+        self.audit_log
+            .record(
+                audit,
+                AuditOperation::Create,
+                resource_type,
+                resource_id,
+                None,
+                restored,
+            )
+            .await;
+
+        Ok(deletion)
+    }
+
+    /// Remove threads and posts whose undo window has passed by `now`
+    ///
+    /// Content placed under legal hold during the undo window stays hidden
+    /// but is kept until the hold is released. Returns the number of
+    /// deletions carried out.
+    pub async fn purge_deletions(&self, now: DateTime<Utc>) -> usize {
+        let mut store = self.store.write().await;
+        let expired: Vec<PendingDeletion> = store
+            .pending_deletions
+            .values()
+            .filter(|deletion| deletion.undo_until <= now)
+            .cloned()
+            .collect();
+
+        let mut purged = 0;
+        for deletion in expired {
+            let held = match deletion.content {
+                DeletedContent::Thread => self
+                    .check_legal_holds(
+                        store
+                            .posts
+                            .values()
+                            .filter(|post| post.thread_id == deletion.thread_id),
+                    )
+                    .is_err(),
+                DeletedContent::Post => deletion
+                    .post_id
+                    .is_some_and(|post_id| self.legal_holds.is_held(HoldKind::Post, post_id)),
+            };
+            if held {
+                continue;
+            }
+
+            store.unmark_deleted(&deletion.id);
+            match deletion.content {
+                DeletedContent::Thread => {
+                    store.threads.remove(&deletion.thread_id);
+                    store
+                        .posts
+                        .retain(|_, post| post.thread_id != deletion.thread_id);
+                }
+                DeletedContent::Post => {
+                    let removed = deletion
+                        .post_id
+                        .and_then(|post_id| store.posts.remove(&post_id));
+                    if let (Some(_), Some(thread)) =
+                        (removed, store.threads.get_mut(&deletion.thread_id))
+                    {
+                        thread.post_count -= 1;
+                    }
+                }
+            }
+            purged += 1;
+        }
+
+        purged
+    }
+
+    /// Pending deletion of a thread, or of one of its posts
+    fn pending_deletion(
+        &self,
+        requester: &UserIdentity,
+        content: DeletedContent,
+        thread: &Thread,
+        post_id: Option<u64>,
+    ) -> PendingDeletion {
+        PendingDeletion {
+            id: uuid::Uuid::new_v4().to_string(),
+            content,
+            board_id: thread.board_id,
+            thread_id: thread.id,
+            post_id,
+            deleted_by: self.actor_id(requester),
+            undo_until: Utc::now() + self.undo_window,
+        }
+    }
+
+    /// Check that `requester` wrote the content stored under `author_id`, or is an admin
+    fn check_author(&self, requester: &UserIdentity, author_id: &str) -> Result<(), AppError> {
+        if requester.has_role(Role::Admin) || self.actor_id(requester) == author_id {
+            return Ok(());
+        }
+        Err(AppError::Forbidden(
+            "Only the author or an admin can delete this".to_string(),
+        ))
+    }
+
+    /// Refuse deleting any of `posts` while it is under legal hold
+    fn check_legal_holds<'a>(
+        &self,
+        mut posts: impl Iterator<Item = &'a Post>,
+    ) -> Result<(), AppError> {
+        posts.try_for_each(|post| self.legal_holds.ensure_not_held(HoldKind::Post, post.id))
+    }
+
+    /// Push a new post to the subscribers allowed to see it
+    ///
+    /// A post is only visible where both its thread and the post itself are.
+    async fn notify_post_created(&self, board_id: u64, thread_author_id: &str, post: &Post) {
+        let Some(jsonrpc_service) = &self.notifications else {
+            return;
+        };
+
+        let event = ServerEvent::PostCreated(PostCreated {
+            board_id,
+            thread_id: post.thread_id,
+            post: post.clone(),
+        });
+        jsonrpc_service
+            .notify_visible(&event, |viewer| {
+                self.moderation.is_visible_to(thread_author_id, viewer)
+                    && self.moderation.is_visible_to(&post.author_id, viewer)
+            })
+            .await;
+    }
+
+    /// Let the author of a thread know about a reply by someone else
+    ///
+    /// Replies of shadow-banned authors are not notified, as the thread
+    /// author cannot see them.
+    async fn notify_reply(&self, board_id: u64, thread_author_id: &str, post: &Post) {
+        let Some(notification_service) = &self.reply_notifications else {
+            return;
+        };
+        if post.author_id == thread_author_id
+            || !self.moderation.is_visible_to(&post.author_id, None)
+        {
+            return;
+        }
+
+        notification_service
+            .notify(
+                thread_author_id,
+                NotificationKind::Reply,
+                board_id,
+                post.thread_id,
+                post.id,
+                post.author_name.clone(),
+            )
+            .await;
+    }
+
+    /// Id authors are stored under
+    fn actor_id(&self, identity: &UserIdentity) -> String {
+        self.audit_log.stored_actor_id(&AuditActor::from(identity))
+    }
+}
+
+impl Default for BoardService {
+    fn default() -> Self {
+        Self::new(AuditLog::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::features::moderation::ShadowBanRequest;
+    use crate::features::users::domain::{AnonymousUserIdentifier, VerifiedUser};
+    use crate::infrastructure::legal_hold::PlaceLegalHoldRequest;
+    use chrono::NaiveDate;
+    use serde_json::Value;
+
+    fn user(id: u64, role: Role) -> UserIdentity {
+        UserIdentity::Verified(VerifiedUser {
+            id,
+            username: format!("user{}", id),
+            email: format!("user{}@example.com", id),
+            role,
+        })
+    }
+
+    fn nurse() -> UserIdentity {
+        UserIdentity::Anonymous(AnonymousUserIdentifier {
+            hospital_code: "H001".to_string(),
+            user_id: "U123".to_string(),
+            user_start_date: NaiveDate::from_ymd_opt(2024, 1, 1).unwrap(),
+            department_code: "D001".to_string(),
+        })
+    }
+
+    async fn board(service: &BoardService) -> Board {
+        service
+            .create_board(
+                &user(1, Role::Admin),
+                CreateBoardRequest {
+                    name: "Ward 3".to_string(),
+                    description: None,
+                },
+                &AuditContext::default(),
+            )
+            .await
+            .unwrap()
+    }
+
+    fn thread_request() -> CreateThreadRequest {
+        CreateThreadRequest {
+            title: "Night shift handover".to_string(),
+            body: "Please use the new checklist.".to_string(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_only_admins_create_boards() {
+        let service = BoardService::default();
+        let result = service
+            .create_board(
+                &user(2, Role::Member),
+                CreateBoardRequest {
+                    name: "Ward 3".to_string(),
+                    description: None,
+                },
+                &AuditContext::default(),
+            )
+            .await;
+        assert!(matches!(result, Err(AppError::Forbidden(_))));
+
+        board(&service).await;
+        assert_eq!(service.list_boards().await.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_thread_and_replies() {
+        let notification_service = NotificationService::default();
+        let service =
+            BoardService::default().with_reply_notifications(notification_service.clone());
+        let board = board(&service).await;
+
+        let thread = service
+            .create_thread(
+                &nurse(),
+                board.id,
+                thread_request(),
+                &AuditContext::default(),
+            )
+            .await
+            .unwrap()
+            .thread;
+        // Raw composite keys are never stored
+        assert!(!thread.author_id.contains("U123"));
+        assert!(thread.author_name.is_none());
+
+        let reply = service
+            .reply(
+                &user(2, Role::Member),
+                board.id,
+                thread.id,
+                ReplyRequest {
+                    body: "Noted".to_string(),
+                },
+                &AuditContext::default(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(reply.author_name.as_deref(), Some("user2"));
+
+        // The thread author is notified of replies by others
+        let notifications = notification_service.list(&nurse()).await;
+        assert_eq!(notifications.unread, 1);
+        assert_eq!(notifications.notifications[0].post_id, reply.id);
+        assert_eq!(
+            notification_service
+                .list(&user(2, Role::Member))
+                .await
+                .notifications
+                .len(),
+            0
+        );
+
+        let detail = service
+            .get_thread(&nurse(), board.id, thread.id)
+            .await
+            .unwrap();
+        assert_eq!(detail.thread.post_count, 2);
+        assert_eq!(detail.posts.len(), 2);
+        assert_eq!(
+            service
+                .list_threads(&nurse(), board.id)
+                .await
+                .unwrap()
+                .len(),
+            1
+        );
+
+        // Threads are only found on their own board
+        assert!(matches!(
+            service.get_thread(&nurse(), board.id + 1, thread.id).await,
+            Err(AppError::NotFound(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_delete_permissions() {
+        let service = BoardService::default();
+        let board = board(&service).await;
+        let detail = service
+            .create_thread(
+                &nurse(),
+                board.id,
+                thread_request(),
+                &AuditContext::default(),
+            )
+            .await
+            .unwrap();
+        let thread_id = detail.thread.id;
+        let reply = service
+            .reply(
+                &nurse(),
+                board.id,
+                thread_id,
+                ReplyRequest {
+                    body: "Noted".to_string(),
+                },
+                &AuditContext::default(),
+            )
+            .await
+            .unwrap();
+
+        // Other members cannot delete, the author can
+        assert!(matches!(
+            service
+                .delete_post(
+                    &user(2, Role::Member),
+                    board.id,
+                    thread_id,
+                    reply.id,
+                    &AuditContext::default()
+                )
+                .await,
+            Err(AppError::Forbidden(_))
+        ));
+        service
+            .delete_post(
+                &nurse(),
+                board.id,
+                thread_id,
+                reply.id,
+                &AuditContext::default(),
+            )
+            .await
+            .unwrap();
+
+        // The first post goes with the thread
+        assert!(matches!(
+            service
+                .delete_post(
+                    &nurse(),
+                    board.id,
+                    thread_id,
+                    detail.posts[0].id,
+                    &AuditContext::default()
+                )
+                .await,
+            Err(AppError::BadRequest(_))
+        ));
+
+        // Admins can delete anything
+        service
+            .delete_thread(
+                &user(1, Role::Admin),
+                board.id,
+                thread_id,
+                &AuditContext::default(),
+            )
+            .await
+            .unwrap();
+        assert!(service
+            .list_threads(&nurse(), board.id)
+            .await
+            .unwrap()
+            .is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_legal_hold_blocks_deletion() {
+        let legal_holds = LegalHolds::default();
+        let service = BoardService::default().with_legal_holds(legal_holds.clone());
+        let admin = user(1, Role::Admin);
+        let board = board(&service).await;
+        let detail = service
+            .create_thread(
+                &nurse(),
+                board.id,
+                thread_request(),
+                &AuditContext::default(),
+            )
+            .await
+            .unwrap();
+        let request = PlaceLegalHoldRequest {
+            kind: HoldKind::Post,
+            id: detail.posts[0].id,
+            reason: "Case 2024-118".to_string(),
+        };
+        legal_holds
+            .place(request, &AuditContext::default())
+            .await
+            .unwrap();
+
+        // Not even admins can delete the post, its thread or its board
+        assert!(matches!(
+            service
+                .delete_thread(&admin, board.id, detail.thread.id, &AuditContext::default())
+                .await,
+            Err(AppError::Forbidden(_))
+        ));
+        assert!(matches!(
+            service
+                .delete_board(&admin, board.id, &AuditContext::default())
+                .await,
+            Err(AppError::Forbidden(_))
+        ));
+        assert_eq!(service.list_boards().await.len(), 1);
+
+        legal_holds
+            .release(HoldKind::Post, detail.posts[0].id, &AuditContext::default())
+            .await
+            .unwrap();
+        service
+            .delete_board(&admin, board.id, &AuditContext::default())
+            .await
+            .unwrap();
+    }
+
+    async fn reply(
+        service: &BoardService,
+        author: &UserIdentity,
+        board_id: u64,
+        thread_id: u64,
+    ) -> Result<Post, AppError> {
+        service
+            .reply(
+                author,
+                board_id,
+                thread_id,
+                ReplyRequest {
+                    body: "Noted".to_string(),
+                },
+                &AuditContext::default(),
+            )
+            .await
+    }
+
+    #[tokio::test]
+    async fn test_shadow_banned_content_is_hidden_from_others() {
+        let moderation = ModerationService::default();
+        let jsonrpc_service = JsonRpcService::new();
+        let service = BoardService::default()
+            .with_moderation(moderation.clone())
+            .with_notifications(jsonrpc_service.clone());
+        let board = board(&service).await;
+        let (banned, other, moderator) = (
+            user(2, Role::Member),
+            user(3, Role::Member),
+            user(4, Role::Moderator),
+        );
+        let events = ["boards.post_created".to_string()];
+        let connections = jsonrpc_service.connections();
+        let (other_connection, mut other_rx) = connections.register_as(Some(other.clone())).await;
+        let (banned_connection, mut banned_rx) =
+            connections.register_as(Some(banned.clone())).await;
+        connections.subscribe(other_connection, &events).await;
+        connections.subscribe(banned_connection, &events).await;
+
+        let thread = service
+            .create_thread(&other, board.id, thread_request(), &AuditContext::default())
+            .await
+            .unwrap()
+            .thread;
+        moderation
+            .shadow_ban(
+                &moderator,
+                ShadowBanRequest {
+                    actor_id: "user:2".to_string(),
+                    reason: "Spam".to_string(),
+                },
+                &AuditContext::default(),
+            )
+            .await
+            .unwrap();
+        reply(&service, &banned, board.id, thread.id).await.unwrap();
+        let spam_thread = service
+            .create_thread(
+                &banned,
+                board.id,
+                thread_request(),
+                &AuditContext::default(),
+            )
+            .await
+            .unwrap()
+            .thread;
+
+        // Everything looks normal to the banned author
+        let detail = service
+            .get_thread(&banned, board.id, thread.id)
+            .await
+            .unwrap();
+        assert_eq!(detail.posts.len(), 2);
+        assert_eq!(detail.thread.post_count, 2);
+        assert_eq!(
+            service.list_threads(&banned, board.id).await.unwrap().len(),
+            2
+        );
+
+        // Others see neither the reply nor the thread, moderators see both
+        let detail = service
+            .get_thread(&other, board.id, thread.id)
+            .await
+            .unwrap();
+        assert_eq!(detail.posts.len(), 1);
+        assert_eq!(detail.thread.post_count, 1);
+        assert_eq!(detail.thread.last_post_at, thread.last_post_at);
+        let threads = service.list_threads(&other, board.id).await.unwrap();
+        assert_eq!(threads.len(), 1);
+        assert_eq!(threads[0].post_count, 1);
+        assert!(matches!(
+            service.get_thread(&other, board.id, spam_thread.id).await,
+            Err(AppError::NotFound(_))
+        ));
+        assert!(matches!(
+            reply(&service, &other, board.id, spam_thread.id).await,
+            Err(AppError::NotFound(_))
+        ));
+        reply(&service, &other, board.id, thread.id).await.unwrap();
+        assert!(service
+            .get_thread(&moderator, board.id, spam_thread.id)
+            .await
+            .is_ok());
+        assert_eq!(
+            service
+                .get_thread(&moderator, board.id, thread.id)
+                .await
+                .unwrap()
+                .posts
+                .len(),
+            3
+        );
+
+        // Notifications of the banned author's posts only reach the author
+        let authors = |rx: &mut tokio::sync::mpsc::Receiver<String>| {
+            std::iter::from_fn(|| rx.try_recv().ok())
+                .map(|message| serde_json::from_str::<Value>(&message).unwrap())
+                .map(|message| message["params"]["post"]["author_id"].clone())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(authors(&mut other_rx), ["user:3", "user:3"]);
+        assert_eq!(
+            authors(&mut banned_rx),
+            ["user:3", "user:2", "user:2", "user:3"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_undo_deletion() {
+        let service = BoardService::default();
+        let board = board(&service).await;
+        let author = user(2, Role::Member);
+        let thread = service
+            .create_thread(
+                &author,
+                board.id,
+                thread_request(),
+                &AuditContext::default(),
+            )
+            .await
+            .unwrap()
+            .thread;
+        let post = reply(&service, &author, board.id, thread.id).await.unwrap();
+
+        // Deleted posts are hidden at once, but kept until the window passes
+        let deletion = service
+            .delete_post(
+                &author,
+                board.id,
+                thread.id,
+                post.id,
+                &AuditContext::default(),
+            )
+            .await
+            .unwrap();
+        let detail = service
+            .get_thread(&author, board.id, thread.id)
+            .await
+            .unwrap();
+        assert_eq!(detail.posts.len(), 1);
+        assert_eq!(detail.thread.post_count, 1);
+
+        // Only the deleter or an admin can undo
+        assert!(service
+            .undo_deletion(
+                &user(3, Role::Member),
+                &deletion.id,
+                &AuditContext::default()
+            )
+            .await
+            .is_err());
+        service
+            .undo_deletion(&author, &deletion.id, &AuditContext::default())
+            .await
+            .unwrap();
+        let detail = service
+            .get_thread(&author, board.id, thread.id)
+            .await
+            .unwrap();
+        assert_eq!(detail.posts.len(), 2);
+        assert!(service
+            .undo_deletion(&author, &deletion.id, &AuditContext::default())
+            .await
+            .is_err());
+
+        // Once the window has passed, deletions are carried out for good
+        let deletion = service
+            .delete_post(
+                &author,
+                board.id,
+                thread.id,
+                post.id,
+                &AuditContext::default(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(service.purge_deletions(Utc::now()).await, 0);
+        assert_eq!(service.purge_deletions(deletion.undo_until).await, 1);
+        assert!(matches!(
+            service
+                .undo_deletion(
+                    &user(1, Role::Admin),
+                    &deletion.id,
+                    &AuditContext::default()
+                )
+                .await,
+            Err(AppError::NotFound(_))
+        ));
+        let detail = service
+            .get_thread(&author, board.id, thread.id)
+            .await
+            .unwrap();
+        assert_eq!(detail.posts.len(), 1);
+        assert_eq!(detail.thread.post_count, 1);
+
+        let deletion = service
+            .delete_thread(&author, board.id, thread.id, &AuditContext::default())
+            .await
+            .unwrap();
+        assert_eq!(service.purge_deletions(deletion.undo_until).await, 1);
+        assert!(service.store.read().await.posts.is_empty());
+    }
+}
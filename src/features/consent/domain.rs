@@ -0,0 +1,148 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Maximum length of a policy version summary in characters
+pub const MAX_SUMMARY_LENGTH: usize = 1_000;
+
+/// Policy document identities have to accept
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PolicyDocument {
+    Terms,
+    PrivacyPolicy,
+}
+
+impl PolicyDocument {
+    /// Human-readable name of the document
+    pub fn name(&self) -> &'static str {
+        match self {
+            PolicyDocument::Terms => "terms of service",
+            PolicyDocument::PrivacyPolicy => "privacy policy",
+        }
+    }
+}
+
+/// Published version of a policy document
+///
+/// Versions are numbered from 1 per document. Publishing a new version
+/// requires every identity to accept it before using the API again.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PolicyVersion {
+    pub document: PolicyDocument,
+    pub version: u32,
+    /// What changed, shown to users when asking for acceptance
+    pub summary: String,
+    /// Where the full text is published
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    pub published_by: String,
+    pub published_at: DateTime<Utc>,
+}
+
+/// Request payload for publishing a new policy version
+#[derive(Debug, Deserialize)]
+pub struct PublishPolicyRequest {
+    pub document: PolicyDocument,
+    pub summary: String,
+    pub url: Option<String>,
+}
+
+impl PublishPolicyRequest {
+    /// Validate publish policy request
+    ///
+    /// Enforces business rules:
+    /// - Summary must not be empty or longer than 1000 characters
+    pub fn validate(&self) -> Result<(), String> {
+        if self.summary.trim().is_empty() {
+            return Err("Summary cannot be empty".to_string());
+        }
+        if self.summary.chars().count() > MAX_SUMMARY_LENGTH {
+            return Err(format!(
+                "Summary cannot be longer than {} characters",
+                MAX_SUMMARY_LENGTH
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Request payload for accepting a policy version
+///
+/// The version is sent explicitly so that a user never accepts a version
+/// published after they were shown the previous one.
+#[derive(Debug, Deserialize)]
+pub struct AcceptPolicyRequest {
+    pub document: PolicyDocument,
+    pub version: u32,
+}
+
+/// Acceptance of a policy version by an identity
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Acceptance {
+    /// Stored actor id of the accepting identity
+    pub actor_id: String,
+    /// Hospital code of anonymous identities
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tenant: Option<String>,
+    pub document: PolicyDocument,
+    pub version: u32,
+    pub accepted_at: DateTime<Utc>,
+}
+
+/// Consent status of the calling identity
+#[derive(Debug, Clone, Serialize)]
+pub struct ConsentStatus {
+    /// Current version of every published document
+    pub current: Vec<PolicyVersion>,
+    /// Latest acceptance of each document
+    pub accepted: Vec<Acceptance>,
+    /// Current versions that have not been accepted yet
+    pub pending: Vec<PolicyVersion>,
+}
+
+/// Query parameters of the consent report
+#[derive(Debug, Default, Deserialize)]
+pub struct ConsentReportQuery {
+    /// Only report identities of this hospital
+    pub hospital_code: Option<String>,
+}
+
+/// Acceptance of the current version of a document
+///
+/// `known` counts identities that were asked to accept it; those that have
+/// not accepted it yet are `pending`.
+#[derive(Debug, Clone, Serialize)]
+pub struct ConsentReport {
+    pub document: PolicyDocument,
+    pub version: u32,
+    pub published_at: DateTime<Utc>,
+    pub known: usize,
+    pub accepted: usize,
+    pub pending: usize,
+    pub acceptance_rate: f64,
+    pub acceptances: Vec<Acceptance>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_publish_request() {
+        let request = PublishPolicyRequest {
+            document: PolicyDocument::Terms,
+            summary: "Clarified data retention".to_string(),
+            url: None,
+        };
+        assert!(request.validate().is_ok());
+
+        let empty = PublishPolicyRequest {
+            summary: " ".to_string(),
+            ..request
+        };
+        assert!(empty.validate().is_err());
+
+        let document: PolicyDocument = serde_json::from_str("\"privacy_policy\"").unwrap();
+        assert_eq!(document, PolicyDocument::PrivacyPolicy);
+    }
+}
@@ -0,0 +1,140 @@
+use axum::{
+    extract::{Query, State},
+    http::StatusCode,
+    Json,
+};
+
+use crate::features::auth::AuthenticatedUser;
+use crate::infrastructure::{AppError, AuditContext, JsonBody};
+
+use super::domain::{
+    AcceptPolicyRequest, Acceptance, ConsentReport, ConsentReportQuery, ConsentStatus,
+    PolicyVersion, PublishPolicyRequest,
+};
+use super::service::ConsentService;
+
+/// Consent status handler
+///
+/// Requires authentication. Reachable while acceptance is pending.
+///
+/// # Route
+/// GET /api/v1/consent
+///
+/// # Response
+/// ```json
+/// {
+///   "current": [
+///     {
+///       "document": "terms",
+///       "version": 2,
+///       "summary": "Clarified data retention",
+///       "url": "https://example.com/terms/2",
+///       "published_by": "user:1",
+///       "published_at": "2024-01-01T00:00:00Z"
+///     }
+///   ],
+///   "accepted": [
+///     {
+///       "actor_id": "anonymous:9f2c...",
+///       "tenant": "H001",
+///       "document": "terms",
+///       "version": 1,
+///       "accepted_at": "2023-06-01T08:00:00Z"
+///     }
+///   ],
+///   "pending": [{ "document": "terms", "version": 2, ... }]
+/// }
+/// ```
+pub async fn consent_status(
+    State(consent_service): State<ConsentService>,
+    user: AuthenticatedUser,
+) -> Json<ConsentStatus> {
+    Json(consent_service.status(&user.0).await)
+}
+
+/// Accept policy handler
+///
+/// Requires authentication. Only the current version can be accepted.
+///
+/// # Route
+/// POST /api/v1/consent/accept
+///
+/// # Request Body
+/// ```json
+/// {
+///   "document": "terms",
+///   "version": 2
+/// }
+/// ```
+///
+/// # Response
+/// The acceptance
+pub async fn accept_policy(
+    State(consent_service): State<ConsentService>,
+    user: AuthenticatedUser,
+    audit: AuditContext,
+    JsonBody(payload): JsonBody<AcceptPolicyRequest>,
+) -> Result<Json<Acceptance>, AppError> {
+    consent_service
+        .accept(&user.0, payload, &audit)
+        .await
+        .map(Json)
+}
+
+/// Publish policy handler
+///
+/// Requires the admin role. Identities are blocked until they accept the
+/// new version.
+///
+/// # Route
+/// POST /api/v1/admin/policies
+///
+/// # Request Body
+/// ```json
+/// {
+///   "document": "privacy_policy",
+///   "summary": "Added GeoIP lookups",
+///   "url": "https://example.com/privacy/3"
+/// }
+/// ```
+///
+/// # Response
+/// 201 Created with the published version
+pub async fn publish_policy(
+    State(consent_service): State<ConsentService>,
+    user: AuthenticatedUser,
+    audit: AuditContext,
+    JsonBody(payload): JsonBody<PublishPolicyRequest>,
+) -> Result<(StatusCode, Json<PolicyVersion>), AppError> {
+    let version = consent_service.publish(&user.0, payload, &audit).await?;
+    Ok((StatusCode::CREATED, Json(version)))
+}
+
+/// Consent report handler
+///
+/// Requires the admin role.
+///
+/// # Route
+/// GET /api/v1/admin/consent?hospital_code=H001
+///
+/// # Response
+/// ```json
+/// [
+///   {
+///     "document": "terms",
+///     "version": 2,
+///     "published_at": "2024-01-01T00:00:00Z",
+///     "known": 40,
+///     "accepted": 30,
+///     "pending": 10,
+///     "acceptance_rate": 0.75,
+///     "acceptances": [{ "actor_id": "anonymous:9f2c...", "tenant": "H001", ... }]
+///   }
+/// ]
+/// ```
+pub async fn consent_report(
+    State(consent_service): State<ConsentService>,
+    Query(query): Query<ConsentReportQuery>,
+) -> Json<Vec<ConsentReport>> {
+    Json(consent_service.report(query.hospital_code.as_deref()).await)
+}
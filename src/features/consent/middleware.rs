@@ -0,0 +1,47 @@
+use axum::{
+    extract::{Request, State},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+
+use crate::features::auth::AuthService;
+use crate::infrastructure::AppError;
+
+use super::service::ConsentService;
+
+/// Consent middleware
+///
+/// Rejects requests with 403 Forbidden while the caller has not accepted
+/// the current version of every published policy document. Requests
+/// without a token pass through. Routes needed to accept (auth, consent)
+/// and admin routes are not wrapped in this middleware.
+pub async fn consent_middleware(
+    State((auth_service, consent_service)): State<(AuthService, ConsentService)>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let identity = request
+        .headers()
+        .get("Authorization")
+        .and_then(|h| h.to_str().ok())
+        .and_then(|header| auth_service.extract_user_from_header(header).ok());
+
+    let Some(identity) = identity else {
+        return next.run(request).await;
+    };
+
+    let pending = consent_service.pending(&identity).await;
+    if pending.is_empty() {
+        return next.run(request).await;
+    }
+
+    let documents: Vec<String> = pending
+        .iter()
+        .map(|version| format!("{} version {}", version.document.name(), version.version))
+        .collect();
+    AppError::Forbidden(format!(
+        "Acceptance required: {}; accept at /api/v1/consent/accept",
+        documents.join(", ")
+    ))
+    .into_response()
+}
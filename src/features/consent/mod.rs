@@ -0,0 +1,43 @@
+//! Consent Feature Module
+//!
+//! Versioned terms of service and privacy policy. Every identity, including
+//! anonymous ones, has to accept the current version of each published
+//! document before using the API; admins publish versions and report on
+//! acceptance.
+//!
+//! ## Architecture
+//!
+//! ### Domain Layer (`domain.rs`)
+//! - `PolicyDocument`, `PolicyVersion`: Published documents and versions
+//! - `Acceptance`: Version accepted by an identity
+//! - `ConsentStatus`, `ConsentReport`: Per-identity status and admin report
+//!
+//! ### Application Layer (`service.rs`)
+//! - `ConsentService`: Publishing, acceptance and reporting
+//!
+//! ### Presentation Layer (`middleware.rs`, `handler.rs`)
+//! - Middleware rejecting requests until the current versions are accepted
+//! - Handlers for accepting, publishing and reporting
+//!
+//! ## Usage
+//! ```rust,ignore
+//! use features::consent;
+//!
+//! let consent_service = consent::ConsentService::new(audit_log.clone());
+//!
+//! api_routes.layer(middleware::from_fn_with_state(
+//!     (auth_service.clone(), consent_service.clone()),
+//!     consent::consent_middleware,
+//! ))
+//! ```
+
+pub mod domain;
+pub mod handler;
+pub mod middleware;
+pub mod service;
+
+// Re-export commonly used items
+pub use domain::{Acceptance, ConsentReport, ConsentStatus, PolicyDocument, PolicyVersion};
+pub use handler::{accept_policy, consent_report, consent_status, publish_policy};
+pub use middleware::consent_middleware;
+pub use service::ConsentService;
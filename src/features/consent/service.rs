@@ -0,0 +1,386 @@
+use chrono::Utc;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+use tokio::sync::RwLock;
+
+use crate::features::users::domain::UserIdentity;
+use crate::infrastructure::{audit::AuditActor, AppError, AuditContext, AuditLog, AuditOperation};
+
+use super::domain::{
+    AcceptPolicyRequest, Acceptance, ConsentReport, ConsentStatus, PolicyDocument, PolicyVersion,
+    PublishPolicyRequest,
+};
+
+/// Consent service containing business logic
+///
+/// Application layer service that stores published versions of the terms
+/// and privacy policy and which version each identity accepted. Identities,
+/// including anonymous ones, are stored under their audit actor id, so the
+/// composite keys of anonymous users are never kept.
+/// In a real application, this would interact with a database repository.
+#[derive(Clone)]
+pub struct ConsentService {
+    /// Published versions per document, oldest first
+    versions: Arc<RwLock<BTreeMap<PolicyDocument, Vec<PolicyVersion>>>>,
+    /// Latest acceptance per identity and document
+    acceptances: Arc<RwLock<HashMap<(String, PolicyDocument), Acceptance>>>,
+    /// Tenant of every identity asked for acceptance, by actor id
+    known: Arc<RwLock<HashMap<String, Option<String>>>>,
+    audit_log: AuditLog,
+}
+
+impl ConsentService {
+    /// Create a new consent service recording writes into the given audit log
+    pub fn new(audit_log: AuditLog) -> Self {
+        Self {
+            versions: Arc::new(RwLock::new(BTreeMap::new())),
+            acceptances: Arc::new(RwLock::new(HashMap::new())),
+            known: Arc::new(RwLock::new(HashMap::new())),
+            audit_log,
+        }
+    }
+
+    /// Publish a new version of a policy document
+    ///
+    /// # Business Logic
+    /// 1. Validate the request
+    /// 2. Number the version after the current one
+    /// 3. Store and audit the version
+    ///
+    /// From then on identities are blocked until they accept it.
+    pub async fn publish(
+        &self,
+        author: &UserIdentity,
+        request: PublishPolicyRequest,
+        audit: &AuditContext,
+    ) -> Result<PolicyVersion, AppError> {
+        request.validate().map_err(AppError::BadRequest)?;
+
+        let mut versions = self.versions.write().await;
+        let history = versions.entry(request.document).or_default();
+        let version = PolicyVersion {
+            document: request.document,
+            version: history.last().map_or(1, |current| current.version + 1),
+            summary: request.summary,
+            url: request.url,
+            published_by: self.audit_log.stored_actor_id(&AuditActor::from(author)),
+            published_at: Utc::now(),
+        };
+        history.push(version.clone());
+        drop(versions);
+
+        tracing::info!(
+            "Published {} version {}",
+            version.document.name(),
+            version.version
+        );
+        self.audit_log
+            .record(
+                audit,
+                AuditOperation::Create,
+                "policy_version",
+                format!("{:?}/{}", version.document, version.version),
+                None,
+                serde_json::to_value(&version).ok(),
+            )
+            .await;
+
+        Ok(version)
+    }
+
+    /// Current version of every published document
+    pub async fn current_versions(&self) -> Vec<PolicyVersion> {
+        self.versions
+            .read()
+            .await
+            .values()
+            .filter_map(|history| history.last().cloned())
+            .collect()
+    }
+
+    /// Current versions the identity has not accepted yet
+    ///
+    /// Records the identity as asked for acceptance.
+    pub async fn pending(&self, identity: &UserIdentity) -> Vec<PolicyVersion> {
+        let current = self.current_versions().await;
+        if current.is_empty() {
+            return current;
+        }
+
+        let actor = self.actor(identity);
+        let acceptances = self.acceptances.read().await;
+        let pending = current
+            .into_iter()
+            .filter(|version| {
+                acceptances
+                    .get(&(actor.id.clone(), version.document))
+                    .is_none_or(|acceptance| acceptance.version < version.version)
+            })
+            .collect();
+        drop(acceptances);
+
+        self.known.write().await.insert(actor.id, actor.tenant);
+        pending
+    }
+
+    /// Consent status of an identity
+    pub async fn status(&self, identity: &UserIdentity) -> ConsentStatus {
+        let pending = self.pending(identity).await;
+        let actor_id = self.actor(identity).id;
+        let accepted = self
+            .acceptances
+            .read()
+            .await
+            .iter()
+            .filter(|((id, _), _)| *id == actor_id)
+            .map(|(_, acceptance)| acceptance.clone())
+            .collect();
+
+        ConsentStatus {
+            current: self.current_versions().await,
+            accepted,
+            pending,
+        }
+    }
+
+    /// Accept the current version of a document
+    ///
+    /// Accepting a version that is not current is rejected, so a version
+    /// published meanwhile is never accepted unseen. Accepting the same
+    /// version again keeps the first acceptance.
+    pub async fn accept(
+        &self,
+        identity: &UserIdentity,
+        request: AcceptPolicyRequest,
+        audit: &AuditContext,
+    ) -> Result<Acceptance, AppError> {
+        let current = self
+            .versions
+            .read()
+            .await
+            .get(&request.document)
+            .and_then(|history| history.last())
+            .map(|current| current.version)
+            .ok_or_else(|| {
+                AppError::NotFound(format!("No {} published", request.document.name()))
+            })?;
+        if request.version != current {
+            return Err(AppError::BadRequest(format!(
+                "Version {} of the {} is not current; the current version is {}",
+                request.version,
+                request.document.name(),
+                current
+            )));
+        }
+
+        let actor = self.actor(identity);
+        let key = (actor.id.clone(), request.document);
+        let mut acceptances = self.acceptances.write().await;
+        if let Some(acceptance) = acceptances
+            .get(&key)
+            .filter(|acceptance| acceptance.version == current)
+        {
+            return Ok(acceptance.clone());
+        }
+
+        let acceptance = Acceptance {
+            actor_id: actor.id.clone(),
+            tenant: actor.tenant.clone(),
+            document: request.document,
+            version: current,
+            accepted_at: Utc::now(),
+        };
+        acceptances.insert(key, acceptance.clone());
+        drop(acceptances);
+        self.known.write().await.insert(actor.id, actor.tenant);
+
+        self.audit_log
+            .record(
+                audit,
+                AuditOperation::Create,
+                "policy_acceptance",
+                format!("{:?}/{}", acceptance.document, acceptance.version),
+                None,
+                serde_json::to_value(&acceptance).ok(),
+            )
+            .await;
+
+        Ok(acceptance)
+    }
+
+    /// Acceptance of the current version of every published document
+    ///
+    /// Limited to identities of `hospital_code` if given.
+    pub async fn report(&self, hospital_code: Option<&str>) -> Vec<ConsentReport> {
+        let in_scope = |tenant: &Option<String>| {
+            hospital_code.is_none_or(|code| tenant.as_deref() == Some(code))
+        };
+        let known = self
+            .known
+            .read()
+            .await
+            .values()
+            .filter(|tenant| in_scope(tenant))
+            .count();
+        let acceptances = self.acceptances.read().await;
+
+        self.current_versions()
+            .await
+            .into_iter()
+            .map(|current| {
+                let mut accepted: Vec<Acceptance> = acceptances
+                    .values()
+                    .filter(|acceptance| {
+                        acceptance.document == current.document
+                            && acceptance.version == current.version
+                            && in_scope(&acceptance.tenant)
+                    })
+                    .cloned()
+                    .collect();
+                accepted.sort_by_key(|acceptance| acceptance.accepted_at);
+
+                ConsentReport {
+                    document: current.document,
+                    version: current.version,
+                    published_at: current.published_at,
+                    known,
+                    accepted: accepted.len(),
+                    pending: known.saturating_sub(accepted.len()),
+                    acceptance_rate: if known == 0 {
+                        0.0
+                    } else {
+                        accepted.len() as f64 / known as f64
+                    },
+                    acceptances: accepted,
+                }
+            })
+            .collect()
+    }
+
+    /// Actor an identity's acceptances are stored under
+    fn actor(&self, identity: &UserIdentity) -> AuditActor {
+        let actor = AuditActor::from(identity);
+        AuditActor {
+            id: self.audit_log.stored_actor_id(&actor),
+            tenant: actor.tenant,
+        }
+    }
+}
+
+impl Default for ConsentService {
+    fn default() -> Self {
+        Self::new(AuditLog::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::features::users::domain::{AnonymousUserIdentifier, Role, VerifiedUser};
+    use chrono::NaiveDate;
+
+    fn admin() -> UserIdentity {
+        UserIdentity::Verified(VerifiedUser {
+            id: 1,
+            username: "admin".to_string(),
+            email: "admin@example.com".to_string(),
+            role: Role::Admin,
+        })
+    }
+
+    fn nurse(hospital_code: &str) -> UserIdentity {
+        UserIdentity::Anonymous(AnonymousUserIdentifier {
+            hospital_code: hospital_code.to_string(),
+            user_id: "U123".to_string(),
+            user_start_date: NaiveDate::from_ymd_opt(2024, 1, 1).unwrap(),
+            department_code: "D001".to_string(),
+        })
+    }
+
+    async fn publish(service: &ConsentService, summary: &str) -> PolicyVersion {
+        service
+            .publish(
+                &admin(),
+                PublishPolicyRequest {
+                    document: PolicyDocument::Terms,
+                    summary: summary.to_string(),
+                    url: None,
+                },
+                &AuditContext::default(),
+            )
+            .await
+            .unwrap()
+    }
+
+    async fn accept(
+        service: &ConsentService,
+        identity: &UserIdentity,
+        version: u32,
+    ) -> Result<Acceptance, AppError> {
+        service
+            .accept(
+                identity,
+                AcceptPolicyRequest {
+                    document: PolicyDocument::Terms,
+                    version,
+                },
+                &AuditContext::default(),
+            )
+            .await
+    }
+
+    #[tokio::test]
+    async fn test_new_version_requires_acceptance() {
+        let service = ConsentService::default();
+        let nurse = nurse("H001");
+
+        // Nothing to accept before the first version is published
+        assert!(service.pending(&nurse).await.is_empty());
+        assert!(accept(&service, &nurse, 1).await.is_err());
+
+        let first = publish(&service, "Initial terms").await;
+        assert_eq!(first.version, 1);
+        assert_eq!(service.pending(&nurse).await.len(), 1);
+
+        accept(&service, &nurse, 1).await.unwrap();
+        assert!(service.pending(&nurse).await.is_empty());
+
+        // A new version blocks again; only the current version can be accepted
+        let second = publish(&service, "Clarified data retention").await;
+        assert_eq!(second.version, 2);
+        assert_eq!(service.pending(&nurse).await.len(), 1);
+        assert!(accept(&service, &nurse, 1).await.is_err());
+
+        let acceptance = accept(&service, &nurse, 2).await.unwrap();
+        assert_eq!(acceptance.tenant.as_deref(), Some("H001"));
+        assert!(!acceptance.actor_id.contains("U123"));
+
+        let status = service.status(&nurse).await;
+        assert_eq!(status.current.len(), 1);
+        assert_eq!(status.accepted[0].version, 2);
+        assert!(status.pending.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_report() {
+        let service = ConsentService::default();
+        publish(&service, "Initial terms").await;
+
+        let accepting = nurse("H001");
+        let pending = nurse("H002");
+        service.pending(&pending).await;
+        accept(&service, &accepting, 1).await.unwrap();
+
+        let report = service.report(None).await;
+        assert_eq!(report.len(), 1);
+        assert_eq!(report[0].known, 2);
+        assert_eq!(report[0].accepted, 1);
+        assert_eq!(report[0].pending, 1);
+        assert_eq!(report[0].acceptance_rate, 0.5);
+
+        let report = service.report(Some("H002")).await;
+        assert_eq!(report[0].known, 1);
+        assert_eq!(report[0].accepted, 0);
+        assert!(report[0].acceptances.is_empty());
+    }
+}
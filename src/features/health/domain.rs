@@ -21,3 +21,62 @@ impl HealthResponse {
         }
     }
 }
+
+/// Health of a component the service depends on
+#[derive(Debug, Clone, Serialize)]
+pub struct ComponentHealth {
+    pub name: String,
+    /// `healthy` or `unhealthy`
+    pub status: String,
+    /// Why the component is unhealthy
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl ComponentHealth {
+    /// Create the health of a component from the result of its check
+    pub fn new(name: &str, result: Result<(), String>) -> Self {
+        Self {
+            name: name.to_string(),
+            status: if result.is_ok() {
+                "healthy"
+            } else {
+                "unhealthy"
+            }
+            .to_string(),
+            error: result.err(),
+        }
+    }
+
+    pub fn is_healthy(&self) -> bool {
+        self.error.is_none()
+    }
+}
+
+/// Readiness probe response model
+///
+/// The service is ready when all components are healthy.
+#[derive(Debug, Serialize)]
+pub struct ReadinessResponse {
+    /// `healthy`, or `unhealthy` if any component is
+    pub status: String,
+    /// Application version
+    pub version: String,
+    pub components: Vec<ComponentHealth>,
+}
+
+impl ReadinessResponse {
+    /// Create a response aggregating the health of the components
+    pub fn new(components: Vec<ComponentHealth>) -> Self {
+        let ready = components.iter().all(ComponentHealth::is_healthy);
+        Self {
+            status: if ready { "healthy" } else { "unhealthy" }.to_string(),
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            components,
+        }
+    }
+
+    pub fn is_ready(&self) -> bool {
+        self.status == "healthy"
+    }
+}
@@ -1,14 +1,17 @@
-use axum::Json;
+use axum::{extract::State, http::StatusCode, Json};
 
-use super::domain::HealthResponse;
+use super::domain::{HealthResponse, ReadinessResponse};
+use super::service::HealthChecks;
 
 /// Health check handler
 ///
-/// Presentation layer handler for the health check endpoint.
-/// Returns the current health status of the service.
+/// Presentation layer handler for the health check and liveness endpoints.
+/// Reports that the process is up and answering, without checking its
+/// dependencies, so orchestrators only restart it when it is stuck.
 ///
 /// # Route
 /// GET /health
+/// GET /health/live
 ///
 /// # Response
 /// ```json
@@ -20,3 +23,38 @@ use super::domain::HealthResponse;
 pub async fn health_check() -> Json<HealthResponse> {
     Json(HealthResponse::healthy())
 }
+
+/// Readiness check handler
+///
+/// Reports whether the service can take traffic, by running the checks
+/// registered by the components it depends on. All services are set up
+/// before the server starts listening, so without failing components the
+/// service is ready as soon as it answers.
+///
+/// # Route
+/// GET /ready
+/// GET /health/ready
+///
+/// # Response
+/// 200 OK when all components are healthy, 503 Service Unavailable otherwise
+/// ```json
+/// {
+///   "status": "unhealthy",
+///   "version": "0.1.0",
+///   "components": [
+///     {"name": "jsonrpc", "status": "healthy"},
+///     {"name": "redis", "status": "unhealthy", "error": "Connection refused (os error 111)"}
+///   ]
+/// }
+/// ```
+pub async fn readiness_check(
+    State(health_checks): State<HealthChecks>,
+) -> (StatusCode, Json<ReadinessResponse>) {
+    let response = health_checks.run().await;
+    let status = if response.is_ready() {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    };
+    (status, Json(response))
+}
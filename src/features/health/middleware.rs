@@ -0,0 +1,37 @@
+use axum::{
+    extract::{Request, State},
+    http::{header::RETRY_AFTER, HeaderValue},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+
+use crate::infrastructure::AppError;
+
+use super::watermarks::WatermarkMonitor;
+
+/// Seconds clients are asked to wait before retrying shed requests
+const SHED_RETRY_AFTER_SECS: u64 = 5;
+
+/// Load shedding middleware
+///
+/// Rejects requests with 503 Service Unavailable while a resource watermark
+/// is exceeded, with a `Retry-After` header. Routes that must stay
+/// reachable under load (health probes, auth, admin) are not wrapped in
+/// this middleware.
+pub async fn load_shedding_middleware(
+    State(monitor): State<WatermarkMonitor>,
+    request: Request,
+    next: Next,
+) -> Response {
+    if !monitor.is_shedding() {
+        return next.run(request).await;
+    }
+
+    let mut response =
+        AppError::ServiceUnavailable("Server is overloaded, try again later".to_string())
+            .into_response();
+    response
+        .headers_mut()
+        .insert(RETRY_AFTER, HeaderValue::from(SHED_RETRY_AFTER_SECS));
+    response
+}
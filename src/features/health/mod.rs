@@ -1,23 +1,44 @@
-/// Health Check Feature
-///
-/// Provides a simple health check endpoint to verify service availability.
-/// This is a lightweight feature with only domain and presentation layers.
-///
-/// ## Architecture
-/// - `domain`: Health response model
-/// - `handler`: HTTP handler for the health endpoint
-///
-/// ## Usage
-/// ```rust
-/// use features::health;
-///
-/// Router::new()
-///     .route("/health", get(health::handler::health_check))
-/// ```
+//! Health Check Feature
+//!
+//! Provides a simple health check endpoint to verify service availability,
+//! and liveness and readiness probes for orchestrators. Readiness aggregates
+//! the checks registered by the components the service depends on. Resource
+//! watermarks flag the service as not ready and shed load when exceeded.
+//!
+//! ## Architecture
+//! - `domain`: Health and readiness response models
+//! - `service`: `HealthCheck` trait and the checks the readiness probe runs
+//! - `watermarks`: Memory, connection and queue watermark alarms
+//! - `middleware`: Load shedding while a watermark is exceeded
+//! - `handler`: HTTP handlers for the health, liveness and readiness endpoints
+//!
+//! ## Usage
+//! ```rust,ignore
+//! use features::health;
+//!
+//! let watermarks = health::WatermarkMonitor::new(health::Watermarks {
+//!     rss_bytes: Some(1024 * 1024 * 1024),
+//!     ..Default::default()
+//! });
+//! let health_checks = health::HealthChecks::new()
+//!     .with_check(jsonrpc_service.clone())
+//!     .with_check(watermarks.clone());
+//!
+//! Router::new()
+//!     .route("/health/live", get(health::handler::health_check))
+//!     .route("/health/ready", get(health::handler::readiness_check))
+//!     .with_state(health_checks)
+//! ```
 
 pub mod domain;
 pub mod handler;
+pub mod middleware;
+pub mod service;
+pub mod watermarks;
 
 // Re-export commonly used items
-pub use domain::HealthResponse;
-pub use handler::health_check;
+pub use domain::{ComponentHealth, HealthResponse, ReadinessResponse};
+pub use handler::{health_check, readiness_check};
+pub use middleware::load_shedding_middleware;
+pub use service::{HealthCheck, HealthChecks};
+pub use watermarks::{process_rss_bytes, ResourceUsage, WatermarkMonitor, Watermarks};
@@ -0,0 +1,152 @@
+use futures::future::{join_all, BoxFuture};
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::infrastructure::Lifecycle;
+
+use super::domain::{ComponentHealth, ReadinessResponse};
+
+/// Default time a component has to answer its check
+const DEFAULT_CHECK_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Probe of a component the service depends on
+///
+/// Implemented by the subsystems that can become unavailable while the
+/// process keeps running, such as a Redis connection or the JSON-RPC
+/// service during shutdown.
+pub trait HealthCheck: Send + Sync {
+    /// Name the component is reported under, e.g. `redis`
+    fn name(&self) -> &'static str;
+
+    /// Check the component; the error tells why it is unavailable
+    fn check(&self) -> BoxFuture<'_, Result<(), String>>;
+}
+
+/// Health checks the readiness probe aggregates
+///
+/// Checks run concurrently, each bounded by a timeout, so one hanging
+/// dependency cannot hold up the probe.
+#[derive(Clone)]
+pub struct HealthChecks {
+    checks: Vec<Arc<dyn HealthCheck>>,
+    timeout: Duration,
+}
+
+impl HealthChecks {
+    /// Create an empty set of checks; the service is ready without any
+    pub fn new() -> Self {
+        Self {
+            checks: Vec::new(),
+            timeout: DEFAULT_CHECK_TIMEOUT,
+        }
+    }
+
+    /// Add the check of a component
+    pub fn with_check(mut self, check: impl HealthCheck + 'static) -> Self {
+        self.checks.push(Arc::new(check));
+        self
+    }
+
+    /// Set how long each check may take before its component is unhealthy
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Run all checks
+    pub async fn run(&self) -> ReadinessResponse {
+        let components = join_all(self.checks.iter().map(|check| async move {
+            let result = match tokio::time::timeout(self.timeout, check.check()).await {
+                Ok(result) => result,
+                Err(_) => Err(format!("No answer within {:?}", self.timeout)),
+            };
+            if let Err(err) = &result {
+                tracing::warn!("Health check {} failed: {}", check.name(), err);
+            }
+            ComponentHealth::new(check.name(), result)
+        }))
+        .await;
+
+        ReadinessResponse::new(components)
+    }
+}
+
+impl Default for HealthChecks {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Not ready until all services started, nor once they are shutting down
+impl HealthCheck for Lifecycle {
+    fn name(&self) -> &'static str {
+        "lifecycle"
+    }
+
+    fn check(&self) -> BoxFuture<'_, Result<(), String>> {
+        Box::pin(self.health())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Fixed(&'static str, Option<&'static str>);
+
+    impl HealthCheck for Fixed {
+        fn name(&self) -> &'static str {
+            self.0
+        }
+
+        fn check(&self) -> BoxFuture<'_, Result<(), String>> {
+            Box::pin(async move { self.1.map_or(Ok(()), |err| Err(err.to_string())) })
+        }
+    }
+
+    struct Hanging;
+
+    impl HealthCheck for Hanging {
+        fn name(&self) -> &'static str {
+            "hanging"
+        }
+
+        fn check(&self) -> BoxFuture<'_, Result<(), String>> {
+            Box::pin(std::future::pending())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_readiness_aggregates_components() {
+        assert!(HealthChecks::new().run().await.is_ready());
+
+        let checks = HealthChecks::new().with_check(Fixed("jsonrpc", None));
+        assert!(checks.run().await.is_ready());
+
+        let response = checks
+            .with_check(Fixed("redis", Some("Connection refused")))
+            .with_check(Hanging)
+            .with_timeout(Duration::from_millis(10))
+            .run()
+            .await;
+        assert!(!response.is_ready());
+        assert_eq!(response.status, "unhealthy");
+        let statuses: Vec<_> = response
+            .components
+            .iter()
+            .map(|c| (c.name.as_str(), c.status.as_str()))
+            .collect();
+        assert_eq!(
+            statuses,
+            [
+                ("jsonrpc", "healthy"),
+                ("redis", "unhealthy"),
+                ("hanging", "unhealthy")
+            ]
+        );
+        assert_eq!(
+            response.components[1].error.as_deref(),
+            Some("Connection refused")
+        );
+    }
+}
@@ -0,0 +1,194 @@
+use futures::future::BoxFuture;
+use serde::Serialize;
+use std::sync::{Arc, PoisonError, RwLock};
+
+use super::service::HealthCheck;
+
+/// Bytes in a megabyte
+const MB: u64 = 1024 * 1024;
+
+/// Resource limits that raise an alarm when exceeded
+///
+/// A limit of `None` is not checked.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Watermarks {
+    /// Resident memory of the process in bytes
+    pub rss_bytes: Option<u64>,
+    /// Open WebSocket connections
+    pub connections: Option<usize>,
+    /// Outgoing messages queued across all WebSocket connections
+    pub queued_messages: Option<usize>,
+}
+
+/// Resource usage sampled for the watermark check
+#[derive(Debug, Clone, Copy, Default, Serialize)]
+pub struct ResourceUsage {
+    /// Resident memory of the process in bytes, where the platform reports it
+    pub rss_bytes: Option<u64>,
+    pub connections: usize,
+    pub queued_messages: usize,
+}
+
+impl Watermarks {
+    /// Watermarks the usage exceeds, described for logs and probes
+    pub fn exceeded(&self, usage: &ResourceUsage) -> Vec<String> {
+        let mut alarms = Vec::new();
+        if let (Some(limit), Some(rss_bytes)) = (self.rss_bytes, usage.rss_bytes) {
+            if rss_bytes > limit {
+                alarms.push(format!(
+                    "Memory {} MB above {} MB",
+                    rss_bytes / MB,
+                    limit / MB
+                ));
+            }
+        }
+        if let Some(limit) = self.connections.filter(|limit| usage.connections > *limit) {
+            alarms.push(format!(
+                "{} WebSocket connections above {}",
+                usage.connections, limit
+            ));
+        }
+        if let Some(limit) = self
+            .queued_messages
+            .filter(|limit| usage.queued_messages > *limit)
+        {
+            alarms.push(format!(
+                "{} queued messages above {}",
+                usage.queued_messages, limit
+            ));
+        }
+        alarms
+    }
+}
+
+/// Watermark alarms of the process
+///
+/// Holds the alarms raised by the latest usage sample. While any alarm is
+/// raised, the readiness probe reports the service as unhealthy and new
+/// requests are shed (see `load_shedding_middleware`), so orchestrators and
+/// load balancers move traffic elsewhere until usage is back down.
+#[derive(Clone, Default)]
+pub struct WatermarkMonitor {
+    watermarks: Watermarks,
+    alarms: Arc<RwLock<Vec<String>>>,
+}
+
+impl WatermarkMonitor {
+    /// Create a monitor checking the given watermarks
+    pub fn new(watermarks: Watermarks) -> Self {
+        Self {
+            watermarks,
+            alarms: Arc::default(),
+        }
+    }
+
+    /// Check a usage sample against the watermarks
+    ///
+    /// Logs a warning when alarms are raised or change, and when they clear.
+    /// Returns the alarms raised by the sample.
+    pub fn update(&self, usage: &ResourceUsage) -> Vec<String> {
+        let alarms = self.watermarks.exceeded(usage);
+        let mut current = self.alarms.write().unwrap_or_else(PoisonError::into_inner);
+        if *current != alarms {
+            if alarms.is_empty() {
+                tracing::info!("Resource usage back below watermarks: {:?}", usage);
+            } else {
+                tracing::warn!(
+                    "Resource watermarks exceeded, shedding load: {}",
+                    alarms.join("; ")
+                );
+            }
+            current.clone_from(&alarms);
+        }
+        alarms
+    }
+
+    /// Alarms raised by the latest sample
+    pub fn alarms(&self) -> Vec<String> {
+        self.alarms
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+
+    /// Check if new requests should be shed
+    pub fn is_shedding(&self) -> bool {
+        !self
+            .alarms
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .is_empty()
+    }
+}
+
+/// Not ready while a watermark is exceeded
+impl HealthCheck for WatermarkMonitor {
+    fn name(&self) -> &'static str {
+        "watermarks"
+    }
+
+    fn check(&self) -> BoxFuture<'_, Result<(), String>> {
+        Box::pin(async move {
+            let alarms = self.alarms();
+            if alarms.is_empty() {
+                Ok(())
+            } else {
+                Err(alarms.join("; "))
+            }
+        })
+    }
+}
+
+/// Resident memory of the current process in bytes
+///
+/// Read from `/proc/self/status`, so only available on Linux.
+pub fn process_rss_bytes() -> Option<u64> {
+    let status = std::fs::read_to_string("/proc/self/status").ok()?;
+    let line = status.lines().find(|line| line.starts_with("VmRSS:"))?;
+    let kb: u64 = line
+        .trim_start_matches("VmRSS:")
+        .trim()
+        .trim_end_matches("kB")
+        .trim()
+        .parse()
+        .ok()?;
+    Some(kb * 1024)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_alarms_raise_and_clear() {
+        let monitor = WatermarkMonitor::new(Watermarks {
+            rss_bytes: Some(512 * MB),
+            connections: Some(100),
+            queued_messages: None,
+        });
+        let mut usage = ResourceUsage {
+            rss_bytes: Some(256 * MB),
+            connections: 100,
+            queued_messages: 10_000,
+        };
+        assert!(monitor.update(&usage).is_empty());
+        assert!(!monitor.is_shedding());
+
+        usage.rss_bytes = Some(600 * MB);
+        usage.connections = 101;
+        assert_eq!(
+            monitor.update(&usage),
+            [
+                "Memory 600 MB above 512 MB",
+                "101 WebSocket connections above 100"
+            ]
+        );
+        assert!(monitor.is_shedding());
+        assert!(monitor.check().await.is_err());
+
+        usage.rss_bytes = None;
+        usage.connections = 1;
+        assert!(monitor.update(&usage).is_empty());
+        assert!(monitor.check().await.is_ok());
+    }
+}
@@ -0,0 +1,320 @@
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{mpsc, watch, Notify, RwLock};
+
+use crate::features::users::domain::UserIdentity;
+
+use super::super::domain::JsonRpcRequest;
+
+/// Number of outgoing messages queued per connection before new ones are dropped
+const OUTBOUND_QUEUE_SIZE: usize = 64;
+
+/// Identifier of a WebSocket connection
+pub type ConnectionId = u64;
+
+/// A connected client that can receive server-initiated messages
+struct Connection {
+    sender: mpsc::Sender<String>,
+    /// Notification methods the client subscribed to
+    subscriptions: HashSet<String>,
+    /// Identity authenticated on the WebSocket upgrade request, if any
+    identity: Option<UserIdentity>,
+}
+
+/// Registry of open WebSocket connections
+///
+/// Lets the server push JSON-RPC notifications to connected clients, either
+/// to the subscribers of a notification method or to every connection.
+/// Messages to clients that don't keep up are dropped rather than buffered
+/// without bound. On shutdown, connections are asked to close through the
+/// `closing` signal.
+#[derive(Clone, Default)]
+pub struct ConnectionRegistry {
+    connections: Arc<RwLock<HashMap<ConnectionId, Connection>>>,
+    next_id: Arc<AtomicU64>,
+    /// Set once all connections should close
+    closing: Arc<watch::Sender<bool>>,
+    /// Woken whenever a connection unregisters
+    unregistered: Arc<Notify>,
+}
+
+impl ConnectionRegistry {
+    /// Create an empty registry
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register a new unauthenticated connection
+    ///
+    /// Returns the connection id and the receiver of messages pushed to it.
+    pub async fn register(&self) -> (ConnectionId, mpsc::Receiver<String>) {
+        self.register_as(None).await
+    }
+
+    /// Register a new connection of the given identity
+    ///
+    /// The identity decides which notifications sent with `notify_visible`
+    /// reach the connection.
+    pub async fn register_as(
+        &self,
+        identity: Option<UserIdentity>,
+    ) -> (ConnectionId, mpsc::Receiver<String>) {
+        let id = self.next_id.fetch_add(1, Ordering::SeqCst);
+        let (sender, receiver) = mpsc::channel(OUTBOUND_QUEUE_SIZE);
+
+        self.connections.write().await.insert(
+            id,
+            Connection {
+                sender,
+                subscriptions: HashSet::new(),
+                identity,
+            },
+        );
+
+        (id, receiver)
+    }
+
+    /// Remove a closed connection
+    ///
+    /// Returns the identity the connection was registered with.
+    pub async fn unregister(&self, id: ConnectionId) -> Option<UserIdentity> {
+        let connection = self.connections.write().await.remove(&id);
+        self.unregistered.notify_waiters();
+        connection?.identity
+    }
+
+    /// Signal telling connections to close, set by `close_all`
+    ///
+    /// Connections registered after `close_all` see the signal set at once.
+    pub fn closing(&self) -> watch::Receiver<bool> {
+        self.closing.subscribe()
+    }
+
+    /// Ask all connections, current and future, to close
+    pub fn close_all(&self) {
+        self.closing.send_replace(true);
+    }
+
+    /// Check if `close_all` was called
+    pub fn is_closing(&self) -> bool {
+        *self.closing.borrow()
+    }
+
+    /// Wait until all connections have unregistered, for at most `timeout`
+    ///
+    /// Returns false if connections were still open when the timeout passed.
+    pub async fn wait_closed(&self, timeout: Duration) -> bool {
+        let deadline = tokio::time::Instant::now() + timeout;
+        loop {
+            // Register for wake-ups before checking, so none is missed
+            let unregistered = self.unregistered.notified();
+            tokio::pin!(unregistered);
+            unregistered.as_mut().enable();
+
+            if self.is_empty().await {
+                return true;
+            }
+            if tokio::time::timeout_at(deadline, unregistered)
+                .await
+                .is_err()
+            {
+                return self.is_empty().await;
+            }
+        }
+    }
+
+    /// Number of open connections
+    pub async fn len(&self) -> usize {
+        self.connections.read().await.len()
+    }
+
+    /// Check if there are no open connections
+    pub async fn is_empty(&self) -> bool {
+        self.connections.read().await.is_empty()
+    }
+
+    /// Number of outgoing messages queued across all connections
+    pub async fn queued_messages(&self) -> usize {
+        self.connections
+            .read()
+            .await
+            .values()
+            .map(|connection| OUTBOUND_QUEUE_SIZE - connection.sender.capacity())
+            .sum()
+    }
+
+    /// Subscribe a connection to notification methods
+    ///
+    /// Returns false if the connection is not registered.
+    pub async fn subscribe(&self, id: ConnectionId, methods: &[String]) -> bool {
+        let mut connections = self.connections.write().await;
+        let Some(connection) = connections.get_mut(&id) else {
+            return false;
+        };
+
+        connection.subscriptions.extend(methods.iter().cloned());
+        true
+    }
+
+    /// Unsubscribe a connection from notification methods
+    ///
+    /// Returns false if the connection is not registered.
+    pub async fn unsubscribe(&self, id: ConnectionId, methods: &[String]) -> bool {
+        let mut connections = self.connections.write().await;
+        let Some(connection) = connections.get_mut(&id) else {
+            return false;
+        };
+
+        for method in methods {
+            connection.subscriptions.remove(method);
+        }
+        true
+    }
+
+    /// Send a notification to the connections subscribed to `method`
+    ///
+    /// Returns the number of connections the notification was queued for.
+    pub async fn notify(&self, method: &str, params: Option<Value>) -> usize {
+        self.send(method, params, |connection| {
+            connection.subscriptions.contains(method)
+        })
+        .await
+    }
+
+    /// Send a notification to the subscribers of `method` whose identity
+    /// passes `visible`
+    ///
+    /// Used for content that is not visible to everyone. Returns the number
+    /// of connections the notification was queued for.
+    pub async fn notify_visible(
+        &self,
+        method: &str,
+        params: Option<Value>,
+        visible: impl Fn(Option<&UserIdentity>) -> bool,
+    ) -> usize {
+        self.send(method, params, |connection| {
+            connection.subscriptions.contains(method) && visible(connection.identity.as_ref())
+        })
+        .await
+    }
+
+    /// Send a notification to the connections whose identity passes
+    /// `recipient`, whether or not they subscribed to `method`
+    ///
+    /// Used for events addressed to a user, such as syncing state between
+    /// their devices. Returns the number of connections the notification was
+    /// queued for.
+    pub async fn notify_identity(
+        &self,
+        method: &str,
+        params: Option<Value>,
+        recipient: impl Fn(&UserIdentity) -> bool,
+    ) -> usize {
+        self.send(method, params, |connection| {
+            connection.identity.as_ref().is_some_and(&recipient)
+        })
+        .await
+    }
+
+    /// Send a notification to every connection
+    ///
+    /// Returns the number of connections the notification was queued for.
+    pub async fn broadcast(&self, method: &str, params: Option<Value>) -> usize {
+        self.send(method, params, |_| true).await
+    }
+
+    async fn send(
+        &self,
+        method: &str,
+        params: Option<Value>,
+        filter: impl Fn(&Connection) -> bool,
+    ) -> usize {
+        let notification = JsonRpcRequest::new(method.to_string(), params, None);
+        let message = match serde_json::to_string(&notification) {
+            Ok(message) => message,
+            Err(e) => {
+                tracing::error!("Failed to serialize notification: {}", e);
+                return 0;
+            }
+        };
+
+        let connections = self.connections.read().await;
+        let mut delivered = 0;
+        for (id, connection) in connections.iter().filter(|(_, c)| filter(c)) {
+            match connection.sender.try_send(message.clone()) {
+                Ok(()) => delivered += 1,
+                Err(mpsc::error::TrySendError::Full(_)) => {
+                    tracing::warn!("Dropped notification {} for slow connection {}", method, id);
+                }
+                // The connection is closing and will unregister itself
+                Err(mpsc::error::TrySendError::Closed(_)) => {}
+            }
+        }
+
+        delivered
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn test_notify_subscribers() {
+        let registry = ConnectionRegistry::new();
+        let (subscriber, mut subscriber_rx) = registry.register().await;
+        let (_other, mut other_rx) = registry.register().await;
+
+        assert!(
+            registry
+                .subscribe(subscriber, &["announcements.published".to_string()])
+                .await
+        );
+        assert_eq!(
+            registry
+                .notify("announcements.published", Some(json!({"id": 1})))
+                .await,
+            1
+        );
+
+        let message: Value = serde_json::from_str(&subscriber_rx.recv().await.unwrap()).unwrap();
+        assert_eq!(message["method"], "announcements.published");
+        assert!(message.get("id").is_none());
+        assert!(other_rx.try_recv().is_err());
+
+        assert_eq!(registry.broadcast("server.shutdown", None).await, 2);
+        assert!(other_rx.try_recv().is_ok());
+
+        registry
+            .unsubscribe(subscriber, &["announcements.published".to_string()])
+            .await;
+        assert_eq!(registry.notify("announcements.published", None).await, 0);
+
+        registry.unregister(subscriber).await;
+        assert_eq!(registry.len().await, 1);
+    }
+
+    #[tokio::test]
+    async fn test_close_all() {
+        let registry = ConnectionRegistry::new();
+        let (id, _rx) = registry.register().await;
+        let mut closing = registry.closing();
+        assert!(!*closing.borrow());
+
+        registry.close_all();
+        assert!(closing.wait_for(|closing| *closing).await.is_ok());
+        assert!(*registry.closing().borrow());
+        assert!(!registry.wait_closed(Duration::from_millis(10)).await);
+
+        let waiting = tokio::spawn({
+            let registry = registry.clone();
+            async move { registry.wait_closed(Duration::from_secs(5)).await }
+        });
+        registry.unregister(id).await;
+        assert!(waiting.await.unwrap());
+    }
+}
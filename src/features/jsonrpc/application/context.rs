@@ -0,0 +1,115 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+
+use crate::features::users::domain::UserIdentity;
+use crate::infrastructure::audit::{AuditActor, AuditContext};
+
+use super::super::domain::RpcError;
+use super::connections::ConnectionId;
+
+/// Client metadata announced with `client.hello`
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct ClientInfo {
+    /// Client application name
+    pub name: String,
+    /// Client application version
+    pub version: String,
+    /// Optional features the client supports
+    #[serde(default)]
+    pub capabilities: Vec<String>,
+    /// Preferred locale of the client, e.g. "ko-KR"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub locale: Option<String>,
+    /// Highest version of each method the client can call, e.g. `{"posts.create": 2}`
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub method_versions: BTreeMap<String, u32>,
+}
+
+/// Context of the connection a JSON-RPC request arrived on
+///
+/// Built by the presentation layer when the WebSocket connection is
+/// established and handed to the service with every request. Clones
+/// share the client metadata of the connection.
+#[derive(Debug, Clone, Default)]
+pub struct RpcContext {
+    /// Identity authenticated on the WebSocket upgrade request, if any
+    pub identity: Option<UserIdentity>,
+    /// Registered connection that server notifications are pushed to, if any
+    pub connection_id: Option<ConnectionId>,
+    /// Address of the client, if known
+    pub remote_addr: Option<SocketAddr>,
+    /// Client metadata announced with `client.hello`, if any
+    client: Arc<RwLock<Option<ClientInfo>>>,
+}
+
+impl RpcContext {
+    /// Create a context for a connection with the given identity
+    pub fn new(identity: Option<UserIdentity>) -> Self {
+        Self {
+            identity,
+            connection_id: None,
+            remote_addr: None,
+            client: Arc::default(),
+        }
+    }
+
+    /// Attach the address of the client
+    pub fn with_remote_addr(mut self, remote_addr: SocketAddr) -> Self {
+        self.remote_addr = Some(remote_addr);
+        self
+    }
+
+    /// Attach the registered connection the context belongs to
+    pub fn with_connection(mut self, connection_id: ConnectionId) -> Self {
+        self.connection_id = Some(connection_id);
+        self
+    }
+
+    /// Identity of the connection, for methods that act on behalf of it
+    ///
+    /// Fails with `Forbidden` on connections without a token.
+    pub fn authenticated(&self) -> Result<&UserIdentity, RpcError> {
+        self.identity
+            .as_ref()
+            .ok_or_else(|| RpcError::Forbidden("Authentication required".to_string()))
+    }
+
+    /// Audit context of the calls made on this connection
+    pub fn audit_context(&self) -> AuditContext {
+        AuditContext {
+            actor: self.identity.as_ref().map(AuditActor::from),
+            ..Default::default()
+        }
+    }
+
+    /// Client metadata announced on this connection
+    pub async fn client(&self) -> Option<ClientInfo> {
+        self.client.read().await.clone()
+    }
+
+    /// Store the client metadata of this connection
+    pub async fn set_client(&self, client: ClientInfo) {
+        *self.client.write().await = Some(client);
+    }
+
+    /// Highest version of a method the client announced it can call
+    pub async fn requested_version(&self, method: &str) -> Option<u32> {
+        self.client
+            .read()
+            .await
+            .as_ref()
+            .and_then(|client| client.method_versions.get(method).copied())
+    }
+
+    /// Check if the client announced support for a capability
+    pub async fn client_supports(&self, capability: &str) -> bool {
+        self.client
+            .read()
+            .await
+            .as_ref()
+            .is_some_and(|client| client.capabilities.iter().any(|c| c == capability))
+    }
+}
@@ -0,0 +1,307 @@
+use schemars::{schema::RootSchema, schema_for, JsonSchema};
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::features::announcements::{AcknowledgementReminder, Announcement};
+use crate::features::anomaly::AnomalyAlert;
+use crate::features::board::Post;
+use crate::features::maintenance::MaintenanceWindow;
+use crate::features::notifications::ReadState;
+
+use super::presence::PresenceChange;
+
+/// Names of the events clients can subscribe to
+///
+/// Events sent to every connection, to a user's own connections or to the
+/// connections an announcement targets are delivered without subscribing;
+/// anomaly alerts only go to webhooks.
+pub const SUBSCRIBABLE_EVENTS: &[&str] = &["boards.post_created", "presence.changed"];
+
+/// Schema version of the data of each event
+///
+/// Bump the version when a field is removed, renamed or changes its type.
+/// Added fields keep the version, so consumers must ignore unknown fields.
+const SCHEMA_VERSIONS: &[(&str, u32)] = &[
+    ("boards.post_created", 1),
+    ("announcements.published", 1),
+    ("announcements.withdrawn", 1),
+    ("announcements.reminder", 1),
+    ("maintenance.announced", 1),
+    ("maintenance.started", 1),
+    ("maintenance.ended", 1),
+    ("maintenance.cancelled", 1),
+    ("notifications.read", 1),
+    ("presence.changed", 1),
+    ("server.shutdown", 1),
+    ("anomaly.detected", 1),
+];
+
+/// Event the server pushes to clients and webhooks
+///
+/// Pushed over WebSocket as a JSON-RPC notification whose method is the
+/// event name and whose params are the event data. Webhooks receive the
+/// `payload`, `{"event": "...", "data": {...}, "schema_version": 1}`.
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "event", content = "data")]
+pub enum ServerEvent {
+    /// A thread was opened or replied to
+    #[serde(rename = "boards.post_created")]
+    PostCreated(PostCreated),
+    /// An announcement was published
+    #[serde(rename = "announcements.published")]
+    AnnouncementPublished(Announcement),
+    /// An announcement was withdrawn before it expired
+    #[serde(rename = "announcements.withdrawn")]
+    AnnouncementWithdrawn(Announcement),
+    /// An announcement still awaits acknowledgement
+    #[serde(rename = "announcements.reminder")]
+    AcknowledgementReminder(AcknowledgementReminder),
+    /// A maintenance window was pre-announced
+    #[serde(rename = "maintenance.announced")]
+    MaintenanceAnnounced(MaintenanceWindow),
+    /// Maintenance mode is on
+    #[serde(rename = "maintenance.started")]
+    MaintenanceStarted(MaintenanceWindow),
+    /// Maintenance mode is off again
+    #[serde(rename = "maintenance.ended")]
+    MaintenanceEnded(MaintenanceWindow),
+    /// An announced maintenance window was cancelled
+    #[serde(rename = "maintenance.cancelled")]
+    MaintenanceCancelled(MaintenanceWindow),
+    /// A user read notifications on one of their devices
+    #[serde(rename = "notifications.read")]
+    NotificationsRead(ReadState),
+    /// A verified user came online or went offline
+    #[serde(rename = "presence.changed")]
+    PresenceChanged(PresenceChange),
+    /// The server is shutting down and closes all connections
+    #[serde(rename = "server.shutdown")]
+    ServerShutdown(ShutdownNotice),
+    /// An anomaly threshold was reached
+    #[serde(rename = "anomaly.detected")]
+    AnomalyDetected(AnomalyAlert),
+}
+
+/// Data of `boards.post_created`
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct PostCreated {
+    pub board_id: u64,
+    pub thread_id: u64,
+    pub post: Post,
+}
+
+/// Data of `server.shutdown`
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct ShutdownNotice {
+    /// Time connections have before they are closed
+    pub grace_period_secs: u64,
+}
+
+impl ServerEvent {
+    /// Event name, used as JSON-RPC method
+    pub fn name(&self) -> &'static str {
+        match self {
+            ServerEvent::PostCreated(_) => "boards.post_created",
+            ServerEvent::AnnouncementPublished(_) => "announcements.published",
+            ServerEvent::AnnouncementWithdrawn(_) => "announcements.withdrawn",
+            ServerEvent::AcknowledgementReminder(_) => "announcements.reminder",
+            ServerEvent::MaintenanceAnnounced(_) => "maintenance.announced",
+            ServerEvent::MaintenanceStarted(_) => "maintenance.started",
+            ServerEvent::MaintenanceEnded(_) => "maintenance.ended",
+            ServerEvent::MaintenanceCancelled(_) => "maintenance.cancelled",
+            ServerEvent::NotificationsRead(_) => "notifications.read",
+            ServerEvent::PresenceChanged(_) => "presence.changed",
+            ServerEvent::ServerShutdown(_) => "server.shutdown",
+            ServerEvent::AnomalyDetected(_) => "anomaly.detected",
+        }
+    }
+
+    /// Schema version of the event data
+    pub fn schema_version(&self) -> u32 {
+        schema_version(self.name())
+    }
+
+    /// Event data with its `schema_version`, used as JSON-RPC params
+    pub fn data(&self) -> Option<Value> {
+        let data = match self {
+            ServerEvent::PostCreated(data) => serde_json::to_value(data),
+            ServerEvent::AnnouncementPublished(announcement)
+            | ServerEvent::AnnouncementWithdrawn(announcement) => {
+                serde_json::to_value(announcement)
+            }
+            ServerEvent::AcknowledgementReminder(reminder) => serde_json::to_value(reminder),
+            ServerEvent::MaintenanceAnnounced(window)
+            | ServerEvent::MaintenanceStarted(window)
+            | ServerEvent::MaintenanceEnded(window)
+            | ServerEvent::MaintenanceCancelled(window) => serde_json::to_value(window),
+            ServerEvent::NotificationsRead(state) => serde_json::to_value(state),
+            ServerEvent::PresenceChanged(change) => serde_json::to_value(change),
+            ServerEvent::ServerShutdown(notice) => serde_json::to_value(notice),
+            ServerEvent::AnomalyDetected(alert) => serde_json::to_value(alert),
+        };
+        let mut data = data
+            .map_err(|err| tracing::error!("Failed to serialize {}: {}", self.name(), err))
+            .ok()?;
+        if let Value::Object(fields) = &mut data {
+            fields.insert("schema_version".to_string(), self.schema_version().into());
+        }
+        Some(data)
+    }
+
+    /// Event with its schema version, as posted to webhooks
+    pub fn payload(&self) -> EventPayload<'_> {
+        EventPayload {
+            event: self,
+            schema_version: self.schema_version(),
+        }
+    }
+}
+
+/// Serialized event together with the schema version of its data
+#[derive(Debug, Serialize)]
+pub struct EventPayload<'a> {
+    #[serde(flatten)]
+    event: &'a ServerEvent,
+    schema_version: u32,
+}
+
+/// JSON Schema of the data of an event
+#[derive(Debug, Serialize)]
+pub struct EventSchema {
+    pub event: &'static str,
+    pub schema_version: u32,
+    pub schema: RootSchema,
+}
+
+impl EventSchema {
+    fn of<T: JsonSchema>(event: &'static str) -> Self {
+        Self {
+            event,
+            schema_version: schema_version(event),
+            schema: schema_for!(T),
+        }
+    }
+}
+
+/// Schemas of the data of all events, at their current version
+pub fn event_schemas() -> Vec<EventSchema> {
+    vec![
+        EventSchema::of::<PostCreated>("boards.post_created"),
+        EventSchema::of::<Announcement>("announcements.published"),
+        EventSchema::of::<Announcement>("announcements.withdrawn"),
+        EventSchema::of::<AcknowledgementReminder>("announcements.reminder"),
+        EventSchema::of::<MaintenanceWindow>("maintenance.announced"),
+        EventSchema::of::<MaintenanceWindow>("maintenance.started"),
+        EventSchema::of::<MaintenanceWindow>("maintenance.ended"),
+        EventSchema::of::<MaintenanceWindow>("maintenance.cancelled"),
+        EventSchema::of::<ReadState>("notifications.read"),
+        EventSchema::of::<PresenceChange>("presence.changed"),
+        EventSchema::of::<ShutdownNotice>("server.shutdown"),
+        EventSchema::of::<AnomalyAlert>("anomaly.detected"),
+    ]
+}
+
+/// Current schema version of the data of `event`
+fn schema_version(event: &str) -> u32 {
+    SCHEMA_VERSIONS
+        .iter()
+        .find(|(name, _)| *name == event)
+        .map_or(1, |(_, version)| *version)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::features::jsonrpc::application::presence::PresenceStatus;
+    use chrono::Utc;
+
+    #[test]
+    fn test_event_names_match_serialized_tags() {
+        let window = MaintenanceWindow {
+            id: 1,
+            message: "Database upgrade".to_string(),
+            announce_at: Utc::now(),
+            starts_at: Utc::now(),
+            ends_at: Utc::now(),
+            announcement_id: None,
+            created_by: "user:1".to_string(),
+            created_at: Utc::now(),
+        };
+        let announcement = Announcement {
+            id: 1,
+            title: "Notice".to_string(),
+            message: "Fire drill at 3pm".to_string(),
+            hospital_code: Some("H001".to_string()),
+            department_code: None,
+            starts_at: Utc::now(),
+            ends_at: Utc::now(),
+            requires_acknowledgement: false,
+            created_by: "1".to_string(),
+            created_at: Utc::now(),
+        };
+        let events = [
+            ServerEvent::PostCreated(PostCreated {
+                board_id: 1,
+                thread_id: 2,
+                post: Post {
+                    id: 3,
+                    thread_id: 2,
+                    body: "Noted".to_string(),
+                    author_id: "user:1".to_string(),
+                    author_name: None,
+                    created_at: Utc::now(),
+                },
+            }),
+            ServerEvent::AnnouncementPublished(announcement.clone()),
+            ServerEvent::AnnouncementWithdrawn(announcement.clone()),
+            ServerEvent::AcknowledgementReminder(AcknowledgementReminder {
+                level: 1,
+                announcement,
+            }),
+            ServerEvent::MaintenanceAnnounced(window.clone()),
+            ServerEvent::MaintenanceStarted(window.clone()),
+            ServerEvent::MaintenanceEnded(window.clone()),
+            ServerEvent::MaintenanceCancelled(window),
+            ServerEvent::NotificationsRead(ReadState {
+                ids: vec![1],
+                read_at: Utc::now(),
+                unread: 0,
+            }),
+            ServerEvent::PresenceChanged(PresenceChange {
+                user_id: 1,
+                username: "alice".to_string(),
+                status: PresenceStatus::Online,
+                changed_at: Utc::now(),
+            }),
+            ServerEvent::ServerShutdown(ShutdownNotice {
+                grace_period_secs: 10,
+            }),
+        ];
+
+        let schemas = event_schemas();
+        for event in events {
+            let serialized = serde_json::to_value(&event).unwrap();
+            assert_eq!(serialized["event"], event.name());
+            assert!(SCHEMA_VERSIONS
+                .iter()
+                .any(|(name, _)| *name == event.name()));
+            assert!(schemas.iter().any(|schema| schema.event == event.name()));
+            assert_eq!(event.data().unwrap()["schema_version"], 1);
+        }
+        assert_eq!(schemas.len(), SCHEMA_VERSIONS.len());
+        assert_eq!(
+            serde_json::to_value(
+                ServerEvent::ServerShutdown(ShutdownNotice {
+                    grace_period_secs: 10
+                })
+                .payload()
+            )
+            .unwrap(),
+            serde_json::json!({
+                "event": "server.shutdown",
+                "data": {"grace_period_secs": 10},
+                "schema_version": 1
+            })
+        );
+    }
+}
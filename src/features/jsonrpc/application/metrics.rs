@@ -0,0 +1,335 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::RwLock;
+
+/// Number of latency histogram buckets
+///
+/// Bucket `i` counts calls that took less than `2^i` microseconds (and at
+/// least `2^(i-1)`), the last bucket also holds everything slower.
+const LATENCY_BUCKETS: usize = 32;
+
+/// Label value of calls not broken down by a label
+pub const ALL_LABEL_VALUE: &str = "all";
+
+/// Label value new values are aggregated under once a label is capped
+pub const OTHER_LABEL_VALUE: &str = "other";
+
+/// Tenant label value of callers without a tenant, such as verified users
+const NO_TENANT_LABEL_VALUE: &str = "none";
+
+/// Default maximum number of distinct values per label
+pub const DEFAULT_MAX_LABEL_VALUES: usize = 100;
+
+/// Labels calls are broken down by
+///
+/// Every label value adds series to the metrics, so each label is capped:
+/// once it has `max_values` distinct values, calls with new values are
+/// counted under `other`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MetricLabels {
+    /// Break calls down by method; otherwise they are counted under `all`
+    pub method: bool,
+    /// Break calls down by tenant (hospital code of anonymous callers)
+    pub tenant: bool,
+    /// Maximum number of distinct values per label
+    pub max_values: usize,
+}
+
+impl MetricLabels {
+    /// Labels from their names (`method`, `tenant`); unknown names are ignored
+    pub fn from_names(names: &[String], max_values: usize) -> Self {
+        let mut labels = Self {
+            method: false,
+            tenant: false,
+            max_values,
+        };
+        for name in names {
+            match name.as_str() {
+                "method" => labels.method = true,
+                "tenant" => labels.tenant = true,
+                _ => tracing::warn!("Ignoring unknown metric label {}", name),
+            }
+        }
+        labels
+    }
+}
+
+impl Default for MetricLabels {
+    fn default() -> Self {
+        Self {
+            method: true,
+            tenant: false,
+            max_values: DEFAULT_MAX_LABEL_VALUES,
+        }
+    }
+}
+
+/// Counters of a single method
+#[derive(Debug, Clone, Default)]
+struct MethodMetrics {
+    calls: u64,
+    errors: u64,
+    latency_buckets: [u64; LATENCY_BUCKETS],
+}
+
+impl MethodMetrics {
+    fn record(&mut self, latency: Duration, is_error: bool) {
+        let micros = latency.as_micros();
+        let bucket = (u128::BITS - micros.leading_zeros()) as usize;
+
+        self.calls += 1;
+        if is_error {
+            self.errors += 1;
+        }
+        self.latency_buckets[bucket.min(LATENCY_BUCKETS - 1)] += 1;
+    }
+
+    fn merge(&mut self, other: &MethodMetrics) {
+        self.calls += other.calls;
+        self.errors += other.errors;
+        for (bucket, count) in self.latency_buckets.iter_mut().zip(other.latency_buckets) {
+            *bucket += count;
+        }
+    }
+
+    /// Latency percentile in milliseconds
+    ///
+    /// Reports the upper bound of the histogram bucket the percentile falls
+    /// into, so values are accurate to within a factor of two.
+    fn percentile_ms(&self, percentile: f64) -> f64 {
+        let rank = ((self.calls as f64) * percentile).ceil().max(1.0) as u64;
+        let mut seen = 0;
+
+        for (bucket, count) in self.latency_buckets.iter().enumerate() {
+            seen += count;
+            if seen >= rank {
+                return (1u64 << bucket) as f64 / 1000.0;
+            }
+        }
+        0.0
+    }
+
+    fn stats(&self) -> MethodStats {
+        MethodStats {
+            calls: self.calls,
+            errors: self.errors,
+            error_rate: if self.calls == 0 {
+                0.0
+            } else {
+                self.errors as f64 / self.calls as f64
+            },
+            latency_ms: LatencyPercentiles {
+                p50: self.percentile_ms(0.50),
+                p90: self.percentile_ms(0.90),
+                p99: self.percentile_ms(0.99),
+            },
+        }
+    }
+}
+
+/// Latency percentiles in milliseconds
+#[derive(Debug, Clone, Serialize)]
+pub struct LatencyPercentiles {
+    pub p50: f64,
+    pub p90: f64,
+    pub p99: f64,
+}
+
+/// Statistics of a single method
+#[derive(Debug, Clone, Serialize)]
+pub struct MethodStats {
+    pub calls: u64,
+    pub errors: u64,
+    pub error_rate: f64,
+    pub latency_ms: LatencyPercentiles,
+}
+
+/// Snapshot of the JSON-RPC metrics since startup
+#[derive(Debug, Clone, Serialize)]
+pub struct RpcStats {
+    pub started_at: DateTime<Utc>,
+    pub uptime_secs: i64,
+    /// Messages rejected before dispatch for exceeding the size limit
+    pub rejected_frames: u64,
+    pub methods: BTreeMap<String, MethodStats>,
+    /// Method statistics per tenant, if broken down by tenant
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub tenants: BTreeMap<String, BTreeMap<String, MethodStats>>,
+}
+
+/// Series of calls, keyed by method and tenant label values
+#[derive(Default)]
+struct Series {
+    metrics: HashMap<(String, Option<String>), MethodMetrics>,
+    methods: HashSet<String>,
+    tenants: HashSet<String>,
+}
+
+/// Value `value` is counted under, given the values a label already has
+fn label_value(seen: &mut HashSet<String>, value: &str, max_values: usize) -> String {
+    if seen.contains(value) {
+        return value.to_string();
+    }
+    if seen.len() >= max_values {
+        return OTHER_LABEL_VALUE.to_string();
+    }
+    seen.insert(value.to_string());
+    value.to_string()
+}
+
+/// JSON-RPC method metrics
+///
+/// Collects call counts, error counts and a latency histogram per method
+/// since startup, optionally per tenant too. Only registered methods are
+/// tracked, and labels are capped, so clients cannot grow the metrics by
+/// calling arbitrary method names or from many tenants.
+#[derive(Clone)]
+pub struct RpcMetrics {
+    started_at: DateTime<Utc>,
+    series: Arc<RwLock<Series>>,
+    rejected_frames: Arc<AtomicU64>,
+    labels: MetricLabels,
+}
+
+impl RpcMetrics {
+    /// Create empty metrics broken down by method
+    pub fn new() -> Self {
+        Self {
+            started_at: Utc::now(),
+            series: Arc::new(RwLock::new(Series::default())),
+            rejected_frames: Arc::new(AtomicU64::new(0)),
+            labels: MetricLabels::default(),
+        }
+    }
+
+    /// Set the labels calls are broken down by
+    pub fn with_labels(mut self, labels: MetricLabels) -> Self {
+        self.labels = labels;
+        self
+    }
+
+    /// Record a completed call by a caller of `tenant`
+    pub async fn record(
+        &self,
+        method: &str,
+        tenant: Option<&str>,
+        latency: Duration,
+        is_error: bool,
+    ) {
+        let mut series = self.series.write().await;
+        let max_values = self.labels.max_values;
+        let method = if self.labels.method {
+            label_value(&mut series.methods, method, max_values)
+        } else {
+            ALL_LABEL_VALUE.to_string()
+        };
+        let tenant = self.labels.tenant.then(|| {
+            let tenant = tenant.unwrap_or(NO_TENANT_LABEL_VALUE);
+            label_value(&mut series.tenants, tenant, max_values)
+        });
+
+        series
+            .metrics
+            .entry((method, tenant))
+            .or_default()
+            .record(latency, is_error);
+    }
+
+    /// Record a message rejected for exceeding the size limit
+    pub fn record_rejected_frame(&self) {
+        self.rejected_frames.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Take a snapshot of the metrics
+    pub async fn snapshot(&self) -> RpcStats {
+        let series = self.series.read().await;
+
+        let mut methods: BTreeMap<String, MethodMetrics> = BTreeMap::new();
+        let mut tenants: BTreeMap<String, BTreeMap<String, MethodStats>> = BTreeMap::new();
+        for ((method, tenant), metrics) in &series.metrics {
+            methods.entry(method.clone()).or_default().merge(metrics);
+            if let Some(tenant) = tenant {
+                tenants
+                    .entry(tenant.clone())
+                    .or_default()
+                    .insert(method.clone(), metrics.stats());
+            }
+        }
+
+        RpcStats {
+            started_at: self.started_at,
+            uptime_secs: (Utc::now() - self.started_at).num_seconds(),
+            rejected_frames: self.rejected_frames.load(Ordering::Relaxed),
+            methods: methods
+                .into_iter()
+                .map(|(name, metrics)| (name, metrics.stats()))
+                .collect(),
+            tenants,
+        }
+    }
+}
+
+impl Default for RpcMetrics {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_snapshot() {
+        let metrics = RpcMetrics::new();
+        for _ in 0..9 {
+            metrics
+                .record("echo", None, Duration::from_micros(100), false)
+                .await;
+        }
+        metrics
+            .record("echo", None, Duration::from_millis(50), true)
+            .await;
+
+        let stats = metrics.snapshot().await;
+        let echo = &stats.methods["echo"];
+        assert_eq!(echo.calls, 10);
+        assert_eq!(echo.errors, 1);
+        assert!((echo.error_rate - 0.1).abs() < f64::EPSILON);
+        // 100us falls into the bucket below 128us, 50ms into the one below ~65ms
+        assert_eq!(echo.latency_ms.p50, 0.128);
+        assert_eq!(echo.latency_ms.p99, 65.536);
+        assert!(stats.tenants.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_labels_are_capped() {
+        let metrics = RpcMetrics::new().with_labels(MetricLabels::from_names(
+            &["tenant".to_string(), "board".to_string()],
+            2,
+        ));
+        for tenant in [Some("H001"), Some("H002"), Some("H003"), None] {
+            metrics
+                .record("echo", tenant, Duration::from_micros(100), false)
+                .await;
+        }
+        metrics
+            .record("ping", Some("H001"), Duration::from_micros(100), false)
+            .await;
+
+        let stats = metrics.snapshot().await;
+        // Methods are not broken down, tenants beyond the cap are aggregated
+        assert_eq!(stats.methods.keys().collect::<Vec<_>>(), ["all"]);
+        assert_eq!(stats.methods["all"].calls, 5);
+        assert_eq!(
+            stats.tenants.keys().collect::<Vec<_>>(),
+            ["H001", "H002", "other"]
+        );
+        assert_eq!(stats.tenants["H001"]["all"].calls, 2);
+        assert_eq!(stats.tenants["other"]["all"].calls, 2);
+    }
+}
@@ -1,18 +1,42 @@
-/// JSON-RPC Application Layer
-///
-/// Contains business logic and orchestration for JSON-RPC operations.
-///
-/// ## Components
-/// - `service`: Method registry and request dispatcher
-///
-/// ## Responsibilities
-/// - Register and manage RPC method handlers
-/// - Dispatch requests to appropriate handlers
-/// - Execute business logic
-/// - Handle async operations
-/// - Manage method lifecycle
+//! JSON-RPC Application Layer
+//!
+//! Contains business logic and orchestration for JSON-RPC operations.
+//!
+//! ## Components
+//! - `service`: Method registry and request dispatcher
+//! - `context`: Per-connection context and client metadata passed with every request
+//! - `connections`: Registry of open connections for server-initiated notifications
+//! - `events`: Typed events the server pushes to connections and webhooks
+//! - `metrics`: Per-method call statistics
+//! - `namespace`: Methods registered under a common prefix
+//! - `policy`: Who may call a method (public, authenticated, role)
+//! - `presence`: Verified users currently connected
+//!
+//! ## Responsibilities
+//! - Register and manage RPC method handlers
+//! - Dispatch requests to appropriate handlers
+//! - Execute business logic
+//! - Handle async operations
+//! - Manage method lifecycle
 
+pub mod connections;
+pub mod context;
+pub mod events;
+pub mod metrics;
+pub mod namespace;
+pub mod policy;
+pub mod presence;
 pub mod service;
 
 // Re-export commonly used types
+pub use connections::{ConnectionId, ConnectionRegistry};
+pub use context::{ClientInfo, RpcContext};
+pub use events::{
+    event_schemas, EventPayload, EventSchema, PostCreated, ServerEvent, ShutdownNotice,
+    SUBSCRIBABLE_EVENTS,
+};
+pub use metrics::{MetricLabels, RpcMetrics, RpcStats};
+pub use namespace::MethodNamespace;
+pub use policy::MethodPolicy;
+pub use presence::{OnlineUser, PresenceChange, PresenceRegistry, PresenceStatus};
 pub use service::JsonRpcService;
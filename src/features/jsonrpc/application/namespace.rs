@@ -0,0 +1,99 @@
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::Value;
+
+use super::super::domain::RpcError;
+use super::context::RpcContext;
+use super::policy::MethodPolicy;
+use super::service::JsonRpcService;
+
+/// Group of methods registered under a common prefix
+///
+/// Methods registered through the namespace `users` are called as
+/// `users.<name>`. A policy set on the namespace applies to all of its
+/// methods, including ones registered later, unless a method has a policy
+/// of its own.
+///
+/// # Example
+/// ```rust,ignore
+/// let users = jsonrpc_service
+///     .namespace("users")
+///     .with_policy(MethodPolicy::Role(Role::Admin));
+/// users.register_typed_method("list", |_: (), _context| async move { Ok(list_users()) });
+/// ```
+pub struct MethodNamespace {
+    service: JsonRpcService,
+    prefix: String,
+}
+
+impl MethodNamespace {
+    pub(super) fn new(service: JsonRpcService, namespace: &str) -> Self {
+        Self {
+            service,
+            prefix: namespace.to_string(),
+        }
+    }
+
+    /// Set who may call the methods of the namespace
+    pub fn with_policy(self, policy: MethodPolicy) -> Self {
+        self.service
+            .set_method_policy(&format!("{}.*", self.prefix), policy);
+        self
+    }
+
+    /// Full name of a method of the namespace
+    pub fn method_name(&self, name: &str) -> String {
+        format!("{}.{}", self.prefix, name)
+    }
+
+    /// Register a method of the namespace, see `JsonRpcService::register_method`
+    pub fn register_method<F, Fut>(&self, name: &str, handler: F)
+    where
+        F: Fn(Option<Value>) -> Fut + Send + Sync + 'static,
+        Fut: futures::future::Future<Output = Result<Value, RpcError>> + Send + 'static,
+    {
+        self.service
+            .register_method(self.method_name(name), handler);
+    }
+
+    /// Register a method of the namespace that needs the connection context,
+    /// see `JsonRpcService::register_method_with_context`
+    pub fn register_method_with_context<F, Fut>(&self, name: &str, handler: F)
+    where
+        F: Fn(Option<Value>, RpcContext) -> Fut + Send + Sync + 'static,
+        Fut: futures::future::Future<Output = Result<Value, RpcError>> + Send + 'static,
+    {
+        self.service
+            .register_method_with_context(self.method_name(name), handler);
+    }
+
+    /// Register a method of the namespace with typed params and result,
+    /// see `JsonRpcService::register_typed_method`
+    pub fn register_typed_method<P, R, F, Fut>(&self, name: &str, handler: F)
+    where
+        P: DeserializeOwned,
+        R: Serialize,
+        F: Fn(P, RpcContext) -> Fut + Send + Sync + 'static,
+        Fut: futures::future::Future<Output = Result<R, RpcError>> + Send + 'static,
+    {
+        self.service
+            .register_typed_method(self.method_name(name), handler);
+    }
+
+    /// Register a specific version of a method of the namespace, see
+    /// `JsonRpcService::register_method_version`
+    pub fn register_method_version<F, Fut>(&self, name: &str, version: u32, handler: F)
+    where
+        F: Fn(Option<Value>, RpcContext) -> Fut + Send + Sync + 'static,
+        Fut: futures::future::Future<Output = Result<Value, RpcError>> + Send + 'static,
+    {
+        self.service
+            .register_method_version(&self.method_name(name), version, handler);
+    }
+
+    /// Set who may call a method of the namespace, overriding the policy of
+    /// the namespace
+    pub fn set_method_policy(&self, name: &str, policy: MethodPolicy) {
+        self.service
+            .set_method_policy(&self.method_name(name), policy);
+    }
+}
@@ -0,0 +1,85 @@
+use crate::features::users::domain::{Role, UserIdentity};
+
+use super::super::domain::RpcError;
+
+/// Who may call a JSON-RPC method
+///
+/// Checked against the identity the connection was authenticated with
+/// before the method handler runs.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum MethodPolicy {
+    /// Any connection, with or without a token
+    #[default]
+    Public,
+    /// Connections authenticated with a token, anonymous or verified
+    Authenticated,
+    /// Connections of users with at least the given role
+    Role(Role),
+}
+
+impl MethodPolicy {
+    /// Check whether a connection with `identity` may call `method`
+    pub fn authorize(&self, method: &str, identity: Option<&UserIdentity>) -> Result<(), RpcError> {
+        let allowed = match self {
+            MethodPolicy::Public => true,
+            MethodPolicy::Authenticated => identity.is_some(),
+            MethodPolicy::Role(role) => identity.is_some_and(|identity| identity.has_role(*role)),
+        };
+        if allowed {
+            return Ok(());
+        }
+
+        let required = match self {
+            MethodPolicy::Public
+            | MethodPolicy::Authenticated
+            | MethodPolicy::Role(Role::Anonymous) => "an authenticated connection",
+            MethodPolicy::Role(Role::Member) => "a verified user",
+            MethodPolicy::Role(Role::Moderator) => "a moderator",
+            MethodPolicy::Role(Role::Admin) => "an admin",
+        };
+        Err(RpcError::Forbidden(format!(
+            "Method '{}' requires {}",
+            method, required
+        )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::features::users::domain::{AnonymousUserIdentifier, VerifiedUser};
+
+    #[test]
+    fn test_authorize() {
+        let anonymous = UserIdentity::Anonymous(AnonymousUserIdentifier {
+            hospital_code: "H001".to_string(),
+            user_id: "P001".to_string(),
+            user_start_date: chrono::NaiveDate::from_ymd_opt(2024, 1, 1).unwrap(),
+            department_code: "D001".to_string(),
+        });
+        let member = UserIdentity::Verified(VerifiedUser {
+            id: 1,
+            username: "alice".to_string(),
+            email: "alice@example.com".to_string(),
+            role: Role::Member,
+        });
+
+        assert!(MethodPolicy::Public.authorize("echo", None).is_ok());
+        assert!(MethodPolicy::Authenticated.authorize("echo", None).is_err());
+        assert!(MethodPolicy::Authenticated
+            .authorize("echo", Some(&anonymous))
+            .is_ok());
+
+        let verified = MethodPolicy::Role(Role::Member);
+        assert!(verified.authorize("rpc.stats", Some(&member)).is_ok());
+        assert_eq!(
+            verified.authorize("rpc.stats", Some(&anonymous)),
+            Err(RpcError::Forbidden(
+                "Method 'rpc.stats' requires a verified user".to_string()
+            ))
+        );
+        assert!(MethodPolicy::Role(Role::Admin)
+            .authorize("users.list", Some(&member))
+            .is_err());
+    }
+}
@@ -0,0 +1,139 @@
+use chrono::{DateTime, Utc};
+use schemars::JsonSchema;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::{Arc, PoisonError, RwLock};
+
+use crate::features::users::domain::VerifiedUser;
+
+/// Verified user with at least one open WebSocket connection
+#[derive(Debug, Clone, Serialize)]
+pub struct OnlineUser {
+    pub user_id: u64,
+    pub username: String,
+    /// Open connections of the user, e.g. one per device
+    pub connections: usize,
+    /// When the first of the open connections was established
+    pub online_since: DateTime<Utc>,
+}
+
+/// Whether a user is online
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum PresenceStatus {
+    Online,
+    Offline,
+}
+
+/// Data of `presence.changed`
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct PresenceChange {
+    pub user_id: u64,
+    pub username: String,
+    pub status: PresenceStatus,
+    pub changed_at: DateTime<Utc>,
+}
+
+/// Registry of the verified users connected over WebSocket
+///
+/// Counts the open connections of each user, so a user is online from
+/// their first connection until their last one closes. Anonymous
+/// connections are not tracked, as their identities belong to patients.
+#[derive(Clone, Default)]
+pub struct PresenceRegistry {
+    users: Arc<RwLock<HashMap<u64, OnlineUser>>>,
+}
+
+impl PresenceRegistry {
+    /// Create an empty registry
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record a new connection of `user`
+    ///
+    /// Returns the change if it is the user's first connection.
+    pub fn connected(&self, user: &VerifiedUser) -> Option<PresenceChange> {
+        let mut users = self.users.write().unwrap_or_else(PoisonError::into_inner);
+        let now = Utc::now();
+        let online = users.entry(user.id).or_insert_with(|| OnlineUser {
+            user_id: user.id,
+            username: user.username.clone(),
+            connections: 0,
+            online_since: now,
+        });
+        online.connections += 1;
+
+        (online.connections == 1).then(|| change(user, PresenceStatus::Online, now))
+    }
+
+    /// Record a closed connection of `user`
+    ///
+    /// Returns the change if it was the user's last connection.
+    pub fn disconnected(&self, user: &VerifiedUser) -> Option<PresenceChange> {
+        let mut users = self.users.write().unwrap_or_else(PoisonError::into_inner);
+        let online = users.get_mut(&user.id)?;
+        online.connections -= 1;
+        if online.connections > 0 {
+            return None;
+        }
+
+        users.remove(&user.id);
+        Some(change(user, PresenceStatus::Offline, Utc::now()))
+    }
+
+    /// Users currently online, by username
+    pub fn list(&self) -> Vec<OnlineUser> {
+        let mut users: Vec<OnlineUser> = self
+            .users
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .values()
+            .cloned()
+            .collect();
+        users.sort_by(|a, b| a.username.cmp(&b.username));
+        users
+    }
+}
+
+fn change(
+    user: &VerifiedUser,
+    status: PresenceStatus,
+    changed_at: DateTime<Utc>,
+) -> PresenceChange {
+    PresenceChange {
+        user_id: user.id,
+        username: user.username.clone(),
+        status,
+        changed_at,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::features::users::domain::Role;
+
+    #[test]
+    fn test_online_until_last_connection_closes() {
+        let presence = PresenceRegistry::new();
+        let alice = VerifiedUser {
+            id: 1,
+            username: "alice".to_string(),
+            email: "alice@example.com".to_string(),
+            role: Role::Member,
+        };
+
+        let change = presence.connected(&alice).unwrap();
+        assert_eq!(change.status, PresenceStatus::Online);
+        // A second device does not change presence
+        assert!(presence.connected(&alice).is_none());
+        assert_eq!(presence.list()[0].connections, 2);
+
+        assert!(presence.disconnected(&alice).is_none());
+        let change = presence.disconnected(&alice).unwrap();
+        assert_eq!(change.status, PresenceStatus::Offline);
+        assert!(presence.list().is_empty());
+        assert!(presence.disconnected(&alice).is_none());
+    }
+}
@@ -1,19 +1,39 @@
 use anyhow::Result;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use serde_json::{json, Value};
-use std::collections::HashMap;
-use std::sync::Arc;
-use tokio::sync::RwLock;
+use std::collections::{BTreeSet, HashMap};
+use std::sync::{Arc, PoisonError, RwLock, RwLockReadGuard, RwLockWriteGuard, Weak};
+use std::time::{Duration, Instant};
 
-use super::super::domain::{
-    JsonRpcErrorCode, JsonRpcErrorObject, JsonRpcErrorResponse, JsonRpcRequest, JsonRpcResponse,
-};
+use crate::features::health::HealthCheck;
+use crate::features::users::domain::{Role, UserIdentity};
+use crate::infrastructure::Service;
+
+use super::super::domain::{JsonRpcErrorResponse, JsonRpcRequest, JsonRpcResponse, RpcError};
+use super::connections::{ConnectionId, ConnectionRegistry};
+use super::context::{ClientInfo, RpcContext};
+use super::events::{ServerEvent, ShutdownNotice, SUBSCRIBABLE_EVENTS};
+use super::metrics::{MetricLabels, RpcMetrics};
+use super::namespace::MethodNamespace;
+use super::policy::MethodPolicy;
+use super::presence::PresenceRegistry;
+
+/// Default maximum size of a single message in bytes
+pub const DEFAULT_MAX_MESSAGE_SIZE: usize = 64 * 1024;
+
+/// Default time connections have to close on shutdown
+pub const DEFAULT_SHUTDOWN_GRACE: Duration = Duration::from_secs(10);
 
 /// Type alias for JSON-RPC method handlers
 ///
 /// A method handler is an async function that takes optional parameters
-/// and returns a Result with either a JSON value or an error object.
+/// and the connection context, and returns a Result with either a JSON
+/// value or a typed `RpcError`.
 type MethodHandler = Arc<
-    dyn Fn(Option<Value>) -> futures::future::BoxFuture<'static, Result<Value, JsonRpcErrorObject>>
+    dyn Fn(
+            Option<Value>,
+            RpcContext,
+        ) -> futures::future::BoxFuture<'static, Result<Value, RpcError>>
         + Send
         + Sync,
 >;
@@ -22,17 +42,37 @@ type MethodHandler = Arc<
 ///
 /// Application layer service that manages method registration and dispatching.
 /// Follows the Single Responsibility Principle by only handling RPC logic.
+/// Methods are registered synchronously, so built-in methods are available
+/// as soon as the service is constructed.
 ///
 /// ## Responsibilities
 /// - Register and manage method handlers
 /// - Dispatch requests to appropriate handlers
 /// - Handle notifications (no response)
 /// - Validate requests
+/// - Enforce the auth policies of methods and namespaces
+/// - Collect per-method metrics
+/// - Push notifications to connected clients
 /// - Generate appropriate error responses
 #[derive(Clone)]
 pub struct JsonRpcService {
     /// Registry of available methods
+    ///
+    /// The lock is never held across an await, so registration does not
+    /// need an async context.
     methods: Arc<RwLock<HashMap<String, MethodHandler>>>,
+    /// Auth policies of methods, and of namespaces under `namespace.*`
+    policies: Arc<RwLock<HashMap<String, MethodPolicy>>>,
+    /// Call statistics of registered methods
+    metrics: RpcMetrics,
+    /// Maximum size of a single message in bytes
+    max_message_size: usize,
+    /// Open connections that notifications are pushed to
+    connections: ConnectionRegistry,
+    /// Verified users with open connections
+    presence: PresenceRegistry,
+    /// Time connections have to close on shutdown
+    shutdown_grace: Duration,
 }
 
 impl JsonRpcService {
@@ -40,6 +80,12 @@ impl JsonRpcService {
     pub fn new() -> Self {
         let service = Self {
             methods: Arc::new(RwLock::new(HashMap::new())),
+            policies: Arc::new(RwLock::new(HashMap::new())),
+            metrics: RpcMetrics::new(),
+            max_message_size: DEFAULT_MAX_MESSAGE_SIZE,
+            connections: ConnectionRegistry::new(),
+            presence: PresenceRegistry::new(),
+            shutdown_grace: DEFAULT_SHUTDOWN_GRACE,
         };
 
         // Register built-in methods
@@ -48,29 +94,291 @@ impl JsonRpcService {
         service
     }
 
+    /// Set the maximum size of a single message in bytes
+    pub fn with_max_message_size(mut self, max_message_size: usize) -> Self {
+        self.max_message_size = max_message_size;
+        self
+    }
+
+    /// Set the time connections have to close on shutdown
+    pub fn with_shutdown_grace(mut self, shutdown_grace: Duration) -> Self {
+        self.shutdown_grace = shutdown_grace;
+        self
+    }
+
+    /// Set the labels method calls are broken down by in the metrics
+    pub fn with_metric_labels(mut self, labels: MetricLabels) -> Self {
+        self.metrics = self.metrics.with_labels(labels);
+        self
+    }
+
+    /// Maximum size of a single message in bytes
+    pub fn max_message_size(&self) -> usize {
+        self.max_message_size
+    }
+
+    /// Call statistics of registered methods
+    pub fn metrics(&self) -> &RpcMetrics {
+        &self.metrics
+    }
+
+    /// Open connections that notifications are pushed to
+    pub fn connections(&self) -> &ConnectionRegistry {
+        &self.connections
+    }
+
+    /// Verified users with open connections
+    pub fn presence(&self) -> &PresenceRegistry {
+        &self.presence
+    }
+
+    /// Register a new connection of the given identity
+    ///
+    /// Subscribers of `presence.changed` learn when a verified user opens
+    /// their first connection. Returns the connection id and the receiver
+    /// of messages pushed to it.
+    pub async fn connect(
+        &self,
+        identity: Option<UserIdentity>,
+    ) -> (ConnectionId, tokio::sync::mpsc::Receiver<String>) {
+        let change = identity
+            .as_ref()
+            .and_then(UserIdentity::as_verified)
+            .and_then(|user| self.presence.connected(user));
+        let registered = self.connections.register_as(identity).await;
+        if let Some(change) = change {
+            self.notify(&ServerEvent::PresenceChanged(change)).await;
+        }
+        registered
+    }
+
+    /// Remove a closed connection
+    ///
+    /// Subscribers of `presence.changed` learn when a verified user closed
+    /// their last connection.
+    pub async fn disconnect(&self, id: ConnectionId) {
+        let identity = self.connections.unregister(id).await;
+        let change = identity
+            .as_ref()
+            .and_then(UserIdentity::as_verified)
+            .and_then(|user| self.presence.disconnected(user));
+        if let Some(change) = change {
+            self.notify(&ServerEvent::PresenceChanged(change)).await;
+        }
+    }
+
+    /// Push an event to the connections subscribed to it
+    ///
+    /// Returns the number of connections the event was sent to.
+    pub async fn notify(&self, event: &ServerEvent) -> usize {
+        self.connections.notify(event.name(), event.data()).await
+    }
+
+    /// Push an event to its subscribers whose identity passes `visible`
+    ///
+    /// Returns the number of connections the event was sent to.
+    pub async fn notify_visible(
+        &self,
+        event: &ServerEvent,
+        visible: impl Fn(Option<&UserIdentity>) -> bool,
+    ) -> usize {
+        self.connections
+            .notify_visible(event.name(), event.data(), visible)
+            .await
+    }
+
+    /// Push an event to the connections whose identity passes `recipient`,
+    /// whether or not they subscribed to it
+    ///
+    /// Returns the number of connections the event was sent to.
+    pub async fn notify_identity(
+        &self,
+        event: &ServerEvent,
+        recipient: impl Fn(&UserIdentity) -> bool,
+    ) -> usize {
+        self.connections
+            .notify_identity(event.name(), event.data(), recipient)
+            .await
+    }
+
+    /// Push an event to every open connection
+    ///
+    /// Returns the number of connections the event was sent to.
+    pub async fn broadcast(&self, event: &ServerEvent) -> usize {
+        self.connections.broadcast(event.name(), event.data()).await
+    }
+
+    /// Close all connections for a server shutdown
+    ///
+    /// Sends a `server.shutdown` notification, then a Close frame to every
+    /// connection, and waits up to `grace_period` for the connections to
+    /// close. Returns the number of connections still open afterwards.
+    pub async fn drain(&self, grace_period: Duration) -> usize {
+        let open = self.connections.len().await;
+        if open == 0 {
+            return 0;
+        }
+
+        tracing::info!("Closing {} WebSocket connections", open);
+        self.broadcast(&ServerEvent::ServerShutdown(ShutdownNotice {
+            grace_period_secs: grace_period.as_secs(),
+        }))
+        .await;
+        self.connections.close_all();
+        self.connections.wait_closed(grace_period).await;
+        self.connections.len().await
+    }
+
+    /// Read access to the method registry
+    ///
+    /// Handlers never run under the lock, so a poisoned registry is still
+    /// consistent and is used as-is.
+    fn methods(&self) -> RwLockReadGuard<'_, HashMap<String, MethodHandler>> {
+        self.methods.read().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Write access to the method registry
+    fn methods_mut(&self) -> RwLockWriteGuard<'_, HashMap<String, MethodHandler>> {
+        self.methods.write().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Set who may call a method, in all its versions
+    ///
+    /// Methods without a policy of their own get the policy of their
+    /// namespace, and are public outside of namespaces with a policy.
+    pub fn set_method_policy(&self, name: &str, policy: MethodPolicy) {
+        self.policies
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+            .insert(name.to_string(), policy);
+    }
+
+    /// Namespace methods are registered under, e.g. `users` for `users.list`
+    pub fn namespace(&self, namespace: &str) -> MethodNamespace {
+        MethodNamespace::new(self.clone(), namespace)
+    }
+
+    /// Policy of a method (without version)
+    ///
+    /// The method's own policy wins over the policy of the innermost
+    /// namespace the method is in.
+    fn policy(&self, method: &str) -> MethodPolicy {
+        let policies = self.policies.read().unwrap_or_else(PoisonError::into_inner);
+        if let Some(policy) = policies.get(method) {
+            return *policy;
+        }
+        method
+            .rmatch_indices('.')
+            .find_map(|(dot, _)| policies.get(&format!("{}.*", &method[..dot])))
+            .copied()
+            .unwrap_or_default()
+    }
+
     /// Register a new method handler
     ///
     /// # Arguments
     /// * `name` - The method name
     /// * `handler` - The async function to handle this method
-    pub async fn register_method<F, Fut>(&self, name: String, handler: F)
+    pub fn register_method<F, Fut>(&self, name: String, handler: F)
     where
         F: Fn(Option<Value>) -> Fut + Send + Sync + 'static,
-        Fut: futures::future::Future<Output = Result<Value, JsonRpcErrorObject>> + Send + 'static,
+        Fut: futures::future::Future<Output = Result<Value, RpcError>> + Send + 'static,
+    {
+        self.register_method_with_context(name, move |params, _context| handler(params));
+    }
+
+    /// Register a new method handler that needs the connection context
+    ///
+    /// # Arguments
+    /// * `name` - The method name
+    /// * `handler` - The async function to handle this method
+    pub fn register_method_with_context<F, Fut>(&self, name: String, handler: F)
+    where
+        F: Fn(Option<Value>, RpcContext) -> Fut + Send + Sync + 'static,
+        Fut: futures::future::Future<Output = Result<Value, RpcError>> + Send + 'static,
+    {
+        let wrapped_handler = Arc::new(move |params: Option<Value>, context: RpcContext| {
+            let fut = handler(params, context);
+            Box::pin(fut) as futures::future::BoxFuture<'static, Result<Value, RpcError>>
+        });
+
+        self.methods_mut().insert(name, wrapped_handler);
+    }
+
+    /// Register a new method handler with typed params and result
+    ///
+    /// Params are deserialized into `P` before the handler runs, and params
+    /// that don't fit are rejected with `InvalidParams`. Missing params are
+    /// deserialized from `null`, so methods without params can take `()` or
+    /// an `Option`. The result is serialized from `R`.
+    ///
+    /// # Arguments
+    /// * `name` - The method name
+    /// * `handler` - The async function to handle this method
+    pub fn register_typed_method<P, R, F, Fut>(&self, name: String, handler: F)
+    where
+        P: DeserializeOwned,
+        R: Serialize,
+        F: Fn(P, RpcContext) -> Fut + Send + Sync + 'static,
+        Fut: futures::future::Future<Output = Result<R, RpcError>> + Send + 'static,
     {
-        let wrapped_handler = Arc::new(move |params: Option<Value>| {
-            let fut = handler(params);
-            Box::pin(fut) as futures::future::BoxFuture<'static, Result<Value, JsonRpcErrorObject>>
+        self.register_method_with_context(name, move |params, context| {
+            let call = serde_json::from_value::<P>(params.unwrap_or(Value::Null))
+                .map(|params| handler(params, context))
+                .map_err(|e| RpcError::InvalidParams(e.to_string()));
+            async move {
+                let result = call?.await?;
+                serde_json::to_value(result).map_err(|e| RpcError::Internal(e.to_string()))
+            }
         });
+    }
+
+    /// Register a specific version of a method
+    ///
+    /// The version is registered as `name@version`, next to the unversioned
+    /// method that clients get by default. Clients opt into newer versions by
+    /// calling the versioned name or through `method_versions` in `client.hello`.
+    ///
+    /// # Arguments
+    /// * `name` - The method name, without version
+    /// * `version` - The method version
+    /// * `handler` - The async function to handle this version
+    pub fn register_method_version<F, Fut>(&self, name: &str, version: u32, handler: F)
+    where
+        F: Fn(Option<Value>, RpcContext) -> Fut + Send + Sync + 'static,
+        Fut: futures::future::Future<Output = Result<Value, RpcError>> + Send + 'static,
+    {
+        self.register_method_with_context(format!("{}@{}", name, version), handler);
+    }
 
-        let mut methods = self.methods.write().await;
-        methods.insert(name, wrapped_handler);
+    /// Resolve the method version a request is dispatched to
+    ///
+    /// Explicitly versioned names (`posts.create@2`) are used as-is. Otherwise
+    /// the highest registered version not above the one the client requested
+    /// in `client.hello` is used, falling back to the unversioned method.
+    async fn resolve_method(&self, method: &str, context: &RpcContext) -> String {
+        if method.contains('@') {
+            return method.to_string();
+        }
+        let Some(requested) = context.requested_version(method).await else {
+            return method.to_string();
+        };
+
+        let prefix = format!("{}@", method);
+        self.methods()
+            .keys()
+            .filter_map(|name| name.strip_prefix(&prefix)?.parse::<u32>().ok())
+            .filter(|version| *version <= requested)
+            .max()
+            .map(|version| format!("{}{}", prefix, version))
+            .unwrap_or_else(|| method.to_string())
     }
 
     /// Process a JSON-RPC request
     ///
     /// # Arguments
     /// * `request` - The JSON-RPC request to process
+    /// * `context` - Context of the connection the request arrived on
     ///
     /// # Returns
     /// * `Some(response)` - For requests that expect a response
@@ -78,147 +386,233 @@ impl JsonRpcService {
     pub async fn handle_request(
         &self,
         request: JsonRpcRequest,
+        context: &RpcContext,
     ) -> Option<Result<JsonRpcResponse, JsonRpcErrorResponse>> {
         // Validate the request
         if let Err(e) = request.validate() {
-            let error_response = JsonRpcErrorResponse::custom(
-                JsonRpcErrorCode::InvalidRequest,
-                e,
+            let error_response = JsonRpcErrorResponse::new(
+                RpcError::InvalidRequest(e).into(),
                 request.id.clone().unwrap_or(Value::Null),
             );
             return Some(Err(error_response));
         }
 
-        // If it's a notification, don't send a response
-        if request.is_notification() {
-            // Still process it, but don't return a response
-            let methods = self.methods.read().await;
-            if let Some(handler) = methods.get(&request.method) {
-                let _ = handler(request.params).await;
-            }
-            return None;
-        }
-
+        // Notifications are processed, but never answered, not even with errors
+        let is_notification = request.is_notification();
         let id = request.id.clone().unwrap_or(Value::Null);
 
-        // Look up the method
-        let methods = self.methods.read().await;
-        let handler = match methods.get(&request.method) {
+        // Look up the method version negotiated with the client
+        let method = self.resolve_method(&request.method, context).await;
+        let handler = match self.methods().get(&method) {
             Some(h) => h.clone(),
+            None if is_notification => return None,
             None => {
-                let error_response = JsonRpcErrorResponse::custom(
-                    JsonRpcErrorCode::MethodNotFound,
-                    format!("Method '{}' not found", request.method),
+                let error_response = JsonRpcErrorResponse::new(
+                    RpcError::MethodNotFound(format!("Method '{}' not found", method)).into(),
                     id,
                 );
                 return Some(Err(error_response));
             }
         };
 
-        // Release the read lock before calling the handler
-        drop(methods);
+        // Check the auth policy of the method, whatever the version
+        let base_method = method.split('@').next().unwrap_or_default();
+        if let Err(err) = self
+            .policy(base_method)
+            .authorize(&request.method, context.identity.as_ref())
+        {
+            if is_notification {
+                return None;
+            }
+            return Some(Err(JsonRpcErrorResponse::new(err.into(), id)));
+        }
 
         // Execute the method handler
-        match handler(request.params).await {
+        let started_at = Instant::now();
+        let result = handler(request.params, context.clone()).await;
+        let tenant = context
+            .identity
+            .as_ref()
+            .and_then(UserIdentity::as_anonymous)
+            .map(|identifier| identifier.hospital_code.as_str());
+        self.metrics
+            .record(&method, tenant, started_at.elapsed(), result.is_err())
+            .await;
+
+        if is_notification {
+            return None;
+        }
+
+        match result {
             Ok(result) => Some(Ok(JsonRpcResponse::new(result, id))),
-            Err(error) => Some(Err(JsonRpcErrorResponse::new(error, id))),
+            Err(error) => Some(Err(JsonRpcErrorResponse::new(error.into(), id))),
         }
     }
 
     /// Register built-in methods that are always available
     fn register_builtin_methods(&self) {
-        let service = self.clone();
-
         // Echo method - returns the parameters sent
-        tokio::spawn(async move {
-            service
-                .register_method("echo".to_string(), |params| async move {
-                    Ok(params.unwrap_or(Value::Null))
-                })
-                .await;
+        self.register_method("echo".to_string(), |params| async move {
+            Ok(params.unwrap_or(Value::Null))
         });
 
-        let service = self.clone();
         // Ping method - simple health check
-        tokio::spawn(async move {
-            service
-                .register_method("ping".to_string(), |_params| async move {
-                    Ok(json!({"pong": true, "timestamp": chrono::Utc::now().timestamp()}))
-                })
-                .await;
+        self.register_method("ping".to_string(), |_params| async move {
+            Ok(json!({"pong": true, "timestamp": chrono::Utc::now().timestamp()}))
         });
 
-        let service = self.clone();
         // Add method - adds two numbers
-        tokio::spawn(async move {
-            service
-                .register_method("add".to_string(), |params| async move {
-                    let params = params.ok_or_else(|| {
-                        JsonRpcErrorObject::custom(
-                            JsonRpcErrorCode::InvalidParams,
-                            "Parameters required".to_string(),
-                            None,
-                        )
-                    })?;
-
-                    let numbers = params.as_array().ok_or_else(|| {
-                        JsonRpcErrorObject::custom(
-                            JsonRpcErrorCode::InvalidParams,
-                            "Parameters must be an array of numbers".to_string(),
-                            None,
-                        )
-                    })?;
-
-                    if numbers.len() != 2 {
-                        return Err(JsonRpcErrorObject::custom(
-                            JsonRpcErrorCode::InvalidParams,
-                            "Exactly two numbers required".to_string(),
-                            None,
-                        ));
-                    }
-
-                    let a = numbers[0].as_f64().ok_or_else(|| {
-                        JsonRpcErrorObject::custom(
-                            JsonRpcErrorCode::InvalidParams,
-                            "First parameter must be a number".to_string(),
-                            None,
-                        )
-                    })?;
-
-                    let b = numbers[1].as_f64().ok_or_else(|| {
-                        JsonRpcErrorObject::custom(
-                            JsonRpcErrorCode::InvalidParams,
-                            "Second parameter must be a number".to_string(),
-                            None,
-                        )
-                    })?;
-
-                    Ok(json!(a + b))
-                })
-                .await;
+        self.register_typed_method(
+            "add".to_string(),
+            |(a, b): (f64, f64), _context| async move { Ok(a + b) },
+        );
+
+        // Server info method - returns information about the server, with
+        // the methods and namespaces registered at the time of the call.
+        // The registry is held weakly, as it holds this handler.
+        let methods = Arc::downgrade(&self.methods);
+        self.register_method_with_context("getServerInfo".to_string(), move |_params, context| {
+            let (capabilities, namespaces) = capabilities(&methods);
+            async move {
+                Ok(json!({
+                    "name": "webboard",
+                    "version": env!("CARGO_PKG_VERSION"),
+                    "jsonrpc_version": "2.0",
+                    "capabilities": capabilities,
+                    "namespaces": namespaces,
+                    "client": context.client().await
+                }))
+            }
+        });
+
+        // Client hello method - stores client metadata on the connection
+        self.register_typed_method(
+            "client.hello".to_string(),
+            |client: ClientInfo, context| async move {
+                if client.name.trim().is_empty() {
+                    return Err(RpcError::InvalidParams(
+                        "Client name must not be empty".to_string(),
+                    ));
+                }
+
+                tracing::info!("Client hello from {} {}", client.name, client.version);
+                context.set_client(client).await;
+
+                Ok(json!({"accepted": true}))
+            },
+        );
+
+        // Subscribe method - receive notifications pushed by the server
+        let connections = self.connections.clone();
+        self.register_typed_method("subscribe".to_string(), move |params, context| {
+            let connections = connections.clone();
+            async move {
+                let (connection_id, events) = subscription_params(params, &context)?;
+                if !connections.subscribe(connection_id, &events).await {
+                    return Err(RpcError::InvalidRequest("Connection is closed".to_string()));
+                }
+                Ok(json!({"subscribed": events}))
+            }
+        });
+
+        // Unsubscribe method - stop receiving notifications
+        let connections = self.connections.clone();
+        self.register_typed_method("unsubscribe".to_string(), move |params, context| {
+            let connections = connections.clone();
+            async move {
+                let (connection_id, events) = subscription_params(params, &context)?;
+                if !connections.unsubscribe(connection_id, &events).await {
+                    return Err(RpcError::InvalidRequest("Connection is closed".to_string()));
+                }
+                Ok(json!({"unsubscribed": events}))
+            }
         });
 
-        let service = self.clone();
-        // Server info method - returns information about the server
-        tokio::spawn(async move {
-            service
-                .register_method("getServerInfo".to_string(), |_params| async move {
-                    Ok(json!({
-                        "name": "webboard",
-                        "version": env!("CARGO_PKG_VERSION"),
-                        "jsonrpc_version": "2.0",
-                        "capabilities": ["echo", "ping", "add", "getServerInfo"]
-                    }))
-                })
-                .await;
+        // Stats method - per-method metrics since startup (admins only)
+        let metrics = self.metrics.clone();
+        self.register_method("rpc.stats".to_string(), move |_params| {
+            let metrics = metrics.clone();
+            async move {
+                serde_json::to_value(metrics.snapshot().await)
+                    .map_err(|e| RpcError::Internal(e.to_string()))
+            }
+        });
+        self.set_method_policy("rpc.stats", MethodPolicy::Role(Role::Admin));
+
+        // Presence methods - verified users online (authenticated connections only)
+        let presence = self
+            .namespace("presence")
+            .with_policy(MethodPolicy::Authenticated);
+        let registry = self.presence.clone();
+        presence.register_typed_method("list", move |_: (), _context| {
+            let registry = registry.clone();
+            async move { Ok(registry.list()) }
         });
     }
 
     /// Get the list of registered methods
-    pub async fn list_methods(&self) -> Vec<String> {
-        let methods = self.methods.read().await;
-        methods.keys().cloned().collect()
+    pub fn list_methods(&self) -> Vec<String> {
+        self.methods().keys().cloned().collect()
+    }
+}
+
+/// Names of the registered methods, including versions such as
+/// `posts.create@2`, and of the namespaces they are in, both sorted
+fn capabilities(
+    methods: &Weak<RwLock<HashMap<String, MethodHandler>>>,
+) -> (Vec<String>, Vec<String>) {
+    let Some(methods) = methods.upgrade() else {
+        return (Vec::new(), Vec::new());
+    };
+    let methods: BTreeSet<String> = methods
+        .read()
+        .unwrap_or_else(PoisonError::into_inner)
+        .keys()
+        .cloned()
+        .collect();
+    let namespaces: BTreeSet<String> = methods
+        .iter()
+        .filter_map(|method| method.split('@').next()?.rsplit_once('.'))
+        .map(|(namespace, _)| namespace.to_string())
+        .collect();
+    (
+        methods.into_iter().collect(),
+        namespaces.into_iter().collect(),
+    )
+}
+
+/// Parameters of `subscribe` and `unsubscribe`
+#[derive(Debug, Deserialize)]
+struct SubscriptionParams {
+    /// Events to (un)subscribe, e.g. `["boards.post_created"]`
+    events: Vec<String>,
+}
+
+/// Check the parameters of `subscribe` and `unsubscribe`
+///
+/// Expects names of subscribable events on a connection that can receive
+/// notifications.
+fn subscription_params(
+    params: SubscriptionParams,
+    context: &RpcContext,
+) -> Result<(ConnectionId, Vec<String>), RpcError> {
+    let connection_id = context.connection_id.ok_or_else(|| {
+        RpcError::InvalidRequest("Connection cannot receive notifications".to_string())
+    })?;
+
+    if let Some(unknown) = params
+        .events
+        .iter()
+        .find(|event| !SUBSCRIBABLE_EVENTS.contains(&event.as_str()))
+    {
+        return Err(RpcError::InvalidParams(format!(
+            "Unknown event '{}', expected one of: {}",
+            unknown,
+            SUBSCRIBABLE_EVENTS.join(", ")
+        )));
     }
+
+    Ok((connection_id, params.events))
 }
 
 impl Default for JsonRpcService {
@@ -227,24 +621,63 @@ impl Default for JsonRpcService {
     }
 }
 
+/// Closes the connections on shutdown
+///
+/// Upgraded WebSocket connections are not awaited by the HTTP server, so
+/// they are drained once it stopped accepting requests.
+impl Service for JsonRpcService {
+    fn name(&self) -> &'static str {
+        "jsonrpc"
+    }
+
+    fn shutdown(&self) -> futures::future::BoxFuture<'_, ()> {
+        Box::pin(async move {
+            let still_open = self.drain(self.shutdown_grace).await;
+            if still_open > 0 {
+                tracing::warn!("{} WebSocket connections did not close in time", still_open);
+            }
+        })
+    }
+}
+
+/// Not ready once connections are being closed for a shutdown
+impl HealthCheck for JsonRpcService {
+    fn name(&self) -> &'static str {
+        "jsonrpc"
+    }
+
+    fn check(&self) -> futures::future::BoxFuture<'_, Result<(), String>> {
+        Box::pin(async move {
+            if self.connections.is_closing() {
+                Err("Closing connections for shutdown".to_string())
+            } else {
+                Ok(())
+            }
+        })
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::features::board::Post;
+    use crate::features::jsonrpc::application::events::PostCreated;
+    use crate::features::jsonrpc::domain::JsonRpcErrorCode;
+    use crate::features::users::domain::VerifiedUser;
 
     #[tokio::test]
     async fn test_echo_method() {
         let service = JsonRpcService::new();
 
-        // Give some time for builtin methods to register
-        tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
-
         let request = JsonRpcRequest::new(
             "echo".to_string(),
             Some(json!({"message": "hello"})),
             Some(json!(1)),
         );
 
-        let response = service.handle_request(request).await;
+        let response = service
+            .handle_request(request, &RpcContext::default())
+            .await;
         assert!(response.is_some());
 
         if let Some(Ok(resp)) = response {
@@ -252,17 +685,38 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_builtin_methods_registered_on_construction() {
+        let service = JsonRpcService::new();
+
+        let methods = service.list_methods();
+        for method in [
+            "echo",
+            "ping",
+            "add",
+            "getServerInfo",
+            "client.hello",
+            "subscribe",
+            "unsubscribe",
+            "rpc.stats",
+            "presence.list",
+        ] {
+            assert!(
+                methods.iter().any(|m| m == method),
+                "{method} not registered"
+            );
+        }
+    }
+
     #[tokio::test]
     async fn test_method_not_found() {
         let service = JsonRpcService::new();
 
-        let request = JsonRpcRequest::new(
-            "nonexistent_method".to_string(),
-            None,
-            Some(json!(1)),
-        );
+        let request = JsonRpcRequest::new("nonexistent_method".to_string(), None, Some(json!(1)));
 
-        let response = service.handle_request(request).await;
+        let response = service
+            .handle_request(request, &RpcContext::default())
+            .await;
         assert!(response.is_some());
 
         if let Some(Err(err)) = response {
@@ -270,6 +724,378 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn test_rpc_stats_requires_admin() {
+        let service = JsonRpcService::new();
+
+        let echo = JsonRpcRequest::new("echo".to_string(), None, Some(json!(1)));
+        service.handle_request(echo, &RpcContext::default()).await;
+
+        let stats = JsonRpcRequest::new("rpc.stats".to_string(), None, Some(json!(2)));
+        let user = |role| {
+            RpcContext::new(Some(UserIdentity::Verified(VerifiedUser {
+                id: 1,
+                username: "alice".to_string(),
+                email: "alice@example.com".to_string(),
+                role,
+            })))
+        };
+        for context in [RpcContext::default(), user(Role::Member)] {
+            let response = service.handle_request(stats.clone(), &context).await;
+            assert!(matches!(
+                response,
+                Some(Err(err)) if err.error.code == -32050
+            ));
+        }
+
+        let Some(Ok(response)) = service.handle_request(stats, &user(Role::Admin)).await else {
+            panic!("rpc.stats should succeed for admins");
+        };
+        assert_eq!(response.result["methods"]["echo"]["calls"], 1);
+    }
+
+    #[tokio::test]
+    async fn test_namespace_policies() {
+        let service = JsonRpcService::new();
+        let users = service
+            .namespace("users")
+            .with_policy(MethodPolicy::Role(Role::Admin));
+        users.register_method("list", |_params| async { Ok(json!([])) });
+        users.register_method("count", |_params| async { Ok(json!(0)) });
+        users.set_method_policy("count", MethodPolicy::Authenticated);
+
+        let user = |role| {
+            RpcContext::new(Some(UserIdentity::Verified(VerifiedUser {
+                id: 1,
+                username: "alice".to_string(),
+                email: "alice@example.com".to_string(),
+                role,
+            })))
+        };
+        let call = |method: &'static str, context: RpcContext| {
+            let service = service.clone();
+            let request = JsonRpcRequest::new(method.to_string(), None, Some(json!(1)));
+            async move {
+                match service.handle_request(request, &context).await {
+                    Some(Ok(_)) => Ok(()),
+                    Some(Err(err)) => Err(err.error.code),
+                    None => panic!("{} should be answered", method),
+                }
+            }
+        };
+
+        assert_eq!(call("users.list", RpcContext::default()).await, Err(-32050));
+        assert_eq!(call("users.list", user(Role::Member)).await, Err(-32050));
+        assert_eq!(call("users.list", user(Role::Admin)).await, Ok(()));
+        // The method's own policy overrides the namespace policy
+        assert_eq!(
+            call("users.count", RpcContext::default()).await,
+            Err(-32050)
+        );
+        assert_eq!(call("users.count", user(Role::Member)).await, Ok(()));
+    }
+
+    #[tokio::test]
+    async fn test_client_hello_echoed_in_server_info() {
+        let service = JsonRpcService::new();
+        let context = RpcContext::default();
+
+        let hello = JsonRpcRequest::new(
+            "client.hello".to_string(),
+            Some(json!({
+                "name": "webboard-ios",
+                "version": "2.1.0",
+                "capabilities": ["batch"],
+                "locale": "ko-KR"
+            })),
+            Some(json!(1)),
+        );
+        assert!(matches!(
+            service.handle_request(hello, &context).await,
+            Some(Ok(_))
+        ));
+        assert!(context.client_supports("batch").await);
+
+        let info = JsonRpcRequest::new("getServerInfo".to_string(), None, Some(json!(2)));
+        let Some(Ok(response)) = service.handle_request(info, &context).await else {
+            panic!("getServerInfo should succeed");
+        };
+        assert_eq!(response.result["client"]["name"], "webboard-ios");
+        assert_eq!(response.result["client"]["locale"], "ko-KR");
+
+        // Other connections don't see the metadata
+        let info = JsonRpcRequest::new("getServerInfo".to_string(), None, Some(json!(3)));
+        let Some(Ok(response)) = service.handle_request(info, &RpcContext::default()).await else {
+            panic!("getServerInfo should succeed");
+        };
+        assert!(response.result["client"].is_null());
+    }
+
+    #[tokio::test]
+    async fn test_server_info_lists_registered_methods() {
+        let service = JsonRpcService::new();
+        service
+            .namespace("users")
+            .register_typed_method(
+                "list",
+                |_: (), _context| async move { Ok(Vec::<u64>::new()) },
+            );
+        service.register_method_version("users.list", 2, |_params, _context| async move {
+            Ok(json!([]))
+        });
+
+        let info = JsonRpcRequest::new("getServerInfo".to_string(), None, Some(json!(1)));
+        let Some(Ok(response)) = service.handle_request(info, &RpcContext::default()).await else {
+            panic!("getServerInfo should succeed");
+        };
+        let capabilities: Vec<String> =
+            serde_json::from_value(response.result["capabilities"].clone()).unwrap();
+        let mut methods = service.list_methods();
+        methods.sort();
+        assert_eq!(capabilities, methods);
+        for method in [
+            "getServerInfo",
+            "rpc.stats",
+            "presence.list",
+            "users.list@2",
+        ] {
+            assert!(capabilities.iter().any(|m| m == method), "{method} missing");
+        }
+        assert_eq!(
+            response.result["namespaces"],
+            json!(["client", "presence", "rpc", "users"])
+        );
+    }
+
+    #[tokio::test]
+    async fn test_typed_method() {
+        #[derive(Deserialize)]
+        struct GreetParams {
+            name: String,
+        }
+
+        let service = JsonRpcService::new();
+        service.register_typed_method(
+            "greet".to_string(),
+            |params: GreetParams, context| async move {
+                Ok(json!({
+                    "greeting": format!("Hello, {}", params.name),
+                    "remote_addr": context.remote_addr,
+                }))
+            },
+        );
+
+        let context = RpcContext::default().with_remote_addr("10.0.0.7:50000".parse().unwrap());
+        let request = JsonRpcRequest::new(
+            "greet".to_string(),
+            Some(json!({"name": "Ada"})),
+            Some(json!(1)),
+        );
+        let Some(Ok(response)) = service.handle_request(request, &context).await else {
+            panic!("greet should succeed");
+        };
+        assert_eq!(response.result["greeting"], "Hello, Ada");
+        assert_eq!(response.result["remote_addr"], "10.0.0.7:50000");
+
+        // Params that don't fit the type are rejected before the handler runs
+        for params in [None, Some(json!({"name": 7}))] {
+            let request = JsonRpcRequest::new("greet".to_string(), params, Some(json!(2)));
+            assert!(matches!(
+                service.handle_request(request, &context).await,
+                Some(Err(err)) if err.error.code == RpcError::InvalidParams(String::new()).code()
+            ));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_method_version_negotiation() {
+        let service = JsonRpcService::new();
+        service.register_method("greet".to_string(), |_params| async move { Ok(json!(1)) });
+        for version in [2, 3] {
+            service.register_method_version(
+                "greet",
+                version,
+                move |_params, _context| async move { Ok(json!(version)) },
+            );
+        }
+
+        async fn call(service: &JsonRpcService, method: &str, context: &RpcContext) -> Value {
+            let request = JsonRpcRequest::new(method.to_string(), None, Some(json!(1)));
+            let response = service.handle_request(request, context).await;
+            response.unwrap().unwrap().result
+        }
+
+        // Clients without a hello get the unversioned method
+        let context = RpcContext::default();
+        assert_eq!(call(&service, "greet", &context).await, 1);
+        assert_eq!(call(&service, "greet@3", &context).await, 3);
+
+        // Clients get the highest version not above the one they announced
+        context
+            .set_client(ClientInfo {
+                name: "webboard-web".to_string(),
+                version: "1.4.0".to_string(),
+                capabilities: Vec::new(),
+                locale: None,
+                method_versions: [("greet".to_string(), 2)].into(),
+            })
+            .await;
+        assert_eq!(call(&service, "greet", &context).await, 2);
+
+        context
+            .set_client(ClientInfo {
+                name: "webboard-web".to_string(),
+                version: "1.5.0".to_string(),
+                capabilities: Vec::new(),
+                locale: None,
+                method_versions: [("greet".to_string(), 5)].into(),
+            })
+            .await;
+        assert_eq!(call(&service, "greet", &context).await, 3);
+    }
+
+    #[tokio::test]
+    async fn test_subscribe_and_notify() {
+        let service = JsonRpcService::new();
+        let (connection_id, mut notifications) = service.connections().register().await;
+        let context = RpcContext::default().with_connection(connection_id);
+
+        let subscribe = JsonRpcRequest::new(
+            "subscribe".to_string(),
+            Some(json!({"events": ["boards.post_created"]})),
+            Some(json!(1)),
+        );
+        assert!(matches!(
+            service.handle_request(subscribe.clone(), &context).await,
+            Some(Ok(_))
+        ));
+
+        // Only known events can be subscribed to
+        let unknown = JsonRpcRequest::new(
+            "subscribe".to_string(),
+            Some(json!({"events": ["boards.post_deleted"]})),
+            Some(json!(3)),
+        );
+        assert!(matches!(
+            service.handle_request(unknown, &context).await,
+            Some(Err(err)) if err.error.code == JsonRpcErrorCode::InvalidParams.code()
+        ));
+
+        // Connections outside the registry cannot subscribe
+        assert!(matches!(
+            service.handle_request(subscribe, &RpcContext::default()).await,
+            Some(Err(err)) if err.error.code == JsonRpcErrorCode::InvalidRequest.code()
+        ));
+
+        let post_created = ServerEvent::PostCreated(PostCreated {
+            board_id: 1,
+            thread_id: 2,
+            post: Post {
+                id: 7,
+                thread_id: 2,
+                body: "Hello".to_string(),
+                author_id: "user:1".to_string(),
+                author_name: None,
+                created_at: chrono::Utc::now(),
+            },
+        });
+        let shutdown = ServerEvent::ServerShutdown(ShutdownNotice {
+            grace_period_secs: 0,
+        });
+        assert_eq!(service.notify(&post_created).await, 1);
+        assert_eq!(service.notify(&shutdown).await, 0);
+
+        let notification: Value =
+            serde_json::from_str(&notifications.recv().await.unwrap()).unwrap();
+        assert_eq!(notification["method"], "boards.post_created");
+        assert_eq!(notification["params"]["post"]["id"], 7);
+
+        let unsubscribe = JsonRpcRequest::new(
+            "unsubscribe".to_string(),
+            Some(json!({"events": ["boards.post_created"]})),
+            Some(json!(2)),
+        );
+        service.handle_request(unsubscribe, &context).await;
+        assert_eq!(service.notify(&post_created).await, 0);
+        assert_eq!(service.broadcast(&shutdown).await, 1);
+    }
+
+    #[tokio::test]
+    async fn test_presence_changes_notified() {
+        let service = JsonRpcService::new();
+        let (watcher_id, mut notifications) = service.connections().register().await;
+        let subscribe = JsonRpcRequest::new(
+            "subscribe".to_string(),
+            Some(json!({"events": ["presence.changed"]})),
+            Some(json!(1)),
+        );
+        service
+            .handle_request(
+                subscribe,
+                &RpcContext::default().with_connection(watcher_id),
+            )
+            .await;
+
+        let alice = UserIdentity::Verified(VerifiedUser {
+            id: 1,
+            username: "alice".to_string(),
+            email: "alice@example.com".to_string(),
+            role: Role::Member,
+        });
+        let (phone, _phone_rx) = service.connect(Some(alice.clone())).await;
+        let (laptop, _laptop_rx) = service.connect(Some(alice.clone())).await;
+
+        let notification: Value =
+            serde_json::from_str(&notifications.recv().await.unwrap()).unwrap();
+        assert_eq!(notification["method"], "presence.changed");
+        assert_eq!(notification["params"]["status"], "online");
+
+        let list = JsonRpcRequest::new("presence.list".to_string(), None, Some(json!(2)));
+        assert!(matches!(
+            service.handle_request(list.clone(), &RpcContext::default()).await,
+            Some(Err(err)) if err.error.code == RpcError::Forbidden(String::new()).code()
+        ));
+        let Some(Ok(response)) = service
+            .handle_request(list, &RpcContext::new(Some(alice)))
+            .await
+        else {
+            panic!("presence.list should succeed for verified users");
+        };
+        assert_eq!(response.result[0]["username"], "alice");
+        assert_eq!(response.result[0]["connections"], 2);
+
+        // Only the last closed connection takes the user offline
+        service.disconnect(phone).await;
+        service.disconnect(laptop).await;
+        let notification: Value =
+            serde_json::from_str(&notifications.recv().await.unwrap()).unwrap();
+        assert_eq!(notification["params"]["status"], "offline");
+        assert!(notifications.try_recv().is_err());
+        assert!(service.presence().list().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_drain_closes_connections() {
+        let service = JsonRpcService::new();
+        assert_eq!(service.drain(std::time::Duration::ZERO).await, 0);
+
+        // A client that leaves once asked to close
+        let connections = service.connections().clone();
+        let (connection_id, mut notifications) = connections.register().await;
+        let mut closing = connections.closing();
+        let client = tokio::spawn(async move {
+            closing.wait_for(|closing| *closing).await.unwrap();
+            let notification = notifications.recv().await.unwrap();
+            connections.unregister(connection_id).await;
+            notification
+        });
+
+        assert_eq!(service.drain(std::time::Duration::from_secs(5)).await, 0);
+        let notification: Value = serde_json::from_str(&client.await.unwrap()).unwrap();
+        assert_eq!(notification["method"], "server.shutdown");
+        assert_eq!(notification["params"]["grace_period_secs"], 5);
+    }
+
     #[tokio::test]
     async fn test_notification_no_response() {
         let service = JsonRpcService::new();
@@ -280,7 +1106,9 @@ mod tests {
             None, // No ID = notification
         );
 
-        let response = service.handle_request(notification).await;
+        let response = service
+            .handle_request(notification, &RpcContext::default())
+            .await;
         assert!(response.is_none());
     }
 }
@@ -3,6 +3,9 @@ use serde_json::Value;
 
 use super::error_code::{JsonRpcErrorCode, JsonRpcErrorObject};
 
+/// System extensions provided by the server under the reserved `rpc.` prefix
+pub const SYSTEM_EXTENSIONS: &[&str] = &["rpc.stats"];
+
 /// JSON-RPC 2.0 Request
 ///
 /// A remote procedure call is represented by sending a Request object to a Server.
@@ -50,7 +53,7 @@ impl JsonRpcRequest {
             return Err("Method name cannot be empty".to_string());
         }
 
-        if self.method.starts_with("rpc.") {
+        if self.method.starts_with("rpc.") && !SYSTEM_EXTENSIONS.contains(&self.method.as_str()) {
             return Err("Method names starting with 'rpc.' are reserved".to_string());
         }
 
@@ -155,6 +158,9 @@ mod tests {
             Some(json!(1)),
         );
         assert!(reserved_method.validate().is_err());
+
+        let system_extension = JsonRpcRequest::new("rpc.stats".to_string(), None, Some(json!(1)));
+        assert!(system_extension.validate().is_ok());
     }
 
     #[test]
@@ -0,0 +1,218 @@
+use serde_json::{json, Value};
+use std::fmt;
+
+use crate::infrastructure::error::AppError;
+
+use super::error_code::{JsonRpcErrorCode, JsonRpcErrorObject};
+
+/// Range of implementation-defined error codes owned by one module
+///
+/// JSON-RPC 2.0 reserves -32000 to -32099 for implementation-defined server
+/// errors. The range is split between modules so codes never conflict; new
+/// codes must be added to the range of the module that returns them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ErrorCodeRange {
+    pub owner: &'static str,
+    /// Highest code of the range (closest to zero)
+    pub first: i32,
+    /// Lowest code of the range
+    pub last: i32,
+}
+
+impl ErrorCodeRange {
+    /// Check if a code belongs to this range
+    pub const fn contains(&self, code: i32) -> bool {
+        code <= self.first && code >= self.last
+    }
+}
+
+/// Generic server errors (-32000..-32009)
+pub const SERVER_ERRORS: ErrorCodeRange = ErrorCodeRange {
+    owner: "server",
+    first: -32000,
+    last: -32009,
+};
+
+/// WebSocket connection and transport errors (-32010..-32029)
+pub const CONNECTION_ERRORS: ErrorCodeRange = ErrorCodeRange {
+    owner: "connection",
+    first: -32010,
+    last: -32029,
+};
+
+/// Errors of individual RPC methods (-32030..-32049)
+pub const METHOD_ERRORS: ErrorCodeRange = ErrorCodeRange {
+    owner: "methods",
+    first: -32030,
+    last: -32049,
+};
+
+/// Authentication and authorization errors (-32050..-32099)
+pub const AUTH_ERRORS: ErrorCodeRange = ErrorCodeRange {
+    owner: "auth",
+    first: -32050,
+    last: -32099,
+};
+
+/// All registered error code ranges
+pub const ERROR_CODE_RANGES: &[ErrorCodeRange] =
+    &[SERVER_ERRORS, CONNECTION_ERRORS, METHOD_ERRORS, AUTH_ERRORS];
+
+/// Typed JSON-RPC error returned by method handlers and the dispatcher
+///
+/// Each variant maps to exactly one error code: either a standard code of
+/// the specification or a code from the range of the module owning it.
+#[derive(Debug, Clone, PartialEq)]
+pub enum RpcError {
+    /// Invalid JSON was received
+    Parse(String),
+    /// The message is not a valid Request object
+    InvalidRequest(String),
+    /// The method does not exist or is not available
+    MethodNotFound(String),
+    /// Invalid method parameter(s)
+    InvalidParams(String),
+    /// Internal error while handling the request
+    Internal(String),
+    /// The caller is not allowed to invoke the method
+    Forbidden(String),
+    /// The message exceeds the maximum message size of the connection
+    MessageTooLarge { size: usize, limit: usize },
+}
+
+impl RpcError {
+    /// Error code sent to the client
+    pub fn code(&self) -> i32 {
+        match self {
+            RpcError::Parse(_) => JsonRpcErrorCode::ParseError.code(),
+            RpcError::InvalidRequest(_) => JsonRpcErrorCode::InvalidRequest.code(),
+            RpcError::MethodNotFound(_) => JsonRpcErrorCode::MethodNotFound.code(),
+            RpcError::InvalidParams(_) => JsonRpcErrorCode::InvalidParams.code(),
+            RpcError::Internal(_) => JsonRpcErrorCode::InternalError.code(),
+            RpcError::Forbidden(_) => -32050,
+            RpcError::MessageTooLarge { .. } => -32010,
+        }
+    }
+
+    /// Range the error code is taken from, `None` for standard codes
+    pub fn range(&self) -> Option<&'static ErrorCodeRange> {
+        match self {
+            RpcError::Forbidden(_) => Some(&AUTH_ERRORS),
+            RpcError::MessageTooLarge { .. } => Some(&CONNECTION_ERRORS),
+            _ => None,
+        }
+    }
+
+    fn message(&self) -> &str {
+        match self {
+            RpcError::Parse(msg)
+            | RpcError::InvalidRequest(msg)
+            | RpcError::MethodNotFound(msg)
+            | RpcError::InvalidParams(msg)
+            | RpcError::Internal(msg)
+            | RpcError::Forbidden(msg) => msg,
+            RpcError::MessageTooLarge { .. } => "Message too large",
+        }
+    }
+
+    /// Additional structured information sent in the error `data` member
+    fn data(&self) -> Option<Value> {
+        match self {
+            RpcError::MessageTooLarge { size, limit } => {
+                Some(json!({"size": size, "limit": limit}))
+            }
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for RpcError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} ({})", self.message(), self.code())
+    }
+}
+
+impl std::error::Error for RpcError {}
+
+/// Error of an application service called by a method handler
+///
+/// Rejected callers stay forbidden and errors about the request become
+/// invalid params. Details of internal errors are logged, not sent.
+impl From<AppError> for RpcError {
+    fn from(error: AppError) -> Self {
+        match &error {
+            AppError::Unauthorized(msg) | AppError::Forbidden(msg) => {
+                RpcError::Forbidden(msg.clone())
+            }
+            AppError::NotFound(_)
+            | AppError::BadRequest(_)
+            | AppError::UnprocessableEntity(_)
+            | AppError::Conflict { .. }
+            | AppError::Validation(_) => RpcError::InvalidParams(error.to_string()),
+            AppError::InternalError(msg) => {
+                tracing::error!("Internal error: {}", msg);
+                RpcError::Internal("Internal server error".to_string())
+            }
+            AppError::TooManyRequests(_) | AppError::ServiceUnavailable(_) => {
+                RpcError::Internal(error.to_string())
+            }
+        }
+    }
+}
+
+impl From<RpcError> for JsonRpcErrorObject {
+    fn from(error: RpcError) -> Self {
+        Self {
+            code: error.code(),
+            message: error.message().to_string(),
+            data: error.data(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ranges_do_not_overlap() {
+        for (i, range) in ERROR_CODE_RANGES.iter().enumerate() {
+            assert!(range.first >= range.last);
+            assert!(range.first <= -32000 && range.last >= -32099);
+
+            for other in &ERROR_CODE_RANGES[i + 1..] {
+                assert!(!range.contains(other.first) && !range.contains(other.last));
+            }
+        }
+    }
+
+    #[test]
+    fn test_codes_are_within_their_range() {
+        let errors = [
+            RpcError::Parse(String::new()),
+            RpcError::InvalidRequest(String::new()),
+            RpcError::MethodNotFound(String::new()),
+            RpcError::InvalidParams(String::new()),
+            RpcError::Internal(String::new()),
+            RpcError::Forbidden(String::new()),
+            RpcError::MessageTooLarge { size: 0, limit: 0 },
+        ];
+
+        for error in errors {
+            match error.range() {
+                Some(range) => assert!(range.contains(error.code()), "{}", error),
+                None => assert!(!(-32099..=-32000).contains(&error.code()), "{}", error),
+            }
+        }
+    }
+
+    #[test]
+    fn test_into_error_object() {
+        let forbidden = RpcError::Forbidden("Admins only".to_string());
+        assert_eq!(forbidden.range(), Some(&AUTH_ERRORS));
+
+        let error: JsonRpcErrorObject = forbidden.into();
+        assert_eq!(error.code, -32050);
+        assert_eq!(error.message, "Admins only");
+    }
+}
@@ -1,72 +1,86 @@
-/// JSON-RPC Feature Module
-///
-/// Implements WebSocket-based JSON-RPC 2.0 protocol for real-time bidirectional communication.
-///
-/// ## Architecture
-///
-/// This module follows clean architecture with three distinct layers:
-///
-/// ### Domain Layer (`domain/`)
-/// - `message`: Request, Response, Error message types
-/// - `error_code`: Standard JSON-RPC error codes and error objects
-/// - Protocol validation and business rules
-/// - No external dependencies
-///
-/// ### Application Layer (`application/`)
-/// - `service`: JSON-RPC service with method registry
-/// - Business logic orchestration
-/// - Method registration and dispatching
-/// - Request/response handling
-///
-/// ### Presentation Layer (`presentation/`)
-/// - `handler`: WebSocket connection handler
-/// - HTTP upgrade handling
-/// - Message serialization/deserialization
-/// - Connection lifecycle management
-///
-/// ## Usage
-///
-/// ```rust
-/// use features::jsonrpc;
-///
-/// // Initialize service
-/// let jsonrpc_service = jsonrpc::JsonRpcService::new();
-///
-/// // Register custom method
-/// jsonrpc_service.register_method("myMethod".to_string(), |params| async move {
-///     // Your logic here
-///     Ok(json!({"result": "success"}))
-/// }).await;
-///
-/// // Add WebSocket route
-/// Router::new()
-///     .route("/live", get(jsonrpc::websocket_handler))
-///     .with_state(jsonrpc_service)
-/// ```
-///
-/// ## Built-in Methods
-///
-/// - `ping`: Health check with timestamp
-/// - `echo`: Echo back parameters
-/// - `add`: Add two numbers
-/// - `getServerInfo`: Get server information
-///
-/// ## Protocol
-///
-/// Implements JSON-RPC 2.0 specification:
-/// - Request/Response pattern
-/// - Notifications (one-way messages)
-/// - Standard error codes
-/// - Parameter validation
+//! JSON-RPC Feature Module
+//!
+//! Implements WebSocket-based JSON-RPC 2.0 protocol for real-time bidirectional communication.
+//!
+//! ## Architecture
+//!
+//! This module follows clean architecture with three distinct layers:
+//!
+//! ### Domain Layer (`domain/`)
+//! - `message`: Request, Response, Error message types
+//! - `error_code`: Standard JSON-RPC error codes and error objects
+//! - `rpc_error`: Typed `RpcError` returned by handlers, with per-module code ranges
+//! - Protocol validation and business rules
+//! - No external dependencies
+//!
+//! ### Application Layer (`application/`)
+//! - `service`: JSON-RPC service with method registry
+//! - `context`: Per-connection context (authenticated identity, client metadata)
+//! - `connections`: Open connections and their subscriptions, for pushing notifications
+//! - `metrics`: Per-method call counts, error rates and latencies
+//! - `presence`: Verified users online, counted per open connection
+//! - Business logic orchestration
+//! - Method registration and dispatching
+//! - Request/response handling
+//!
+//! ### Presentation Layer (`presentation/`)
+//! - `handler`: WebSocket connection handler
+//! - HTTP upgrade handling
+//! - Message serialization/deserialization
+//! - Connection lifecycle management
+//!
+//! ## Usage
+//!
+//! ```rust,ignore
+//! use features::jsonrpc;
+//!
+//! // Initialize service
+//! let jsonrpc_service = jsonrpc::JsonRpcService::new();
+//!
+//! // Register custom method
+//! jsonrpc_service.register_method("myMethod".to_string(), |params| async move {
+//!     // Your logic here
+//!     Ok(json!({"result": "success"}))
+//! });
+//!
+//! // Add WebSocket route
+//! Router::new()
+//!     .route("/live", get(jsonrpc::websocket_handler))
+//!     .with_state(jsonrpc_service)
+//! ```
+//!
+//! ## Built-in Methods
+//!
+//! - `ping`: Health check with timestamp
+//! - `echo`: Echo back parameters
+//! - `add`: Add two numbers
+//! - `getServerInfo`: Get server information
+//! - `client.hello`: Announce client name, version, capabilities and locale
+//! - `subscribe` / `unsubscribe`: Manage subscriptions to server notifications
+//! - `rpc.stats`: Per-method metrics since startup (admins only)
+//! - `presence.list`: Verified users currently connected (authenticated connections only)
+//!
+//! ## Protocol
+//!
+//! Implements JSON-RPC 2.0 specification:
+//! - Request/Response pattern
+//! - Notifications (one-way messages), in both directions
+//! - Batch requests, dispatched concurrently
+//! - Standard error codes
+//! - Parameter validation
 
 pub mod application;
 pub mod domain;
 pub mod presentation;
 
 // Re-export commonly used types for convenience
-pub use application::JsonRpcService;
+pub use application::{
+    ClientInfo, ConnectionId, ConnectionRegistry, JsonRpcService, MethodNamespace, MethodPolicy,
+    MetricLabels, OnlineUser, PostCreated, PresenceChange, PresenceRegistry, PresenceStatus,
+    RpcContext, ServerEvent, ShutdownNotice,
+};
 pub use domain::{
     JsonRpcErrorCode, JsonRpcErrorObject, JsonRpcErrorResponse, JsonRpcMessage, JsonRpcRequest,
-    JsonRpcResponse,
+    JsonRpcResponse, RpcError,
 };
-pub use presentation::websocket_handler;
+pub use presentation::{list_event_schemas, list_presence, websocket_handler};
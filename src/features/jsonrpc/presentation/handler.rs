@@ -1,15 +1,27 @@
 use axum::{
     extract::{
-        ws::{Message, WebSocket, WebSocketUpgrade},
-        State,
+        ws::{close_code, CloseFrame, Message, WebSocket, WebSocketUpgrade},
+        ConnectInfo, State,
     },
     response::Response,
+    Json,
 };
 use futures::{SinkExt, StreamExt};
 use serde_json::Value;
+use std::net::SocketAddr;
 
-use super::super::application::JsonRpcService;
-use super::super::domain::{JsonRpcErrorCode, JsonRpcErrorResponse, JsonRpcRequest};
+use crate::features::auth::AuthenticatedUser;
+
+use super::super::application::{
+    event_schemas, EventSchema, JsonRpcService, OnlineUser, RpcContext,
+};
+use super::super::domain::{JsonRpcErrorResponse, JsonRpcMessage, JsonRpcRequest, RpcError};
+
+/// WebSocket subprotocol selected for clients that offer it
+///
+/// Browsers passing their token as a `bearer.<token>` subprotocol must offer
+/// this one too, as they require the server to select one of theirs.
+pub const JSONRPC_PROTOCOL: &str = "jsonrpc";
 
 /// WebSocket handler for the /live endpoint
 ///
@@ -19,8 +31,22 @@ use super::super::domain::{JsonRpcErrorCode, JsonRpcErrorResponse, JsonRpcReques
 /// # Route
 /// WebSocket: ws://127.0.0.1:3000/live
 ///
+/// # Authentication
+/// Optional. A token on the upgrade request identifies the connection, which
+/// admin-only methods require. It is sent as `Authorization` header, as
+/// `access_token` query parameter or as `bearer.<token>` subprotocol next to
+/// `jsonrpc` (see `websocket_auth_middleware`). Method handlers get the
+/// identity from `RpcContext::identity` and the client address from
+/// `RpcContext::remote_addr`.
+///
 /// # Protocol
-/// JSON-RPC 2.0 over WebSocket
+/// JSON-RPC 2.0 over WebSocket, including batch requests. The WebSocket
+/// transport drops connections sending messages or frames larger than the
+/// configured maximum message size before buffering them; larger messages
+/// reaching `process_message` are answered with a `-32010` error.
+/// The server pushes notifications for the events the client subscribed to.
+/// On shutdown, the server sends a `server.shutdown` notification followed by
+/// a Close frame with code 1001 (going away).
 ///
 /// # Example
 /// ```json
@@ -33,27 +59,126 @@ use super::super::domain::{JsonRpcErrorCode, JsonRpcErrorResponse, JsonRpcReques
 pub async fn websocket_handler(
     ws: WebSocketUpgrade,
     State(jsonrpc_service): State<JsonRpcService>,
+    user: Option<AuthenticatedUser>,
+    connect_info: Option<ConnectInfo<SocketAddr>>,
 ) -> Response {
-    ws.on_upgrade(|socket| handle_socket(socket, jsonrpc_service))
+    let mut context = RpcContext::new(user.map(|user| user.0));
+    if let Some(ConnectInfo(remote_addr)) = connect_info {
+        context = context.with_remote_addr(remote_addr);
+    }
+    let limit = jsonrpc_service.max_message_size();
+    ws.protocols([JSONRPC_PROTOCOL])
+        .max_message_size(limit)
+        .max_frame_size(limit)
+        .on_upgrade(|socket| handle_socket(socket, jsonrpc_service, context))
+}
+
+/// List event schemas handler
+///
+/// JSON Schemas of the data of the events pushed to WebSocket clients and
+/// webhooks, with the schema version every published event carries.
+///
+/// # Route
+/// GET /api/v1/events/schemas
+///
+/// # Response
+/// ```json
+/// [
+///   {
+///     "event": "server.shutdown",
+///     "schema_version": 1,
+///     "schema": {
+///       "$schema": "http://json-schema.org/draft-07/schema#",
+///       "title": "ShutdownNotice",
+///       "type": "object",
+///       "required": ["grace_period_secs"],
+///       "properties": {"grace_period_secs": {"type": "integer", "format": "uint64", "minimum": 0.0}}
+///     }
+///   }
+/// ]
+/// ```
+pub async fn list_event_schemas() -> Json<Vec<EventSchema>> {
+    Json(event_schemas())
+}
+
+/// List online users handler
+///
+/// Verified users with at least one open WebSocket connection, by username.
+/// Clients follow changes by subscribing to `presence.changed`.
+///
+/// # Route
+/// GET /api/v1/presence
+///
+/// # Response
+/// ```json
+/// [
+///   {
+///     "user_id": 1,
+///     "username": "alice",
+///     "connections": 2,
+///     "online_since": "2024-01-01T08:00:00Z"
+///   }
+/// ]
+/// ```
+pub async fn list_presence(State(jsonrpc_service): State<JsonRpcService>) -> Json<Vec<OnlineUser>> {
+    Json(jsonrpc_service.presence().list())
 }
 
 /// Handle an individual WebSocket connection
 ///
-/// Processes incoming JSON-RPC messages and sends responses back.
+/// Processes incoming JSON-RPC messages and sends responses back, and
+/// forwards notifications pushed by the server to the client.
 /// Each connection is handled independently with its own task.
-async fn handle_socket(socket: WebSocket, jsonrpc_service: JsonRpcService) {
+async fn handle_socket(socket: WebSocket, jsonrpc_service: JsonRpcService, context: RpcContext) {
     let (mut sender, mut receiver) = socket.split();
 
-    tracing::info!("New WebSocket connection established");
+    // Register the connection so notifications can be pushed to it
+    let (connection_id, mut notifications) =
+        jsonrpc_service.connect(context.identity.clone()).await;
+    let context = context.with_connection(connection_id);
+    let mut closing = jsonrpc_service.connections().closing();
+
+    tracing::info!("New WebSocket connection {} established", connection_id);
 
-    // Process incoming messages
-    while let Some(msg) = receiver.next().await {
+    loop {
+        let msg = tokio::select! {
+            msg = receiver.next() => match msg {
+                Some(msg) => msg,
+                None => break,
+            },
+            // The registry keeps the sender until the connection unregisters
+            Some(notification) = notifications.recv() => {
+                if let Err(e) = sender.send(Message::Text(notification)).await {
+                    tracing::error!("Failed to send notification: {}", e);
+                    break;
+                }
+                continue;
+            }
+            // The server is shutting down: flush queued messages, then close
+            _ = async { closing.wait_for(|closing| *closing).await.map(|_| ()) } => {
+                while let Ok(notification) = notifications.try_recv() {
+                    if sender.send(Message::Text(notification)).await.is_err() {
+                        break;
+                    }
+                }
+                let close = CloseFrame {
+                    code: close_code::AWAY,
+                    reason: "Server shutting down".into(),
+                };
+                if let Err(e) = sender.send(Message::Close(Some(close))).await {
+                    tracing::debug!("Failed to send close frame: {}", e);
+                }
+                break;
+            }
+        };
+
+        // Process incoming messages
         match msg {
             Ok(Message::Text(text)) => {
                 tracing::debug!("Received message: {}", text);
 
                 // Process the JSON-RPC request
-                match process_message(&text, &jsonrpc_service).await {
+                match process_message(&text, &jsonrpc_service, &context).await {
                     Some(response) => {
                         // Send response back to client
                         if let Err(e) = sender.send(Message::Text(response)).await {
@@ -95,7 +220,8 @@ async fn handle_socket(socket: WebSocket, jsonrpc_service: JsonRpcService) {
         }
     }
 
-    tracing::info!("WebSocket connection closed");
+    jsonrpc_service.disconnect(connection_id).await;
+    tracing::info!("WebSocket connection {} closed", connection_id);
 }
 
 /// Process a JSON-RPC message
@@ -103,23 +229,48 @@ async fn handle_socket(socket: WebSocket, jsonrpc_service: JsonRpcService) {
 /// # Arguments
 /// * `text` - The raw JSON text from the client
 /// * `jsonrpc_service` - The JSON-RPC service to handle the request
+/// * `context` - Context of the connection the message arrived on
 ///
 /// # Returns
 /// * `Some(String)` - A JSON response to send back to the client
 /// * `None` - For notifications that don't require a response
-async fn process_message(text: &str, jsonrpc_service: &JsonRpcService) -> Option<String> {
-    // Parse the JSON-RPC request
-    let request: JsonRpcRequest = match serde_json::from_str(text) {
-        Ok(req) => req,
+async fn process_message(
+    text: &str,
+    jsonrpc_service: &JsonRpcService,
+    context: &RpcContext,
+) -> Option<String> {
+    // Reject oversized messages before parsing them
+    let limit = jsonrpc_service.max_message_size();
+    if text.len() > limit {
+        tracing::warn!("Rejected message of {} bytes (limit {})", text.len(), limit);
+        jsonrpc_service.metrics().record_rejected_frame();
+        return Some(create_message_too_large_error(text.len(), limit));
+    }
+
+    // Parse the message; an array is a batch of requests
+    let message: Value = match serde_json::from_str(text) {
+        Ok(message) => message,
         Err(e) => {
             tracing::warn!("Failed to parse JSON-RPC request: {}", e);
             let error = create_parse_error(format!("Invalid JSON: {}", e));
             return Some(error);
         }
     };
+    if let Value::Array(entries) = message {
+        return process_batch(entries, jsonrpc_service, context).await;
+    }
+
+    // Valid JSON that is not a request object, e.g. `1`, is an invalid request
+    let request: JsonRpcRequest = match serde_json::from_value(message) {
+        Ok(req) => req,
+        Err(e) => {
+            tracing::warn!("Invalid JSON-RPC request: {}", e);
+            return Some(create_invalid_request_error(e.to_string()));
+        }
+    };
 
     // Handle the request
-    let response = jsonrpc_service.handle_request(request).await;
+    let response = jsonrpc_service.handle_request(request, context).await;
 
     // Convert response to JSON string
     response.map(|result| match result {
@@ -134,21 +285,89 @@ async fn process_message(text: &str, jsonrpc_service: &JsonRpcService) -> Option
     })
 }
 
+/// Process a batch of JSON-RPC requests
+///
+/// Entries are dispatched concurrently. As required by the specification, an
+/// empty batch is answered with a single Invalid Request error, invalid
+/// entries get an Invalid Request error each, and notifications get no entry
+/// in the response array. Nothing is sent back if every entry is a notification.
+async fn process_batch(
+    entries: Vec<Value>,
+    jsonrpc_service: &JsonRpcService,
+    context: &RpcContext,
+) -> Option<String> {
+    if entries.is_empty() {
+        return Some(create_invalid_request_error("Empty batch".to_string()));
+    }
+
+    let responses = futures::future::join_all(entries.into_iter().map(|entry| async move {
+        let request: JsonRpcRequest = match serde_json::from_value(entry) {
+            Ok(req) => req,
+            Err(e) => {
+                let error = JsonRpcErrorResponse::new(
+                    RpcError::InvalidRequest(e.to_string()).into(),
+                    Value::Null,
+                );
+                return Some(JsonRpcMessage::Error(error));
+            }
+        };
+
+        jsonrpc_service
+            .handle_request(request, context)
+            .await
+            .map(|result| match result {
+                Ok(success) => JsonRpcMessage::Response(success),
+                Err(error) => JsonRpcMessage::Error(error),
+            })
+    }))
+    .await;
+
+    let responses: Vec<JsonRpcMessage> = responses.into_iter().flatten().collect();
+    if responses.is_empty() {
+        return None;
+    }
+
+    Some(serde_json::to_string(&responses).unwrap_or_else(|e| {
+        tracing::error!("Failed to serialize batch response: {}", e);
+        create_internal_error()
+    }))
+}
+
 /// Create a parse error response
 fn create_parse_error(message: String) -> String {
-    let error = JsonRpcErrorResponse::custom(
-        JsonRpcErrorCode::ParseError,
-        message,
+    let error = JsonRpcErrorResponse::new(RpcError::Parse(message).into(), Value::Null);
+    serde_json::to_string(&error).unwrap_or_else(|_| {
+        r#"{"jsonrpc":"2.0","error":{"code":-32700,"message":"Parse error"},"id":null}"#.to_string()
+    })
+}
+
+/// Create an invalid request error response
+fn create_invalid_request_error(message: String) -> String {
+    let error = JsonRpcErrorResponse::new(RpcError::InvalidRequest(message).into(), Value::Null);
+    serde_json::to_string(&error).unwrap_or_else(|_| {
+        r#"{"jsonrpc":"2.0","error":{"code":-32600,"message":"Invalid Request"},"id":null}"#
+            .to_string()
+    })
+}
+
+/// Create a message too large error response
+fn create_message_too_large_error(size: usize, limit: usize) -> String {
+    let error = JsonRpcErrorResponse::new(
+        RpcError::MessageTooLarge { size, limit }.into(),
         Value::Null,
     );
     serde_json::to_string(&error).unwrap_or_else(|_| {
-        r#"{"jsonrpc":"2.0","error":{"code":-32700,"message":"Parse error"},"id":null}"#.to_string()
+        r#"{"jsonrpc":"2.0","error":{"code":-32010,"message":"Message too large"},"id":null}"#
+            .to_string()
     })
 }
 
 /// Create an internal error response
 fn create_internal_error() -> String {
-    let error = JsonRpcErrorResponse::from_code(JsonRpcErrorCode::InternalError, Value::Null);
+    let error = JsonRpcErrorResponse::new(
+        RpcError::Internal("Internal error".to_string()).into(),
+        Value::Null,
+    );
     serde_json::to_string(&error).unwrap_or_else(|_| {
         r#"{"jsonrpc":"2.0","error":{"code":-32603,"message":"Internal error"},"id":null}"#
             .to_string()
@@ -158,18 +377,14 @@ fn create_internal_error() -> String {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use serde_json::json;
 
     #[tokio::test]
     async fn test_process_valid_request() {
         let service = JsonRpcService::new();
 
-        // Give time for builtin methods to register
-        tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
-
         let request = r#"{"jsonrpc":"2.0","method":"echo","params":{"test":"value"},"id":1}"#;
 
-        let response = process_message(request, &service).await;
+        let response = process_message(request, &service, &RpcContext::default()).await;
         assert!(response.is_some());
 
         if let Some(resp) = response {
@@ -178,13 +393,69 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn test_process_batch() {
+        let service = JsonRpcService::new();
+
+        let request = r#"[
+            {"jsonrpc":"2.0","method":"add","params":[1,2],"id":1},
+            {"jsonrpc":"2.0","method":"echo","params":"notify"},
+            {"jsonrpc":"2.0","method":"nonexistent","id":2},
+            {"foo":"bar"}
+        ]"#;
+
+        let response = process_message(request, &service, &RpcContext::default())
+            .await
+            .unwrap();
+        let responses: Vec<Value> = serde_json::from_str(&response).unwrap();
+        assert_eq!(responses.len(), 3);
+        assert_eq!(responses[0]["result"], 3.0);
+        assert_eq!(responses[1]["error"]["code"], -32601);
+        assert_eq!(responses[2]["error"]["code"], -32600);
+        assert!(responses[2]["id"].is_null());
+    }
+
+    #[tokio::test]
+    async fn test_process_batch_edge_cases() {
+        let service = JsonRpcService::new();
+        let context = RpcContext::default();
+
+        // An empty batch is a single invalid request
+        let response = process_message("[]", &service, &context).await.unwrap();
+        let response: Value = serde_json::from_str(&response).unwrap();
+        assert_eq!(response["error"]["code"], -32600);
+
+        // A batch of notifications gets no response at all
+        let notifications =
+            r#"[{"jsonrpc":"2.0","method":"echo"},{"jsonrpc":"2.0","method":"ping"}]"#;
+        assert!(process_message(notifications, &service, &context)
+            .await
+            .is_none());
+    }
+
+    #[tokio::test]
+    async fn test_process_oversized_message() {
+        let service = JsonRpcService::new().with_max_message_size(64);
+
+        let request = format!(
+            r#"{{"jsonrpc":"2.0","method":"echo","params":"{}","id":1}}"#,
+            "x".repeat(64)
+        );
+
+        let response = process_message(&request, &service, &RpcContext::default())
+            .await
+            .unwrap();
+        assert!(response.contains("-32010"));
+        assert_eq!(service.metrics().snapshot().await.rejected_frames, 1);
+    }
+
     #[tokio::test]
     async fn test_process_invalid_json() {
         let service = JsonRpcService::new();
 
         let request = r#"{"invalid json"#;
 
-        let response = process_message(request, &service).await;
+        let response = process_message(request, &service, &RpcContext::default()).await;
         assert!(response.is_some());
 
         if let Some(resp) = response {
@@ -193,15 +464,28 @@ mod tests {
     }
 
     #[tokio::test]
-    async fn test_process_notification() {
+    async fn test_process_invalid_request() {
         let service = JsonRpcService::new();
 
-        tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
+        // Valid JSON that is not a request object
+        for request in ["1", r#""x""#, "null", r#"{"foo":"bar"}"#] {
+            let response = process_message(request, &service, &RpcContext::default())
+                .await
+                .unwrap();
+            let response: Value = serde_json::from_str(&response).unwrap();
+            assert_eq!(response["error"]["code"], -32600, "{}", request);
+            assert!(response["id"].is_null());
+        }
+    }
+
+    #[tokio::test]
+    async fn test_process_notification() {
+        let service = JsonRpcService::new();
 
         // Notification has no id
         let request = r#"{"jsonrpc":"2.0","method":"echo","params":{"test":"value"}}"#;
 
-        let response = process_message(request, &service).await;
+        let response = process_message(request, &service, &RpcContext::default()).await;
         // Notifications should not return a response
         assert!(response.is_none());
     }
@@ -1,18 +1,18 @@
-/// JSON-RPC Presentation Layer
-///
-/// Contains HTTP and WebSocket handlers for JSON-RPC communication.
-///
-/// ## Components
-/// - `handler`: WebSocket connection and message handling
-///
-/// ## Responsibilities
-/// - Handle WebSocket protocol (upgrade, ping/pong, close)
-/// - Parse incoming messages
-/// - Serialize outgoing messages
-/// - Manage connection lifecycle
-/// - Handle protocol errors
+//! JSON-RPC Presentation Layer
+//!
+//! Contains HTTP and WebSocket handlers for JSON-RPC communication.
+//!
+//! ## Components
+//! - `handler`: WebSocket connection and message handling, event schemas, presence
+//!
+//! ## Responsibilities
+//! - Handle WebSocket protocol (upgrade, ping/pong, close)
+//! - Parse incoming messages
+//! - Serialize outgoing messages
+//! - Manage connection lifecycle
+//! - Handle protocol errors
 
 pub mod handler;
 
 // Re-export commonly used types
-pub use handler::websocket_handler;
+pub use handler::{list_event_schemas, list_presence, websocket_handler};
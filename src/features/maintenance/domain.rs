@@ -0,0 +1,168 @@
+use chrono::{DateTime, Duration, Utc};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// Default lead time of the maintenance banner
+pub const DEFAULT_ANNOUNCE_BEFORE_MINUTES: i64 = 60;
+
+/// Scheduled maintenance window
+///
+/// From `announce_at` a banner pre-announces the window; between
+/// `starts_at` and `ends_at` the server is in maintenance mode.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct MaintenanceWindow {
+    pub id: u64,
+    pub message: String,
+    pub announce_at: DateTime<Utc>,
+    pub starts_at: DateTime<Utc>,
+    pub ends_at: DateTime<Utc>,
+    /// Announcement banner published for the window
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub announcement_id: Option<u64>,
+    pub created_by: String,
+    pub created_at: DateTime<Utc>,
+}
+
+impl MaintenanceWindow {
+    /// Phase of the window at the given time
+    pub fn phase_at(&self, now: DateTime<Utc>) -> MaintenancePhase {
+        if now >= self.ends_at {
+            MaintenancePhase::Ended
+        } else if now >= self.starts_at {
+            MaintenancePhase::Active
+        } else if now >= self.announce_at {
+            MaintenancePhase::Announced
+        } else {
+            MaintenancePhase::Scheduled
+        }
+    }
+}
+
+/// Phase of a maintenance window, in chronological order
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MaintenancePhase {
+    /// Not announced yet
+    Scheduled,
+    /// Announced to clients, not started yet
+    Announced,
+    /// Maintenance mode is on
+    Active,
+    Ended,
+}
+
+/// Request payload for scheduling a maintenance window
+#[derive(Debug, Deserialize)]
+pub struct ScheduleMaintenanceRequest {
+    pub message: String,
+    pub starts_at: DateTime<Utc>,
+    pub ends_at: DateTime<Utc>,
+    /// Minutes before the start the window is announced (default 60)
+    pub announce_before_minutes: Option<i64>,
+}
+
+impl ScheduleMaintenanceRequest {
+    /// Validate maintenance scheduling request
+    ///
+    /// Enforces business rules:
+    /// - Message must not be empty
+    /// - The window must start in the future and end after it starts
+    /// - The announcement lead time must not be negative
+    pub fn validate(&self, now: DateTime<Utc>) -> Result<(), String> {
+        if self.message.trim().is_empty() {
+            return Err("Message cannot be empty".to_string());
+        }
+        if self.starts_at <= now {
+            return Err("Maintenance must start in the future".to_string());
+        }
+        if self.ends_at <= self.starts_at {
+            return Err("Maintenance must end after it starts".to_string());
+        }
+        if self
+            .announce_before_minutes
+            .is_some_and(|minutes| minutes < 0)
+        {
+            return Err("Announcement lead time cannot be negative".to_string());
+        }
+        Ok(())
+    }
+
+    /// Time the window is announced from, never before `now`
+    pub fn announce_at(&self, now: DateTime<Utc>) -> DateTime<Utc> {
+        let lead_time = Duration::minutes(
+            self.announce_before_minutes
+                .unwrap_or(DEFAULT_ANNOUNCE_BEFORE_MINUTES),
+        );
+        (self.starts_at - lead_time).max(now)
+    }
+}
+
+/// Current maintenance state reported to clients
+#[derive(Debug, Clone, Serialize)]
+pub struct MaintenanceStatus {
+    /// Maintenance mode is on
+    pub active: bool,
+    /// Active window, otherwise the next announced one
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub window: Option<MaintenanceWindow>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(now: DateTime<Utc>) -> ScheduleMaintenanceRequest {
+        ScheduleMaintenanceRequest {
+            message: "EMR upgrade".to_string(),
+            starts_at: now + Duration::minutes(30),
+            ends_at: now + Duration::minutes(90),
+            announce_before_minutes: None,
+        }
+    }
+
+    #[test]
+    fn test_validate() {
+        let now = Utc::now();
+        assert!(request(now).validate(now).is_ok());
+
+        let mut past = request(now);
+        past.starts_at = now - Duration::minutes(1);
+        assert!(past.validate(now).is_err());
+
+        let mut inverted = request(now);
+        inverted.ends_at = inverted.starts_at;
+        assert!(inverted.validate(now).is_err());
+    }
+
+    #[test]
+    fn test_phases() {
+        let now = Utc::now();
+        let request = request(now);
+        // The default lead time reaches back before now
+        assert_eq!(request.announce_at(now), now);
+
+        let window = MaintenanceWindow {
+            id: 1,
+            message: request.message.clone(),
+            announce_at: now + Duration::minutes(10),
+            starts_at: request.starts_at,
+            ends_at: request.ends_at,
+            announcement_id: None,
+            created_by: "1".to_string(),
+            created_at: now,
+        };
+        assert_eq!(window.phase_at(now), MaintenancePhase::Scheduled);
+        assert_eq!(
+            window.phase_at(now + Duration::minutes(10)),
+            MaintenancePhase::Announced
+        );
+        assert_eq!(
+            window.phase_at(now + Duration::minutes(30)),
+            MaintenancePhase::Active
+        );
+        assert_eq!(
+            window.phase_at(now + Duration::minutes(90)),
+            MaintenancePhase::Ended
+        );
+    }
+}
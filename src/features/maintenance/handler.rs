@@ -0,0 +1,103 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Json,
+};
+
+use crate::features::auth::AuthenticatedUser;
+use crate::infrastructure::{AppError, AuditContext, JsonBody};
+
+use super::domain::{MaintenanceStatus, MaintenanceWindow, ScheduleMaintenanceRequest};
+use super::service::MaintenanceService;
+
+/// Maintenance status handler
+///
+/// Public endpoint reporting whether maintenance mode is on, with the
+/// active window or the next announced one.
+///
+/// # Route
+/// GET /api/v1/maintenance
+///
+/// # Response
+/// ```json
+/// {
+///   "active": false,
+///   "window": {
+///     "id": 1,
+///     "message": "EMR upgrade",
+///     "announce_at": "2024-01-01T21:00:00Z",
+///     "starts_at": "2024-01-01T22:00:00Z",
+///     "ends_at": "2024-01-01T23:00:00Z",
+///     "announcement_id": 4,
+///     "created_by": "1",
+///     "created_at": "2024-01-01T09:00:00Z"
+///   }
+/// }
+/// ```
+pub async fn maintenance_status(
+    State(maintenance_service): State<MaintenanceService>,
+) -> Json<MaintenanceStatus> {
+    Json(maintenance_service.status().await)
+}
+
+/// List maintenance windows handler
+///
+/// Windows that have not ended, by start time. Requires the admin role.
+///
+/// # Route
+/// GET /api/v1/admin/maintenance
+pub async fn list_maintenance_windows(
+    State(maintenance_service): State<MaintenanceService>,
+) -> Json<Vec<MaintenanceWindow>> {
+    Json(maintenance_service.list().await)
+}
+
+/// Schedule maintenance handler
+///
+/// Requires the admin role.
+///
+/// # Route
+/// POST /api/v1/admin/maintenance
+///
+/// # Request Body
+/// ```json
+/// {
+///   "message": "EMR upgrade",
+///   "starts_at": "2024-01-01T22:00:00Z",
+///   "ends_at": "2024-01-01T23:00:00Z",
+///   "announce_before_minutes": 60
+/// }
+/// ```
+///
+/// # Response
+/// 201 Created with the scheduled window
+pub async fn schedule_maintenance(
+    State(maintenance_service): State<MaintenanceService>,
+    user: AuthenticatedUser,
+    audit: AuditContext,
+    JsonBody(payload): JsonBody<ScheduleMaintenanceRequest>,
+) -> Result<(StatusCode, Json<MaintenanceWindow>), AppError> {
+    let window = maintenance_service
+        .schedule(&user.0, payload, &audit)
+        .await?;
+    Ok((StatusCode::CREATED, Json(window)))
+}
+
+/// Cancel maintenance handler
+///
+/// Requires the admin role.
+///
+/// # Route
+/// DELETE /api/v1/admin/maintenance/:id
+///
+/// # Response
+/// 204 No Content
+pub async fn cancel_maintenance(
+    State(maintenance_service): State<MaintenanceService>,
+    user: AuthenticatedUser,
+    audit: AuditContext,
+    Path(id): Path<u64>,
+) -> Result<StatusCode, AppError> {
+    maintenance_service.cancel(&user.0, id, &audit).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
@@ -0,0 +1,39 @@
+use axum::{
+    extract::{Request, State},
+    http::{header::RETRY_AFTER, HeaderValue},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use chrono::Utc;
+
+use crate::infrastructure::AppError;
+
+use super::service::MaintenanceService;
+
+/// Maintenance mode middleware
+///
+/// Rejects requests with 503 Service Unavailable while a maintenance
+/// window is active. The `Retry-After` header carries the seconds until
+/// the window ends. Routes that must stay reachable during maintenance
+/// (auth, admin) are not wrapped in this middleware.
+pub async fn maintenance_middleware(
+    State(maintenance_service): State<MaintenanceService>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let Some(window) = maintenance_service.active_window().await else {
+        return next.run(request).await;
+    };
+
+    let mut response = AppError::ServiceUnavailable(format!(
+        "Maintenance in progress until {}: {}",
+        window.ends_at.to_rfc3339(),
+        window.message
+    ))
+    .into_response();
+    let retry_after = (window.ends_at - Utc::now()).num_seconds().max(1);
+    if let Ok(value) = HeaderValue::from_str(&retry_after.to_string()) {
+        response.headers_mut().insert(RETRY_AFTER, value);
+    }
+    response
+}
@@ -0,0 +1,58 @@
+//! Maintenance Feature Module
+//!
+//! Scheduled maintenance windows. During a window the server is in
+//! maintenance mode; windows are pre-announced with a banner and pushed to
+//! WebSocket clients when they are announced, start and end.
+//!
+//! ## Architecture
+//!
+//! ### Domain Layer (`domain.rs`)
+//! - `MaintenanceWindow`: Window with announcement, start and end times
+//! - `MaintenancePhase`: Scheduled, announced, active, ended
+//! - `ScheduleMaintenanceRequest`: Value object with validation
+//!
+//! ### Application Layer (`service.rs`)
+//! - `MaintenanceService`: Scheduling, banners and phase notifications
+//!
+//! ### Presentation Layer (`middleware.rs`, `handler.rs`)
+//! - Middleware rejecting requests while maintenance mode is on
+//! - Admin handlers scheduling windows, and a public status handler
+//!
+//! ## Usage
+//! ```rust,ignore
+//! use features::maintenance;
+//!
+//! let maintenance_service = maintenance::MaintenanceService::new(
+//!     announcement_service.clone(),
+//!     jsonrpc_service.clone(),
+//!     audit_log.clone(),
+//! );
+//! jobs.schedule("maintenance", Duration::from_secs(1), {
+//!     let maintenance_service = maintenance_service.clone();
+//!     move || {
+//!         let maintenance_service = maintenance_service.clone();
+//!         async move {
+//!             maintenance_service.advance(Utc::now()).await;
+//!             Ok(())
+//!         }
+//!     }
+//! });
+//!
+//! api_routes.layer(middleware::from_fn_with_state(
+//!     maintenance_service.clone(),
+//!     maintenance::maintenance_middleware,
+//! ))
+//! ```
+
+pub mod domain;
+pub mod handler;
+pub mod middleware;
+pub mod service;
+
+// Re-export commonly used items
+pub use domain::{MaintenanceStatus, MaintenanceWindow, ScheduleMaintenanceRequest};
+pub use handler::{
+    cancel_maintenance, list_maintenance_windows, maintenance_status, schedule_maintenance,
+};
+pub use middleware::maintenance_middleware;
+pub use service::MaintenanceService;
@@ -0,0 +1,369 @@
+use chrono::{DateTime, Utc};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use tokio::sync::RwLock;
+
+use crate::features::announcements::{AnnouncementService, CreateAnnouncementRequest};
+use crate::features::jsonrpc::{JsonRpcService, ServerEvent};
+use crate::features::users::domain::UserIdentity;
+use crate::infrastructure::{AppError, AuditContext, AuditLog, AuditOperation};
+
+use super::domain::{
+    MaintenancePhase, MaintenanceStatus, MaintenanceWindow, ScheduleMaintenanceRequest,
+};
+
+/// Title of the banners announcing maintenance windows
+const ANNOUNCEMENT_TITLE: &str = "Scheduled maintenance";
+
+/// Maintenance service containing business logic
+///
+/// Application layer service that stores scheduled maintenance windows.
+/// Maintenance mode is on while a window is active. Each window is
+/// pre-announced with a banner, and connected WebSocket clients are
+/// notified when a window is announced, starts and ends.
+#[derive(Clone)]
+pub struct MaintenanceService {
+    windows: Arc<RwLock<BTreeMap<u64, MaintenanceWindow>>>,
+    /// Last phase clients were notified of, per window
+    notified: Arc<RwLock<HashMap<u64, MaintenancePhase>>>,
+    next_id: Arc<AtomicU64>,
+    announcement_service: AnnouncementService,
+    jsonrpc_service: JsonRpcService,
+    audit_log: AuditLog,
+}
+
+impl MaintenanceService {
+    /// Create a maintenance service announcing windows through the given
+    /// announcement and JSON-RPC services
+    pub fn new(
+        announcement_service: AnnouncementService,
+        jsonrpc_service: JsonRpcService,
+        audit_log: AuditLog,
+    ) -> Self {
+        Self {
+            windows: Arc::new(RwLock::new(BTreeMap::new())),
+            notified: Arc::new(RwLock::new(HashMap::new())),
+            next_id: Arc::new(AtomicU64::new(1)),
+            announcement_service,
+            jsonrpc_service,
+            audit_log,
+        }
+    }
+
+    /// Schedule a maintenance window
+    ///
+    /// # Business Logic
+    /// 1. Validate the request
+    /// 2. Reject windows overlapping another window
+    /// 3. Publish the banner announcing the window
+    /// 4. Store and audit the window
+    pub async fn schedule(
+        &self,
+        author: &UserIdentity,
+        request: ScheduleMaintenanceRequest,
+        audit: &AuditContext,
+    ) -> Result<MaintenanceWindow, AppError> {
+        let now = Utc::now();
+        request.validate(now).map_err(AppError::BadRequest)?;
+
+        let overlapping = self
+            .windows
+            .read()
+            .await
+            .values()
+            .find(|window| window.starts_at < request.ends_at && request.starts_at < window.ends_at)
+            .map(|window| window.id);
+        if let Some(other) = overlapping {
+            return Err(AppError::BadRequest(format!(
+                "Maintenance window overlaps window {}",
+                other
+            )));
+        }
+
+        let announce_at = request.announce_at(now);
+        let announcement = self
+            .announcement_service
+            .create_announcement(
+                author,
+                CreateAnnouncementRequest {
+                    title: ANNOUNCEMENT_TITLE.to_string(),
+                    message: request.message.clone(),
+                    hospital_code: None,
+                    department_code: None,
+                    starts_at: Some(announce_at),
+                    ends_at: request.ends_at,
+                    requires_acknowledgement: false,
+                },
+                audit,
+            )
+            .await?;
+
+        let window = MaintenanceWindow {
+            id: self.next_id.fetch_add(1, Ordering::SeqCst),
+            message: request.message,
+            announce_at,
+            starts_at: request.starts_at,
+            ends_at: request.ends_at,
+            announcement_id: Some(announcement.id),
+            created_by: announcement.created_by,
+            created_at: now,
+        };
+        self.windows.write().await.insert(window.id, window.clone());
+
+        tracing::info!("Scheduled maintenance: {:?}", window);
+        self.audit_log
+            .record(
+                audit,
+                AuditOperation::Create,
+                "maintenance_window",
+                window.id,
+                None,
+                serde_json::to_value(&window).ok(),
+            )
+            .await;
+
+        // Windows announced right away are pushed without waiting for the next tick
+        self.advance(now).await;
+
+        Ok(window)
+    }
+
+    /// Cancel a maintenance window that has not ended
+    ///
+    /// Withdraws its banner and, if clients were already notified, pushes a
+    /// cancellation to them.
+    pub async fn cancel(
+        &self,
+        author: &UserIdentity,
+        id: u64,
+        audit: &AuditContext,
+    ) -> Result<(), AppError> {
+        let window =
+            self.windows.write().await.remove(&id).ok_or_else(|| {
+                AppError::NotFound(format!("Maintenance window {} not found", id))
+            })?;
+        let notified = self.notified.write().await.remove(&id);
+
+        if let Some(announcement_id) = window.announcement_id {
+            match self
+                .announcement_service
+                .delete_announcement(author, announcement_id, audit)
+                .await
+            {
+                // The banner may have been withdrawn by hand
+                Ok(()) | Err(AppError::NotFound(_)) => {}
+                Err(err) => return Err(err),
+            }
+        }
+
+        if notified.is_some() {
+            self.jsonrpc_service
+                .broadcast(&ServerEvent::MaintenanceCancelled(window.clone()))
+                .await;
+        }
+
+        tracing::info!("Cancelled maintenance: {:?}", window);
+        self.audit_log
+            .record(
+                audit,
+                AuditOperation::Delete,
+                "maintenance_window",
+                id,
+                serde_json::to_value(&window).ok(),
+                None,
+            )
+            .await;
+
+        Ok(())
+    }
+
+    /// Windows that have not ended, by start time
+    pub async fn list(&self) -> Vec<MaintenanceWindow> {
+        let now = Utc::now();
+        let mut windows: Vec<_> = self
+            .windows
+            .read()
+            .await
+            .values()
+            .filter(|window| window.phase_at(now) < MaintenancePhase::Ended)
+            .cloned()
+            .collect();
+        windows.sort_by_key(|window| window.starts_at);
+        windows
+    }
+
+    /// Window in progress, if maintenance mode is on
+    pub async fn active_window(&self) -> Option<MaintenanceWindow> {
+        let now = Utc::now();
+        self.windows
+            .read()
+            .await
+            .values()
+            .find(|window| window.phase_at(now) == MaintenancePhase::Active)
+            .cloned()
+    }
+
+    /// Current maintenance state
+    ///
+    /// Reports the active window or, outside maintenance, the next window
+    /// that has been announced.
+    pub async fn status(&self) -> MaintenanceStatus {
+        if let Some(window) = self.active_window().await {
+            return MaintenanceStatus {
+                active: true,
+                window: Some(window),
+            };
+        }
+
+        let now = Utc::now();
+        let window = self
+            .list()
+            .await
+            .into_iter()
+            .find(|window| window.phase_at(now) == MaintenancePhase::Announced);
+        MaintenanceStatus {
+            active: false,
+            window,
+        }
+    }
+
+    /// Notify clients of windows that entered a new phase by `now`
+    ///
+    /// Ended windows are dropped once their end has been pushed.
+    pub async fn advance(&self, now: DateTime<Utc>) {
+        let mut windows = self.windows.write().await;
+        let mut notified = self.notified.write().await;
+
+        let mut ended = Vec::new();
+        for window in windows.values() {
+            let phase = window.phase_at(now);
+            let last = notified
+                .get(&window.id)
+                .copied()
+                .unwrap_or(MaintenancePhase::Scheduled);
+            if phase <= last {
+                continue;
+            }
+
+            if let Some(event) = phase_event(phase, window) {
+                tracing::info!("Maintenance window {} is now {:?}", window.id, phase);
+                self.jsonrpc_service.broadcast(&event).await;
+            }
+            notified.insert(window.id, phase);
+            if phase == MaintenancePhase::Ended {
+                ended.push(window.id);
+            }
+        }
+
+        for id in ended {
+            windows.remove(&id);
+            notified.remove(&id);
+        }
+    }
+}
+
+/// Event pushed to clients when a window enters the phase
+fn phase_event(phase: MaintenancePhase, window: &MaintenanceWindow) -> Option<ServerEvent> {
+    let window = window.clone();
+    match phase {
+        MaintenancePhase::Scheduled => None,
+        MaintenancePhase::Announced => Some(ServerEvent::MaintenanceAnnounced(window)),
+        MaintenancePhase::Active => Some(ServerEvent::MaintenanceStarted(window)),
+        MaintenancePhase::Ended => Some(ServerEvent::MaintenanceEnded(window)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::features::users::domain::{Role, VerifiedUser};
+    use chrono::Duration;
+    use serde_json::Value;
+
+    fn admin() -> UserIdentity {
+        UserIdentity::Verified(VerifiedUser {
+            id: 1,
+            username: "admin".to_string(),
+            email: "admin@example.com".to_string(),
+            role: Role::Admin,
+        })
+    }
+
+    fn service() -> (MaintenanceService, AnnouncementService, JsonRpcService) {
+        let audit_log = AuditLog::default();
+        let announcement_service = AnnouncementService::new(audit_log.clone());
+        let jsonrpc_service = JsonRpcService::new();
+        let service = MaintenanceService::new(
+            announcement_service.clone(),
+            jsonrpc_service.clone(),
+            audit_log,
+        );
+        (service, announcement_service, jsonrpc_service)
+    }
+
+    #[tokio::test]
+    async fn test_window_lifecycle() {
+        let (service, announcement_service, jsonrpc_service) = service();
+        let (_, mut messages) = jsonrpc_service.connections().register().await;
+        let now = Utc::now();
+
+        let window = service
+            .schedule(
+                &admin(),
+                ScheduleMaintenanceRequest {
+                    message: "EMR upgrade".to_string(),
+                    starts_at: now + Duration::minutes(30),
+                    ends_at: now + Duration::minutes(90),
+                    announce_before_minutes: Some(60),
+                },
+                &AuditContext::default(),
+            )
+            .await
+            .unwrap();
+
+        // Announced right away, with a banner
+        let message: Value = serde_json::from_str(&messages.recv().await.unwrap()).unwrap();
+        assert_eq!(message["method"], "maintenance.announced");
+        assert_eq!(announcement_service.list_active(None).await.len(), 1);
+        assert!(!service.status().await.active);
+        assert_eq!(service.status().await.window.unwrap().id, window.id);
+
+        service.advance(now + Duration::minutes(30)).await;
+        let message: Value = serde_json::from_str(&messages.recv().await.unwrap()).unwrap();
+        assert_eq!(message["method"], "maintenance.started");
+
+        service.advance(now + Duration::minutes(90)).await;
+        let message: Value = serde_json::from_str(&messages.recv().await.unwrap()).unwrap();
+        assert_eq!(message["method"], "maintenance.ended");
+        assert!(service.list().await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_overlap_and_cancel() {
+        let (service, announcement_service, _) = service();
+        let now = Utc::now();
+        let request = |offset| ScheduleMaintenanceRequest {
+            message: "EMR upgrade".to_string(),
+            starts_at: now + Duration::minutes(offset),
+            ends_at: now + Duration::minutes(offset + 60),
+            announce_before_minutes: None,
+        };
+
+        let window = service
+            .schedule(&admin(), request(30), &AuditContext::default())
+            .await
+            .unwrap();
+        assert!(service
+            .schedule(&admin(), request(60), &AuditContext::default())
+            .await
+            .is_err());
+
+        service
+            .cancel(&admin(), window.id, &AuditContext::default())
+            .await
+            .unwrap();
+        assert!(service.list().await.is_empty());
+        assert!(announcement_service.list_active(None).await.is_empty());
+    }
+}
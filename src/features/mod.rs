@@ -1,50 +1,139 @@
-/// Features Module
-///
-/// Contains all feature modules organized by business capability.
-/// Each feature is self-contained with its own layers (domain, application, presentation).
-///
-/// ## Organization
-///
-/// Features are organized following clean architecture principles:
-/// - Vertical slicing by feature (health, users, jsonrpc)
-/// - Horizontal slicing by layer (domain, application, presentation)
-///
-/// ## Available Features
-///
-/// ### Auth (`auth/`)
-/// Authentication and authorization for verified and anonymous users.
-/// - Layers: domain, application (service), middleware
-///
-/// ### Health (`health/`)
-/// Simple health check endpoint to verify service availability.
-/// - Layers: domain, presentation
-///
-/// ### Users (`users/`)
-/// User management functionality with CRUD operations.
-/// - Layers: domain, application (service), presentation (handlers)
-///
-/// ### JSON-RPC (`jsonrpc/`)
-/// WebSocket-based JSON-RPC 2.0 protocol for real-time communication.
-/// - Layers: domain, application (service), presentation (handler)
-///
-/// ## Benefits of this structure
-///
-/// 1. **High Cohesion**: Related code is grouped together by feature
-/// 2. **Low Coupling**: Features are independent and self-contained
-/// 3. **Easy Navigation**: Clear structure makes finding code intuitive
-/// 4. **Scalability**: New features can be added without affecting existing ones
-/// 5. **Testability**: Each layer can be tested independently
+//! Features Module
+//!
+//! Contains all feature modules organized by business capability.
+//! Each feature is self-contained with its own layers (domain, application, presentation).
+//!
+//! ## Organization
+//!
+//! Features are organized following clean architecture principles:
+//! - Vertical slicing by feature (health, users, jsonrpc)
+//! - Horizontal slicing by layer (domain, application, presentation)
+//!
+//! ## Available Features
+//!
+//! ### Anomaly (`anomaly/`)
+//! Alerts on suspicious authentication and traffic patterns.
+//! - Layers: domain, application (service), presentation (handlers)
+//!
+//! ### Announcements (`announcements/`)
+//! Time-bound banners targeted by hospital and department.
+//! - Layers: domain, application (service), presentation (handlers)
+//!
+//! ### Audit (`audit/`)
+//! Admin search over the audit log, data retention reporting, legal holds,
+//! background jobs and dead letters.
+//! - Layers: presentation (handler)
+//!
+//! ### Auth (`auth/`)
+//! Authentication and authorization for verified and anonymous users.
+//! - Layers: domain, application (service), middleware
+//!
+//! ### Backup (`backup/`)
+//! Scheduled backups of users and board content into object storage, and restores.
+//! - Layers: domain, application (service), presentation (handler)
+//!
+//! ### Board (`board/`)
+//! Message boards with threads and posts.
+//! - Layers: domain, application (service), presentation (handlers)
+//!
+//! ### Consent (`consent/`)
+//! Versioned terms and privacy policy with acceptance tracking per identity.
+//! - Layers: domain, application (service), middleware, presentation (handlers)
+//!
+//! ### Health (`health/`)
+//! Simple health check endpoint to verify service availability, plus liveness
+//! and readiness probes aggregating dependency checks, and load shedding
+//! while resource watermarks are exceeded.
+//! - Layers: domain, application (service, watermarks), middleware, presentation
+//!
+//! ### Maintenance (`maintenance/`)
+//! Scheduled maintenance windows with banners, notifications and maintenance mode.
+//! - Layers: domain, application (service), middleware, presentation (handlers)
+//!
+//! ### Moderation (`moderation/`)
+//! Shadow bans hiding an identity's content from everyone but itself and moderators.
+//! - Layers: domain, application (service), presentation (handlers)
+//!
+//! ### Notifications (`notifications/`)
+//! Per-user notifications with read state synced across devices.
+//! - Layers: domain, application (service), presentation (handlers)
+//!
+//! ### Users (`users/`)
+//! User management functionality with CRUD operations.
+//! - Layers: domain, application (service), presentation (handlers)
+//!
+//! ### Usage (`usage/`)
+//! API call metering per caller and daily quotas per tenant (hospital).
+//! - Layers: domain, application (service), middleware, presentation (handlers)
+//!
+//! ### JSON-RPC (`jsonrpc/`)
+//! WebSocket-based JSON-RPC 2.0 protocol for real-time communication.
+//! - Layers: domain, application (service), presentation (handler)
+//!
+//! ## Benefits of this structure
+//!
+//! 1. **High Cohesion**: Related code is grouped together by feature
+//! 2. **Low Coupling**: Features are independent and self-contained
+//! 3. **Easy Navigation**: Clear structure makes finding code intuitive
+//! 4. **Scalability**: New features can be added without affecting existing ones
+//! 5. **Testability**: Each layer can be tested independently
 
+pub mod announcements;
+pub mod anomaly;
+pub mod audit;
 pub mod auth;
+pub mod backup;
+pub mod board;
+pub mod consent;
 pub mod health;
 pub mod jsonrpc;
+pub mod maintenance;
+pub mod moderation;
+pub mod notifications;
+pub mod usage;
 pub mod users;
 
 // Re-export commonly used items for convenience
+pub use announcements::{
+    acknowledge_announcement, create_announcement, delete_announcement,
+    get_acknowledgement_stats, list_active_announcements, AnnouncementService,
+};
+pub use anomaly::{
+    get_anomaly_thresholds, list_anomalies, set_anomaly_thresholds, AnomalyDetector,
+};
+pub use audit::{
+    discard_dead_letter, list_dead_letters, list_jobs, list_legal_holds, list_sessions,
+    place_legal_hold, release_legal_hold, retention_report, retry_dead_letter, revoke_session,
+    run_job, search_audit_log,
+};
 pub use auth::{
-    anonymous_token, auth_middleware, login, me, optional_auth_middleware, register, AuthService,
-    AuthenticatedUser,
+    anonymous_token, auth_middleware, create_action_token, create_automation_token,
+    device_login_approve, device_login_poll, device_login_start, list_automation_tokens, login,
+    logout, me, optional_auth_middleware, refresh, register, require_role, revoke_automation_token,
+    rotate_automation_token, websocket_auth_middleware, AuthService, AuthenticatedUser, JwtKeys,
+    TokenBlacklist, UserRepository,
+};
+pub use backup::{backup_status, BackupService};
+pub use board::{
+    create_board, create_thread, delete_board, delete_post, delete_thread, get_thread, list_boards,
+    list_threads, reply_to_thread, undo_deletion, BoardService,
+};
+pub use consent::{
+    accept_policy, consent_middleware, consent_report, consent_status, publish_policy,
+    ConsentService,
+};
+pub use health::{
+    health_check, load_shedding_middleware, readiness_check, HealthCheck, HealthChecks,
+    HealthResponse, WatermarkMonitor,
+};
+pub use jsonrpc::{list_event_schemas, list_presence, websocket_handler, JsonRpcService};
+pub use maintenance::{
+    cancel_maintenance, list_maintenance_windows, maintenance_middleware, maintenance_status,
+    schedule_maintenance, MaintenanceService,
+};
+pub use moderation::{lift_shadow_ban, list_shadow_bans, shadow_ban, ModerationService};
+pub use notifications::{list_notifications, mark_notifications_read, NotificationService};
+pub use usage::{
+    get_my_usage, get_tenant_usage, list_tenant_usage, usage_middleware, UsageService,
 };
-pub use health::{health_check, HealthResponse};
-pub use jsonrpc::{websocket_handler, JsonRpcService};
-pub use users::{create_user, get_user, list_users, User, UserService};
+pub use users::{create_user, delete_user, get_user, list_users, Role, User, UserService};
@@ -0,0 +1,51 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Maximum length of a moderation reason in characters
+pub const MAX_REASON_LENGTH: usize = 500;
+
+/// Shadow ban of an identity
+///
+/// Content of a shadow-banned identity looks normal to that identity but is
+/// hidden from everyone else except moderators, in listings and in
+/// real-time notifications alike.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ShadowBan {
+    /// Stored actor id of the banned identity, as shown on its content
+    pub actor_id: String,
+    pub reason: String,
+    /// Stored actor id of the moderator who placed the ban
+    pub banned_by: String,
+    pub banned_at: DateTime<Utc>,
+}
+
+/// Request payload for shadow-banning an identity
+#[derive(Debug, Deserialize)]
+pub struct ShadowBanRequest {
+    /// Author id of the identity's content, e.g. `user:7`
+    pub actor_id: String,
+    pub reason: String,
+}
+
+impl ShadowBanRequest {
+    /// Validate shadow ban request
+    ///
+    /// Enforces business rules:
+    /// - Actor id must not be empty
+    /// - Reason must not be empty or longer than 500 characters
+    pub fn validate(&self) -> Result<(), String> {
+        if self.actor_id.trim().is_empty() {
+            return Err("Actor id cannot be empty".to_string());
+        }
+        if self.reason.trim().is_empty() {
+            return Err("Reason cannot be empty".to_string());
+        }
+        if self.reason.chars().count() > MAX_REASON_LENGTH {
+            return Err(format!(
+                "Reason cannot be longer than {} characters",
+                MAX_REASON_LENGTH
+            ));
+        }
+        Ok(())
+    }
+}
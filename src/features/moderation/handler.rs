@@ -0,0 +1,85 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Json,
+};
+
+use crate::features::auth::AuthenticatedUser;
+use crate::infrastructure::{AppError, AuditContext, JsonBody};
+
+use super::domain::{ShadowBan, ShadowBanRequest};
+use super::service::ModerationService;
+
+/// List shadow bans handler
+///
+/// Requires the moderator role.
+///
+/// # Route
+/// GET /api/v1/moderation/shadow-bans
+///
+/// # Response
+/// ```json
+/// [
+///   {
+///     "actor_id": "user:7",
+///     "reason": "Repeated spam",
+///     "banned_by": "user:2",
+///     "banned_at": "2024-01-01T00:00:00Z"
+///   }
+/// ]
+/// ```
+pub async fn list_shadow_bans(
+    State(moderation_service): State<ModerationService>,
+) -> Json<Vec<ShadowBan>> {
+    Json(moderation_service.list_shadow_bans())
+}
+
+/// Shadow ban handler
+///
+/// Requires the moderator role. The identity is given by the author id shown
+/// on its content. Its posts stay visible to itself and to moderators only.
+///
+/// # Route
+/// POST /api/v1/moderation/shadow-bans
+///
+/// # Request Body
+/// ```json
+/// {
+///   "actor_id": "user:7",
+///   "reason": "Repeated spam"
+/// }
+/// ```
+///
+/// # Response
+/// 201 Created with the ban
+pub async fn shadow_ban(
+    State(moderation_service): State<ModerationService>,
+    user: AuthenticatedUser,
+    audit: AuditContext,
+    JsonBody(payload): JsonBody<ShadowBanRequest>,
+) -> Result<(StatusCode, Json<ShadowBan>), AppError> {
+    let ban = moderation_service
+        .shadow_ban(&user.0, payload, &audit)
+        .await?;
+    Ok((StatusCode::CREATED, Json(ban)))
+}
+
+/// Lift shadow ban handler
+///
+/// Requires the moderator role.
+///
+/// # Route
+/// DELETE /api/v1/moderation/shadow-bans/:actor_id
+///
+/// # Response
+/// 204 No Content
+pub async fn lift_shadow_ban(
+    State(moderation_service): State<ModerationService>,
+    audit: AuditContext,
+    Path(actor_id): Path<String>,
+) -> Result<StatusCode, AppError> {
+    moderation_service
+        .lift_shadow_ban(&actor_id, &audit)
+        .await?;
+    Ok(StatusCode::NO_CONTENT)
+}
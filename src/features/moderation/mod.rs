@@ -0,0 +1,39 @@
+//! Moderation Feature Module
+//!
+//! Shadow bans placed by moderators. Content of a shadow-banned identity
+//! looks normal to that identity but is hidden from everyone else except
+//! moderators.
+//!
+//! ## Architecture
+//!
+//! ### Domain Layer (`domain.rs`)
+//! - `ShadowBan`: Ban entity keyed on the stored actor id of the identity
+//! - `ShadowBanRequest`: Value object with validation
+//!
+//! ### Application Layer (`service.rs`)
+//! - `ModerationService`: Placing and lifting bans, content visibility per viewer
+//!
+//! ### Presentation Layer (`handler.rs`)
+//! - HTTP handlers for the moderation endpoints
+//!
+//! ## Usage
+//! ```rust,ignore
+//! use features::moderation;
+//!
+//! let moderation_service = moderation::ModerationService::new(audit_log.clone());
+//! let board_service = BoardService::new(audit_log.clone())
+//!     .with_moderation(moderation_service.clone());
+//!
+//! Router::new()
+//!     .route("/shadow-bans", get(moderation::list_shadow_bans).post(moderation::shadow_ban))
+//!     .with_state(moderation_service)
+//! ```
+
+pub mod domain;
+pub mod handler;
+pub mod service;
+
+// Re-export commonly used items
+pub use domain::{ShadowBan, ShadowBanRequest};
+pub use handler::{lift_shadow_ban, list_shadow_bans, shadow_ban};
+pub use service::ModerationService;
@@ -0,0 +1,250 @@
+use chrono::Utc;
+use std::collections::{BTreeMap, HashSet};
+use std::sync::{Arc, PoisonError, RwLock};
+
+use crate::features::users::domain::{Role, UserIdentity};
+use crate::infrastructure::{audit::AuditActor, AppError, AuditContext, AuditLog, AuditOperation};
+
+use super::domain::{ShadowBan, ShadowBanRequest};
+
+/// Moderation service containing business logic
+///
+/// Application layer service that keeps the shadow bans placed by
+/// moderators and decides whose content an identity gets to see. Visibility
+/// is checked while pushing notifications, which is synchronous, hence the
+/// std lock.
+/// In a real application, this would interact with a database repository.
+#[derive(Clone)]
+pub struct ModerationService {
+    /// Shadow bans by stored actor id
+    shadow_bans: Arc<RwLock<BTreeMap<String, ShadowBan>>>,
+    audit_log: AuditLog,
+}
+
+impl ModerationService {
+    /// Create a new moderation service recording bans into the given audit log
+    pub fn new(audit_log: AuditLog) -> Self {
+        Self {
+            shadow_bans: Arc::new(RwLock::new(BTreeMap::new())),
+            audit_log,
+        }
+    }
+
+    /// Shadow-ban an identity
+    ///
+    /// # Business Logic
+    /// 1. Only moderators may shadow-ban
+    /// 2. Validate the request
+    /// 3. Store and audit the ban
+    ///
+    /// The banned identity is not told; its content is hidden from others
+    /// from then on, including content posted before the ban.
+    pub async fn shadow_ban(
+        &self,
+        moderator: &UserIdentity,
+        request: ShadowBanRequest,
+        audit: &AuditContext,
+    ) -> Result<ShadowBan, AppError> {
+        if !moderator.has_role(Role::Moderator) {
+            return Err(AppError::Forbidden(
+                "Only moderators can shadow-ban".to_string(),
+            ));
+        }
+        request.validate().map_err(AppError::BadRequest)?;
+
+        let ban = ShadowBan {
+            actor_id: request.actor_id.trim().to_string(),
+            reason: request.reason,
+            banned_by: self.actor_id(moderator),
+            banned_at: Utc::now(),
+        };
+        if ban.actor_id == ban.banned_by {
+            return Err(AppError::BadRequest(
+                "Moderators cannot shadow-ban themselves".to_string(),
+            ));
+        }
+        {
+            let mut shadow_bans = self
+                .shadow_bans
+                .write()
+                .unwrap_or_else(PoisonError::into_inner);
+            if shadow_bans.contains_key(&ban.actor_id) {
+                return Err(AppError::BadRequest(format!(
+                    "{} is already shadow-banned",
+                    ban.actor_id
+                )));
+            }
+            shadow_bans.insert(ban.actor_id.clone(), ban.clone());
+        }
+
+        tracing::info!("Shadow-banned {}", ban.actor_id);
+        self.audit_log
+            .record(
+                audit,
+                AuditOperation::Create,
+                "shadow_ban",
+                &ban.actor_id,
+                None,
+                serde_json::to_value(&ban).ok(),
+            )
+            .await;
+
+        Ok(ban)
+    }
+
+    /// Lift the shadow ban of an identity
+    ///
+    /// Its content becomes visible to everyone again.
+    pub async fn lift_shadow_ban(
+        &self,
+        actor_id: &str,
+        audit: &AuditContext,
+    ) -> Result<ShadowBan, AppError> {
+        let ban = self
+            .shadow_bans
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+            .remove(actor_id)
+            .ok_or_else(|| AppError::NotFound(format!("{} is not shadow-banned", actor_id)))?;
+
+        tracing::info!("Lifted shadow ban of {}", actor_id);
+        self.audit_log
+            .record(
+                audit,
+                AuditOperation::Delete,
+                "shadow_ban",
+                actor_id,
+                serde_json::to_value(&ban).ok(),
+                None,
+            )
+            .await;
+
+        Ok(ban)
+    }
+
+    /// All shadow bans in place
+    pub fn list_shadow_bans(&self) -> Vec<ShadowBan> {
+        self.shadow_bans
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .values()
+            .cloned()
+            .collect()
+    }
+
+    /// Check if content stored under `author_id` is visible to `viewer`
+    ///
+    /// Content of shadow-banned identities is only visible to themselves
+    /// and to moderators.
+    pub fn is_visible_to(&self, author_id: &str, viewer: Option<&UserIdentity>) -> bool {
+        let is_banned = self
+            .shadow_bans
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .contains_key(author_id);
+        !is_banned
+            || viewer.is_some_and(|viewer| {
+                viewer.has_role(Role::Moderator) || self.actor_id(viewer) == author_id
+            })
+    }
+
+    /// Author ids whose content is hidden from `viewer`
+    ///
+    /// Lets callers filter many items without checking each one.
+    pub fn hidden_authors(&self, viewer: &UserIdentity) -> HashSet<String> {
+        if viewer.has_role(Role::Moderator) {
+            return HashSet::new();
+        }
+
+        let shadow_bans = self
+            .shadow_bans
+            .read()
+            .unwrap_or_else(PoisonError::into_inner);
+        if shadow_bans.is_empty() {
+            return HashSet::new();
+        }
+        let viewer_id = self.actor_id(viewer);
+        shadow_bans
+            .keys()
+            .filter(|actor_id| **actor_id != viewer_id)
+            .cloned()
+            .collect()
+    }
+
+    /// Id the content of an identity is stored under
+    fn actor_id(&self, identity: &UserIdentity) -> String {
+        self.audit_log.stored_actor_id(&AuditActor::from(identity))
+    }
+}
+
+impl Default for ModerationService {
+    fn default() -> Self {
+        Self::new(AuditLog::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::features::users::domain::VerifiedUser;
+
+    fn user(id: u64, role: Role) -> UserIdentity {
+        UserIdentity::Verified(VerifiedUser {
+            id,
+            username: format!("user{}", id),
+            email: format!("user{}@example.com", id),
+            role,
+        })
+    }
+
+    fn request(actor_id: &str) -> ShadowBanRequest {
+        ShadowBanRequest {
+            actor_id: actor_id.to_string(),
+            reason: "Spam".to_string(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_shadow_ban_visibility() {
+        let service = ModerationService::default();
+        let moderator = user(1, Role::Moderator);
+        let banned = user(2, Role::Member);
+        let other = user(3, Role::Member);
+        let audit = AuditContext::default();
+
+        // Members cannot shadow-ban, moderators cannot ban themselves
+        assert!(matches!(
+            service.shadow_ban(&other, request("user:2"), &audit).await,
+            Err(AppError::Forbidden(_))
+        ));
+        assert!(service
+            .shadow_ban(&moderator, request("user:1"), &audit)
+            .await
+            .is_err());
+
+        let ban = service
+            .shadow_ban(&moderator, request("user:2"), &audit)
+            .await
+            .unwrap();
+        assert_eq!(ban.banned_by, "user:1");
+        assert!(service
+            .shadow_ban(&moderator, request("user:2"), &audit)
+            .await
+            .is_err());
+
+        assert!(service.is_visible_to("user:2", Some(&banned)));
+        assert!(service.is_visible_to("user:2", Some(&moderator)));
+        assert!(!service.is_visible_to("user:2", Some(&other)));
+        assert!(!service.is_visible_to("user:2", None));
+        assert!(service.is_visible_to("user:3", None));
+
+        assert!(service.hidden_authors(&banned).is_empty());
+        assert!(service.hidden_authors(&moderator).is_empty());
+        assert!(service.hidden_authors(&other).contains("user:2"));
+
+        service.lift_shadow_ban("user:2", &audit).await.unwrap();
+        assert!(service.is_visible_to("user:2", Some(&other)));
+        assert!(service.lift_shadow_ban("user:2", &audit).await.is_err());
+        assert!(service.list_shadow_bans().is_empty());
+    }
+}
@@ -0,0 +1,64 @@
+use chrono::{DateTime, Utc};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// Maximum number of notifications kept per user; the oldest are dropped first
+pub const MAX_NOTIFICATIONS_PER_USER: usize = 200;
+
+/// What a notification is about
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NotificationKind {
+    /// Someone replied to a thread of the user
+    Reply,
+}
+
+/// Notification for a user
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Notification {
+    pub id: u64,
+    pub kind: NotificationKind,
+    pub board_id: u64,
+    pub thread_id: u64,
+    pub post_id: u64,
+    /// Username of the author of the post, if they are a verified user
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub author_name: Option<String>,
+    pub created_at: DateTime<Utc>,
+    /// When the notification was read, on any device
+    pub read_at: Option<DateTime<Utc>>,
+}
+
+impl Notification {
+    pub fn is_read(&self) -> bool {
+        self.read_at.is_some()
+    }
+}
+
+/// Notifications of a user, newest first, with the unread count for badges
+#[derive(Debug, Clone, Serialize)]
+pub struct NotificationList {
+    pub unread: usize,
+    pub notifications: Vec<Notification>,
+}
+
+/// Request payload for marking notifications as read
+///
+/// Marks all notifications as read when `ids` is omitted.
+#[derive(Debug, Default, Deserialize)]
+pub struct MarkReadRequest {
+    pub ids: Option<Vec<u64>>,
+}
+
+/// Read state after marking notifications as read
+///
+/// Returned to the device that read the notifications and pushed to the
+/// other live connections of the user as `notifications.read`.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct ReadState {
+    /// Notifications that were marked as read
+    pub ids: Vec<u64>,
+    pub read_at: DateTime<Utc>,
+    /// Unread notifications left
+    pub unread: usize,
+}
@@ -0,0 +1,71 @@
+use axum::{extract::State, Json};
+
+use crate::features::auth::AuthenticatedUser;
+use crate::infrastructure::JsonBody;
+
+use super::domain::{MarkReadRequest, NotificationList, ReadState};
+use super::service::NotificationService;
+
+/// List notifications handler
+///
+/// Notifications of the caller, newest first. Requires authentication.
+///
+/// # Route
+/// GET /api/v1/notifications
+///
+/// # Response
+/// ```json
+/// {
+///   "unread": 1,
+///   "notifications": [
+///     {
+///       "id": 4,
+///       "kind": "reply",
+///       "board_id": 1,
+///       "thread_id": 3,
+///       "post_id": 12,
+///       "author_name": "user2",
+///       "created_at": "2024-01-01T09:30:00Z",
+///       "read_at": null
+///     }
+///   ]
+/// }
+/// ```
+pub async fn list_notifications(
+    State(notification_service): State<NotificationService>,
+    user: AuthenticatedUser,
+) -> Json<NotificationList> {
+    Json(notification_service.list(&user.0).await)
+}
+
+/// Mark notifications as read handler
+///
+/// Requires authentication. Marks all notifications of the caller as read
+/// when `ids` is omitted. The read state is pushed to the caller's live
+/// connections as a `notifications.read` notification.
+///
+/// # Route
+/// POST /api/v1/notifications/read
+///
+/// # Request Body
+/// ```json
+/// {
+///   "ids": [4, 5]
+/// }
+/// ```
+///
+/// # Response
+/// ```json
+/// {
+///   "ids": [4],
+///   "read_at": "2024-01-01T09:31:00Z",
+///   "unread": 0
+/// }
+/// ```
+pub async fn mark_notifications_read(
+    State(notification_service): State<NotificationService>,
+    user: AuthenticatedUser,
+    JsonBody(payload): JsonBody<MarkReadRequest>,
+) -> Json<ReadState> {
+    Json(notification_service.mark_read(&user.0, payload).await)
+}
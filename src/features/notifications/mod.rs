@@ -0,0 +1,36 @@
+//! Notifications Feature Module
+//!
+//! Per-user notifications, such as replies to a user's threads, with read
+//! state shared across the user's devices.
+//!
+//! ## Architecture
+//!
+//! ### Domain Layer (`domain.rs`)
+//! - `Notification`, `NotificationKind`: What happened and whether it was read
+//! - `NotificationList`: Notifications with the unread count for badges
+//! - `ReadState`: Result of marking notifications as read, synced to devices
+//!
+//! ### Application Layer (`service.rs`)
+//! - `NotificationService`: Inboxes, read state and live sync
+//!
+//! ### Presentation Layer (`handler.rs`)
+//! - Handlers for listing notifications and marking them as read
+//!
+//! ## Usage
+//! ```rust,ignore
+//! use features::notifications;
+//!
+//! let notification_service = notifications::NotificationService::new(audit_log.clone())
+//!     .with_live_sync(jsonrpc_service.clone());
+//! let board_service = BoardService::new(audit_log.clone())
+//!     .with_reply_notifications(notification_service.clone());
+//! ```
+
+pub mod domain;
+pub mod handler;
+pub mod service;
+
+// Re-export commonly used items
+pub use domain::{Notification, NotificationKind, NotificationList, ReadState};
+pub use handler::{list_notifications, mark_notifications_read};
+pub use service::NotificationService;
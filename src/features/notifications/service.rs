@@ -0,0 +1,204 @@
+use chrono::Utc;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use tokio::sync::RwLock;
+
+use crate::features::jsonrpc::{JsonRpcService, ServerEvent};
+use crate::features::users::domain::UserIdentity;
+use crate::infrastructure::audit::AuditActor;
+use crate::infrastructure::AuditLog;
+
+use super::domain::{
+    MarkReadRequest, Notification, NotificationKind, NotificationList, ReadState,
+    MAX_NOTIFICATIONS_PER_USER,
+};
+
+/// Notification service containing business logic
+///
+/// Application layer service keeping the notifications of each user and
+/// whether they were read. Read state is shared by all devices of a user:
+/// marking notifications as read on one device is pushed to the live
+/// connections of the others, so unread badges stay consistent. Users are
+/// keyed by their audit actor id, like board authors.
+/// In a real application, this would interact with a database repository.
+#[derive(Clone)]
+pub struct NotificationService {
+    inboxes: Arc<RwLock<HashMap<String, VecDeque<Notification>>>>,
+    next_id: Arc<AtomicU64>,
+    audit_log: AuditLog,
+    /// Pushes read state to WebSocket connections, if set
+    live: Option<JsonRpcService>,
+}
+
+impl NotificationService {
+    /// Create a notification service keying users like the given audit log
+    pub fn new(audit_log: AuditLog) -> Self {
+        Self {
+            inboxes: Arc::new(RwLock::new(HashMap::new())),
+            next_id: Arc::new(AtomicU64::new(1)),
+            audit_log,
+            live: None,
+        }
+    }
+
+    /// Push read state to the WebSocket connections of the reading user
+    pub fn with_live_sync(mut self, jsonrpc_service: JsonRpcService) -> Self {
+        self.live = Some(jsonrpc_service);
+        self
+    }
+
+    /// Add a notification for the user with the given stored actor id
+    pub async fn notify(
+        &self,
+        recipient_id: &str,
+        kind: NotificationKind,
+        board_id: u64,
+        thread_id: u64,
+        post_id: u64,
+        author_name: Option<String>,
+    ) -> Notification {
+        let notification = Notification {
+            id: self.next_id.fetch_add(1, Ordering::SeqCst),
+            kind,
+            board_id,
+            thread_id,
+            post_id,
+            author_name,
+            created_at: Utc::now(),
+            read_at: None,
+        };
+
+        let mut inboxes = self.inboxes.write().await;
+        let inbox = inboxes.entry(recipient_id.to_string()).or_default();
+        if inbox.len() >= MAX_NOTIFICATIONS_PER_USER {
+            inbox.pop_front();
+        }
+        inbox.push_back(notification.clone());
+
+        notification
+    }
+
+    /// Notifications of a user, newest first
+    pub async fn list(&self, identity: &UserIdentity) -> NotificationList {
+        let inboxes = self.inboxes.read().await;
+        let notifications: Vec<Notification> = inboxes
+            .get(&self.actor_id(identity))
+            .map(|inbox| inbox.iter().rev().cloned().collect())
+            .unwrap_or_default();
+
+        NotificationList {
+            unread: notifications.iter().filter(|n| !n.is_read()).count(),
+            notifications,
+        }
+    }
+
+    /// Mark notifications of a user as read
+    ///
+    /// # Business Logic
+    /// 1. Mark the requested notifications, or all of them, as read;
+    ///    unknown and already read ids are skipped
+    /// 2. Push the read state to the live connections of the user
+    pub async fn mark_read(&self, identity: &UserIdentity, request: MarkReadRequest) -> ReadState {
+        let actor_id = self.actor_id(identity);
+        let requested: Option<HashSet<u64>> = request.ids.map(|ids| ids.into_iter().collect());
+        let now = Utc::now();
+
+        let mut inboxes = self.inboxes.write().await;
+        let inbox = inboxes.entry(actor_id.clone()).or_default();
+        let mut ids = Vec::new();
+        for notification in inbox.iter_mut().filter(|n| !n.is_read()) {
+            if requested
+                .as_ref()
+                .is_none_or(|requested| requested.contains(&notification.id))
+            {
+                notification.read_at = Some(now);
+                ids.push(notification.id);
+            }
+        }
+        let state = ReadState {
+            ids,
+            read_at: now,
+            unread: inbox.iter().filter(|n| !n.is_read()).count(),
+        };
+        drop(inboxes);
+
+        if let (Some(jsonrpc_service), false) = (&self.live, state.ids.is_empty()) {
+            jsonrpc_service
+                .notify_identity(
+                    &ServerEvent::NotificationsRead(state.clone()),
+                    |connection| self.actor_id(connection) == actor_id,
+                )
+                .await;
+        }
+
+        state
+    }
+
+    /// Id users are stored under
+    fn actor_id(&self, identity: &UserIdentity) -> String {
+        self.audit_log.stored_actor_id(&AuditActor::from(identity))
+    }
+}
+
+impl Default for NotificationService {
+    fn default() -> Self {
+        Self::new(AuditLog::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::features::users::domain::{Role, VerifiedUser};
+    use serde_json::Value;
+
+    fn user(id: u64) -> UserIdentity {
+        UserIdentity::Verified(VerifiedUser {
+            id,
+            username: format!("user{}", id),
+            email: format!("user{}@example.com", id),
+            role: Role::Member,
+        })
+    }
+
+    #[tokio::test]
+    async fn test_read_state_is_synced_across_devices() {
+        let jsonrpc_service = JsonRpcService::new();
+        let service = NotificationService::default().with_live_sync(jsonrpc_service.clone());
+        let (reader, other) = (user(1), user(2));
+        let connections = jsonrpc_service.connections();
+        let (_, mut phone_rx) = connections.register_as(Some(reader.clone())).await;
+        let (_, mut other_rx) = connections.register_as(Some(other.clone())).await;
+
+        let first = service
+            .notify("user:1", NotificationKind::Reply, 1, 1, 2, None)
+            .await;
+        service
+            .notify("user:1", NotificationKind::Reply, 1, 1, 3, None)
+            .await;
+        assert_eq!(service.list(&reader).await.unread, 2);
+
+        let state = service
+            .mark_read(
+                &reader,
+                MarkReadRequest {
+                    ids: Some(vec![first.id, 999]),
+                },
+            )
+            .await;
+        assert_eq!((state.ids, state.unread), (vec![first.id], 1));
+
+        // Other devices of the reader learn about it, other users don't
+        let message: Value = serde_json::from_str(&phone_rx.try_recv().unwrap()).unwrap();
+        assert_eq!(message["method"], "notifications.read");
+        assert_eq!(message["params"]["unread"], 1);
+        assert!(other_rx.try_recv().is_err());
+
+        let state = service.mark_read(&reader, MarkReadRequest::default()).await;
+        assert_eq!((state.ids.len(), state.unread), (1, 0));
+        let list = service.list(&reader).await;
+        assert!(list.notifications.iter().all(Notification::is_read));
+        assert_eq!(service.list(&other).await.notifications.len(), 0);
+    }
+}
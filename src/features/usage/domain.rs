@@ -0,0 +1,194 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::Serialize;
+
+/// API call counters of a tenant or a single caller
+#[derive(Debug, Clone, Serialize)]
+pub struct ApiCallCounters {
+    /// UTC day the daily counter belongs to
+    pub day: NaiveDate,
+    /// API calls made on `day`
+    pub daily_api_calls: u64,
+    /// API calls made since startup
+    pub total_api_calls: u64,
+    pub last_seen: DateTime<Utc>,
+}
+
+impl ApiCallCounters {
+    /// Create empty counters
+    pub fn new(now: DateTime<Utc>) -> Self {
+        Self {
+            day: now.date_naive(),
+            daily_api_calls: 0,
+            total_api_calls: 0,
+            last_seen: now,
+        }
+    }
+
+    /// Count one API call, resetting the daily counter on a new UTC day
+    pub fn record_call(&mut self, now: DateTime<Utc>) {
+        let today = now.date_naive();
+        if self.day != today {
+            self.day = today;
+            self.daily_api_calls = 0;
+        }
+        self.daily_api_calls += 1;
+        self.total_api_calls += 1;
+        self.last_seen = now;
+    }
+
+    /// API calls made today, as of `now`
+    pub fn calls_on(&self, now: DateTime<Utc>) -> u64 {
+        if self.day == now.date_naive() {
+            self.daily_api_calls
+        } else {
+            0
+        }
+    }
+}
+
+/// Usage counters for a single tenant (hospital)
+#[derive(Debug, Clone, Serialize)]
+pub struct TenantUsage {
+    pub hospital_code: String,
+    #[serde(flatten)]
+    pub calls: ApiCallCounters,
+}
+
+impl TenantUsage {
+    /// Create empty usage counters for a tenant
+    pub fn new(hospital_code: String, now: DateTime<Utc>) -> Self {
+        Self {
+            hospital_code,
+            calls: ApiCallCounters::new(now),
+        }
+    }
+
+    /// Count one API call, resetting the daily counter on a new UTC day
+    pub fn record_call(&mut self, now: DateTime<Utc>) {
+        self.calls.record_call(now);
+    }
+}
+
+/// Standing of a tenant against its daily quota
+#[derive(Debug, Clone, Serialize)]
+pub struct QuotaStanding {
+    pub hospital_code: String,
+    /// API calls the tenant made today
+    pub daily_api_calls: u64,
+    pub soft_daily_api_calls: Option<u64>,
+    pub hard_daily_api_calls: Option<u64>,
+    /// Calls left before requests are rejected, if there is a hard limit
+    pub remaining_api_calls: Option<u64>,
+    pub soft_limit_exceeded: bool,
+    pub hard_limit_exceeded: bool,
+}
+
+/// API usage of the calling user
+///
+/// Lets client developers see why they are being throttled.
+#[derive(Debug, Clone, Serialize)]
+pub struct CallerUsage {
+    /// API calls made with the caller's tokens
+    pub calls: ApiCallCounters,
+    /// Quota standing of the caller's tenant; verified users are not metered
+    /// against a quota
+    pub quota: Option<QuotaStanding>,
+}
+
+/// Daily API call limits per tenant
+///
+/// `None` disables the corresponding limit.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct QuotaLimits {
+    /// Calls after which responses carry a quota warning
+    pub soft_daily_api_calls: Option<u64>,
+    /// Calls after which requests are rejected
+    pub hard_daily_api_calls: Option<u64>,
+}
+
+/// Result of checking a call against the tenant quota
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QuotaStatus {
+    Ok,
+    SoftLimitExceeded { used: u64, limit: u64 },
+    HardLimitExceeded { limit: u64 },
+}
+
+impl QuotaLimits {
+    /// Standing of a tenant that made the given number of daily calls
+    pub fn standing(&self, hospital_code: String, daily_api_calls: u64) -> QuotaStanding {
+        QuotaStanding {
+            hospital_code,
+            daily_api_calls,
+            soft_daily_api_calls: self.soft_daily_api_calls,
+            hard_daily_api_calls: self.hard_daily_api_calls,
+            remaining_api_calls: self
+                .hard_daily_api_calls
+                .map(|limit| limit.saturating_sub(daily_api_calls)),
+            soft_limit_exceeded: self
+                .soft_daily_api_calls
+                .is_some_and(|limit| daily_api_calls > limit),
+            hard_limit_exceeded: self
+                .hard_daily_api_calls
+                .is_some_and(|limit| daily_api_calls > limit),
+        }
+    }
+
+    /// Classify the given number of daily calls against the limits
+    pub fn check(&self, daily_api_calls: u64) -> QuotaStatus {
+        if let Some(limit) = self.hard_daily_api_calls {
+            if daily_api_calls > limit {
+                return QuotaStatus::HardLimitExceeded { limit };
+            }
+        }
+        if let Some(limit) = self.soft_daily_api_calls {
+            if daily_api_calls > limit {
+                return QuotaStatus::SoftLimitExceeded {
+                    used: daily_api_calls,
+                    limit,
+                };
+            }
+        }
+        QuotaStatus::Ok
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    #[test]
+    fn test_record_call_resets_daily_counter() {
+        let now = Utc::now();
+        let mut usage = TenantUsage::new("H001".to_string(), now);
+        usage.record_call(now);
+        usage.record_call(now);
+        assert_eq!(usage.calls.daily_api_calls, 2);
+
+        usage.record_call(now + Duration::days(1));
+        assert_eq!(usage.calls.daily_api_calls, 1);
+        assert_eq!(usage.calls.total_api_calls, 3);
+        assert_eq!(usage.calls.calls_on(now + Duration::days(2)), 0);
+    }
+
+    #[test]
+    fn test_quota_check() {
+        let limits = QuotaLimits {
+            soft_daily_api_calls: Some(2),
+            hard_daily_api_calls: Some(3),
+        };
+        assert_eq!(limits.check(2), QuotaStatus::Ok);
+        assert_eq!(
+            limits.check(3),
+            QuotaStatus::SoftLimitExceeded { used: 3, limit: 2 }
+        );
+        assert_eq!(limits.check(4), QuotaStatus::HardLimitExceeded { limit: 3 });
+        assert_eq!(QuotaLimits::default().check(u64::MAX), QuotaStatus::Ok);
+
+        let standing = limits.standing("H001".to_string(), 3);
+        assert!(standing.soft_limit_exceeded);
+        assert!(!standing.hard_limit_exceeded);
+        assert_eq!(standing.remaining_api_calls, Some(0));
+    }
+}
@@ -0,0 +1,102 @@
+use axum::{
+    extract::{Path, State},
+    Json,
+};
+
+use crate::features::auth::AuthenticatedUser;
+use crate::features::users::domain::Role;
+use crate::infrastructure::AppError;
+
+use super::domain::{CallerUsage, TenantUsage};
+use super::service::UsageService;
+
+/// List tenant usage handler
+///
+/// Requires the admin role.
+///
+/// # Route
+/// GET /api/v1/admin/usage
+///
+/// # Response
+/// ```json
+/// [
+///   {
+///     "hospital_code": "H001",
+///     "day": "2024-01-01",
+///     "daily_api_calls": 1520,
+///     "total_api_calls": 48210,
+///     "last_seen": "2024-01-01T09:30:00Z"
+///   }
+/// ]
+/// ```
+pub async fn list_tenant_usage(
+    State(usage_service): State<UsageService>,
+    user: AuthenticatedUser,
+) -> Result<Json<Vec<TenantUsage>>, AppError> {
+    require_admin(&user)?;
+    Ok(Json(usage_service.list_usage().await))
+}
+
+/// Get tenant usage handler
+///
+/// Requires the admin role.
+///
+/// # Route
+/// GET /api/v1/admin/usage/:hospital_code
+pub async fn get_tenant_usage(
+    State(usage_service): State<UsageService>,
+    user: AuthenticatedUser,
+    Path(hospital_code): Path<String>,
+) -> Result<Json<TenantUsage>, AppError> {
+    require_admin(&user)?;
+    usage_service
+        .get_usage(&hospital_code)
+        .await
+        .map(Json)
+        .ok_or_else(|| AppError::NotFound(format!("No usage recorded for {}", hospital_code)))
+}
+
+/// Get own usage handler
+///
+/// Requires authentication. Shows the caller's API calls and the quota
+/// standing of their tenant, to help debug throttling.
+///
+/// # Route
+/// GET /api/v1/users/me/usage
+///
+/// # Response
+/// ```json
+/// {
+///   "calls": {
+///     "day": "2024-01-01",
+///     "daily_api_calls": 42,
+///     "total_api_calls": 1380,
+///     "last_seen": "2024-01-01T09:30:00Z"
+///   },
+///   "quota": {
+///     "hospital_code": "H001",
+///     "daily_api_calls": 1520,
+///     "soft_daily_api_calls": 1000,
+///     "hard_daily_api_calls": 2000,
+///     "remaining_api_calls": 480,
+///     "soft_limit_exceeded": true,
+///     "hard_limit_exceeded": false
+///   }
+/// }
+/// ```
+pub async fn get_my_usage(
+    State(usage_service): State<UsageService>,
+    user: AuthenticatedUser,
+) -> Json<CallerUsage> {
+    Json(usage_service.caller_usage(&user.0).await)
+}
+
+fn require_admin(user: &AuthenticatedUser) -> Result<(), AppError> {
+    if user.0.has_role(Role::Admin) {
+        Ok(())
+    } else {
+        Err(AppError::Forbidden(
+            "Only admins can read tenant usage".to_string(),
+        ))
+    }
+}
@@ -0,0 +1,68 @@
+use axum::{
+    extract::{Request, State},
+    http::HeaderValue,
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+
+use crate::features::auth::AuthService;
+use crate::infrastructure::AppError;
+
+use super::domain::QuotaStatus;
+use super::service::UsageService;
+
+/// Usage metering middleware
+///
+/// Counts API calls per caller and per tenant (the hospital code of
+/// anonymous tokens) and enforces the tenant quota. Requests without a
+/// token are not metered; requests with a verified user token are counted
+/// for the caller but not metered against a quota.
+///
+/// - Soft limit exceeded: the request proceeds and the response carries an
+///   `x-quota-warning` header
+/// - Hard limit exceeded: the request is rejected with 429 Too Many Requests
+pub async fn usage_middleware(
+    State((auth_service, usage_service)): State<(AuthService, UsageService)>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let identity = request
+        .headers()
+        .get("Authorization")
+        .and_then(|h| h.to_str().ok())
+        .and_then(|header| auth_service.extract_user_from_header(header).ok());
+
+    let Some(identity) = identity else {
+        return next.run(request).await;
+    };
+    usage_service.record_caller_call(&identity).await;
+
+    let Some(hospital_code) = identity.as_anonymous().map(|id| id.hospital_code.clone()) else {
+        return next.run(request).await;
+    };
+
+    match usage_service.record_api_call(&hospital_code).await {
+        QuotaStatus::Ok => next.run(request).await,
+        QuotaStatus::SoftLimitExceeded { used, limit } => {
+            tracing::warn!(
+                "Tenant {} exceeded soft quota: {} of {} daily API calls",
+                hospital_code,
+                used,
+                limit
+            );
+            let mut response = next.run(request).await;
+            if let Ok(value) = HeaderValue::from_str(&format!(
+                "daily API call quota exceeded ({}/{})",
+                used, limit
+            )) {
+                response.headers_mut().insert("x-quota-warning", value);
+            }
+            response
+        }
+        QuotaStatus::HardLimitExceeded { limit } => AppError::TooManyRequests(format!(
+            "Quota exceeded: tenant {} reached its limit of {} API calls per day",
+            hospital_code, limit
+        ))
+        .into_response(),
+    }
+}
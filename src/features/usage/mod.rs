@@ -0,0 +1,42 @@
+//! Usage Feature Module
+//!
+//! Meters API calls per tenant (hospital code) and per caller, and enforces
+//! daily quotas.
+//!
+//! ## Architecture
+//!
+//! ### Domain Layer (`domain.rs`)
+//! - `ApiCallCounters`: Daily and total API call counters
+//! - `TenantUsage`: Per-tenant usage counters
+//! - `CallerUsage` / `QuotaStanding`: Usage reported to the caller
+//! - `QuotaLimits` / `QuotaStatus`: Soft and hard daily limits
+//!
+//! ### Application Layer (`service.rs`)
+//! - `UsageService`: Counting and quota checks
+//!
+//! ### Presentation Layer (`middleware.rs`, `handler.rs`)
+//! - Metering middleware applied to the API routes
+//! - Admin handlers exposing usage, and a handler for the caller's own usage
+//!
+//! ## Usage
+//! ```rust,ignore
+//! use features::usage;
+//!
+//! let usage_service = usage::UsageService::new(limits);
+//!
+//! api_routes.layer(middleware::from_fn_with_state(
+//!     (auth_service.clone(), usage_service.clone()),
+//!     usage::usage_middleware,
+//! ))
+//! ```
+
+pub mod domain;
+pub mod handler;
+pub mod middleware;
+pub mod service;
+
+// Re-export commonly used items
+pub use domain::{CallerUsage, QuotaLimits, TenantUsage};
+pub use handler::{get_my_usage, get_tenant_usage, list_tenant_usage};
+pub use middleware::usage_middleware;
+pub use service::UsageService;
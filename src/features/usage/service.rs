@@ -0,0 +1,182 @@
+use chrono::Utc;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+
+use crate::features::users::domain::UserIdentity;
+use crate::infrastructure::audit::AuditActor;
+
+use super::domain::{ApiCallCounters, CallerUsage, QuotaLimits, QuotaStatus, TenantUsage};
+
+/// Key of a caller's counters
+///
+/// Anonymous composite keys are hashed so they are not kept in memory.
+fn caller_key(identity: &UserIdentity) -> String {
+    let actor = AuditActor::from(identity);
+    format!("{:x}", Sha256::digest(actor.id.as_bytes()))
+}
+
+/// Usage metering service
+///
+/// Application layer service that counts API calls per tenant and checks
+/// them against the configured quota limits. Calls are also counted per
+/// caller so users can inspect their own usage.
+/// In a real application, counters would live in shared storage so limits
+/// hold across instances.
+#[derive(Clone)]
+pub struct UsageService {
+    usage: Arc<RwLock<HashMap<String, TenantUsage>>>,
+    callers: Arc<RwLock<HashMap<String, ApiCallCounters>>>,
+    limits: QuotaLimits,
+}
+
+impl UsageService {
+    /// Create a new usage service with the given quota limits
+    pub fn new(limits: QuotaLimits) -> Self {
+        Self {
+            usage: Arc::new(RwLock::new(HashMap::new())),
+            callers: Arc::new(RwLock::new(HashMap::new())),
+            limits,
+        }
+    }
+
+    /// Record an API call for a tenant and check its quota
+    ///
+    /// Calls rejected by the hard limit are still counted, so a tenant
+    /// hammering the API stays over the limit until the next day.
+    pub async fn record_api_call(&self, hospital_code: &str) -> QuotaStatus {
+        let now = Utc::now();
+        let mut usage = self.usage.write().await;
+        let tenant = usage
+            .entry(hospital_code.to_string())
+            .or_insert_with(|| TenantUsage::new(hospital_code.to_string(), now));
+        tenant.record_call(now);
+
+        self.limits.check(tenant.calls.daily_api_calls)
+    }
+
+    /// Record an API call made by an authenticated caller
+    pub async fn record_caller_call(&self, identity: &UserIdentity) {
+        let now = Utc::now();
+        let mut callers = self.callers.write().await;
+        callers
+            .entry(caller_key(identity))
+            .or_insert_with(|| ApiCallCounters::new(now))
+            .record_call(now);
+    }
+
+    /// Get the API usage of a caller and the quota standing of its tenant
+    pub async fn caller_usage(&self, identity: &UserIdentity) -> CallerUsage {
+        let now = Utc::now();
+        let calls = self
+            .callers
+            .read()
+            .await
+            .get(&caller_key(identity))
+            .cloned()
+            .unwrap_or_else(|| ApiCallCounters::new(now));
+
+        let quota = match identity.as_anonymous() {
+            Some(identifier) => {
+                let daily_api_calls = self
+                    .get_usage(&identifier.hospital_code)
+                    .await
+                    .map(|tenant| tenant.calls.calls_on(now))
+                    .unwrap_or(0);
+                Some(
+                    self.limits
+                        .standing(identifier.hospital_code.clone(), daily_api_calls),
+                )
+            }
+            None => None,
+        };
+
+        CallerUsage { calls, quota }
+    }
+
+    /// Get usage of a single tenant
+    pub async fn get_usage(&self, hospital_code: &str) -> Option<TenantUsage> {
+        let usage = self.usage.read().await;
+        usage.get(hospital_code).cloned()
+    }
+
+    /// List usage of all tenants, ordered by hospital code
+    pub async fn list_usage(&self) -> Vec<TenantUsage> {
+        let usage = self.usage.read().await;
+        let mut tenants: Vec<TenantUsage> = usage.values().cloned().collect();
+        tenants.sort_by(|a, b| a.hospital_code.cmp(&b.hospital_code));
+        tenants
+    }
+}
+
+impl Default for UsageService {
+    fn default() -> Self {
+        Self::new(QuotaLimits::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::features::users::domain::{AnonymousUserIdentifier, Role, VerifiedUser};
+    use chrono::NaiveDate;
+
+    #[tokio::test]
+    async fn test_record_api_call() {
+        let service = UsageService::new(QuotaLimits {
+            soft_daily_api_calls: Some(1),
+            hard_daily_api_calls: Some(2),
+        });
+
+        assert_eq!(service.record_api_call("H001").await, QuotaStatus::Ok);
+        assert!(matches!(
+            service.record_api_call("H001").await,
+            QuotaStatus::SoftLimitExceeded { .. }
+        ));
+        assert!(matches!(
+            service.record_api_call("H001").await,
+            QuotaStatus::HardLimitExceeded { .. }
+        ));
+        assert_eq!(service.record_api_call("H002").await, QuotaStatus::Ok);
+
+        let usage = service.get_usage("H001").await.unwrap();
+        assert_eq!(usage.calls.daily_api_calls, 3);
+        assert_eq!(service.list_usage().await.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_caller_usage() {
+        let service = UsageService::new(QuotaLimits {
+            soft_daily_api_calls: None,
+            hard_daily_api_calls: Some(5),
+        });
+        let caller = UserIdentity::Anonymous(AnonymousUserIdentifier {
+            hospital_code: "H001".to_string(),
+            user_id: "U001".to_string(),
+            user_start_date: NaiveDate::from_ymd_opt(2024, 1, 1).unwrap(),
+            department_code: "D001".to_string(),
+        });
+
+        service.record_caller_call(&caller).await;
+        service.record_api_call("H001").await;
+        service.record_api_call("H001").await;
+
+        let usage = service.caller_usage(&caller).await;
+        assert_eq!(usage.calls.daily_api_calls, 1);
+        let quota = usage.quota.unwrap();
+        assert_eq!(quota.daily_api_calls, 2);
+        assert_eq!(quota.remaining_api_calls, Some(3));
+
+        // Verified users are not metered against a tenant quota
+        let user = UserIdentity::Verified(VerifiedUser {
+            id: 1,
+            username: "admin".to_string(),
+            email: "admin@example.com".to_string(),
+            role: Role::Member,
+        });
+        let usage = service.caller_usage(&user).await;
+        assert_eq!(usage.calls.total_api_calls, 0);
+        assert!(usage.quota.is_none());
+    }
+}
@@ -1,6 +1,9 @@
 use serde::{Deserialize, Serialize};
 use chrono::NaiveDate;
 
+use crate::infrastructure::audit::AuditActor;
+use crate::infrastructure::validation::{Validate, ValidationErrors};
+
 /// Anonymous User Identifier
 ///
 /// Unique identifier for anonymous users based on composite key:
@@ -13,30 +16,56 @@ pub struct AnonymousUserIdentifier {
     pub department_code: String,
 }
 
-impl AnonymousUserIdentifier {
+impl Validate for AnonymousUserIdentifier {
     /// Validate anonymous user identifier
-    pub fn validate(&self) -> Result<(), String> {
-        if self.hospital_code.is_empty() {
-            return Err("Hospital code cannot be empty".to_string());
-        }
-        if self.user_id.is_empty() {
-            return Err("User ID cannot be empty".to_string());
-        }
-        if self.department_code.is_empty() {
-            return Err("Department code cannot be empty".to_string());
-        }
-        Ok(())
+    fn validate(&self) -> Result<(), ValidationErrors> {
+        let mut errors = ValidationErrors::new();
+        errors.check(
+            !self.hospital_code.is_empty(),
+            "hospital_code",
+            "Hospital code cannot be empty",
+        );
+        errors.check(
+            !self.user_id.is_empty(),
+            "user_id",
+            "User ID cannot be empty",
+        );
+        errors.check(
+            !self.department_code.is_empty(),
+            "department_code",
+            "Department code cannot be empty",
+        );
+        errors.into_result()
     }
 }
 
+/// User role, ordered by privilege
+///
+/// Anonymous users always have the `Anonymous` role. Verified users are
+/// members unless they are moderators or admins. Admins can do everything
+/// moderators can.
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum Role {
+    Anonymous,
+    #[default]
+    Member,
+    Moderator,
+    Admin,
+}
+
 /// Verified User domain model
 ///
 /// Represents an authenticated user with credentials.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct VerifiedUser {
     pub id: u64,
     pub username: String,
     pub email: String,
+    #[serde(default)]
+    pub role: Role,
 }
 
 /// User Identity
@@ -60,6 +89,19 @@ impl UserIdentity {
         matches!(self, UserIdentity::Anonymous(_))
     }
 
+    /// Role of the user
+    pub fn role(&self) -> Role {
+        match self {
+            UserIdentity::Verified(user) => user.role,
+            UserIdentity::Anonymous(_) => Role::Anonymous,
+        }
+    }
+
+    /// Check if the user has at least the given role
+    pub fn has_role(&self, role: Role) -> bool {
+        self.role() >= role
+    }
+
     /// Get verified user if available
     pub fn as_verified(&self) -> Option<&VerifiedUser> {
         match self {
@@ -77,6 +119,24 @@ impl UserIdentity {
     }
 }
 
+impl From<&UserIdentity> for AuditActor {
+    fn from(identity: &UserIdentity) -> Self {
+        match identity {
+            UserIdentity::Verified(user) => AuditActor::verified(user.id),
+            UserIdentity::Anonymous(identifier) => AuditActor::anonymous(
+                &format!(
+                    "{}/{}/{}/{}",
+                    identifier.hospital_code,
+                    identifier.user_id,
+                    identifier.user_start_date,
+                    identifier.department_code
+                ),
+                identifier.hospital_code.clone(),
+            ),
+        }
+    }
+}
+
 /// Legacy User domain model (kept for backward compatibility)
 ///
 /// Core business entity representing a user in the system.
@@ -87,6 +147,16 @@ pub struct User {
     pub email: String,
 }
 
+impl From<VerifiedUser> for User {
+    fn from(user: VerifiedUser) -> Self {
+        Self {
+            id: user.id,
+            username: user.username,
+            email: user.email,
+        }
+    }
+}
+
 /// Request payload for creating a user
 ///
 /// Value object for user creation with built-in validation.
@@ -96,24 +166,27 @@ pub struct CreateUserRequest {
     pub email: String,
 }
 
-impl CreateUserRequest {
+impl Validate for CreateUserRequest {
     /// Validate user creation request
     ///
     /// Enforces business rules:
     /// - Username must not be empty
     /// - Username must be at least 3 characters
     /// - Email must contain '@' symbol
-    pub fn validate(&self) -> Result<(), String> {
-        if self.username.is_empty() {
-            return Err("Username cannot be empty".to_string());
-        }
-        if self.username.len() < 3 {
-            return Err("Username must be at least 3 characters".to_string());
-        }
-        if !self.email.contains('@') {
-            return Err("Invalid email format".to_string());
-        }
-        Ok(())
+    fn validate(&self) -> Result<(), ValidationErrors> {
+        let mut errors = ValidationErrors::new();
+        validate_username(&mut errors, &self.username);
+        errors.check(self.email.contains('@'), "email", "Invalid email format");
+        errors.into_result()
+    }
+}
+
+/// Check that a username is present and at least 3 characters long
+pub fn validate_username(errors: &mut ValidationErrors, username: &str) {
+    if username.is_empty() {
+        errors.add("username", "Username cannot be empty");
+    } else if username.len() < 3 {
+        errors.add("username", "Username must be at least 3 characters");
     }
 }
 
@@ -176,12 +249,28 @@ mod tests {
         assert!(invalid_identifier.validate().is_err());
     }
 
+    #[test]
+    fn test_errors_of_all_fields_collected() {
+        let request = CreateUserRequest {
+            username: "ab".to_string(),
+            email: "invalid".to_string(),
+        };
+        let errors = request.validate().unwrap_err();
+        let fields: Vec<&str> = errors
+            .errors()
+            .iter()
+            .map(|error| error.field.as_str())
+            .collect();
+        assert_eq!(fields, ["username", "email"]);
+    }
+
     #[test]
     fn test_user_identity_verified() {
         let verified = UserIdentity::Verified(VerifiedUser {
             id: 1,
             username: "john".to_string(),
             email: "john@example.com".to_string(),
+            role: Role::Member,
         });
 
         assert!(verified.is_verified());
@@ -5,9 +5,13 @@ use axum::{
 };
 use serde::Deserialize;
 
-use crate::infrastructure::AppError;
+use crate::features::auth::{
+    ActionTokenHeader, AuthService, AuthenticatedUser, DELETE_USER_ACTION,
+};
+use crate::features::jsonrpc::{JsonRpcService, MethodPolicy, RpcContext};
+use crate::infrastructure::{AppError, AuditContext, JsonBody};
 
-use super::domain::{CreateUserRequest, User};
+use super::domain::{CreateUserRequest, Role, User};
 use super::service::UserService;
 
 /// Query parameters for list users endpoint
@@ -19,6 +23,7 @@ pub struct ListUsersQuery {
 /// List users handler
 ///
 /// Presentation layer handler for listing users with optional pagination.
+/// Requires the admin role.
 ///
 /// # Route
 /// GET /api/v1/users?limit=10
@@ -40,7 +45,8 @@ pub async fn list_users(
 
 /// Create user handler
 ///
-/// Presentation layer handler for creating a new user.
+/// Presentation layer handler for creating a new user. Requires the admin
+/// role.
 ///
 /// # Route
 /// POST /api/v1/users
@@ -64,15 +70,17 @@ pub async fn list_users(
 /// ```
 pub async fn create_user(
     State(user_service): State<UserService>,
-    Json(payload): Json<CreateUserRequest>,
+    audit: AuditContext,
+    JsonBody(payload): JsonBody<CreateUserRequest>,
 ) -> Result<(StatusCode, Json<User>), AppError> {
-    let user = user_service.create_user(payload).await?;
+    let user = user_service.create_user(payload, &audit).await?;
     Ok((StatusCode::CREATED, Json(user)))
 }
 
 /// Get user by ID handler
 ///
-/// Presentation layer handler for retrieving a specific user.
+/// Presentation layer handler for retrieving a specific user. Requires the
+/// admin role.
 ///
 /// # Route
 /// GET /api/v1/users/:id
@@ -92,3 +100,232 @@ pub async fn get_user(
     let user = user_service.get_user(id).await?;
     Ok(Json(user))
 }
+
+/// Delete user handler
+///
+/// Presentation layer handler for deleting a user. Requires the admin role
+/// and an action token minted for `users.delete`.
+///
+/// # Route
+/// DELETE /api/v1/users/:id
+/// X-Action-Token: <token>
+///
+/// # Response
+/// 204 No Content
+pub async fn delete_user(
+    State(user_service): State<UserService>,
+    State(auth_service): State<AuthService>,
+    user: AuthenticatedUser,
+    ActionTokenHeader(action_token): ActionTokenHeader,
+    Path(id): Path<u64>,
+    audit: AuditContext,
+) -> Result<StatusCode, AppError> {
+    auth_service.consume_action_token(&user.0, DELETE_USER_ACTION, &action_token)?;
+    user_service.delete_user(id, &audit).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Parameters of the `users.delete` method
+#[derive(Deserialize)]
+pub struct DeleteUserParams {
+    id: u64,
+    /// Action token minted for `users.delete`
+    action_token: String,
+}
+
+/// Register the JSON-RPC methods of the users feature
+///
+/// All methods are reserved to admins.
+///
+/// # Methods
+/// - `users.delete` `{"id": 5, "action_token": "<token>"}`: delete a user,
+///   confirmed with an action token like `DELETE /api/v1/users/:id`
+pub fn register_rpc_methods(
+    jsonrpc_service: &JsonRpcService,
+    user_service: UserService,
+    auth_service: AuthService,
+) {
+    let users = jsonrpc_service
+        .namespace("users")
+        .with_policy(MethodPolicy::Role(Role::Admin));
+    users.register_typed_method(
+        "delete",
+        move |params: DeleteUserParams, context: RpcContext| {
+            let user_service = user_service.clone();
+            let auth_service = auth_service.clone();
+            async move {
+                auth_service.consume_action_token(
+                    context.authenticated()?,
+                    DELETE_USER_ACTION,
+                    &params.action_token,
+                )?;
+                user_service
+                    .delete_user(params.id, &context.audit_context())
+                    .await?;
+                Ok(())
+            }
+        },
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::features::auth::{
+        auth_middleware, CreateActionTokenRequest, UserCredentials, DELETE_BOARD_ACTION,
+    };
+    use crate::features::jsonrpc::{JsonRpcRequest, RpcError};
+    use crate::features::users::domain::{UserIdentity, VerifiedUser};
+    use axum::{body::Body, extract::FromRef, http::Request, middleware, routing::delete, Router};
+    use serde_json::json;
+    use tower::util::ServiceExt;
+
+    #[derive(Clone)]
+    struct TestState {
+        user_service: UserService,
+        auth_service: AuthService,
+    }
+
+    impl FromRef<TestState> for UserService {
+        fn from_ref(state: &TestState) -> Self {
+            state.user_service.clone()
+        }
+    }
+
+    impl FromRef<TestState> for AuthService {
+        fn from_ref(state: &TestState) -> Self {
+            state.auth_service.clone()
+        }
+    }
+
+    /// Services with a seeded admin and the members `user1` to `user3`
+    async fn services() -> (UserService, AuthService, UserIdentity) {
+        let admin = VerifiedUser {
+            id: 1,
+            username: "admin".to_string(),
+            email: "admin@example.com".to_string(),
+            role: Role::Admin,
+        };
+        let auth_service = AuthService::new("secret".to_string());
+        auth_service
+            .seed_credentials(vec![UserCredentials {
+                user: admin.clone(),
+                password_hash: String::new(),
+            }])
+            .await;
+        let user_service = UserService::default().with_auth_service(auth_service.clone());
+        for i in 1..=3 {
+            let request = CreateUserRequest {
+                username: format!("user{}", i),
+                email: format!("user{}@example.com", i),
+            };
+            user_service
+                .create_user(request, &AuditContext::default())
+                .await
+                .unwrap();
+        }
+        (user_service, auth_service, UserIdentity::Verified(admin))
+    }
+
+    fn action_token(auth_service: &AuthService, identity: &UserIdentity, action: &str) -> String {
+        let request = CreateActionTokenRequest {
+            action: action.to_string(),
+        };
+        auth_service
+            .issue_action_token(identity, request)
+            .unwrap()
+            .token
+    }
+
+    #[tokio::test]
+    async fn test_delete_user_requires_action_token() {
+        let (user_service, auth_service, admin) = services().await;
+        let UserIdentity::Verified(verified) = &admin else {
+            unreachable!()
+        };
+        let bearer = format!(
+            "Bearer {}",
+            auth_service
+                .generate_verified_user_token(verified, None)
+                .unwrap()
+        );
+        let app = Router::new()
+            .route(
+                "/users/:id",
+                delete(delete_user).layer(middleware::from_fn_with_state(
+                    auth_service.clone(),
+                    auth_middleware,
+                )),
+            )
+            .with_state(TestState {
+                user_service: user_service.clone(),
+                auth_service: auth_service.clone(),
+            });
+        let delete = |id: u64, action_token: Option<String>| {
+            let mut request = Request::builder()
+                .uri(format!("/users/{}", id))
+                .method("DELETE")
+                .header("authorization", &bearer);
+            if let Some(action_token) = action_token {
+                request = request.header("x-action-token", action_token);
+            }
+            app.clone().oneshot(request.body(Body::empty()).unwrap())
+        };
+
+        // Missing tokens and tokens minted for another action are rejected
+        assert_eq!(
+            delete(2, None).await.unwrap().status(),
+            StatusCode::FORBIDDEN
+        );
+        let token = action_token(&auth_service, &admin, DELETE_BOARD_ACTION);
+        assert_eq!(
+            delete(2, Some(token)).await.unwrap().status(),
+            StatusCode::FORBIDDEN
+        );
+
+        // A token confirms a single deletion
+        let token = action_token(&auth_service, &admin, DELETE_USER_ACTION);
+        assert_eq!(
+            delete(2, Some(token.clone())).await.unwrap().status(),
+            StatusCode::NO_CONTENT
+        );
+        assert_eq!(
+            delete(3, Some(token)).await.unwrap().status(),
+            StatusCode::FORBIDDEN
+        );
+        assert!(user_service.get_user(2).await.is_err());
+        assert!(user_service.get_user(3).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_delete_user_rpc_requires_action_token() {
+        let (user_service, auth_service, admin) = services().await;
+        let jsonrpc_service = JsonRpcService::new();
+        register_rpc_methods(&jsonrpc_service, user_service.clone(), auth_service.clone());
+        let context = RpcContext::new(Some(admin.clone()));
+        let call = |id: u64, action_token: &str| {
+            let request = JsonRpcRequest::new(
+                "users.delete".to_string(),
+                Some(json!({"id": id, "action_token": action_token})),
+                Some(json!(1)),
+            );
+            let jsonrpc_service = jsonrpc_service.clone();
+            let context = context.clone();
+            async move { jsonrpc_service.handle_request(request, &context).await }
+        };
+
+        let token = action_token(&auth_service, &admin, DELETE_USER_ACTION);
+        assert!(matches!(call(2, &token).await, Some(Ok(_))));
+        assert!(user_service.get_user(2).await.is_err());
+
+        // Used tokens are rejected as forbidden
+        let Some(Err(response)) = call(3, &token).await else {
+            panic!("expected an error response");
+        };
+        assert_eq!(
+            response.error.code,
+            RpcError::Forbidden(String::new()).code()
+        );
+        assert!(user_service.get_user(3).await.is_ok());
+    }
+}
@@ -1,121 +1,194 @@
-use std::sync::atomic::{AtomicU64, Ordering};
-use std::sync::Arc;
+use crate::features::auth::{AuthService, UserRepository};
+use crate::infrastructure::{
+    AppError, AuditContext, AuditLog, AuditOperation, HoldKind, LegalHolds, Validate,
+};
 
-use crate::infrastructure::AppError;
-
-use super::domain::{CreateUserRequest, User};
+use super::domain::{CreateUserRequest, Role, User};
 
 /// User service containing business logic
 ///
-/// Application layer service that orchestrates user-related operations.
-/// In a real application, this would interact with a database repository.
+/// Application layer service that orchestrates user-related operations on
+/// the users registered with the auth service.
 #[derive(Clone)]
 pub struct UserService {
-    next_id: Arc<AtomicU64>,
+    users: UserRepository,
+    /// Auth service the users are registered with, if any
+    auth_service: Option<AuthService>,
+    audit_log: AuditLog,
+    legal_holds: LegalHolds,
 }
 
 impl UserService {
-    /// Create a new user service
-    pub fn new() -> Self {
+    /// Create a new user service recording writes into the given audit log
+    pub fn new(audit_log: AuditLog) -> Self {
         Self {
-            next_id: Arc::new(AtomicU64::new(1)),
+            users: UserRepository::new(),
+            auth_service: None,
+            legal_holds: LegalHolds::new(audit_log.clone()),
+            audit_log,
         }
     }
 
+    /// Manage the users in the given repository, e.g. the one of the auth
+    /// service
+    pub fn with_user_repository(mut self, users: UserRepository) -> Self {
+        self.users = users;
+        self
+    }
+
+    /// Manage the users registered with the given auth service
+    ///
+    /// Deleted users lose their refresh tokens, so they cannot renew their
+    /// access tokens.
+    pub fn with_auth_service(mut self, auth_service: AuthService) -> Self {
+        self.users = auth_service.user_repository();
+        self.auth_service = Some(auth_service);
+        self
+    }
+
+    /// Refuse to delete users under these legal holds
+    pub fn with_legal_holds(mut self, legal_holds: LegalHolds) -> Self {
+        self.legal_holds = legal_holds;
+        self
+    }
+
     /// Create a new user
     ///
     /// # Business Logic
     /// 1. Validate the request
-    /// 2. Generate a unique ID
-    /// 3. Create the user entity
-    /// 4. (In real app: persist to database)
-    /// 5. Return the created user
-    pub async fn create_user(&self, request: CreateUserRequest) -> Result<User, AppError> {
+    /// 2. Store the user as a member with the next free ID
+    /// 3. Record the creation in the audit log
+    /// 4. Return the created user
+    ///
+    /// Usernames and emails must be unique, as when registering. The user
+    /// has no password, so it cannot log in.
+    pub async fn create_user(
+        &self,
+        request: CreateUserRequest,
+        audit: &AuditContext,
+    ) -> Result<User, AppError> {
         // Validate request
-        request
-            .validate()
-            .map_err(|msg| AppError::BadRequest(msg))?;
-
-        // Generate unique ID
-        let id = self.next_id.fetch_add(1, Ordering::SeqCst);
-
-        // Create user (in real app, this would save to database)
-        let user = User {
-            id,
-            username: request.username,
-            email: request.email,
-        };
+        request.validate()?;
+
+        let user = User::from(
+            self.users
+                .insert(request.username, request.email, Role::Member, String::new())
+                .await?,
+        );
 
         tracing::info!("Created user: {:?}", user);
+        self.audit_log
+            .record(
+                audit,
+                AuditOperation::Create,
+                "user",
+                user.id,
+                None,
+                serde_json::to_value(&user).ok(),
+            )
+            .await;
+
         Ok(user)
     }
 
     /// Get user by ID
-    ///
-    /// # Business Logic
-    /// 1. Validate the ID
-    /// 2. (In real app: fetch from database)
-    /// 3. Return the user or error if not found
     pub async fn get_user(&self, id: u64) -> Result<User, AppError> {
-        // In real app, fetch from database
-        // For demo, return mock user or error
-        if id == 0 {
-            return Err(AppError::BadRequest("Invalid user ID".to_string()));
-        }
-
-        if id > 100 {
-            return Err(AppError::NotFound(format!("User {} not found", id)));
-        }
-
-        Ok(User {
-            id,
-            username: format!("user{}", id),
-            email: format!("user{}@example.com", id),
-        })
+        self.users
+            .find(id)
+            .await
+            .map(User::from)
+            .ok_or_else(|| AppError::NotFound(format!("User {} not found", id)))
     }
 
-    /// List all users (paginated)
+    /// List users by ID
     ///
     /// # Business Logic
     /// 1. Validate and apply limit (max 100 items)
-    /// 2. (In real app: fetch from database with pagination)
-    /// 3. Return the list of users
+    /// 2. Return the users with the lowest IDs
     pub async fn list_users(&self, limit: Option<usize>) -> Result<Vec<User>, AppError> {
         let limit = limit.unwrap_or(10).min(100); // Max 100 items
 
-        // In real app, fetch from database with pagination
-        // For demo, return mock data
-        let users: Vec<User> = (1..=limit)
-            .map(|i| User {
-                id: i as u64,
-                username: format!("user{}", i),
-                email: format!("user{}@example.com", i),
-            })
-            .collect();
+        Ok(self
+            .users
+            .list()
+            .await
+            .into_iter()
+            .take(limit)
+            .map(|credentials| User::from(credentials.user))
+            .collect())
+    }
+
+    /// Delete a user
+    ///
+    /// # Business Logic
+    /// 1. Look up the user
+    /// 2. Refuse users under legal hold
+    /// 3. Remove the user with its credentials and refresh tokens
+    /// 4. Record the deletion in the audit log
+    pub async fn delete_user(&self, id: u64, audit: &AuditContext) -> Result<(), AppError> {
+        self.get_user(id).await?;
+        self.legal_holds.ensure_not_held(HoldKind::User, id)?;
+        let user = User::from(
+            self.users
+                .remove(id)
+                .await
+                .ok_or_else(|| AppError::NotFound(format!("User {} not found", id)))?,
+        );
+        if let Some(auth_service) = &self.auth_service {
+            auth_service.revoke_refresh_tokens(id).await;
+        }
 
-        Ok(users)
+        tracing::info!("Deleted user: {:?}", user);
+        self.audit_log
+            .record(
+                audit,
+                AuditOperation::Delete,
+                "user",
+                user.id,
+                serde_json::to_value(&user).ok(),
+                None,
+            )
+            .await;
+
+        Ok(())
     }
 }
 
 impl Default for UserService {
     fn default() -> Self {
-        Self::new()
+        Self::new(AuditLog::default())
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::features::auth::domain::{ClientFingerprint, LoginRequest, RegisterRequest};
+    use crate::infrastructure::legal_hold::PlaceLegalHoldRequest;
+
+    /// Create users named `user1`, `user2`, ... with ids 1, 2, ...
+    async fn create_users(service: &UserService, count: usize) {
+        for i in 1..=count {
+            let request = CreateUserRequest {
+                username: format!("user{}", i),
+                email: format!("user{}@example.com", i),
+            };
+            service
+                .create_user(request, &AuditContext::default())
+                .await
+                .unwrap();
+        }
+    }
 
     #[tokio::test]
     async fn test_create_user_success() {
-        let service = UserService::new();
+        let service = UserService::default();
         let request = CreateUserRequest {
             username: "testuser".to_string(),
             email: "test@example.com".to_string(),
         };
 
-        let result = service.create_user(request).await;
+        let result = service.create_user(request, &AuditContext::default()).await;
         assert!(result.is_ok());
 
         let user = result.unwrap();
@@ -123,39 +196,181 @@ mod tests {
         assert_eq!(user.email, "test@example.com");
     }
 
+    #[tokio::test]
+    async fn test_create_user_is_audited() {
+        let audit_log = AuditLog::default();
+        let service = UserService::new(audit_log.clone());
+        let request = CreateUserRequest {
+            username: "testuser".to_string(),
+            email: "test@example.com".to_string(),
+        };
+
+        let user = service
+            .create_user(request, &AuditContext::default())
+            .await
+            .unwrap();
+
+        let entries = audit_log.search(&Default::default()).await;
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].operation, AuditOperation::Create);
+        assert_eq!(entries[0].resource_id, user.id.to_string());
+    }
+
     #[tokio::test]
     async fn test_create_user_invalid() {
-        let service = UserService::new();
+        let service = UserService::default();
         let request = CreateUserRequest {
             username: "ab".to_string(), // Too short
             email: "test@example.com".to_string(),
         };
 
-        let result = service.create_user(request).await;
+        let result = service.create_user(request, &AuditContext::default()).await;
         assert!(result.is_err());
     }
 
+    #[tokio::test]
+    async fn test_create_user_duplicate() {
+        let service = UserService::default();
+        create_users(&service, 1).await;
+
+        for (username, email, field) in [
+            ("user1", "other@example.com", "username"),
+            ("other", "USER1@example.com", "email"),
+        ] {
+            let request = CreateUserRequest {
+                username: username.to_string(),
+                email: email.to_string(),
+            };
+            match service.create_user(request, &AuditContext::default()).await {
+                Err(AppError::Conflict {
+                    field: conflict, ..
+                }) => assert_eq!(conflict, field),
+                other => panic!("expected a conflict on {}, got {:?}", field, other),
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_users_are_shared_with_the_repository() {
+        let users = UserRepository::new();
+        let service = UserService::default().with_user_repository(users.clone());
+        create_users(&service, 1).await;
+
+        // Users created here are registered users, without a password
+        let stored = users.find_by_username("user1").await.unwrap();
+        assert_eq!(stored.user.role, Role::Member);
+        assert!(stored.password_hash.is_empty());
+        assert_eq!(
+            service.get_user(stored.user.id).await.unwrap().username,
+            "user1"
+        );
+    }
+
     #[tokio::test]
     async fn test_get_user_valid() {
-        let service = UserService::new();
+        let service = UserService::default();
+        create_users(&service, 5).await;
         let result = service.get_user(5).await;
         assert!(result.is_ok());
     }
 
     #[tokio::test]
     async fn test_get_user_not_found() {
-        let service = UserService::new();
+        let service = UserService::default();
         let result = service.get_user(999).await;
-        assert!(result.is_err());
+        assert!(matches!(result, Err(AppError::NotFound(_))));
     }
 
     #[tokio::test]
     async fn test_list_users() {
-        let service = UserService::new();
+        let service = UserService::default();
+        create_users(&service, 7).await;
         let result = service.list_users(Some(5)).await;
         assert!(result.is_ok());
 
         let users = result.unwrap();
         assert_eq!(users.len(), 5);
+        assert!(users.iter().map(|user| user.id).eq(1..=5));
+    }
+
+    #[tokio::test]
+    async fn test_delete_user_is_audited() {
+        let audit_log = AuditLog::default();
+        let service = UserService::new(audit_log.clone());
+        create_users(&service, 5).await;
+
+        service
+            .delete_user(5, &AuditContext::default())
+            .await
+            .unwrap();
+        assert!(service
+            .delete_user(5, &AuditContext::default())
+            .await
+            .is_err());
+        assert!(service.get_user(5).await.is_err());
+
+        let entries = audit_log.search(&Default::default()).await;
+        assert_eq!(entries.len(), 6);
+        assert_eq!(entries[0].operation, AuditOperation::Delete);
+        assert!(entries[0].before.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_deleted_user_cannot_refresh() {
+        let auth_service = AuthService::new("secret".to_string()).with_password_hash_cost(4);
+        let service = UserService::default().with_auth_service(auth_service.clone());
+        let user = auth_service
+            .register(RegisterRequest {
+                username: "testuser".to_string(),
+                email: "test@example.com".to_string(),
+                password: "password123".to_string(),
+            })
+            .await
+            .unwrap();
+        let login = LoginRequest {
+            username: "testuser".to_string(),
+            password: "password123".to_string(),
+        };
+        let client = ClientFingerprint::new(Some("laptop"), None, None);
+        let refresh_token = auth_service
+            .login(login, Some(&client))
+            .await
+            .unwrap()
+            .refresh_token
+            .unwrap();
+
+        service
+            .delete_user(user.id, &AuditContext::default())
+            .await
+            .unwrap();
+        assert!(matches!(
+            auth_service.refresh(&refresh_token, &client).await,
+            Err(AppError::Unauthorized(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_delete_user_under_legal_hold() {
+        let legal_holds = LegalHolds::default();
+        let service = UserService::default().with_legal_holds(legal_holds.clone());
+        create_users(&service, 5).await;
+        let request = PlaceLegalHoldRequest {
+            kind: HoldKind::User,
+            id: 5,
+            reason: "Case 2024-118".to_string(),
+        };
+        legal_holds
+            .place(request, &AuditContext::default())
+            .await
+            .unwrap();
+
+        assert!(matches!(
+            service.delete_user(5, &AuditContext::default()).await,
+            Err(AppError::Forbidden(_))
+        ));
+        assert!(service
+            .delete_user(4, &AuditContext::default())
+            .await
+            .is_ok());
     }
 }
@@ -0,0 +1,532 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use tokio::sync::RwLock;
+
+use super::geoip::GeoLocation;
+
+/// Kind of write operation recorded in the audit log
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AuditOperation {
+    Create,
+    Update,
+    Delete,
+}
+
+const ANONYMOUS_ACTOR_PREFIX: &str = "anonymous:";
+const PSEUDONYM_ACTOR_PREFIX: &str = "pseudonym:";
+
+/// Hex-encoded SHA-256 hash of `value` salted with `salt`
+fn salted_hash(salt: &str, value: &str) -> String {
+    let digest = Sha256::new()
+        .chain_update(salt.as_bytes())
+        .chain_update(value.as_bytes())
+        .finalize();
+    format!("{:x}", digest)
+}
+
+/// Who performed an audited operation
+///
+/// `tenant` is the hospital code for anonymous users and `None` for
+/// verified users, which are not bound to a hospital.
+///
+/// Anonymous actors carry the raw composite key only while a request is
+/// being handled; the audit log hashes it before storing the entry.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AuditActor {
+    pub id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tenant: Option<String>,
+}
+
+impl AuditActor {
+    /// Actor for a verified user
+    pub fn verified(user_id: u64) -> Self {
+        Self {
+            id: format!("user:{}", user_id),
+            tenant: None,
+        }
+    }
+
+    /// Actor for an anonymous user identified by its composite key
+    pub fn anonymous(composite_key: &str, tenant: String) -> Self {
+        Self {
+            id: format!("{}{}", ANONYMOUS_ACTOR_PREFIX, composite_key),
+            tenant: Some(tenant),
+        }
+    }
+
+    /// Check if the actor is an anonymous user that has not been pseudonymized
+    pub fn is_anonymous(&self) -> bool {
+        self.id.starts_with(ANONYMOUS_ACTOR_PREFIX)
+    }
+
+    /// Replace the anonymous composite key with a hash salted per tenant
+    ///
+    /// The tenant salt is derived from `secret` and the hospital code, so the
+    /// same person always maps to the same id within a hospital while ids of
+    /// different hospitals cannot be correlated. Non-anonymous actors are
+    /// untouched.
+    fn hash_identifier(&mut self, secret: &str) {
+        let (Some(composite_key), Some(tenant)) =
+            (self.id.strip_prefix(ANONYMOUS_ACTOR_PREFIX), &self.tenant)
+        else {
+            return;
+        };
+
+        let tenant_salt = salted_hash(secret, tenant);
+        self.id = format!(
+            "{}{}",
+            ANONYMOUS_ACTOR_PREFIX,
+            salted_hash(&tenant_salt, composite_key)
+        );
+    }
+
+    /// Replace the anonymous identifier with a hash under a retention salt
+    ///
+    /// The hash is deterministic so entries of the same person can still be
+    /// correlated after pseudonymization. Non-anonymous actors are untouched.
+    pub fn pseudonymize(&mut self, salt: &str) {
+        let Some(identifier) = self.id.strip_prefix(ANONYMOUS_ACTOR_PREFIX) else {
+            return;
+        };
+
+        self.id = format!(
+            "{}{}",
+            PSEUDONYM_ACTOR_PREFIX,
+            salted_hash(salt, identifier)
+        );
+    }
+}
+
+/// Request-scoped information attached to every audit entry
+///
+/// Built by the presentation layer from the authenticated user, the
+/// `x-request-id` header and the client location, then handed to services
+/// performing writes.
+#[derive(Debug, Clone, Default)]
+pub struct AuditContext {
+    pub actor: Option<AuditActor>,
+    pub request_id: Option<String>,
+    pub location: Option<GeoLocation>,
+    /// Id (`jti`) of the access token the request was authenticated with
+    pub token_id: Option<String>,
+}
+
+/// A single audit log entry
+#[derive(Debug, Clone, Serialize)]
+pub struct AuditEntry {
+    pub id: u64,
+    pub timestamp: DateTime<Utc>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub actor: Option<AuditActor>,
+    pub operation: AuditOperation,
+    pub resource_type: String,
+    pub resource_id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub before: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub after: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub request_id: Option<String>,
+    /// Location of the client that made the request
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub location: Option<GeoLocation>,
+    /// Id of the access token the request was authenticated with
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub token_id: Option<String>,
+}
+
+/// Filter used to search the audit log
+///
+/// All fields are optional; unset fields match every entry.
+#[derive(Debug, Default, Deserialize)]
+pub struct AuditQuery {
+    pub actor: Option<String>,
+    pub tenant: Option<String>,
+    pub operation: Option<AuditOperation>,
+    pub resource_type: Option<String>,
+    pub resource_id: Option<String>,
+    pub request_id: Option<String>,
+    pub token_id: Option<String>,
+    pub since: Option<DateTime<Utc>>,
+    pub limit: Option<usize>,
+}
+
+impl AuditQuery {
+    fn matches(&self, entry: &AuditEntry) -> bool {
+        let actor = entry.actor.as_ref();
+
+        self.actor
+            .as_ref()
+            .is_none_or(|id| actor.is_some_and(|actor| actor.id == *id))
+            && self.tenant.as_ref().is_none_or(|tenant| {
+                actor.is_some_and(|actor| actor.tenant.as_ref() == Some(tenant))
+            })
+            && self.operation.is_none_or(|op| entry.operation == op)
+            && self
+                .resource_type
+                .as_ref()
+                .is_none_or(|resource_type| entry.resource_type == *resource_type)
+            && self
+                .resource_id
+                .as_ref()
+                .is_none_or(|resource_id| entry.resource_id == *resource_id)
+            && self
+                .request_id
+                .as_ref()
+                .is_none_or(|request_id| entry.request_id.as_ref() == Some(request_id))
+            && self
+                .token_id
+                .as_ref()
+                .is_none_or(|token_id| entry.token_id.as_ref() == Some(token_id))
+            && self.since.is_none_or(|since| entry.timestamp >= since)
+    }
+}
+
+/// Audit log
+///
+/// In-memory, append-only record of write operations. Entries are kept in
+/// insertion (and therefore timestamp) order so retention can prune from
+/// the front. The log never holds more than `max_entries` entries; age-based
+/// retention is applied by the retention job.
+/// Anonymous composite keys are never stored: they are hashed with a per-tenant
+/// salt derived from `identifier_secret` when an entry is recorded.
+/// In a real application, this would be backed by a database.
+#[derive(Clone)]
+pub struct AuditLog {
+    entries: Arc<RwLock<VecDeque<AuditEntry>>>,
+    next_id: Arc<AtomicU64>,
+    max_entries: usize,
+    identifier_secret: Arc<str>,
+}
+
+impl AuditLog {
+    /// Create a new audit log holding at most `max_entries` entries
+    pub fn new(max_entries: usize, identifier_secret: &str) -> Self {
+        Self {
+            entries: Arc::new(RwLock::new(VecDeque::new())),
+            next_id: Arc::new(AtomicU64::new(1)),
+            max_entries,
+            identifier_secret: Arc::from(identifier_secret),
+        }
+    }
+
+    /// Id an actor is stored under, with anonymous identifiers hashed
+    ///
+    /// Lets features keep per-person state without storing raw composite
+    /// keys; ids match the actor ids of audit entries.
+    pub fn stored_actor_id(&self, actor: &AuditActor) -> String {
+        let mut actor = actor.clone();
+        actor.hash_identifier(&self.identifier_secret);
+        actor.id
+    }
+
+    /// Record a write operation
+    ///
+    /// `before` and `after` summarize the resource state around the
+    /// operation; creates only have `after`, deletes only `before`.
+    pub async fn record(
+        &self,
+        context: &AuditContext,
+        operation: AuditOperation,
+        resource_type: &str,
+        resource_id: impl ToString,
+        before: Option<Value>,
+        after: Option<Value>,
+    ) {
+        let mut actor = context.actor.clone();
+        if let Some(actor) = actor.as_mut() {
+            actor.hash_identifier(&self.identifier_secret);
+        }
+
+        let entry = AuditEntry {
+            id: self.next_id.fetch_add(1, Ordering::SeqCst),
+            timestamp: Utc::now(),
+            actor,
+            operation,
+            resource_type: resource_type.to_string(),
+            resource_id: resource_id.to_string(),
+            before,
+            after,
+            request_id: context.request_id.clone(),
+            location: context.location.clone(),
+            token_id: context.token_id.clone(),
+        };
+
+        tracing::debug!("Audit: {:?}", entry);
+
+        let mut entries = self.entries.write().await;
+        entries.push_back(entry);
+        while entries.len() > self.max_entries {
+            entries.pop_front();
+        }
+    }
+
+    /// Search the audit log, newest entries first
+    ///
+    /// Returns at most `limit` entries (default 100, max 1000).
+    pub async fn search(&self, query: &AuditQuery) -> Vec<AuditEntry> {
+        let limit = query.limit.unwrap_or(100).min(1000);
+        let entries = self.entries.read().await;
+
+        entries
+            .iter()
+            .rev()
+            .filter(|entry| query.matches(entry))
+            .take(limit)
+            .cloned()
+            .collect()
+    }
+
+    /// Remove entries recorded before `cutoff`, except `exempt` ones
+    ///
+    /// Returns the number of affected entries. With `dry_run` the log is
+    /// left untouched and only the count is computed.
+    pub async fn purge_before(
+        &self,
+        cutoff: DateTime<Utc>,
+        dry_run: bool,
+        exempt: impl Fn(&AuditEntry) -> bool,
+    ) -> usize {
+        let mut entries = self.entries.write().await;
+        let expired = |entry: &AuditEntry| entry.timestamp < cutoff && !exempt(entry);
+        let affected = entries.iter().filter(|entry| expired(entry)).count();
+
+        if !dry_run && affected > 0 {
+            entries.retain(|entry| !expired(entry));
+        }
+        affected
+    }
+
+    /// Pseudonymize anonymous actors of entries recorded before `cutoff`,
+    /// except `exempt` ones
+    ///
+    /// Returns the number of affected entries. With `dry_run` the log is
+    /// left untouched and only the count is computed.
+    pub async fn pseudonymize_anonymous_before(
+        &self,
+        cutoff: DateTime<Utc>,
+        salt: &str,
+        dry_run: bool,
+        exempt: impl Fn(&AuditEntry) -> bool,
+    ) -> usize {
+        let mut entries = self.entries.write().await;
+        let mut affected = 0;
+
+        for entry in entries
+            .iter_mut()
+            .take_while(|entry| entry.timestamp < cutoff)
+        {
+            if exempt(entry) {
+                continue;
+            }
+            let Some(actor) = entry.actor.as_mut().filter(|actor| actor.is_anonymous()) else {
+                continue;
+            };
+
+            affected += 1;
+            if !dry_run {
+                actor.pseudonymize(salt);
+            }
+        }
+        affected
+    }
+}
+
+impl Default for AuditLog {
+    fn default() -> Self {
+        Self::new(10_000, "default-identifier-secret")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn context(actor: &str, tenant: Option<&str>) -> AuditContext {
+        AuditContext {
+            actor: Some(AuditActor {
+                id: actor.to_string(),
+                tenant: tenant.map(str::to_string),
+            }),
+            request_id: Some("req-1".to_string()),
+            location: None,
+            token_id: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_record_and_search() {
+        let log = AuditLog::default();
+        log.record(
+            &context("user:1", None),
+            AuditOperation::Create,
+            "user",
+            1,
+            None,
+            Some(json!({"username": "john"})),
+        )
+        .await;
+        log.record(
+            &context("anonymous:H001", Some("H001")),
+            AuditOperation::Delete,
+            "announcement",
+            7,
+            Some(json!({"title": "Notice"})),
+            None,
+        )
+        .await;
+
+        let all = log.search(&AuditQuery::default()).await;
+        assert_eq!(all.len(), 2);
+        assert_eq!(all[0].resource_type, "announcement");
+
+        let by_tenant = log
+            .search(&AuditQuery {
+                tenant: Some("H001".to_string()),
+                ..Default::default()
+            })
+            .await;
+        assert_eq!(by_tenant.len(), 1);
+        assert_eq!(by_tenant[0].operation, AuditOperation::Delete);
+
+        let by_request = log
+            .search(&AuditQuery {
+                request_id: Some("req-1".to_string()),
+                resource_type: Some("user".to_string()),
+                ..Default::default()
+            })
+            .await;
+        assert_eq!(by_request.len(), 1);
+        assert_eq!(by_request[0].resource_id, "1");
+    }
+
+    #[tokio::test]
+    async fn test_max_entries() {
+        let log = AuditLog::new(2, "secret");
+        for id in 1..=3 {
+            log.record(
+                &AuditContext::default(),
+                AuditOperation::Create,
+                "user",
+                id,
+                None,
+                None,
+            )
+            .await;
+        }
+
+        let entries = log.search(&AuditQuery::default()).await;
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[1].resource_id, "2");
+    }
+
+    #[tokio::test]
+    async fn test_purge_before() {
+        let log = AuditLog::default();
+        log.record(
+            &AuditContext::default(),
+            AuditOperation::Create,
+            "user",
+            1,
+            None,
+            None,
+        )
+        .await;
+
+        let cutoff = Utc::now() + chrono::Duration::seconds(1);
+        assert_eq!(log.purge_before(cutoff, true, |_| false).await, 1);
+        assert_eq!(log.search(&AuditQuery::default()).await.len(), 1);
+
+        assert_eq!(log.purge_before(cutoff, false, |_| true).await, 0);
+        assert_eq!(log.purge_before(cutoff, false, |_| false).await, 1);
+        assert!(log.search(&AuditQuery::default()).await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_anonymous_identifier_is_hashed_per_tenant() {
+        let log = AuditLog::default();
+        for (tenant, resource_id) in [("H001", 1), ("H001", 2), ("H002", 3)] {
+            let context = AuditContext {
+                actor: Some(AuditActor::anonymous(
+                    &format!("{}/U123/2024-01-01/D001", tenant),
+                    tenant.to_string(),
+                )),
+                request_id: None,
+                location: None,
+                token_id: None,
+            };
+            log.record(
+                &context,
+                AuditOperation::Create,
+                "user",
+                resource_id,
+                None,
+                None,
+            )
+            .await;
+        }
+
+        let entries = log.search(&AuditQuery::default()).await;
+        let ids: Vec<&str> = entries
+            .iter()
+            .map(|entry| entry.actor.as_ref().unwrap().id.as_str())
+            .collect();
+        assert!(ids.iter().all(|id| id.starts_with(ANONYMOUS_ACTOR_PREFIX)));
+        assert!(ids.iter().all(|id| !id.contains("U123")));
+        assert_eq!(ids[1], ids[2]);
+        assert_ne!(ids[0], ids[1]);
+    }
+
+    #[tokio::test]
+    async fn test_pseudonymize_anonymous_before() {
+        let log = AuditLog::default();
+        let anonymous = AuditContext {
+            actor: Some(AuditActor::anonymous(
+                "H001/U123/2024-01-01/D001",
+                "H001".to_string(),
+            )),
+            request_id: None,
+            location: None,
+            token_id: None,
+        };
+        log.record(&anonymous, AuditOperation::Create, "user", 1, None, None)
+            .await;
+        log.record(&anonymous, AuditOperation::Create, "user", 2, None, None)
+            .await;
+        log.record(
+            &context("user:1", None),
+            AuditOperation::Create,
+            "user",
+            3,
+            None,
+            None,
+        )
+        .await;
+
+        let cutoff = Utc::now() + chrono::Duration::seconds(1);
+        assert_eq!(
+            log.pseudonymize_anonymous_before(cutoff, "salt", false, |_| false)
+                .await,
+            2
+        );
+
+        let entries = log.search(&AuditQuery::default()).await;
+        let pseudonyms: Vec<&AuditActor> = entries
+            .iter()
+            .filter_map(|entry| entry.actor.as_ref())
+            .filter(|actor| actor.id.starts_with(PSEUDONYM_ACTOR_PREFIX))
+            .collect();
+        assert_eq!(pseudonyms.len(), 2);
+        assert_eq!(pseudonyms[0], pseudonyms[1]);
+        assert_eq!(pseudonyms[0].tenant.as_deref(), Some("H001"));
+        assert!(!pseudonyms[0].id.contains("U123"));
+    }
+}
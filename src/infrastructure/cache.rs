@@ -0,0 +1,97 @@
+//! In-memory cache with per-entry time to live
+//!
+//! Entries expire after their TTL and are dropped lazily on access and
+//! whenever new entries are inserted. Clones share the same entries.
+
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::{Duration, Instant};
+
+/// Cache of values that expire after a time to live
+#[derive(Debug, Clone)]
+pub struct TtlCache<K, V> {
+    entries: Arc<Mutex<HashMap<K, (V, Instant)>>>,
+}
+
+impl<K, V> Default for TtlCache<K, V> {
+    fn default() -> Self {
+        Self {
+            entries: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+}
+
+impl<K: Eq + Hash, V: Clone> TtlCache<K, V> {
+    /// Create an empty cache
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Insert `value` under `key`, expiring after `ttl`
+    ///
+    /// Replaces any previous value of `key` and drops expired entries.
+    pub fn insert(&self, key: K, value: V, ttl: Duration) {
+        let now = Instant::now();
+        let mut entries = self.entries.lock().unwrap_or_else(PoisonError::into_inner);
+        entries.retain(|_, (_, expires_at)| *expires_at > now);
+        entries.insert(key, (value, now + ttl));
+    }
+
+    /// Value of `key`, if present and not expired
+    pub fn get(&self, key: &K) -> Option<V> {
+        let entries = self.entries.lock().unwrap_or_else(PoisonError::into_inner);
+        entries
+            .get(key)
+            .filter(|(_, expires_at)| *expires_at > Instant::now())
+            .map(|(value, _)| value.clone())
+    }
+
+    /// Remove and return the value of `key`, if present and not expired
+    ///
+    /// The removal is atomic: of several concurrent calls for the same key,
+    /// only one gets the value.
+    pub fn take(&self, key: &K) -> Option<V> {
+        let mut entries = self.entries.lock().unwrap_or_else(PoisonError::into_inner);
+        entries
+            .remove(key)
+            .filter(|(_, expires_at)| *expires_at > Instant::now())
+            .map(|(value, _)| value)
+    }
+
+    /// Number of entries that have not expired
+    pub fn len(&self) -> usize {
+        let now = Instant::now();
+        let entries = self.entries.lock().unwrap_or_else(PoisonError::into_inner);
+        entries
+            .values()
+            .filter(|(_, expires_at)| *expires_at > now)
+            .count()
+    }
+
+    /// Check if the cache holds no unexpired entries
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_take_once_and_expiry() {
+        let cache = TtlCache::new();
+        cache.insert("a", 1, Duration::from_secs(60));
+        cache.insert("b", 2, Duration::ZERO);
+
+        assert_eq!(cache.get(&"a"), Some(1));
+        assert_eq!(cache.get(&"b"), None);
+        assert_eq!(cache.len(), 1);
+
+        let shared = cache.clone();
+        assert_eq!(shared.take(&"a"), Some(1));
+        assert_eq!(cache.take(&"a"), None);
+        assert!(cache.is_empty());
+    }
+}
@@ -0,0 +1,97 @@
+use chrono::{DateTime, Duration, Utc};
+use reqwest::header::DATE;
+
+/// How long the time source may take to answer
+const REQUEST_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(5);
+
+/// Clock skew check against a trusted time source
+///
+/// Compares the system clock with the `Date` header of an HTTP server, such
+/// as an internal NTP-synchronized host. The header has a resolution of one
+/// second, which is plenty to spot VMs whose clocks have drifted by minutes.
+#[derive(Clone)]
+pub struct ClockCheck {
+    client: reqwest::Client,
+    time_source_url: String,
+}
+
+impl ClockCheck {
+    /// Create a check against the server at `time_source_url`
+    pub fn new(time_source_url: &str) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            time_source_url: time_source_url.to_string(),
+        }
+    }
+
+    /// Offset of the system clock from the time source
+    ///
+    /// Positive when the system clock is ahead. The request time is taken
+    /// halfway through the round trip.
+    pub async fn skew(&self) -> anyhow::Result<Duration> {
+        let sent_at = Utc::now();
+        let response = self
+            .client
+            .head(&self.time_source_url)
+            .timeout(REQUEST_TIMEOUT)
+            .send()
+            .await?;
+        let received_at = Utc::now();
+
+        let date = response
+            .headers()
+            .get(DATE)
+            .ok_or_else(|| anyhow::anyhow!("Time source sent no Date header"))?
+            .to_str()?;
+        let local_time = sent_at + (received_at - sent_at) / 2;
+        skew_from_date(date, local_time)
+    }
+
+    /// Log a warning if the system clock is off by more than `tolerance`
+    pub async fn warn_if_skewed(&self, tolerance: Duration) {
+        match self.skew().await {
+            Ok(skew) if skew.num_seconds().abs() > tolerance.num_seconds() => tracing::warn!(
+                "System clock is {}s {} {}; tokens may be rejected as expired or not yet valid",
+                skew.num_seconds().abs(),
+                if skew > Duration::zero() {
+                    "ahead of"
+                } else {
+                    "behind"
+                },
+                self.time_source_url
+            ),
+            Ok(skew) => tracing::info!(
+                "System clock is within {}s of {}",
+                skew.num_seconds().abs(),
+                self.time_source_url
+            ),
+            Err(err) => tracing::warn!("Clock skew check failed: {}", err),
+        }
+    }
+}
+
+/// Offset of `local_time` from an HTTP `Date` header value
+fn skew_from_date(date: &str, local_time: DateTime<Utc>) -> anyhow::Result<Duration> {
+    let remote_time = DateTime::parse_from_rfc2822(date)?.with_timezone(&Utc);
+    Ok(local_time - remote_time)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_skew_from_date() {
+        let local_time = DateTime::parse_from_rfc3339("2024-03-01T12:05:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+
+        let skew = skew_from_date("Fri, 01 Mar 2024 12:00:00 GMT", local_time).unwrap();
+        assert_eq!(skew, Duration::minutes(5));
+
+        let skew = skew_from_date("Fri, 01 Mar 2024 12:07:30 GMT", local_time).unwrap();
+        assert_eq!(skew, Duration::seconds(-150));
+
+        assert!(skew_from_date("yesterday", local_time).is_err());
+    }
+}
@@ -1,7 +1,8 @@
 use std::env;
+use std::fmt;
 
 /// Application configuration loaded from environment variables
-#[derive(Clone, Debug)]
+#[derive(Clone)]
 pub struct AppConfig {
     /// Server host address
     pub host: String,
@@ -13,8 +14,116 @@ pub struct AppConfig {
     pub request_timeout_secs: u64,
     /// Maximum request body size in bytes
     pub max_body_size: usize,
+    /// Reject unknown fields in JSON request bodies instead of ignoring them
+    pub strict_deserialization: bool,
+    /// Maximum size of a single WebSocket message in bytes
+    pub ws_max_message_size: usize,
+    /// Seconds WebSocket connections get to close on shutdown
+    pub shutdown_grace_secs: u64,
+    /// Resident memory in MB above which load is shed (0 = unchecked)
+    pub watermark_rss_mb: u64,
+    /// Open WebSocket connections above which load is shed (0 = unchecked)
+    pub watermark_connections: usize,
+    /// Queued outgoing WebSocket messages above which load is shed (0 = unchecked)
+    pub watermark_queued_messages: usize,
+    /// Interval in seconds between resource usage samples
+    pub watermark_check_secs: u64,
+    /// Labels JSON-RPC call metrics are broken down by (method, tenant)
+    pub rpc_metrics_labels: Vec<String>,
+    /// Maximum number of distinct values per metric label
+    pub rpc_metrics_max_label_values: usize,
+    /// Origins allowed to make cross-origin requests; `*` allows any
+    pub cors_allowed_origins: Vec<String>,
+    /// Methods allowed in cross-origin requests; `*` allows any
+    pub cors_allowed_methods: Vec<String>,
+    /// Request headers allowed in cross-origin requests; `*` allows any
+    pub cors_allowed_headers: Vec<String>,
+    /// Allow cross-origin requests to send credentials such as cookies
+    pub cors_allow_credentials: bool,
+    /// Requests per minute each client regains on routes without an override (0 = unlimited)
+    pub rate_limit_per_minute: u32,
+    /// Requests each client can burst on routes without an override
+    pub rate_limit_burst: u32,
+    /// Per-route overrides as `prefix=per_minute[:burst]`
+    pub rate_limit_routes: Vec<String>,
     /// JWT secret key for token signing
     pub jwt_secret: String,
+    /// Algorithm tokens are signed with (HS256, RS256, ES256, ...)
+    pub jwt_algorithm: String,
+    /// Id of the current signing key, sent as `kid` header
+    pub jwt_key_id: Option<String>,
+    /// PEM file of the private key tokens are signed with (RS256, ES256, ...)
+    pub jwt_private_key_file: Option<String>,
+    /// PEM file of the public key matching `jwt_private_key_file`
+    pub jwt_public_key_file: Option<String>,
+    /// Previous keys still accepted as `kid=secret` (HS256) or `kid=public key file`
+    pub jwt_previous_keys: Vec<String>,
+    /// Token binding to client fingerprints (off, lenient, strict)
+    pub token_binding: String,
+    /// Number of days refresh tokens stay valid
+    pub refresh_token_lifetime_days: i64,
+    /// Seconds of clock skew tolerated when validating token timestamps
+    pub jwt_leeway_secs: u64,
+    /// HTTP server whose Date header the system clock is checked against at startup
+    pub time_source_url: Option<String>,
+    /// bcrypt cost used to hash passwords
+    pub password_hash_cost: u32,
+    /// JSON file of users seeded with their roles and password hashes
+    pub user_seed_file: Option<String>,
+    /// Redis URL revoked access tokens are shared through (in-memory only if unset)
+    pub redis_url: Option<String>,
+    /// Interval in seconds between pulls of revoked tokens from Redis
+    pub token_blacklist_sync_secs: u64,
+    /// Whether anonymous tokens refer to revocable server-side sessions
+    pub anonymous_sessions: bool,
+    /// Seconds during which deleted threads and posts can be restored
+    pub undo_window_secs: i64,
+    /// Seconds between reminders to acknowledge an announcement
+    pub acknowledgement_reminder_secs: i64,
+    /// Length of the anomaly detection window in seconds
+    pub anomaly_window_secs: i64,
+    /// Failed logins per username within the window that raise an alert
+    pub anomaly_failed_logins: usize,
+    /// Reused refresh tokens per identity within the window that raise an alert
+    pub anomaly_token_reuse: usize,
+    /// Distinct client IPs per identity within the window that raise an alert
+    pub anomaly_distinct_ips: usize,
+    /// URL anomaly alerts are posted to; webhook alerts are disabled when unset
+    pub anomaly_webhook_url: Option<String>,
+    /// Delivery attempts per webhook payload before it is dead-lettered
+    pub webhook_max_attempts: u32,
+    /// Path of a MaxMind GeoIP2/GeoLite2 database (GeoIP lookup disabled if unset)
+    pub geoip_database_path: Option<String>,
+    /// Interval in seconds between GeoIP database reloads
+    pub geoip_reload_interval_secs: u64,
+    /// Number of days audit log entries are retained
+    pub audit_retention_days: i64,
+    /// Maximum number of audit log entries kept in memory
+    pub audit_max_entries: usize,
+    /// Number of days anonymous composite keys are kept before pseudonymization
+    pub anonymous_id_retention_days: i64,
+    /// Secret used to derive per-tenant salts for hashing anonymous composite keys at rest
+    pub anonymous_id_hash_secret: String,
+    /// Salt used to pseudonymize anonymous identifiers past retention
+    pub pseudonymization_salt: String,
+    /// Interval between retention job runs in seconds
+    pub retention_interval_secs: u64,
+    /// Directory backups are stored in (backups disabled if unset)
+    pub backup_dir: Option<String>,
+    /// Interval between backups in seconds
+    pub backup_interval_secs: u64,
+    /// Number of backups kept, older ones are removed
+    pub backup_keep: usize,
+    /// Daily API calls per tenant after which responses carry a quota warning (0 = unlimited)
+    pub tenant_soft_daily_api_calls: u64,
+    /// Daily API calls per tenant after which requests are rejected (0 = unlimited)
+    pub tenant_hard_daily_api_calls: u64,
+    /// Consul agent URL; service registration is disabled when unset
+    pub consul_url: Option<String>,
+    /// Service name registered with Consul
+    pub service_name: String,
+    /// Address other services use to reach this instance
+    pub service_address: String,
 }
 
 impl AppConfig {
@@ -33,12 +142,187 @@ impl AppConfig {
             .unwrap_or_else(|_| "30".to_string())
             .parse()
             .unwrap_or(30);
+        let password_hash_cost = env::var("PASSWORD_HASH_COST")
+            .unwrap_or_else(|_| "12".to_string())
+            .parse()
+            .unwrap_or(12);
+        let user_seed_file = env::var("USER_SEED_FILE")
+            .ok()
+            .filter(|path| !path.is_empty());
+        let redis_url = env::var("REDIS_URL").ok().filter(|url| !url.is_empty());
+        let token_blacklist_sync_secs = env::var("TOKEN_BLACKLIST_SYNC_SECS")
+            .unwrap_or_else(|_| "5".to_string())
+            .parse()
+            .unwrap_or(5);
+        let anonymous_sessions = env::var("ANONYMOUS_SESSIONS")
+            .unwrap_or_else(|_| "false".to_string())
+            .parse()
+            .unwrap_or(false);
+        let undo_window_secs = env::var("UNDO_WINDOW_SECS")
+            .unwrap_or_else(|_| "30".to_string())
+            .parse()
+            .unwrap_or(30);
+        let acknowledgement_reminder_secs = env::var("ACKNOWLEDGEMENT_REMINDER_SECS")
+            .unwrap_or_else(|_| "3600".to_string())
+            .parse()
+            .unwrap_or(3600);
         let max_body_size = env::var("MAX_BODY_SIZE")
             .unwrap_or_else(|_| "2097152".to_string()) // 2MB default
             .parse()
             .unwrap_or(2_097_152);
+        let strict_deserialization = env::var("STRICT_DESERIALIZATION")
+            .unwrap_or_else(|_| "false".to_string())
+            .parse()
+            .unwrap_or(false);
+        let ws_max_message_size = env::var("WS_MAX_MESSAGE_SIZE")
+            .unwrap_or_else(|_| "65536".to_string()) // 64KB default
+            .parse()
+            .unwrap_or(65_536);
+        let shutdown_grace_secs = env::var("SHUTDOWN_GRACE_SECS")
+            .unwrap_or_else(|_| "10".to_string())
+            .parse()
+            .unwrap_or(10);
+        let cors_allowed_origins = comma_separated(
+            &env::var("CORS_ALLOWED_ORIGINS")
+                .unwrap_or_else(|_| "http://localhost:3000".to_string()),
+        );
+        let watermark_rss_mb = env::var("WATERMARK_RSS_MB")
+            .unwrap_or_else(|_| "0".to_string())
+            .parse()
+            .unwrap_or(0);
+        let watermark_connections = env::var("WATERMARK_CONNECTIONS")
+            .unwrap_or_else(|_| "0".to_string())
+            .parse()
+            .unwrap_or(0);
+        let watermark_queued_messages = env::var("WATERMARK_QUEUED_MESSAGES")
+            .unwrap_or_else(|_| "0".to_string())
+            .parse()
+            .unwrap_or(0);
+        let watermark_check_secs = env::var("WATERMARK_CHECK_SECS")
+            .unwrap_or_else(|_| "5".to_string())
+            .parse()
+            .unwrap_or(5);
+        let rpc_metrics_labels = comma_separated(
+            &env::var("RPC_METRICS_LABELS").unwrap_or_else(|_| "method".to_string()),
+        );
+        let rpc_metrics_max_label_values = env::var("RPC_METRICS_MAX_LABEL_VALUES")
+            .unwrap_or_else(|_| "100".to_string())
+            .parse()
+            .unwrap_or(100);
+        let cors_allowed_methods = comma_separated(
+            &env::var("CORS_ALLOWED_METHODS").unwrap_or_else(|_| "GET,POST,PUT,DELETE".to_string()),
+        );
+        let cors_allowed_headers =
+            comma_separated(&env::var("CORS_ALLOWED_HEADERS").unwrap_or_else(|_| "*".to_string()));
+        let cors_allow_credentials = env::var("CORS_ALLOW_CREDENTIALS")
+            .unwrap_or_else(|_| "false".to_string())
+            .parse()
+            .unwrap_or(false);
+        let rate_limit_per_minute = env::var("RATE_LIMIT_PER_MINUTE")
+            .unwrap_or_else(|_| "300".to_string())
+            .parse()
+            .unwrap_or(300);
+        let rate_limit_burst = env::var("RATE_LIMIT_BURST")
+            .unwrap_or_else(|_| "60".to_string())
+            .parse()
+            .unwrap_or(60);
+        let rate_limit_routes = comma_separated(
+            &env::var("RATE_LIMIT_ROUTES")
+                .unwrap_or_else(|_| "/api/v1/auth/login=10:5".to_string()),
+        );
         let jwt_secret = env::var("JWT_SECRET")
             .unwrap_or_else(|_| "default-secret-key-change-in-production".to_string());
+        let jwt_algorithm = env::var("JWT_ALGORITHM").unwrap_or_else(|_| "HS256".to_string());
+        let jwt_key_id = env::var("JWT_KEY_ID").ok().filter(|id| !id.is_empty());
+        let jwt_private_key_file = env::var("JWT_PRIVATE_KEY_FILE")
+            .ok()
+            .filter(|path| !path.is_empty());
+        let jwt_public_key_file = env::var("JWT_PUBLIC_KEY_FILE")
+            .ok()
+            .filter(|path| !path.is_empty());
+        let jwt_previous_keys = comma_separated(&env::var("JWT_PREVIOUS_KEYS").unwrap_or_default());
+        let token_binding = env::var("TOKEN_BINDING").unwrap_or_else(|_| "off".to_string());
+        let refresh_token_lifetime_days = env::var("REFRESH_TOKEN_LIFETIME_DAYS")
+            .unwrap_or_else(|_| "30".to_string())
+            .parse()
+            .unwrap_or(30);
+        let anomaly_window_secs = env::var("ANOMALY_WINDOW_SECS")
+            .unwrap_or_else(|_| "600".to_string())
+            .parse()
+            .unwrap_or(600);
+        let anomaly_failed_logins = env::var("ANOMALY_FAILED_LOGINS")
+            .unwrap_or_else(|_| "5".to_string())
+            .parse()
+            .unwrap_or(5);
+        let anomaly_token_reuse = env::var("ANOMALY_TOKEN_REUSE")
+            .unwrap_or_else(|_| "1".to_string())
+            .parse()
+            .unwrap_or(1);
+        let anomaly_distinct_ips = env::var("ANOMALY_DISTINCT_IPS")
+            .unwrap_or_else(|_| "4".to_string())
+            .parse()
+            .unwrap_or(4);
+        let anomaly_webhook_url = env::var("ANOMALY_WEBHOOK_URL")
+            .ok()
+            .filter(|url| !url.is_empty());
+        let webhook_max_attempts = env::var("WEBHOOK_MAX_ATTEMPTS")
+            .unwrap_or_else(|_| "3".to_string())
+            .parse()
+            .unwrap_or(3);
+        let jwt_leeway_secs = env::var("JWT_LEEWAY_SECS")
+            .unwrap_or_else(|_| "60".to_string())
+            .parse()
+            .unwrap_or(60);
+        let time_source_url = env::var("TIME_SOURCE_URL")
+            .ok()
+            .filter(|url| !url.is_empty());
+        let geoip_database_path = env::var("GEOIP_DATABASE_PATH")
+            .ok()
+            .filter(|path| !path.is_empty());
+        let geoip_reload_interval_secs = env::var("GEOIP_RELOAD_INTERVAL_SECS")
+            .unwrap_or_else(|_| "86400".to_string())
+            .parse()
+            .unwrap_or(86400);
+        let audit_retention_days = env::var("AUDIT_RETENTION_DAYS")
+            .unwrap_or_else(|_| "90".to_string())
+            .parse()
+            .unwrap_or(90);
+        let audit_max_entries = env::var("AUDIT_MAX_ENTRIES")
+            .unwrap_or_else(|_| "10000".to_string())
+            .parse()
+            .unwrap_or(10_000);
+        let anonymous_id_retention_days = env::var("ANONYMOUS_ID_RETENTION_DAYS")
+            .unwrap_or_else(|_| "30".to_string())
+            .parse()
+            .unwrap_or(30);
+        let anonymous_id_hash_secret = env::var("ANONYMOUS_ID_HASH_SECRET")
+            .unwrap_or_else(|_| "default-hash-secret-change-in-production".to_string());
+        let pseudonymization_salt = env::var("PSEUDONYMIZATION_SALT")
+            .unwrap_or_else(|_| "default-salt-change-in-production".to_string());
+        let retention_interval_secs = env::var("RETENTION_INTERVAL_SECS")
+            .unwrap_or_else(|_| "3600".to_string())
+            .parse()
+            .unwrap_or(3600);
+        let backup_dir = env::var("BACKUP_DIR").ok().filter(|dir| !dir.is_empty());
+        let backup_interval_secs = env::var("BACKUP_INTERVAL_SECS")
+            .unwrap_or_else(|_| "3600".to_string())
+            .parse()
+            .unwrap_or(3600);
+        let backup_keep = env::var("BACKUP_KEEP")
+            .unwrap_or_else(|_| "24".to_string())
+            .parse()
+            .unwrap_or(24);
+        let tenant_soft_daily_api_calls = env::var("TENANT_SOFT_DAILY_API_CALLS")
+            .unwrap_or_else(|_| "0".to_string())
+            .parse()
+            .unwrap_or(0);
+        let tenant_hard_daily_api_calls = env::var("TENANT_HARD_DAILY_API_CALLS")
+            .unwrap_or_else(|_| "0".to_string())
+            .parse()
+            .unwrap_or(0);
+        let consul_url = env::var("CONSUL_URL").ok().filter(|url| !url.is_empty());
+        let service_name = env::var("SERVICE_NAME").unwrap_or_else(|_| "webboard".to_string());
+        let service_address = env::var("SERVICE_ADDRESS").unwrap_or_else(|_| host.clone());
 
         Ok(Self {
             host,
@@ -46,7 +330,61 @@ impl AppConfig {
             log_level,
             request_timeout_secs,
             max_body_size,
+            strict_deserialization,
+            ws_max_message_size,
+            shutdown_grace_secs,
+            watermark_rss_mb,
+            watermark_connections,
+            watermark_queued_messages,
+            watermark_check_secs,
+            rpc_metrics_labels,
+            rpc_metrics_max_label_values,
+            cors_allowed_origins,
+            cors_allowed_methods,
+            cors_allowed_headers,
+            cors_allow_credentials,
+            rate_limit_per_minute,
+            rate_limit_burst,
+            rate_limit_routes,
             jwt_secret,
+            jwt_algorithm,
+            jwt_key_id,
+            jwt_private_key_file,
+            jwt_public_key_file,
+            jwt_previous_keys,
+            token_binding,
+            refresh_token_lifetime_days,
+            jwt_leeway_secs,
+            time_source_url,
+            password_hash_cost,
+            user_seed_file,
+            redis_url,
+            token_blacklist_sync_secs,
+            anonymous_sessions,
+            undo_window_secs,
+            acknowledgement_reminder_secs,
+            anomaly_window_secs,
+            anomaly_failed_logins,
+            anomaly_token_reuse,
+            anomaly_distinct_ips,
+            anomaly_webhook_url,
+            webhook_max_attempts,
+            geoip_database_path,
+            geoip_reload_interval_secs,
+            audit_retention_days,
+            audit_max_entries,
+            anonymous_id_retention_days,
+            anonymous_id_hash_secret,
+            pseudonymization_salt,
+            retention_interval_secs,
+            backup_dir,
+            backup_interval_secs,
+            backup_keep,
+            tenant_soft_daily_api_calls,
+            tenant_hard_daily_api_calls,
+            consul_url,
+            service_name,
+            service_address,
         })
     }
 
@@ -55,3 +393,193 @@ impl AppConfig {
         format!("{}:{}", self.host, self.port)
     }
 }
+
+/// Shown in place of secrets when the configuration is logged
+const REDACTED: &str = "<redacted>";
+
+/// Redacts secrets, so the configuration can be logged at startup
+///
+/// URLs may carry credentials and are redacted too; optional secrets still
+/// show whether they are set, and previous JWT keys their key ids.
+impl fmt::Debug for AppConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // Destructured so that new fields cannot be forgotten here
+        let Self {
+            host,
+            port,
+            log_level,
+            request_timeout_secs,
+            max_body_size,
+            strict_deserialization,
+            ws_max_message_size,
+            shutdown_grace_secs,
+            watermark_rss_mb,
+            watermark_connections,
+            watermark_queued_messages,
+            watermark_check_secs,
+            rpc_metrics_labels,
+            rpc_metrics_max_label_values,
+            cors_allowed_origins,
+            cors_allowed_methods,
+            cors_allowed_headers,
+            cors_allow_credentials,
+            rate_limit_per_minute,
+            rate_limit_burst,
+            rate_limit_routes,
+            jwt_secret: _,
+            jwt_algorithm,
+            jwt_key_id,
+            jwt_private_key_file,
+            jwt_public_key_file,
+            jwt_previous_keys,
+            token_binding,
+            refresh_token_lifetime_days,
+            jwt_leeway_secs,
+            time_source_url,
+            password_hash_cost,
+            user_seed_file,
+            redis_url,
+            token_blacklist_sync_secs,
+            anonymous_sessions,
+            undo_window_secs,
+            acknowledgement_reminder_secs,
+            anomaly_window_secs,
+            anomaly_failed_logins,
+            anomaly_token_reuse,
+            anomaly_distinct_ips,
+            anomaly_webhook_url,
+            webhook_max_attempts,
+            geoip_database_path,
+            geoip_reload_interval_secs,
+            audit_retention_days,
+            audit_max_entries,
+            anonymous_id_retention_days,
+            anonymous_id_hash_secret: _,
+            pseudonymization_salt: _,
+            retention_interval_secs,
+            backup_dir,
+            backup_interval_secs,
+            backup_keep,
+            tenant_soft_daily_api_calls,
+            tenant_hard_daily_api_calls,
+            consul_url,
+            service_name,
+            service_address,
+        } = self;
+        f.debug_struct("AppConfig")
+            .field("host", host)
+            .field("port", port)
+            .field("log_level", log_level)
+            .field("request_timeout_secs", request_timeout_secs)
+            .field("max_body_size", max_body_size)
+            .field("strict_deserialization", strict_deserialization)
+            .field("ws_max_message_size", ws_max_message_size)
+            .field("shutdown_grace_secs", shutdown_grace_secs)
+            .field("watermark_rss_mb", watermark_rss_mb)
+            .field("watermark_connections", watermark_connections)
+            .field("watermark_queued_messages", watermark_queued_messages)
+            .field("watermark_check_secs", watermark_check_secs)
+            .field("rpc_metrics_labels", rpc_metrics_labels)
+            .field("rpc_metrics_max_label_values", rpc_metrics_max_label_values)
+            .field("cors_allowed_origins", cors_allowed_origins)
+            .field("cors_allowed_methods", cors_allowed_methods)
+            .field("cors_allowed_headers", cors_allowed_headers)
+            .field("cors_allow_credentials", cors_allow_credentials)
+            .field("rate_limit_per_minute", rate_limit_per_minute)
+            .field("rate_limit_burst", rate_limit_burst)
+            .field("rate_limit_routes", rate_limit_routes)
+            .field("jwt_secret", &REDACTED)
+            .field("jwt_algorithm", jwt_algorithm)
+            .field("jwt_key_id", jwt_key_id)
+            .field("jwt_private_key_file", jwt_private_key_file)
+            .field("jwt_public_key_file", jwt_public_key_file)
+            .field(
+                "jwt_previous_keys",
+                &jwt_previous_keys
+                    .iter()
+                    .map(|key| match key.split_once('=') {
+                        Some((key_id, _)) => format!("{}={}", key_id, REDACTED),
+                        None => REDACTED.to_string(),
+                    })
+                    .collect::<Vec<_>>(),
+            )
+            .field("token_binding", token_binding)
+            .field("refresh_token_lifetime_days", refresh_token_lifetime_days)
+            .field("jwt_leeway_secs", jwt_leeway_secs)
+            .field("time_source_url", time_source_url)
+            .field("password_hash_cost", password_hash_cost)
+            .field("user_seed_file", user_seed_file)
+            .field("redis_url", &redis_url.as_ref().map(|_| REDACTED))
+            .field("token_blacklist_sync_secs", token_blacklist_sync_secs)
+            .field("anonymous_sessions", anonymous_sessions)
+            .field("undo_window_secs", undo_window_secs)
+            .field(
+                "acknowledgement_reminder_secs",
+                acknowledgement_reminder_secs,
+            )
+            .field("anomaly_window_secs", anomaly_window_secs)
+            .field("anomaly_failed_logins", anomaly_failed_logins)
+            .field("anomaly_token_reuse", anomaly_token_reuse)
+            .field("anomaly_distinct_ips", anomaly_distinct_ips)
+            .field(
+                "anomaly_webhook_url",
+                &anomaly_webhook_url.as_ref().map(|_| REDACTED),
+            )
+            .field("webhook_max_attempts", webhook_max_attempts)
+            .field("geoip_database_path", geoip_database_path)
+            .field("geoip_reload_interval_secs", geoip_reload_interval_secs)
+            .field("audit_retention_days", audit_retention_days)
+            .field("audit_max_entries", audit_max_entries)
+            .field("anonymous_id_retention_days", anonymous_id_retention_days)
+            .field("anonymous_id_hash_secret", &REDACTED)
+            .field("pseudonymization_salt", &REDACTED)
+            .field("retention_interval_secs", retention_interval_secs)
+            .field("backup_dir", backup_dir)
+            .field("backup_interval_secs", backup_interval_secs)
+            .field("backup_keep", backup_keep)
+            .field("tenant_soft_daily_api_calls", tenant_soft_daily_api_calls)
+            .field("tenant_hard_daily_api_calls", tenant_hard_daily_api_calls)
+            .field("consul_url", consul_url)
+            .field("service_name", service_name)
+            .field("service_address", service_address)
+            .finish()
+    }
+}
+
+/// Split a comma-separated list, dropping empty entries
+fn comma_separated(value: &str) -> Vec<String> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|entry| !entry.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_debug_redacts_secrets() {
+        let mut config = AppConfig::from_env().unwrap();
+        config.jwt_secret = "jwt-secret".to_string();
+        config.jwt_previous_keys = vec!["2024-01=previous-secret".to_string()];
+        config.redis_url = Some("redis://:redis-password@127.0.0.1:6379".to_string());
+        config.anonymous_id_hash_secret = "hash-secret".to_string();
+        config.pseudonymization_salt = "salt-secret".to_string();
+
+        let logged = format!("{:?}", config);
+        for secret in [
+            "jwt-secret",
+            "previous-secret",
+            "redis-password",
+            "hash-secret",
+            "salt-secret",
+        ] {
+            assert!(!logged.contains(secret), "{} is logged", secret);
+        }
+        assert!(logged.contains(r#"jwt_previous_keys: ["2024-01=<redacted>"]"#));
+        assert!(logged.contains(&format!("port: {}", config.port)));
+    }
+}
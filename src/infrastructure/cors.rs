@@ -0,0 +1,128 @@
+use axum::http::{HeaderName, HeaderValue, Method};
+use tower_http::cors::{AllowHeaders, AllowMethods, AllowOrigin, CorsLayer};
+
+/// Wildcard entry allowing any origin, method or header
+pub const WILDCARD: &str = "*";
+
+/// Build the CORS layer from the configured origins, methods and headers
+///
+/// A `*` entry allows any value. With credentials allowed, browsers reject
+/// wildcard responses, so the request's own origin, method or headers are
+/// echoed back instead. Entries that are not valid origins, methods or
+/// header names are skipped with a warning.
+pub fn cors_layer(
+    allowed_origins: &[String],
+    allowed_methods: &[String],
+    allowed_headers: &[String],
+    allow_credentials: bool,
+) -> CorsLayer {
+    let origins = if allowed_origins.iter().any(|origin| origin == WILDCARD) {
+        if allow_credentials {
+            AllowOrigin::mirror_request()
+        } else {
+            AllowOrigin::any()
+        }
+    } else {
+        AllowOrigin::list(parse_entries::<HeaderValue>("origin", allowed_origins))
+    };
+
+    let methods = if allowed_methods.iter().any(|method| method == WILDCARD) {
+        if allow_credentials {
+            AllowMethods::mirror_request()
+        } else {
+            AllowMethods::any()
+        }
+    } else {
+        AllowMethods::list(parse_entries::<Method>("method", allowed_methods))
+    };
+
+    let headers = if allowed_headers.iter().any(|header| header == WILDCARD) {
+        if allow_credentials {
+            AllowHeaders::mirror_request()
+        } else {
+            AllowHeaders::any()
+        }
+    } else {
+        AllowHeaders::list(parse_entries::<HeaderName>("header", allowed_headers))
+    };
+
+    CorsLayer::new()
+        .allow_origin(origins)
+        .allow_methods(methods)
+        .allow_headers(headers)
+        .allow_credentials(allow_credentials)
+}
+
+/// Parse configured entries, skipping invalid ones
+fn parse_entries<T: std::str::FromStr>(kind: &str, entries: &[String]) -> Vec<T> {
+    entries
+        .iter()
+        .filter_map(|entry| match entry.parse() {
+            Ok(value) => Some(value),
+            Err(_) => {
+                tracing::warn!("Ignoring invalid CORS {}: {}", kind, entry);
+                None
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{body::Body, http::Request, routing::get, Router};
+    use tower::ServiceExt;
+
+    fn strings(values: &[&str]) -> Vec<String> {
+        values.iter().map(|value| value.to_string()).collect()
+    }
+
+    async fn allowed_origin(layer: CorsLayer, origin: &str) -> Option<String> {
+        let app = Router::new().route("/", get(|| async {})).layer(layer);
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/")
+                    .header("origin", origin)
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        response
+            .headers()
+            .get("access-control-allow-origin")
+            .map(|value| value.to_str().unwrap().to_string())
+    }
+
+    #[tokio::test]
+    async fn test_cors_origins() {
+        let methods = strings(&["GET", "not a method"]);
+        let headers = strings(&["*"]);
+
+        let listed = strings(&["http://localhost:3000", "https://board.example.com"]);
+        let layer = cors_layer(&listed, &methods, &headers, false);
+        assert_eq!(
+            allowed_origin(layer.clone(), "https://board.example.com").await,
+            Some("https://board.example.com".to_string())
+        );
+        assert_eq!(
+            allowed_origin(layer, "https://evil.example.com").await,
+            None
+        );
+
+        let any = strings(&["*"]);
+        let layer = cors_layer(&any, &methods, &headers, false);
+        assert_eq!(
+            allowed_origin(layer, "https://evil.example.com").await,
+            Some("*".to_string())
+        );
+
+        // Credentials cannot be combined with a literal wildcard
+        let layer = cors_layer(&any, &methods, &headers, true);
+        assert_eq!(
+            allowed_origin(layer, "https://board.example.com").await,
+            Some("https://board.example.com".to_string())
+        );
+    }
+}
@@ -0,0 +1,269 @@
+use chrono::{DateTime, Utc};
+use futures::future::BoxFuture;
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap};
+use std::future::Future;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use tokio::sync::RwLock;
+
+use super::audit::{AuditContext, AuditLog, AuditOperation};
+use super::error::AppError;
+
+/// Maximum number of entries kept; the oldest are dropped first
+const MAX_DEAD_LETTERS: usize = 1000;
+
+/// Function delivering a payload to a destination once
+type Redelivery =
+    Arc<dyn Fn(String, Value) -> BoxFuture<'static, Result<(), String>> + Send + Sync>;
+
+/// Failed attempt to deliver a payload
+#[derive(Debug, Clone, Serialize)]
+pub struct DeliveryAttempt {
+    pub attempted_at: DateTime<Utc>,
+    pub error: String,
+}
+
+/// Payload that could not be delivered
+#[derive(Debug, Clone, Serialize)]
+pub struct DeadLetter {
+    pub id: u64,
+    /// How the payload is delivered, e.g. `webhook`
+    pub kind: String,
+    /// What produced the payload, e.g. `anomaly_alert`
+    pub source: String,
+    /// Where the payload is delivered to, e.g. the webhook URL
+    pub destination: String,
+    pub payload: Value,
+    /// Failed attempts, oldest first
+    pub attempts: Vec<DeliveryAttempt>,
+    pub dead_lettered_at: DateTime<Utc>,
+}
+
+/// Dead-letter queue of failed asynchronous deliveries
+///
+/// Deliveries that keep failing are parked here with their payload and
+/// attempt history, so admins can retry them once the cause is fixed, or
+/// discard them. Retrying uses the redelivery registered for the kind of
+/// the entry. Discarding is recorded in the audit log. Clones share the
+/// same entries.
+#[derive(Clone)]
+pub struct DeadLetterQueue {
+    entries: Arc<RwLock<BTreeMap<u64, DeadLetter>>>,
+    next_id: Arc<AtomicU64>,
+    redeliveries: HashMap<String, Redelivery>,
+    audit_log: AuditLog,
+}
+
+impl DeadLetterQueue {
+    /// Create an empty queue recording discards into the given audit log
+    pub fn new(audit_log: AuditLog) -> Self {
+        Self {
+            entries: Arc::new(RwLock::new(BTreeMap::new())),
+            next_id: Arc::new(AtomicU64::new(1)),
+            redeliveries: HashMap::new(),
+            audit_log,
+        }
+    }
+
+    /// Retry entries of `kind` by calling `redeliver` with their destination
+    /// and payload
+    pub fn with_redelivery<F, Fut>(mut self, kind: &str, redeliver: F) -> Self
+    where
+        F: Fn(String, Value) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<(), String>> + Send + 'static,
+    {
+        self.redeliveries.insert(
+            kind.to_string(),
+            Arc::new(move |destination, payload| Box::pin(redeliver(destination, payload))),
+        );
+        self
+    }
+
+    /// Park a payload whose delivery failed
+    pub async fn push(
+        &self,
+        kind: &str,
+        source: &str,
+        destination: &str,
+        payload: Value,
+        attempts: Vec<DeliveryAttempt>,
+    ) -> DeadLetter {
+        let entry = DeadLetter {
+            id: self.next_id.fetch_add(1, Ordering::SeqCst),
+            kind: kind.to_string(),
+            source: source.to_string(),
+            destination: destination.to_string(),
+            payload,
+            attempts,
+            dead_lettered_at: Utc::now(),
+        };
+        tracing::warn!(
+            "Dead-lettered {} {} to {} after {} attempts",
+            entry.source,
+            entry.kind,
+            entry.destination,
+            entry.attempts.len()
+        );
+
+        let mut entries = self.entries.write().await;
+        if entries.len() >= MAX_DEAD_LETTERS {
+            entries.pop_first();
+        }
+        entries.insert(entry.id, entry.clone());
+        entry
+    }
+
+    /// All entries, oldest first
+    pub async fn list(&self) -> Vec<DeadLetter> {
+        self.entries.read().await.values().cloned().collect()
+    }
+
+    /// Deliver an entry again
+    ///
+    /// The entry is removed once delivered. A failed retry is added to its
+    /// attempts and reported as unavailable.
+    pub async fn retry(&self, id: u64) -> Result<(), AppError> {
+        let entry = self.get(id).await?;
+        let redeliver = self.redeliveries.get(&entry.kind).ok_or_else(|| {
+            AppError::UnprocessableEntity(format!("{} entries cannot be retried", entry.kind))
+        })?;
+
+        match redeliver(entry.destination.clone(), entry.payload.clone()).await {
+            Ok(()) => {
+                self.entries.write().await.remove(&id);
+                tracing::info!("Redelivered dead letter {} to {}", id, entry.destination);
+                Ok(())
+            }
+            Err(error) => {
+                if let Some(entry) = self.entries.write().await.get_mut(&id) {
+                    entry.attempts.push(DeliveryAttempt {
+                        attempted_at: Utc::now(),
+                        error: error.clone(),
+                    });
+                }
+                Err(AppError::ServiceUnavailable(format!(
+                    "Redelivery to {} failed: {}",
+                    entry.destination, error
+                )))
+            }
+        }
+    }
+
+    /// Drop an entry without delivering it
+    pub async fn discard(&self, id: u64, audit: &AuditContext) -> Result<DeadLetter, AppError> {
+        let entry = self
+            .entries
+            .write()
+            .await
+            .remove(&id)
+            .ok_or_else(|| Self::not_found(id))?;
+
+        tracing::info!("Discarded dead letter {}", id);
+        self.audit_log
+            .record(
+                audit,
+                AuditOperation::Delete,
+                "dead_letter",
+                id,
+                serde_json::to_value(&entry).ok(),
+                None,
+            )
+            .await;
+
+        Ok(entry)
+    }
+
+    async fn get(&self, id: u64) -> Result<DeadLetter, AppError> {
+        self.entries
+            .read()
+            .await
+            .get(&id)
+            .cloned()
+            .ok_or_else(|| Self::not_found(id))
+    }
+
+    fn not_found(id: u64) -> AppError {
+        AppError::NotFound(format!("Dead letter {} not found", id))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infrastructure::audit::AuditQuery;
+    use std::sync::atomic::AtomicBool;
+
+    fn attempt(error: &str) -> DeliveryAttempt {
+        DeliveryAttempt {
+            attempted_at: Utc::now(),
+            error: error.to_string(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_retry_and_discard() {
+        let audit_log = AuditLog::default();
+        let available = Arc::new(AtomicBool::new(false));
+        let queue = DeadLetterQueue::new(audit_log.clone()).with_redelivery("webhook", {
+            let available = available.clone();
+            move |_, _| {
+                let available = available.load(Ordering::SeqCst);
+                async move {
+                    if available {
+                        Ok(())
+                    } else {
+                        Err("connection refused".to_string())
+                    }
+                }
+            }
+        });
+
+        let alert = queue
+            .push(
+                "webhook",
+                "anomaly_alert",
+                "http://alerts.local/hook",
+                serde_json::json!({"id": 1}),
+                vec![attempt("timeout")],
+            )
+            .await;
+        let unknown = queue
+            .push("email", "digest", "admin@example.com", Value::Null, vec![])
+            .await;
+
+        // A failed retry is kept with its attempt
+        assert!(matches!(
+            queue.retry(alert.id).await,
+            Err(AppError::ServiceUnavailable(_))
+        ));
+        assert_eq!(queue.list().await[0].attempts.len(), 2);
+
+        available.store(true, Ordering::SeqCst);
+        queue.retry(alert.id).await.unwrap();
+        assert!(matches!(
+            queue.retry(alert.id).await,
+            Err(AppError::NotFound(_))
+        ));
+
+        // Kinds without a redelivery can only be discarded
+        assert!(matches!(
+            queue.retry(unknown.id).await,
+            Err(AppError::UnprocessableEntity(_))
+        ));
+        queue
+            .discard(unknown.id, &AuditContext::default())
+            .await
+            .unwrap();
+        assert!(queue.list().await.is_empty());
+
+        let entries = audit_log
+            .search(&AuditQuery {
+                resource_type: Some("dead_letter".to_string()),
+                ..Default::default()
+            })
+            .await;
+        assert_eq!(entries.len(), 1);
+    }
+}
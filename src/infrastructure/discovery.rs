@@ -0,0 +1,136 @@
+use futures::future::BoxFuture;
+use serde::Serialize;
+
+use super::lifecycle::Service;
+
+/// Consul service registration
+///
+/// Registers the server with the local Consul agent on startup, including an
+/// HTTP health check against the readiness probe `/health/ready`, and
+/// deregisters it as soon as a graceful shutdown is requested. Consul removes the service by itself if the health check stays
+/// critical, so a crashed instance does not linger in the catalog.
+#[derive(Clone)]
+pub struct ServiceRegistration {
+    client: reqwest::Client,
+    consul_url: String,
+    service: ConsulService,
+}
+
+/// Payload of the Consul agent `service/register` endpoint
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "PascalCase")]
+struct ConsulService {
+    #[serde(rename = "ID")]
+    id: String,
+    name: String,
+    address: String,
+    port: u16,
+    check: ConsulCheck,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "PascalCase")]
+struct ConsulCheck {
+    #[serde(rename = "HTTP")]
+    http: String,
+    interval: String,
+    deregister_critical_service_after: String,
+}
+
+impl ServiceRegistration {
+    /// Create a registration for `name` reachable at `address:port`
+    pub fn new(consul_url: &str, name: &str, address: &str, port: u16) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            consul_url: consul_url.trim_end_matches('/').to_string(),
+            service: ConsulService {
+                id: format!("{}-{}-{}", name, address, port),
+                name: name.to_string(),
+                address: address.to_string(),
+                port,
+                check: ConsulCheck {
+                    http: format!("http://{}:{}/health/ready", address, port),
+                    interval: "10s".to_string(),
+                    deregister_critical_service_after: "1m".to_string(),
+                },
+            },
+        }
+    }
+
+    /// Register the service with the Consul agent
+    pub async fn register(&self) -> anyhow::Result<()> {
+        self.client
+            .put(format!("{}/v1/agent/service/register", self.consul_url))
+            .json(&self.service)
+            .send()
+            .await?
+            .error_for_status()?;
+
+        tracing::info!("Registered service {} with Consul", self.service.id);
+        Ok(())
+    }
+
+    /// Deregister the service from the Consul agent
+    pub async fn deregister(&self) -> anyhow::Result<()> {
+        self.client
+            .put(format!(
+                "{}/v1/agent/service/deregister/{}",
+                self.consul_url, self.service.id
+            ))
+            .send()
+            .await?
+            .error_for_status()?;
+
+        tracing::info!("Deregistered service {} from Consul", self.service.id);
+        Ok(())
+    }
+}
+
+/// Registered once everything else started, deregistered first when a
+/// shutdown is requested, before in-flight requests are drained
+///
+/// An unreachable Consul agent does not keep the server from starting.
+impl Service for ServiceRegistration {
+    fn name(&self) -> &'static str {
+        "discovery"
+    }
+
+    fn start(&self) -> BoxFuture<'_, Result<(), String>> {
+        Box::pin(async move {
+            if let Err(err) = self.register().await {
+                tracing::warn!("Service registration failed: {}", err);
+            }
+            Ok(())
+        })
+    }
+
+    fn drain(&self) -> BoxFuture<'_, ()> {
+        Box::pin(async move {
+            if let Err(err) = self.deregister().await {
+                tracing::warn!("Service deregistration failed: {}", err);
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_registration_payload() {
+        let registration =
+            ServiceRegistration::new("http://consul:8500/", "webboard", "10.0.0.5", 3000);
+        assert_eq!(registration.consul_url, "http://consul:8500");
+
+        let payload = serde_json::to_value(&registration.service).unwrap();
+        assert_eq!(payload["ID"], "webboard-10.0.0.5-3000");
+        assert_eq!(payload["Name"], "webboard");
+        assert_eq!(payload["Port"], 3000);
+        assert_eq!(
+            payload["Check"]["HTTP"],
+            "http://10.0.0.5:3000/health/ready"
+        );
+        assert_eq!(payload["Check"]["DeregisterCriticalServiceAfter"], "1m");
+    }
+}
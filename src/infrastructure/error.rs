@@ -6,13 +6,26 @@ use axum::{
 use serde::Serialize;
 use std::fmt;
 
+use super::validation::FieldError;
+
 /// Application error type with HTTP status codes
 #[derive(Debug)]
 pub enum AppError {
     NotFound(String),
     BadRequest(String),
+    UnprocessableEntity(String),
     InternalError(String),
     Unauthorized(String),
+    Forbidden(String),
+    TooManyRequests(String),
+    ServiceUnavailable(String),
+    /// A unique field already has the given value, e.g. `email`
+    Conflict {
+        field: String,
+        message: String,
+    },
+    /// Fields of the request are invalid, see `validation::Validate`
+    Validation(Vec<FieldError>),
 }
 
 impl fmt::Display for AppError {
@@ -20,8 +33,23 @@ impl fmt::Display for AppError {
         match self {
             AppError::NotFound(msg) => write!(f, "Not Found: {}", msg),
             AppError::BadRequest(msg) => write!(f, "Bad Request: {}", msg),
+            AppError::UnprocessableEntity(msg) => write!(f, "Unprocessable Entity: {}", msg),
             AppError::InternalError(msg) => write!(f, "Internal Error: {}", msg),
             AppError::Unauthorized(msg) => write!(f, "Unauthorized: {}", msg),
+            AppError::Forbidden(msg) => write!(f, "Forbidden: {}", msg),
+            AppError::TooManyRequests(msg) => write!(f, "Too Many Requests: {}", msg),
+            AppError::ServiceUnavailable(msg) => write!(f, "Service Unavailable: {}", msg),
+            AppError::Conflict { field, message } => {
+                write!(f, "Conflict: {} ({})", message, field)
+            }
+            AppError::Validation(errors) => {
+                write!(f, "Validation Failed: ")?;
+                for (i, error) in errors.iter().enumerate() {
+                    let separator = if i == 0 { "" } else { "; " };
+                    write!(f, "{}{} ({})", separator, error.message, error.field)?;
+                }
+                Ok(())
+            }
         }
     }
 }
@@ -33,13 +61,32 @@ impl std::error::Error for AppError {}
 struct ErrorResponse {
     error: String,
     message: String,
+    /// Field the error is about
+    #[serde(skip_serializing_if = "Option::is_none")]
+    field: Option<String>,
+    /// Errors of the invalid fields of the request
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    errors: Vec<FieldError>,
 }
 
 impl IntoResponse for AppError {
     fn into_response(self) -> Response {
+        let field = match &self {
+            AppError::Conflict { field, .. } => Some(field.clone()),
+            _ => None,
+        };
+        let errors = match &self {
+            AppError::Validation(errors) => errors.clone(),
+            _ => Vec::new(),
+        };
         let (status, error_type, message) = match self {
             AppError::NotFound(msg) => (StatusCode::NOT_FOUND, "NOT_FOUND", msg),
             AppError::BadRequest(msg) => (StatusCode::BAD_REQUEST, "BAD_REQUEST", msg),
+            AppError::UnprocessableEntity(msg) => (
+                StatusCode::UNPROCESSABLE_ENTITY,
+                "UNPROCESSABLE_ENTITY",
+                msg,
+            ),
             AppError::InternalError(msg) => {
                 // Log internal errors but don't expose details to client
                 tracing::error!("Internal error: {}", msg);
@@ -50,11 +97,26 @@ impl IntoResponse for AppError {
                 )
             }
             AppError::Unauthorized(msg) => (StatusCode::UNAUTHORIZED, "UNAUTHORIZED", msg),
+            AppError::Forbidden(msg) => (StatusCode::FORBIDDEN, "FORBIDDEN", msg),
+            AppError::TooManyRequests(msg) => {
+                (StatusCode::TOO_MANY_REQUESTS, "TOO_MANY_REQUESTS", msg)
+            }
+            AppError::ServiceUnavailable(msg) => {
+                (StatusCode::SERVICE_UNAVAILABLE, "SERVICE_UNAVAILABLE", msg)
+            }
+            AppError::Conflict { message, .. } => (StatusCode::CONFLICT, "CONFLICT", message),
+            AppError::Validation(_) => (
+                StatusCode::UNPROCESSABLE_ENTITY,
+                "VALIDATION_FAILED",
+                "Request validation failed".to_string(),
+            ),
         };
 
         let body = Json(ErrorResponse {
             error: error_type.to_string(),
             message,
+            field,
+            errors,
         });
 
         (status, body).into_response()
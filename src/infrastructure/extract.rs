@@ -0,0 +1,140 @@
+use axum::{
+    async_trait,
+    extract::{FromRequest, Request},
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde::de::DeserializeOwned;
+
+use super::error::AppError;
+
+/// How unknown fields in JSON request bodies are handled
+///
+/// Strict mode gives integrators early feedback about misspelled or
+/// unsupported fields; lenient mode keeps older clients working.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DeserializationMode {
+    /// Unknown fields are ignored
+    #[default]
+    Lenient,
+    /// Unknown fields are rejected with 422 Unprocessable Entity
+    Strict,
+}
+
+/// JSON request body extractor honoring the deserialization mode
+///
+/// Drop-in replacement for `axum::Json` in handlers. The mode is read from
+/// the `DeserializationMode` request extension and defaults to lenient when
+/// the extension is missing.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct JsonBody<T>(pub T);
+
+#[async_trait]
+impl<T, S> FromRequest<S> for JsonBody<T>
+where
+    T: DeserializeOwned,
+    S: Send + Sync,
+{
+    type Rejection = Response;
+
+    async fn from_request(request: Request, state: &S) -> Result<Self, Self::Rejection> {
+        let mode = request
+            .extensions()
+            .get::<DeserializationMode>()
+            .copied()
+            .unwrap_or_default();
+
+        // Syntax and content type errors keep axum's rejections
+        let Json(value) = Json::<serde_json::Value>::from_request(request, state)
+            .await
+            .map_err(IntoResponse::into_response)?;
+
+        let mut unknown_fields = Vec::new();
+        let payload = serde_ignored::deserialize(value, |path| {
+            unknown_fields.push(path.to_string());
+        })
+        .map_err(|e| {
+            AppError::UnprocessableEntity(format!("Invalid request body: {}", e)).into_response()
+        })?;
+
+        if !unknown_fields.is_empty() {
+            match mode {
+                DeserializationMode::Strict => {
+                    return Err(AppError::UnprocessableEntity(format!(
+                        "Unknown fields in request body: {}",
+                        unknown_fields.join(", ")
+                    ))
+                    .into_response());
+                }
+                DeserializationMode::Lenient => {
+                    tracing::debug!("Ignoring unknown fields: {}", unknown_fields.join(", "));
+                }
+            }
+        }
+
+        Ok(JsonBody(payload))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{
+        body::Body,
+        http::{Request, StatusCode},
+        routing::post,
+        Extension, Router,
+    };
+    use serde::Deserialize;
+    use tower::util::ServiceExt;
+
+    #[derive(Deserialize)]
+    struct Payload {
+        #[allow(dead_code)]
+        name: String,
+    }
+
+    async fn handler(JsonBody(_payload): JsonBody<Payload>) -> StatusCode {
+        StatusCode::OK
+    }
+
+    async fn send(mode: DeserializationMode, body: &'static str) -> StatusCode {
+        let app = Router::new()
+            .route("/", post(handler))
+            .layer(Extension(mode));
+
+        let request = Request::builder()
+            .uri("/")
+            .method("POST")
+            .header("content-type", "application/json")
+            .body(Body::from(body))
+            .unwrap();
+
+        app.oneshot(request).await.unwrap().status()
+    }
+
+    #[tokio::test]
+    async fn test_unknown_fields() {
+        let body = r#"{"name":"john","nmae":"typo"}"#;
+        assert_eq!(
+            send(DeserializationMode::Lenient, body).await,
+            StatusCode::OK
+        );
+        assert_eq!(
+            send(DeserializationMode::Strict, body).await,
+            StatusCode::UNPROCESSABLE_ENTITY
+        );
+    }
+
+    #[tokio::test]
+    async fn test_invalid_body() {
+        assert_eq!(
+            send(DeserializationMode::Strict, r#"{"name":1}"#).await,
+            StatusCode::UNPROCESSABLE_ENTITY
+        );
+        assert_eq!(
+            send(DeserializationMode::Strict, r#"{"name":"#).await,
+            StatusCode::BAD_REQUEST
+        );
+    }
+}
@@ -0,0 +1,158 @@
+use axum::{
+    extract::{ConnectInfo, Request, State},
+    middleware::Next,
+    response::Response,
+};
+use futures::future::BoxFuture;
+use maxminddb::{geoip2, MaxMindDBError, Reader};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::net::{IpAddr, SocketAddr};
+use std::path::PathBuf;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+
+use super::lifecycle::Service;
+
+/// A loaded MaxMind database
+type Database = Arc<Reader<Vec<u8>>>;
+
+/// Country and region an IP address is located in
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct GeoLocation {
+    /// ISO 3166-1 country code
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub country: Option<String>,
+    /// ISO 3166-2 subdivision code, without the country prefix
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub region: Option<String>,
+}
+
+/// GeoIP lookup backed by a MaxMind database
+///
+/// The database is optional: without one every lookup returns `None`.
+/// It is read from `path` and reloaded periodically, so updated databases
+/// are picked up without a restart. A failed reload keeps the database
+/// loaded before.
+#[derive(Clone, Default)]
+pub struct GeoIp {
+    path: Option<PathBuf>,
+    reader: Arc<RwLock<Option<Database>>>,
+}
+
+impl GeoIp {
+    /// Create a lookup for the database at `path`
+    ///
+    /// Nothing is read until `reload` is called.
+    pub fn new(path: Option<PathBuf>) -> Self {
+        Self {
+            path,
+            reader: Arc::new(RwLock::new(None)),
+        }
+    }
+
+    /// Check if a database is loaded
+    pub async fn is_loaded(&self) -> bool {
+        self.reader.read().await.is_some()
+    }
+
+    /// Read the database from disk again
+    pub async fn reload(&self) -> Result<(), MaxMindDBError> {
+        let Some(path) = self.path.clone() else {
+            return Ok(());
+        };
+
+        let reader = tokio::task::spawn_blocking(move || Reader::open_readfile(path))
+            .await
+            .map_err(|e| MaxMindDBError::IoError(e.to_string()))??;
+
+        *self.reader.write().await = Some(Arc::new(reader));
+        Ok(())
+    }
+
+    /// Location of an IP address, if the database knows it
+    pub async fn lookup(&self, ip: IpAddr) -> Option<GeoLocation> {
+        let reader = self.reader.read().await.clone()?;
+
+        let city = match reader.lookup::<geoip2::City>(ip) {
+            Ok(city) => city,
+            Err(MaxMindDBError::AddressNotFoundError(_)) => return None,
+            Err(e) => {
+                tracing::debug!("GeoIP lookup of {} failed: {}", ip, e);
+                return None;
+            }
+        };
+
+        let location = GeoLocation {
+            country: city
+                .country
+                .and_then(|country| country.iso_code)
+                .map(str::to_string),
+            region: city
+                .subdivisions
+                .and_then(|subdivisions| subdivisions.into_iter().next())
+                .and_then(|subdivision| subdivision.iso_code)
+                .map(str::to_string),
+        };
+        (location.country.is_some() || location.region.is_some()).then_some(location)
+    }
+}
+
+/// Loads the database on startup
+///
+/// Without a database lookups return `None`, so a missing or broken
+/// database is logged and the server starts anyway.
+impl Service for GeoIp {
+    fn name(&self) -> &'static str {
+        "geoip"
+    }
+
+    fn start(&self) -> BoxFuture<'_, Result<(), String>> {
+        Box::pin(async move {
+            if let Err(err) = self.reload().await {
+                tracing::warn!("GeoIP database not loaded: {}", err);
+            }
+            Ok(())
+        })
+    }
+}
+
+/// GeoIP middleware
+///
+/// Looks up the client IP and adds its `GeoLocation` to the request
+/// extensions, where the audit context and anomaly detection pick it up.
+/// Requests from unknown addresses get no location.
+pub async fn geoip_middleware(
+    State(geoip): State<GeoIp>,
+    mut request: Request,
+    next: Next,
+) -> Response {
+    let ip = request
+        .extensions()
+        .get::<ConnectInfo<SocketAddr>>()
+        .map(|ConnectInfo(addr)| addr.ip());
+
+    if let Some(ip) = ip {
+        if let Some(location) = geoip.lookup(ip).await {
+            request.extensions_mut().insert(location);
+        }
+    }
+
+    next.run(request).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_lookup_without_database() {
+        let geoip = GeoIp::new(Some(PathBuf::from("/nonexistent/GeoLite2-City.mmdb")));
+        assert!(geoip.reload().await.is_err());
+        assert!(!geoip.is_loaded().await);
+        assert_eq!(geoip.lookup("8.8.8.8".parse().unwrap()).await, None);
+
+        // Without a path there is nothing to load
+        assert!(GeoIp::default().reload().await.is_ok());
+    }
+}
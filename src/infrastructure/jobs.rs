@@ -0,0 +1,402 @@
+use chrono::{DateTime, Utc};
+use futures::future::BoxFuture;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::future::Future;
+use std::sync::{Arc, Mutex, PoisonError, RwLock};
+use std::time::Duration;
+use tokio::sync::{watch, Notify};
+use tokio::time::Instant;
+
+use super::error::AppError;
+use super::lifecycle::Service;
+
+/// Outcome of a job run, with the error message of a failed run
+pub type JobResult = Result<(), String>;
+
+/// Function running a job once
+type JobFn = Arc<dyn Fn() -> BoxFuture<'static, JobResult> + Send + Sync>;
+
+/// Job waiting for the scheduler to start, with its delay and interval
+type PendingJob = (Arc<Job>, Duration, Duration, JobFn);
+
+/// State of a background job
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum JobState {
+    /// Waiting for its next run; the last run, if any, succeeded
+    Scheduled,
+    /// Currently running
+    Running,
+    /// Waiting for its next run after the last run failed
+    Failed,
+}
+
+/// Status of a background job
+#[derive(Debug, Clone, Serialize)]
+pub struct JobStatus {
+    pub name: String,
+    pub state: JobState,
+    pub interval_secs: u64,
+    /// Next scheduled run, unless the job is running
+    pub next_run_at: Option<DateTime<Utc>>,
+    pub last_started_at: Option<DateTime<Utc>>,
+    pub last_finished_at: Option<DateTime<Utc>>,
+    /// Error of the last failed run, kept until a run succeeds
+    pub last_error: Option<String>,
+    pub runs: u64,
+    pub failures: u64,
+}
+
+/// A scheduled job with its status
+struct Job {
+    status: Mutex<JobStatus>,
+    /// Wakes the job for a run ahead of schedule
+    trigger: Notify,
+    /// Held while the job runs, so shutdown can wait for the run to finish
+    running: tokio::sync::Mutex<()>,
+}
+
+impl Job {
+    fn update(&self, update: impl FnOnce(&mut JobStatus)) {
+        update(&mut self.status.lock().unwrap_or_else(PoisonError::into_inner));
+    }
+
+    fn status(&self) -> JobStatus {
+        self.status
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+}
+
+/// In-process scheduler of background jobs
+///
+/// Runs each job periodically on its own task and keeps its status, so
+/// operators can see what runs when and why it failed. Jobs can be triggered
+/// ahead of schedule, e.g. to retry a failed run. Clones share the same
+/// jobs. Jobs scheduled before the scheduler is started wait for `start`,
+/// so they never run before the services they use are started. On shutdown
+/// no new runs start and running jobs are waited for.
+#[derive(Clone)]
+pub struct JobScheduler {
+    jobs: Arc<RwLock<BTreeMap<String, Arc<Job>>>>,
+    /// Jobs waiting for the scheduler to start, `None` once it started
+    pending: Arc<Mutex<Option<Vec<PendingJob>>>>,
+    /// Set once the scheduler is shut down
+    stopped: Arc<watch::Sender<bool>>,
+}
+
+impl JobScheduler {
+    /// Create a scheduler without jobs
+    pub fn new() -> Self {
+        Self {
+            jobs: Arc::default(),
+            pending: Arc::new(Mutex::new(Some(Vec::new()))),
+            stopped: Arc::new(watch::Sender::new(false)),
+        }
+    }
+
+    /// Run `run` every `interval`, starting once the scheduler started
+    pub fn schedule<F, Fut>(&self, name: &str, interval: Duration, run: F)
+    where
+        F: Fn() -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = JobResult> + Send + 'static,
+    {
+        self.schedule_after(name, Duration::ZERO, interval, run)
+    }
+
+    /// Run `run` every `interval`, starting `delay` after the scheduler
+    /// started
+    ///
+    /// The interval is counted from the end of a run, so runs never
+    /// overlap. A job scheduled under an existing name replaces it in the
+    /// listing; its task keeps running.
+    pub fn schedule_after<F, Fut>(&self, name: &str, delay: Duration, interval: Duration, run: F)
+    where
+        F: Fn() -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = JobResult> + Send + 'static,
+    {
+        let run: JobFn = Arc::new(move || Box::pin(run()));
+        let job = Arc::new(Job {
+            status: Mutex::new(JobStatus {
+                name: name.to_string(),
+                state: JobState::Scheduled,
+                interval_secs: interval.as_secs(),
+                next_run_at: None,
+                last_started_at: None,
+                last_finished_at: None,
+                last_error: None,
+                runs: 0,
+                failures: 0,
+            }),
+            trigger: Notify::new(),
+            running: tokio::sync::Mutex::new(()),
+        });
+        self.jobs
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+            .insert(name.to_string(), job.clone());
+
+        match self
+            .pending
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .as_mut()
+        {
+            Some(pending) => pending.push((job, delay, interval, run)),
+            None => self.spawn(job, delay, interval, run),
+        }
+    }
+
+    /// Start running the jobs scheduled so far
+    ///
+    /// Jobs scheduled afterwards start right away.
+    pub fn start(&self) {
+        let pending = self
+            .pending
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .take()
+            .unwrap_or_default();
+        for (job, delay, interval, run) in pending {
+            self.spawn(job, delay, interval, run);
+        }
+    }
+
+    fn spawn(&self, job: Arc<Job>, delay: Duration, interval: Duration, run: JobFn) {
+        let mut stopped = self.stopped.subscribe();
+        tokio::spawn(async move {
+            let mut next_run = Instant::now() + delay;
+            loop {
+                let wait = next_run.saturating_duration_since(Instant::now());
+                job.update(|status| {
+                    status.next_run_at = chrono::Duration::from_std(wait)
+                        .ok()
+                        .map(|wait| Utc::now() + wait);
+                });
+                tokio::select! {
+                    _ = tokio::time::sleep_until(next_run) => {}
+                    _ = job.trigger.notified() => {}
+                    Ok(_) = stopped.wait_for(|stopped| *stopped) => break,
+                }
+                let _running = job.running.lock().await;
+                if *stopped.borrow() {
+                    break;
+                }
+
+                job.update(|status| {
+                    status.state = JobState::Running;
+                    status.next_run_at = None;
+                    status.last_started_at = Some(Utc::now());
+                });
+                let result = run().await;
+                job.update(|status| {
+                    status.runs += 1;
+                    status.last_finished_at = Some(Utc::now());
+                    match result {
+                        Ok(()) => {
+                            status.state = JobState::Scheduled;
+                            status.last_error = None;
+                        }
+                        Err(err) => {
+                            tracing::warn!("Job {} failed: {}", status.name, err);
+                            status.state = JobState::Failed;
+                            status.failures += 1;
+                            status.last_error = Some(err);
+                        }
+                    }
+                });
+                next_run = Instant::now() + interval;
+            }
+            job.update(|status| status.next_run_at = None);
+        });
+    }
+
+    /// Status of all jobs, by name
+    pub fn list(&self) -> Vec<JobStatus> {
+        self.jobs
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .values()
+            .map(|job| job.status())
+            .collect()
+    }
+
+    /// Run a job now instead of waiting for its next scheduled run
+    ///
+    /// A job that is running runs again right after. Returns the status of
+    /// the job before the run.
+    pub fn trigger(&self, name: &str) -> Result<JobStatus, AppError> {
+        let job = self
+            .jobs
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get(name)
+            .cloned()
+            .ok_or_else(|| AppError::NotFound(format!("Job {} not found", name)))?;
+        job.trigger.notify_one();
+        tracing::info!("Job {} triggered", name);
+        Ok(job.status())
+    }
+
+    /// Stop running jobs on schedule
+    ///
+    /// Waits for the runs in progress to finish. Jobs scheduled afterwards
+    /// never run.
+    pub async fn shutdown(&self) {
+        self.stopped.send_replace(true);
+        let jobs: Vec<Arc<Job>> = self
+            .jobs
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .values()
+            .cloned()
+            .collect();
+        for job in jobs {
+            drop(job.running.lock().await);
+        }
+    }
+}
+
+impl Default for JobScheduler {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Service for JobScheduler {
+    fn name(&self) -> &'static str {
+        "jobs"
+    }
+
+    fn start(&self) -> BoxFuture<'_, Result<(), String>> {
+        Box::pin(async move {
+            JobScheduler::start(self);
+            Ok(())
+        })
+    }
+
+    fn shutdown(&self) -> BoxFuture<'_, ()> {
+        Box::pin(JobScheduler::shutdown(self))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicU64, Ordering};
+
+    #[tokio::test]
+    async fn test_failed_job_is_retried_on_trigger() {
+        let scheduler = JobScheduler::new();
+        let calls = Arc::new(AtomicU64::new(0));
+        let finished = Arc::new(Notify::new());
+        scheduler.schedule("flaky", Duration::from_secs(3600), {
+            let calls = calls.clone();
+            let finished = finished.clone();
+            move || {
+                let calls = calls.clone();
+                let finished = finished.clone();
+                async move {
+                    let call = calls.fetch_add(1, Ordering::SeqCst);
+                    finished.notify_one();
+                    if call == 0 {
+                        Err("storage unavailable".to_string())
+                    } else {
+                        Ok(())
+                    }
+                }
+            }
+        });
+        scheduler.start();
+
+        // The first run starts at once and fails
+        finished.notified().await;
+        tokio::task::yield_now().await;
+        let status = &scheduler.list()[0];
+        assert_eq!(status.state, JobState::Failed);
+        assert_eq!(status.last_error.as_deref(), Some("storage unavailable"));
+        assert!(status.next_run_at.is_some());
+
+        // Retrying runs it again without waiting for the interval
+        scheduler.trigger("flaky").unwrap();
+        finished.notified().await;
+        tokio::task::yield_now().await;
+        let status = &scheduler.list()[0];
+        assert_eq!(status.state, JobState::Scheduled);
+        assert!(status.last_error.is_none());
+        assert_eq!((status.runs, status.failures), (2, 1));
+
+        assert!(matches!(
+            scheduler.trigger("missing"),
+            Err(AppError::NotFound(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_shutdown_waits_for_running_job() {
+        let scheduler = JobScheduler::new();
+        let runs = Arc::new(AtomicU64::new(0));
+        let started = Arc::new(Notify::new());
+        scheduler.schedule("slow", Duration::from_millis(10), {
+            let runs = runs.clone();
+            let started = started.clone();
+            move || {
+                let runs = runs.clone();
+                let started = started.clone();
+                async move {
+                    started.notify_one();
+                    tokio::time::sleep(Duration::from_millis(50)).await;
+                    runs.fetch_add(1, Ordering::SeqCst);
+                    Ok(())
+                }
+            }
+        });
+
+        scheduler.start();
+        started.notified().await;
+        scheduler.shutdown().await;
+        assert_eq!(runs.load(Ordering::SeqCst), 1);
+
+        // No runs start after shutdown
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert_eq!(runs.load(Ordering::SeqCst), 1);
+        assert!(scheduler.list()[0].next_run_at.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_jobs_wait_for_start() {
+        let scheduler = JobScheduler::new();
+        let runs = Arc::new(AtomicU64::new(0));
+        let finished = Arc::new(Notify::new());
+        let job = {
+            let runs = runs.clone();
+            let finished = finished.clone();
+            move || {
+                let runs = runs.clone();
+                let finished = finished.clone();
+                async move {
+                    runs.fetch_add(1, Ordering::SeqCst);
+                    finished.notify_one();
+                    Ok(())
+                }
+            }
+        };
+        scheduler.schedule("early", Duration::from_secs(3600), job.clone());
+
+        // Nothing runs before the scheduler is started, e.g. while restoring
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        assert_eq!(runs.load(Ordering::SeqCst), 0);
+        assert_eq!(scheduler.list()[0].state, JobState::Scheduled);
+
+        scheduler.start();
+        finished.notified().await;
+        assert_eq!(runs.load(Ordering::SeqCst), 1);
+
+        // Jobs scheduled once started run right away
+        scheduler.schedule("late", Duration::from_secs(3600), job);
+        finished.notified().await;
+        assert_eq!(runs.load(Ordering::SeqCst), 2);
+    }
+}
@@ -0,0 +1,299 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::sync::{Arc, PoisonError, RwLock};
+
+use super::audit::{AuditContext, AuditEntry, AuditLog, AuditOperation};
+use super::error::AppError;
+
+/// Kind of content a legal hold can be placed on
+///
+/// Named after the audit log resource types of the held content.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HoldKind {
+    Post,
+    User,
+}
+
+impl fmt::Display for HoldKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HoldKind::Post => write!(f, "post"),
+            HoldKind::User => write!(f, "user"),
+        }
+    }
+}
+
+/// Legal hold on a post or user
+///
+/// Held content is exempt from retention and cannot be deleted, not even
+/// by admins, until the hold is released.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LegalHold {
+    pub kind: HoldKind,
+    pub id: u64,
+    pub reason: String,
+    /// Stored actor id of the admin who placed the hold
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub placed_by: Option<String>,
+    pub placed_at: DateTime<Utc>,
+}
+
+/// Request to place a legal hold
+#[derive(Debug, Deserialize)]
+pub struct PlaceLegalHoldRequest {
+    pub kind: HoldKind,
+    pub id: u64,
+    pub reason: String,
+}
+
+impl PlaceLegalHoldRequest {
+    /// Validate place legal hold request
+    pub fn validate(&self) -> Result<(), String> {
+        if self.reason.trim().is_empty() {
+            return Err("Reason cannot be empty".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// Registry of legal holds
+///
+/// Checked by the retention job and by services before deleting content.
+/// Placing and releasing holds is recorded in the audit log together with
+/// the reason. Holds are checked from synchronous retention filters, hence
+/// the std lock.
+#[derive(Clone)]
+pub struct LegalHolds {
+    holds: Arc<RwLock<BTreeMap<(HoldKind, u64), LegalHold>>>,
+    audit_log: AuditLog,
+}
+
+impl LegalHolds {
+    /// Create an empty registry recording holds into the given audit log
+    pub fn new(audit_log: AuditLog) -> Self {
+        Self {
+            holds: Arc::new(RwLock::new(BTreeMap::new())),
+            audit_log,
+        }
+    }
+
+    /// Place a legal hold
+    ///
+    /// Holds can be placed on content before it is known to this instance.
+    /// Content can only be under one hold at a time.
+    pub async fn place(
+        &self,
+        request: PlaceLegalHoldRequest,
+        audit: &AuditContext,
+    ) -> Result<LegalHold, AppError> {
+        request.validate().map_err(AppError::BadRequest)?;
+
+        let hold = LegalHold {
+            kind: request.kind,
+            id: request.id,
+            reason: request.reason,
+            placed_by: audit
+                .actor
+                .as_ref()
+                .map(|actor| self.audit_log.stored_actor_id(actor)),
+            placed_at: Utc::now(),
+        };
+        {
+            let mut holds = self.holds.write().unwrap_or_else(PoisonError::into_inner);
+            if let Some(existing) = holds.get(&(hold.kind, hold.id)) {
+                return Err(AppError::BadRequest(format!(
+                    "The {} {} is already under legal hold since {}",
+                    existing.kind,
+                    existing.id,
+                    existing.placed_at.to_rfc3339()
+                )));
+            }
+            holds.insert((hold.kind, hold.id), hold.clone());
+        }
+
+        tracing::info!("Placed legal hold on {} {}", hold.kind, hold.id);
+        self.audit_log
+            .record(
+                audit,
+                AuditOperation::Create,
+                "legal_hold",
+                format!("{}/{}", hold.kind, hold.id),
+                None,
+                serde_json::to_value(&hold).ok(),
+            )
+            .await;
+
+        Ok(hold)
+    }
+
+    /// Release a legal hold
+    pub async fn release(
+        &self,
+        kind: HoldKind,
+        id: u64,
+        audit: &AuditContext,
+    ) -> Result<LegalHold, AppError> {
+        let hold = self
+            .holds
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+            .remove(&(kind, id))
+            .ok_or_else(|| AppError::NotFound(format!("No legal hold on {} {}", kind, id)))?;
+
+        tracing::info!("Released legal hold on {} {}", kind, id);
+        self.audit_log
+            .record(
+                audit,
+                AuditOperation::Delete,
+                "legal_hold",
+                format!("{}/{}", kind, id),
+                serde_json::to_value(&hold).ok(),
+                None,
+            )
+            .await;
+
+        Ok(hold)
+    }
+
+    /// All legal holds in place
+    pub fn list(&self) -> Vec<LegalHold> {
+        self.holds
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .values()
+            .cloned()
+            .collect()
+    }
+
+    /// Check if the content is under legal hold
+    pub fn is_held(&self, kind: HoldKind, id: u64) -> bool {
+        self.holds
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .contains_key(&(kind, id))
+    }
+
+    /// Reject deleting content under legal hold
+    pub fn ensure_not_held(&self, kind: HoldKind, id: u64) -> Result<(), AppError> {
+        if self.is_held(kind, id) {
+            return Err(AppError::Forbidden(format!(
+                "The {} {} is under legal hold and cannot be deleted",
+                kind, id
+            )));
+        }
+        Ok(())
+    }
+
+    /// Check if an audit entry concerns held content
+    ///
+    /// Covers entries about a held post or user and entries recorded by a
+    /// held user.
+    pub fn covers(&self, entry: &AuditEntry) -> bool {
+        let holds = self.holds.read().unwrap_or_else(PoisonError::into_inner);
+        if holds.is_empty() {
+            return false;
+        }
+
+        holds.values().any(|hold| {
+            let id = hold.id.to_string();
+            (entry.resource_type == hold.kind.to_string() && entry.resource_id == id)
+                || (hold.kind == HoldKind::User
+                    && entry
+                        .actor
+                        .as_ref()
+                        .is_some_and(|actor| actor.id == format!("user:{}", id)))
+        })
+    }
+}
+
+impl Default for LegalHolds {
+    fn default() -> Self {
+        Self::new(AuditLog::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infrastructure::audit::{AuditActor, AuditQuery};
+
+    #[tokio::test]
+    async fn test_place_and_release() {
+        let audit_log = AuditLog::default();
+        let holds = LegalHolds::new(audit_log.clone());
+        let admin = AuditContext {
+            actor: Some(AuditActor::verified(1)),
+            ..Default::default()
+        };
+
+        let request = |reason: &str| PlaceLegalHoldRequest {
+            kind: HoldKind::Post,
+            id: 7,
+            reason: reason.to_string(),
+        };
+        assert!(holds.place(request(" "), &admin).await.is_err());
+
+        let hold = holds.place(request("Case 2024-118"), &admin).await.unwrap();
+        assert_eq!(hold.placed_by.as_deref(), Some("user:1"));
+        assert!(holds.is_held(HoldKind::Post, 7));
+        assert!(!holds.is_held(HoldKind::User, 7));
+        assert!(holds.ensure_not_held(HoldKind::Post, 7).is_err());
+        assert!(holds.place(request("Again"), &admin).await.is_err());
+
+        holds.release(HoldKind::Post, 7, &admin).await.unwrap();
+        assert!(holds.ensure_not_held(HoldKind::Post, 7).is_ok());
+        assert!(holds.release(HoldKind::Post, 7, &admin).await.is_err());
+
+        // The reason is kept in the audit log
+        let query = AuditQuery {
+            resource_type: Some("legal_hold".to_string()),
+            ..Default::default()
+        };
+        let entries = audit_log.search(&query).await;
+        assert_eq!(entries.len(), 2);
+        assert_eq!(
+            entries[1].after.as_ref().unwrap()["reason"],
+            "Case 2024-118"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_covers_entries_of_held_content() {
+        let audit_log = AuditLog::default();
+        let holds = LegalHolds::new(audit_log.clone());
+        for (kind, id) in [(HoldKind::Post, 7), (HoldKind::User, 3)] {
+            let request = PlaceLegalHoldRequest {
+                kind,
+                id,
+                reason: "Case 2024-118".to_string(),
+            };
+            holds
+                .place(request, &AuditContext::default())
+                .await
+                .unwrap();
+        }
+
+        let entry =
+            |actor: Option<AuditActor>, resource_type: &str, resource_id: &str| AuditEntry {
+                id: 1,
+                timestamp: Utc::now(),
+                actor,
+                operation: AuditOperation::Create,
+                resource_type: resource_type.to_string(),
+                resource_id: resource_id.to_string(),
+                before: None,
+                after: None,
+                request_id: None,
+                location: None,
+                token_id: None,
+            };
+        assert!(holds.covers(&entry(None, "post", "7")));
+        assert!(holds.covers(&entry(None, "user", "3")));
+        assert!(holds.covers(&entry(Some(AuditActor::verified(3)), "board", "1")));
+        assert!(!holds.covers(&entry(None, "post", "8")));
+        assert!(!holds.covers(&entry(Some(AuditActor::verified(4)), "user", "7")));
+    }
+}
@@ -0,0 +1,259 @@
+use futures::future::BoxFuture;
+use std::sync::{Arc, PoisonError, RwLock};
+
+/// Component started before the server accepts requests and shut down
+/// after it stopped accepting them
+///
+/// When a shutdown is requested, components are drained first, while
+/// in-flight requests are still being served.
+/// All hooks default to doing nothing, so components implement only the
+/// ones they need.
+pub trait Service: Send + Sync {
+    /// Name the component is logged and reported under, e.g. `jobs`
+    fn name(&self) -> &'static str;
+
+    /// Prepare the component; an error aborts startup
+    fn start(&self) -> BoxFuture<'_, Result<(), String>> {
+        Box::pin(async { Ok(()) })
+    }
+
+    /// Check the component; the error tells why it is unavailable
+    fn health(&self) -> BoxFuture<'_, Result<(), String>> {
+        Box::pin(async { Ok(()) })
+    }
+
+    /// Stop attracting new work, e.g. leave service discovery
+    fn drain(&self) -> BoxFuture<'_, ()> {
+        Box::pin(async {})
+    }
+
+    /// Release the component, e.g. finish work in progress
+    fn shutdown(&self) -> BoxFuture<'_, ()> {
+        Box::pin(async {})
+    }
+}
+
+/// Where the services are in their lifecycle
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LifecycleState {
+    /// Not started yet, or starting
+    Starting,
+    /// All services started
+    Running,
+    /// Shutting down or shut down
+    Stopping,
+}
+
+/// Services started in order and shut down in reverse order
+///
+/// A service can rely on the services added before it during its whole
+/// lifetime: they are started before it and shut down after it. When a
+/// service fails to start, the services already started are shut down again.
+#[derive(Clone)]
+pub struct Lifecycle {
+    services: Vec<Arc<dyn Service>>,
+    state: Arc<RwLock<LifecycleState>>,
+}
+
+impl Lifecycle {
+    /// Create a lifecycle without services
+    pub fn new() -> Self {
+        Self {
+            services: Vec::new(),
+            state: Arc::new(RwLock::new(LifecycleState::Starting)),
+        }
+    }
+
+    /// Add a service, started after the services added so far
+    pub fn with_service(mut self, service: impl Service + 'static) -> Self {
+        self.services.push(Arc::new(service));
+        self
+    }
+
+    /// Current state
+    pub fn state(&self) -> LifecycleState {
+        *self.state.read().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn set_state(&self, state: LifecycleState) {
+        *self.state.write().unwrap_or_else(PoisonError::into_inner) = state;
+    }
+
+    /// Start all services in order
+    ///
+    /// Stops at the first service failing to start, shuts down the ones
+    /// started before it and returns its error.
+    pub async fn start(&self) -> Result<(), String> {
+        for (started, service) in self.services.iter().enumerate() {
+            tracing::info!("Starting {}", service.name());
+            if let Err(err) = service.start().await {
+                self.set_state(LifecycleState::Stopping);
+                for service in self.services[..started].iter().rev() {
+                    tracing::info!("Shutting down {}", service.name());
+                    service.shutdown().await;
+                }
+                return Err(format!("Failed to start {}: {}", service.name(), err));
+            }
+        }
+        self.set_state(LifecycleState::Running);
+        Ok(())
+    }
+
+    /// Drain all services in reverse order once a shutdown was requested
+    ///
+    /// Health checks fail from now on, so the instance is taken out of
+    /// rotation before in-flight requests are drained.
+    pub async fn drain(&self) {
+        self.set_state(LifecycleState::Stopping);
+        for service in self.services.iter().rev() {
+            tracing::info!("Draining {}", service.name());
+            service.drain().await;
+        }
+    }
+
+    /// Shut down all services in reverse order
+    pub async fn shutdown(&self) {
+        self.set_state(LifecycleState::Stopping);
+        for service in self.services.iter().rev() {
+            tracing::info!("Shutting down {}", service.name());
+            service.shutdown().await;
+        }
+    }
+
+    /// Check all services
+    ///
+    /// Fails while the services are starting or shutting down, and with the
+    /// errors of all unhealthy services otherwise.
+    pub async fn health(&self) -> Result<(), String> {
+        match self.state() {
+            LifecycleState::Starting => return Err("Services are starting".to_string()),
+            LifecycleState::Stopping => return Err("Services are shutting down".to_string()),
+            LifecycleState::Running => {}
+        }
+
+        let mut errors = Vec::new();
+        for service in &self.services {
+            if let Err(err) = service.health().await {
+                errors.push(format!("{}: {}", service.name(), err));
+            }
+        }
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors.join("; "))
+        }
+    }
+}
+
+impl Default for Lifecycle {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    /// Service recording its hooks in a shared log
+    struct Recorder {
+        name: &'static str,
+        log: Arc<Mutex<Vec<String>>>,
+        fail_start: bool,
+    }
+
+    impl Service for Recorder {
+        fn name(&self) -> &'static str {
+            self.name
+        }
+
+        fn start(&self) -> BoxFuture<'_, Result<(), String>> {
+            Box::pin(async move {
+                self.log
+                    .lock()
+                    .unwrap()
+                    .push(format!("start {}", self.name));
+                if self.fail_start {
+                    Err("unreachable".to_string())
+                } else {
+                    Ok(())
+                }
+            })
+        }
+
+        fn drain(&self) -> BoxFuture<'_, ()> {
+            Box::pin(async move {
+                self.log
+                    .lock()
+                    .unwrap()
+                    .push(format!("drain {}", self.name));
+            })
+        }
+
+        fn shutdown(&self) -> BoxFuture<'_, ()> {
+            Box::pin(async move {
+                self.log
+                    .lock()
+                    .unwrap()
+                    .push(format!("shutdown {}", self.name));
+            })
+        }
+    }
+
+    fn lifecycle(log: &Arc<Mutex<Vec<String>>>, failing: Option<&str>) -> Lifecycle {
+        ["cache", "jobs", "discovery"]
+            .into_iter()
+            .fold(Lifecycle::new(), |lifecycle, name| {
+                lifecycle.with_service(Recorder {
+                    name,
+                    log: log.clone(),
+                    fail_start: failing == Some(name),
+                })
+            })
+    }
+
+    #[tokio::test]
+    async fn test_ordered_startup_and_reverse_shutdown() {
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let services = lifecycle(&log, None);
+        assert!(services.health().await.is_err());
+
+        services.start().await.unwrap();
+        assert!(services.health().await.is_ok());
+        // Draining takes the instance out of rotation before shutting down
+        services.drain().await;
+        assert_eq!(services.state(), LifecycleState::Stopping);
+        assert!(services.health().await.is_err());
+        services.shutdown().await;
+        assert_eq!(
+            *log.lock().unwrap(),
+            [
+                "start cache",
+                "start jobs",
+                "start discovery",
+                "drain discovery",
+                "drain jobs",
+                "drain cache",
+                "shutdown discovery",
+                "shutdown jobs",
+                "shutdown cache"
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_failed_start_shuts_down_started_services() {
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let services = lifecycle(&log, Some("jobs"));
+
+        assert_eq!(
+            services.start().await,
+            Err("Failed to start jobs: unreachable".to_string())
+        );
+        assert_eq!(
+            *log.lock().unwrap(),
+            ["start cache", "start jobs", "shutdown cache"]
+        );
+    }
+}
@@ -1,15 +1,66 @@
-/// Infrastructure Layer
-///
-/// Contains cross-cutting concerns and infrastructure components:
-/// - Configuration management
-/// - Error handling and error types
-/// - Logging setup
-/// - Common utilities
-///
-/// This layer provides foundational services that all features can use.
+//! Infrastructure Layer
+//!
+//! Contains cross-cutting concerns and infrastructure components:
+//! - Configuration management
+//! - In-memory caching with expiry
+//! - Audit logging of write operations
+//! - CORS policy
+//! - Clock skew check against a trusted time source
+//! - Dead-letter queue of failed deliveries
+//! - Data retention and pseudonymization
+//! - GeoIP lookup of client addresses
+//! - Scheduling of background jobs
+//! - Legal holds exempting content from retention and deletion
+//! - Ordered startup and shutdown of services
+//! - Per-client rate limiting
+//! - Service discovery registration
+//! - Revocable server-side sessions
+//! - Object storage in a local directory
+//! - Webhook delivery with retries
+//! - Error handling and error types
+//! - Request extractors
+//! - Field-level request validation
+//! - Logging setup
+//! - Common utilities
+//!
+//! This layer provides foundational services that all features can use.
 
+pub mod audit;
+pub mod cache;
+pub mod clock;
 pub mod config;
+pub mod cors;
+pub mod dead_letter;
+pub mod discovery;
 pub mod error;
+pub mod extract;
+pub mod geoip;
+pub mod jobs;
+pub mod legal_hold;
+pub mod lifecycle;
+pub mod rate_limit;
+pub mod retention;
+pub mod sessions;
+pub mod storage;
+pub mod validation;
+pub mod webhook;
 
+pub use audit::{AuditContext, AuditLog, AuditOperation};
+pub use cache::TtlCache;
+pub use clock::ClockCheck;
 pub use config::AppConfig;
+pub use cors::cors_layer;
+pub use dead_letter::{DeadLetter, DeadLetterQueue};
+pub use discovery::ServiceRegistration;
 pub use error::AppError;
+pub use extract::{DeserializationMode, JsonBody};
+pub use geoip::{geoip_middleware, GeoIp, GeoLocation};
+pub use jobs::{JobScheduler, JobState, JobStatus};
+pub use legal_hold::{HoldKind, LegalHolds};
+pub use lifecycle::{Lifecycle, LifecycleState, Service};
+pub use rate_limit::{rate_limit_middleware, RateLimit, RateLimiter};
+pub use retention::RetentionJob;
+pub use sessions::{Session, SessionQuery, SessionStore};
+pub use storage::ObjectStorage;
+pub use validation::{FieldError, Validate, ValidationErrors};
+pub use webhook::Webhook;
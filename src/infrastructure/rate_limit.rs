@@ -0,0 +1,468 @@
+use axum::{
+    extract::{ConnectInfo, OriginalUri, Request, State},
+    http::{header::RETRY_AFTER, HeaderMap, HeaderValue},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::net::SocketAddr;
+use std::str::FromStr;
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::{Duration, Instant};
+
+use super::error::AppError;
+
+/// Number of tracked buckets above which the least recently used are dropped
+const MAX_TRACKED_BUCKETS: usize = 10_000;
+
+/// Token bucket rate limit
+///
+/// Clients start with `burst` requests and regain `per_minute` requests
+/// per minute, up to `burst` again.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RateLimit {
+    pub per_minute: u32,
+    pub burst: u32,
+}
+
+impl RateLimit {
+    /// Requests regained per second
+    fn refill_rate(&self) -> f64 {
+        f64::from(self.per_minute) / 60.0
+    }
+}
+
+/// Parses `per_minute` or `per_minute:burst`; the burst defaults to the
+/// rate per minute
+impl FromStr for RateLimit {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let (per_minute, burst) = match value.split_once(':') {
+            Some((per_minute, burst)) => (per_minute, Some(burst)),
+            None => (value, None),
+        };
+        let per_minute: u32 = per_minute
+            .trim()
+            .parse()
+            .map_err(|_| format!("Invalid requests per minute: {}", per_minute))?;
+        let burst: u32 = match burst {
+            Some(burst) => burst
+                .trim()
+                .parse()
+                .map_err(|_| format!("Invalid burst: {}", burst))?,
+            None => per_minute,
+        };
+        if per_minute == 0 || burst == 0 {
+            return Err("Rate limits must allow at least one request".to_string());
+        }
+        Ok(Self { per_minute, burst })
+    }
+}
+
+/// Extracts the authenticated user id from request headers
+type IdentifyFn = dyn Fn(&HeaderMap) -> Option<String> + Send + Sync;
+
+/// State of one client's bucket
+#[derive(Debug, Clone, Copy)]
+struct Bucket {
+    tokens: f64,
+    updated_at: Instant,
+}
+
+impl Bucket {
+    /// Tokens in the bucket at `now`
+    fn refilled(&self, limit: &RateLimit, now: Instant) -> f64 {
+        let elapsed = now.saturating_duration_since(self.updated_at).as_secs_f64();
+        (self.tokens + elapsed * limit.refill_rate()).min(f64::from(limit.burst))
+    }
+}
+
+/// Route prefix and client a bucket is kept for
+type BucketKey = (String, String);
+
+/// Buckets of the clients seen most recently
+///
+/// Holds at most `capacity` buckets; a new bucket evicts the least recently
+/// used one, in logarithmic time. Clients that were idle the longest most
+/// likely have a full bucket again, so evicting them rarely matters.
+struct Buckets {
+    capacity: usize,
+    /// Buckets with the tick they were last used at
+    buckets: HashMap<BucketKey, (Bucket, u64)>,
+    /// Keys by the tick they were last used at, least recent first
+    recency: BTreeMap<u64, BucketKey>,
+    tick: u64,
+}
+
+impl Buckets {
+    fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            buckets: HashMap::new(),
+            recency: BTreeMap::new(),
+            tick: 0,
+        }
+    }
+
+    /// Bucket of `key`, inserting `new` if there is none, marked as used last
+    fn touch(&mut self, key: BucketKey, new: Bucket) -> &mut Bucket {
+        self.tick += 1;
+        let tick = self.tick;
+        if !self.buckets.contains_key(&key) && self.buckets.len() >= self.capacity {
+            if let Some((_, least_recent)) = self.recency.pop_first() {
+                self.buckets.remove(&least_recent);
+            }
+        }
+
+        let (bucket, used) = self.buckets.entry(key.clone()).or_insert((new, tick));
+        self.recency.remove(used);
+        *used = tick;
+        self.recency.insert(tick, key);
+        bucket
+    }
+}
+
+/// Per-client rate limiter
+///
+/// Keeps a token bucket per client and route, for the 10 000 clients seen
+/// most recently. Clients are keyed on the user id given by the identity
+/// extractor, and on the client IP otherwise. Routes with an override are
+/// limited separately from the rest of the API, so exhausting the login
+/// limit does not block other requests. Clones share the same buckets.
+#[derive(Clone)]
+pub struct RateLimiter {
+    default_limit: Option<RateLimit>,
+    /// Limits per path prefix, longest prefix first
+    routes: Vec<(String, RateLimit)>,
+    identify: Option<Arc<IdentifyFn>>,
+    buckets: Arc<Mutex<Buckets>>,
+}
+
+impl RateLimiter {
+    /// Create a limiter applying `default_limit` to every route without an
+    /// override; routes without an override are unlimited if `None`
+    pub fn new(default_limit: Option<RateLimit>) -> Self {
+        Self {
+            default_limit,
+            routes: Vec::new(),
+            identify: None,
+            buckets: Arc::new(Mutex::new(Buckets::new(MAX_TRACKED_BUCKETS))),
+        }
+    }
+
+    /// Limit requests to paths under `prefix` with `limit` instead
+    pub fn with_route(mut self, prefix: impl Into<String>, limit: RateLimit) -> Self {
+        let prefix = prefix.into();
+        let prefix = prefix.trim_end_matches('/').to_string();
+        self.routes.retain(|(existing, _)| *existing != prefix);
+        self.routes.push((prefix, limit));
+        self.routes
+            .sort_by_key(|(prefix, _)| std::cmp::Reverse(prefix.len()));
+        self
+    }
+
+    /// Add route overrides configured as `prefix=per_minute[:burst]`
+    ///
+    /// Invalid entries are skipped with a warning.
+    pub fn with_routes(self, entries: &[String]) -> Self {
+        entries.iter().fold(self, |limiter, entry| {
+            let parsed = entry
+                .split_once('=')
+                .ok_or_else(|| "Expected prefix=per_minute[:burst]".to_string())
+                .and_then(|(prefix, limit)| Ok((prefix.trim(), limit.parse::<RateLimit>()?)));
+            match parsed {
+                Ok((prefix, limit)) if prefix.starts_with('/') => limiter.with_route(prefix, limit),
+                Ok(_) => {
+                    tracing::warn!(
+                        "Ignoring rate limit override {}: prefix must start with /",
+                        entry
+                    );
+                    limiter
+                }
+                Err(err) => {
+                    tracing::warn!("Ignoring rate limit override {}: {}", entry, err);
+                    limiter
+                }
+            }
+        })
+    }
+
+    /// Key clients on the user id `identify` extracts from request headers
+    ///
+    /// Requests it returns `None` for are keyed on the client IP. Only
+    /// return ids that are costly to obtain, such as those of verified
+    /// users: a client minting free ids would get a fresh bucket for each.
+    pub fn with_identity(
+        mut self,
+        identify: impl Fn(&HeaderMap) -> Option<String> + Send + Sync + 'static,
+    ) -> Self {
+        self.identify = Some(Arc::new(identify));
+        self
+    }
+
+    /// Limit that applies to `path` and the prefix its buckets are keyed on
+    fn limit_for(&self, path: &str) -> Option<(&str, RateLimit)> {
+        self.routes
+            .iter()
+            .find(|(prefix, _)| {
+                path.strip_prefix(prefix.as_str())
+                    .is_some_and(|rest| rest.is_empty() || rest.starts_with('/'))
+            })
+            .map(|(prefix, limit)| (prefix.as_str(), *limit))
+            .or_else(|| self.default_limit.map(|limit| ("", limit)))
+    }
+
+    /// Take a token from the client's bucket for `path`
+    ///
+    /// Returns how long the client has to wait if the bucket is empty.
+    pub fn check(&self, client: &str, path: &str) -> Result<(), Duration> {
+        self.check_at(client, path, Instant::now())
+    }
+
+    fn check_at(&self, client: &str, path: &str, now: Instant) -> Result<(), Duration> {
+        let Some((route, limit)) = self.limit_for(path) else {
+            return Ok(());
+        };
+
+        let mut buckets = self.buckets.lock().unwrap_or_else(PoisonError::into_inner);
+        let bucket = buckets.touch(
+            (route.to_string(), client.to_string()),
+            Bucket {
+                tokens: f64::from(limit.burst),
+                updated_at: now,
+            },
+        );
+        bucket.tokens = bucket.refilled(&limit, now);
+        bucket.updated_at = now;
+
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            Ok(())
+        } else {
+            Err(Duration::from_secs_f64(
+                (1.0 - bucket.tokens) / limit.refill_rate(),
+            ))
+        }
+    }
+
+    /// Key of the client sending a request
+    fn client_key(&self, request: &Request) -> String {
+        if let Some(user) = self
+            .identify
+            .as_ref()
+            .and_then(|identify| identify(request.headers()))
+        {
+            return user;
+        }
+
+        request
+            .extensions()
+            .get::<ConnectInfo<SocketAddr>>()
+            .map_or_else(
+                || "ip:unknown".to_string(),
+                |ConnectInfo(addr)| format!("ip:{}", addr.ip()),
+            )
+    }
+}
+
+impl fmt::Debug for RateLimiter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RateLimiter")
+            .field("default_limit", &self.default_limit)
+            .field("routes", &self.routes)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Rate limiting middleware
+///
+/// Rejects requests of clients that exhausted their bucket with 429 Too
+/// Many Requests. The `Retry-After` header carries the seconds until the
+/// next request is allowed. Route overrides match the full request path,
+/// also when the middleware is layered on a nested router.
+pub async fn rate_limit_middleware(
+    State(limiter): State<RateLimiter>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let client = limiter.client_key(&request);
+    let path = request
+        .extensions()
+        .get::<OriginalUri>()
+        .map_or(request.uri().path(), |OriginalUri(uri)| uri.path());
+    let Err(wait) = limiter.check(&client, path) else {
+        return next.run(request).await;
+    };
+
+    let retry_after = wait.as_secs_f64().ceil().max(1.0) as u64;
+    let mut response = AppError::TooManyRequests(format!(
+        "Rate limit exceeded; retry in {} seconds",
+        retry_after
+    ))
+    .into_response();
+    if let Ok(value) = HeaderValue::from_str(&retry_after.to_string()) {
+        response.headers_mut().insert(RETRY_AFTER, value);
+    }
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{body::Body, http::StatusCode, routing::get, Router};
+    use tower::ServiceExt;
+
+    fn limit(per_minute: u32, burst: u32) -> RateLimit {
+        RateLimit { per_minute, burst }
+    }
+
+    #[test]
+    fn test_parse_rate_limit() {
+        assert_eq!("10".parse(), Ok(limit(10, 10)));
+        assert_eq!("60:5".parse(), Ok(limit(60, 5)));
+        assert!("0".parse::<RateLimit>().is_err());
+        assert!("ten".parse::<RateLimit>().is_err());
+    }
+
+    #[test]
+    fn test_token_bucket_per_route() {
+        let limiter = RateLimiter::new(Some(limit(60, 2)))
+            .with_routes(&["/api/v1/auth/login=6:1".to_string(), "broken".to_string()]);
+        let start = Instant::now();
+
+        // The login override is keyed separately from the rest of the API
+        assert!(limiter
+            .check_at("ip:1", "/api/v1/auth/login", start)
+            .is_ok());
+        let wait = limiter
+            .check_at("ip:1", "/api/v1/auth/login", start)
+            .unwrap_err();
+        assert_eq!(wait.as_secs(), 10);
+        assert!(limiter.check_at("ip:1", "/api/v1/users", start).is_ok());
+        assert!(limiter.check_at("ip:1", "/api/v1/users/7", start).is_ok());
+        assert!(limiter.check_at("ip:1", "/api/v1/users", start).is_err());
+
+        // Other clients have their own buckets
+        assert!(limiter.check_at("user:1", "/api/v1/users", start).is_ok());
+
+        // Tokens are regained over time, up to the burst
+        let later = start + Duration::from_secs(1);
+        assert!(limiter.check_at("ip:1", "/api/v1/users", later).is_ok());
+        assert!(limiter.check_at("ip:1", "/api/v1/users", later).is_err());
+        let much_later = start + Duration::from_secs(600);
+        assert!(limiter
+            .check_at("ip:1", "/api/v1/auth/login", much_later)
+            .is_ok());
+        assert!(limiter
+            .check_at("ip:1", "/api/v1/auth/login", much_later)
+            .is_err());
+
+        // A prefix only matches whole path segments
+        let unlimited = RateLimiter::new(None).with_route("/api/v1/auth/login", limit(6, 1));
+        assert!(unlimited
+            .check_at("ip:1", "/api/v1/auth/login", start)
+            .is_ok());
+        assert!(unlimited
+            .check_at("ip:1", "/api/v1/auth/logins", start)
+            .is_ok());
+        assert!(unlimited
+            .check_at("ip:1", "/api/v1/auth/logins", start)
+            .is_ok());
+    }
+
+    #[test]
+    fn test_least_recently_used_buckets_are_evicted() {
+        let mut buckets = Buckets::new(2);
+        let now = Instant::now();
+        let key = |client: &str| (String::new(), client.to_string());
+        let bucket = |tokens| Bucket {
+            tokens,
+            updated_at: now,
+        };
+
+        buckets.touch(key("ip:1"), bucket(0.0));
+        buckets.touch(key("ip:2"), bucket(0.0));
+        // Using a bucket keeps it, whatever its tokens
+        assert_eq!(buckets.touch(key("ip:1"), bucket(5.0)).tokens, 0.0);
+        buckets.touch(key("ip:3"), bucket(0.0));
+
+        assert_eq!(buckets.buckets.len(), 2);
+        assert_eq!(buckets.recency.len(), 2);
+        assert!(!buckets.buckets.contains_key(&key("ip:2")));
+        assert_eq!(buckets.touch(key("ip:2"), bucket(5.0)).tokens, 5.0);
+        assert!(!buckets.buckets.contains_key(&key("ip:1")));
+    }
+
+    #[tokio::test]
+    async fn test_middleware_returns_retry_after() {
+        let limiter = RateLimiter::new(Some(limit(30, 1))).with_identity(|headers| {
+            headers
+                .get("x-user")
+                .and_then(|value| value.to_str().ok())
+                .map(str::to_string)
+        });
+        let app =
+            Router::new()
+                .route("/", get(|| async {}))
+                .layer(axum::middleware::from_fn_with_state(
+                    limiter,
+                    rate_limit_middleware,
+                ));
+        let request = |user: &str| {
+            Request::builder()
+                .uri("/")
+                .header("x-user", user)
+                .body(Body::empty())
+                .unwrap()
+        };
+
+        let response = app.clone().oneshot(request("alice")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let response = app.clone().oneshot(request("alice")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(response.headers()[RETRY_AFTER], "2");
+
+        let response = app.oneshot(request("bob")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_middleware_on_nested_routes_only() {
+        let limiter =
+            RateLimiter::new(Some(limit(30, 1))).with_route("/api/v1/login", limit(30, 2));
+        let api = Router::new()
+            .route("/boards", get(|| async {}))
+            .route("/login", get(|| async {}))
+            .layer(axum::middleware::from_fn_with_state(
+                limiter,
+                rate_limit_middleware,
+            ));
+        let app = Router::new()
+            .route("/health", get(|| async {}))
+            .nest("/api/v1", api);
+        let status = |uri: &str| {
+            let app = app.clone();
+            let request = Request::builder().uri(uri).body(Body::empty()).unwrap();
+            async move { app.oneshot(request).await.unwrap().status() }
+        };
+
+        assert_eq!(status("/api/v1/boards").await, StatusCode::OK);
+        assert_eq!(
+            status("/api/v1/boards").await,
+            StatusCode::TOO_MANY_REQUESTS
+        );
+
+        // Overrides match the path before nesting stripped the prefix
+        assert_eq!(status("/api/v1/login").await, StatusCode::OK);
+        assert_eq!(status("/api/v1/login").await, StatusCode::OK);
+        assert_eq!(status("/api/v1/login").await, StatusCode::TOO_MANY_REQUESTS);
+
+        // Probes outside the API are never limited
+        for _ in 0..3 {
+            assert_eq!(status("/health").await, StatusCode::OK);
+        }
+    }
+}
@@ -0,0 +1,194 @@
+use chrono::{DateTime, Duration, Utc};
+use serde::Serialize;
+
+use super::audit::AuditLog;
+use super::legal_hold::LegalHolds;
+
+/// Retention rules per data class
+#[derive(Debug, Clone)]
+pub struct RetentionPolicy {
+    /// Age after which audit log entries are deleted
+    pub audit_log_max_age: Duration,
+    /// Age after which anonymous identifiers are pseudonymized
+    pub anonymous_identifier_max_age: Duration,
+    /// Salt used when re-hashing anonymous identifiers
+    pub pseudonymization_salt: String,
+}
+
+/// Outcome of applying one retention rule
+#[derive(Debug, Clone, Serialize)]
+pub struct RetentionRuleReport {
+    pub data_class: &'static str,
+    pub action: &'static str,
+    pub cutoff: DateTime<Utc>,
+    pub affected: usize,
+}
+
+/// Outcome of a retention run
+#[derive(Debug, Clone, Serialize)]
+pub struct RetentionReport {
+    pub dry_run: bool,
+    pub executed_at: DateTime<Utc>,
+    pub rules: Vec<RetentionRuleReport>,
+}
+
+/// Retention job
+///
+/// Applies the retention policy to the data held by the infrastructure
+/// layer. Runs periodically in the background and can be executed as a
+/// dry run to report what would be affected. Audit entries concerning
+/// content under legal hold are exempt.
+#[derive(Clone)]
+pub struct RetentionJob {
+    audit_log: AuditLog,
+    policy: RetentionPolicy,
+    legal_holds: LegalHolds,
+}
+
+impl RetentionJob {
+    /// Create a new retention job
+    pub fn new(audit_log: AuditLog, policy: RetentionPolicy) -> Self {
+        Self {
+            legal_holds: LegalHolds::new(audit_log.clone()),
+            audit_log,
+            policy,
+        }
+    }
+
+    /// Exempt audit entries concerning content under these legal holds
+    pub fn with_legal_holds(mut self, legal_holds: LegalHolds) -> Self {
+        self.legal_holds = legal_holds;
+        self
+    }
+
+    /// Apply every retention rule once
+    ///
+    /// Pseudonymization runs before deletion so the report counts entries
+    /// that are pseudonymized and later deleted by the same run.
+    pub async fn run_once(&self, dry_run: bool) -> RetentionReport {
+        let now = Utc::now();
+        let exempt = |entry: &_| self.legal_holds.covers(entry);
+
+        let anonymous_cutoff = now - self.policy.anonymous_identifier_max_age;
+        let pseudonymized = self
+            .audit_log
+            .pseudonymize_anonymous_before(
+                anonymous_cutoff,
+                &self.policy.pseudonymization_salt,
+                dry_run,
+                exempt,
+            )
+            .await;
+
+        let audit_cutoff = now - self.policy.audit_log_max_age;
+        let purged = self
+            .audit_log
+            .purge_before(audit_cutoff, dry_run, exempt)
+            .await;
+
+        RetentionReport {
+            dry_run,
+            executed_at: now,
+            rules: vec![
+                RetentionRuleReport {
+                    data_class: "anonymous_identifiers",
+                    action: "pseudonymize",
+                    cutoff: anonymous_cutoff,
+                    affected: pseudonymized,
+                },
+                RetentionRuleReport {
+                    data_class: "audit_logs",
+                    action: "delete",
+                    cutoff: audit_cutoff,
+                    affected: purged,
+                },
+            ],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infrastructure::audit::{AuditActor, AuditContext, AuditOperation, AuditQuery};
+    use crate::infrastructure::legal_hold::{HoldKind, PlaceLegalHoldRequest};
+
+    fn policy(max_age: Duration) -> RetentionPolicy {
+        RetentionPolicy {
+            audit_log_max_age: max_age,
+            anonymous_identifier_max_age: max_age,
+            pseudonymization_salt: "salt".to_string(),
+        }
+    }
+
+    async fn record_anonymous(audit_log: &AuditLog) {
+        let context = AuditContext {
+            actor: Some(AuditActor::anonymous(
+                "H001/U123/2024-01-01/D001",
+                "H001".to_string(),
+            )),
+            request_id: None,
+            location: None,
+            token_id: None,
+        };
+        audit_log
+            .record(&context, AuditOperation::Create, "user", 1, None, None)
+            .await;
+    }
+
+    #[tokio::test]
+    async fn test_dry_run_leaves_data_untouched() {
+        let audit_log = AuditLog::default();
+        record_anonymous(&audit_log).await;
+
+        let job = RetentionJob::new(audit_log.clone(), policy(Duration::seconds(-1)));
+        let report = job.run_once(true).await;
+
+        assert!(report.dry_run);
+        assert!(report.rules.iter().all(|rule| rule.affected == 1));
+
+        let entries = audit_log.search(&AuditQuery::default()).await;
+        assert_eq!(entries.len(), 1);
+        assert!(entries[0].actor.as_ref().unwrap().is_anonymous());
+    }
+
+    #[tokio::test]
+    async fn test_run_applies_rules() {
+        let audit_log = AuditLog::default();
+        record_anonymous(&audit_log).await;
+
+        let keep = RetentionJob::new(audit_log.clone(), policy(Duration::days(30)));
+        let report = keep.run_once(false).await;
+        assert!(report.rules.iter().all(|rule| rule.affected == 0));
+
+        let expire = RetentionJob::new(audit_log.clone(), policy(Duration::seconds(-1)));
+        expire.run_once(false).await;
+        assert!(audit_log.search(&AuditQuery::default()).await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_legal_hold_exempts_entries() {
+        let audit_log = AuditLog::default();
+        record_anonymous(&audit_log).await;
+
+        let legal_holds = LegalHolds::new(AuditLog::default());
+        let request = PlaceLegalHoldRequest {
+            kind: HoldKind::User,
+            id: 1,
+            reason: "Case 2024-118".to_string(),
+        };
+        legal_holds
+            .place(request, &AuditContext::default())
+            .await
+            .unwrap();
+
+        let job = RetentionJob::new(audit_log.clone(), policy(Duration::seconds(-1)))
+            .with_legal_holds(legal_holds);
+        let report = job.run_once(false).await;
+        assert!(report.rules.iter().all(|rule| rule.affected == 0));
+
+        let entries = audit_log.search(&AuditQuery::default()).await;
+        assert_eq!(entries.len(), 1);
+        assert!(entries[0].actor.as_ref().unwrap().is_anonymous());
+    }
+}
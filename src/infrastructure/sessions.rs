@@ -0,0 +1,364 @@
+use chrono::{DateTime, Utc};
+use futures::future::BoxFuture;
+use redis::AsyncCommands;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::RwLock;
+
+use super::audit::{AuditActor, AuditContext, AuditLog, AuditOperation};
+use super::cache::TtlCache;
+use super::error::AppError;
+use super::lifecycle::Service;
+
+/// Prefix of the Redis keys sessions are stored under
+pub const REDIS_SESSION_PREFIX: &str = "webboard:session:";
+
+/// Prefix of the Redis keys revoked session ids are stored under
+pub const REDIS_REVOKED_PREFIX: &str = "webboard:session-revoked:";
+
+/// Server-side session a token refers to
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Session {
+    pub id: String,
+    /// Actor id of the owner, with anonymous composite keys hashed as in
+    /// the audit log
+    pub actor: String,
+    /// Hospital code of anonymous owners
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tenant: Option<String>,
+    pub created_at: DateTime<Utc>,
+    /// When the token of the session stops being accepted
+    pub expires_at: DateTime<Utc>,
+}
+
+/// Filter for listing sessions
+///
+/// All fields are optional; unset fields match every session.
+#[derive(Debug, Default, Deserialize)]
+pub struct SessionQuery {
+    /// Actor id as listed, e.g. from an audit entry
+    pub actor: Option<String>,
+    pub tenant: Option<String>,
+}
+
+impl SessionQuery {
+    fn matches(&self, session: &Session) -> bool {
+        self.actor
+            .as_ref()
+            .is_none_or(|actor| session.actor == *actor)
+            && self
+                .tenant
+                .as_ref()
+                .is_none_or(|tenant| session.tenant.as_ref() == Some(tenant))
+    }
+}
+
+/// Store of server-side sessions
+///
+/// Lets stateless tokens be revoked one session at a time: each token
+/// carries the id of its session, and revoking the session rejects the
+/// token until it expires. Sessions are kept in memory, or in Redis when
+/// configured, so every instance lists the same sessions. Like the token
+/// blacklist, revoked ids are checked locally while decoding; `sync` pulls
+/// in the sessions revoked on other instances. Revocations are recorded in
+/// the audit log. Clones share the same sessions.
+#[derive(Clone)]
+pub struct SessionStore {
+    /// Sessions by id, when not stored in Redis
+    sessions: Arc<RwLock<HashMap<String, Session>>>,
+    revoked: TtlCache<String, ()>,
+    redis: Option<redis::Client>,
+    audit_log: AuditLog,
+}
+
+impl SessionStore {
+    /// Create an in-memory store recording revocations into the given audit log
+    pub fn new(audit_log: AuditLog) -> Self {
+        Self {
+            sessions: Arc::new(RwLock::new(HashMap::new())),
+            revoked: TtlCache::new(),
+            redis: None,
+            audit_log,
+        }
+    }
+
+    /// Store sessions in the Redis server at `url`
+    ///
+    /// Only the URL is checked here; the server is connected to when
+    /// sessions are created, listed, revoked and synced.
+    pub fn with_redis(mut self, url: &str) -> Result<Self, redis::RedisError> {
+        self.redis = Some(redis::Client::open(url)?);
+        Ok(self)
+    }
+
+    /// Start a session of `actor` lasting `ttl`
+    ///
+    /// Fails with `ServiceUnavailable` if the session could not be written
+    /// to Redis, as it could then not be listed or revoked.
+    pub async fn create(&self, actor: &AuditActor, ttl: Duration) -> Result<Session, AppError> {
+        let now = Utc::now();
+        let session = Session {
+            id: uuid::Uuid::new_v4().to_string(),
+            actor: self.audit_log.stored_actor_id(actor),
+            tenant: actor.tenant.clone(),
+            created_at: now,
+            expires_at: now + chrono::Duration::from_std(ttl).unwrap_or_default(),
+        };
+
+        let Some(client) = &self.redis else {
+            let mut sessions = self.sessions.write().await;
+            sessions.retain(|_, session| session.expires_at > now);
+            sessions.insert(session.id.clone(), session.clone());
+            return Ok(session);
+        };
+        async {
+            let mut connection = client.get_multiplexed_async_connection().await?;
+            connection
+                .set_ex::<_, _, ()>(
+                    format!("{}{}", REDIS_SESSION_PREFIX, session.id),
+                    serde_json::to_string(&session).unwrap_or_default(),
+                    ttl.as_secs().max(1),
+                )
+                .await
+        }
+        .await
+        .map_err(Self::unavailable)?;
+        Ok(session)
+    }
+
+    /// Check if the session with id `id` has been revoked
+    pub fn is_revoked(&self, id: &str) -> bool {
+        self.revoked.get(&id.to_string()).is_some()
+    }
+
+    /// Sessions matching `query` that have not expired, oldest first
+    pub async fn list(&self, query: &SessionQuery) -> Result<Vec<Session>, AppError> {
+        let mut sessions = match &self.redis {
+            Some(client) => Self::redis_sessions(client)
+                .await
+                .map_err(Self::unavailable)?,
+            None => {
+                let now = Utc::now();
+                self.sessions
+                    .read()
+                    .await
+                    .values()
+                    .filter(|session| session.expires_at > now)
+                    .cloned()
+                    .collect()
+            }
+        };
+        sessions.retain(|session| query.matches(session));
+        sessions.sort_by_key(|session| session.created_at);
+        Ok(sessions)
+    }
+
+    /// Revoke a session, rejecting its token until it expires
+    pub async fn revoke(&self, id: &str, audit: &AuditContext) -> Result<Session, AppError> {
+        let session = match &self.redis {
+            Some(client) => Self::redis_revoke(client, id)
+                .await
+                .map_err(Self::unavailable)?,
+            None => self.sessions.write().await.remove(id),
+        }
+        .filter(|session| session.expires_at > Utc::now())
+        .ok_or_else(|| AppError::NotFound(format!("Session {} not found", id)))?;
+
+        let ttl = (session.expires_at - Utc::now())
+            .to_std()
+            .unwrap_or_default();
+        self.revoked.insert(id.to_string(), (), ttl);
+
+        tracing::info!("Revoked session {}", id);
+        self.audit_log
+            .record(
+                audit,
+                AuditOperation::Delete,
+                "session",
+                id,
+                serde_json::to_value(&session).ok(),
+                None,
+            )
+            .await;
+
+        Ok(session)
+    }
+
+    /// Pull the sessions revoked on other instances from Redis
+    ///
+    /// Returns the number of revoked sessions in Redis. Does nothing
+    /// without a Redis backend.
+    pub async fn sync(&self) -> Result<usize, redis::RedisError> {
+        let Some(client) = &self.redis else {
+            return Ok(0);
+        };
+        let mut connection = client.get_multiplexed_async_connection().await?;
+
+        let keys = scan(&mut connection, REDIS_REVOKED_PREFIX).await?;
+        if keys.is_empty() {
+            return Ok(0);
+        }
+
+        let mut pipe = redis::pipe();
+        for key in &keys {
+            pipe.pttl(key);
+        }
+        let ttls: Vec<i64> = pipe.query_async(&mut connection).await?;
+        for (key, ttl) in keys.iter().zip(ttls) {
+            // Negative TTLs mark keys that are gone or never expire
+            if let (Some(id), Ok(ttl)) =
+                (key.strip_prefix(REDIS_REVOKED_PREFIX), u64::try_from(ttl))
+            {
+                self.revoked
+                    .insert(id.to_string(), (), Duration::from_millis(ttl));
+            }
+        }
+        Ok(keys.len())
+    }
+
+    async fn redis_sessions(client: &redis::Client) -> Result<Vec<Session>, redis::RedisError> {
+        let mut connection = client.get_multiplexed_async_connection().await?;
+        let keys = scan(&mut connection, REDIS_SESSION_PREFIX).await?;
+        if keys.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        // Sessions expiring between the scan and the read are skipped
+        let values: Vec<Option<String>> = connection.mget(&keys).await?;
+        Ok(values
+            .into_iter()
+            .flatten()
+            .filter_map(|value| serde_json::from_str(&value).ok())
+            .collect())
+    }
+
+    /// Remove a session from Redis and mark it revoked for other instances
+    async fn redis_revoke(
+        client: &redis::Client,
+        id: &str,
+    ) -> Result<Option<Session>, redis::RedisError> {
+        let mut connection = client.get_multiplexed_async_connection().await?;
+        let key = format!("{}{}", REDIS_SESSION_PREFIX, id);
+        let Some(value) = connection.get::<_, Option<String>>(&key).await? else {
+            return Ok(None);
+        };
+        let Ok(session) = serde_json::from_str::<Session>(&value) else {
+            return Ok(None);
+        };
+
+        let ttl = (session.expires_at - Utc::now()).num_seconds().max(1) as u64;
+        redis::pipe()
+            .atomic()
+            .del(&key)
+            .set_ex(format!("{}{}", REDIS_REVOKED_PREFIX, id), 1, ttl)
+            .query_async::<()>(&mut connection)
+            .await?;
+        Ok(Some(session))
+    }
+
+    fn unavailable(err: redis::RedisError) -> AppError {
+        AppError::ServiceUnavailable(format!("Session store unavailable: {}", err))
+    }
+}
+
+/// Keys under `prefix`
+async fn scan(
+    connection: &mut redis::aio::MultiplexedConnection,
+    prefix: &str,
+) -> Result<Vec<String>, redis::RedisError> {
+    let mut keys = Vec::new();
+    let mut iter = connection
+        .scan_match::<_, String>(format!("{}*", prefix))
+        .await?;
+    while let Some(key) = iter.next_item().await {
+        keys.push(key);
+    }
+    Ok(keys)
+}
+
+/// Pulls in the sessions revoked elsewhere before requests are served
+impl Service for SessionStore {
+    fn name(&self) -> &'static str {
+        "sessions"
+    }
+
+    fn start(&self) -> BoxFuture<'_, Result<(), String>> {
+        Box::pin(async move {
+            if let Err(err) = self.sync().await {
+                tracing::warn!("Failed to sync revoked sessions: {}", err);
+            }
+            Ok(())
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_revoked_session_is_rejected_until_it_expires() {
+        let sessions = SessionStore::new(AuditLog::new(100, "secret"));
+        let actor = AuditActor::anonymous("H001:U123", "H001".to_string());
+        let session = sessions
+            .create(&actor, Duration::from_secs(60))
+            .await
+            .unwrap();
+        sessions.create(&actor, Duration::ZERO).await.unwrap();
+
+        // Composite keys are stored hashed, like in the audit log
+        assert_ne!(session.actor, actor.id);
+        assert_eq!(session.tenant.as_deref(), Some("H001"));
+        let query = SessionQuery {
+            tenant: Some("H001".to_string()),
+            ..Default::default()
+        };
+        // The expired session is not listed
+        let listed = sessions.list(&query).await.unwrap();
+        assert_eq!(listed.len(), 1);
+        assert_eq!(listed[0], session);
+        let query = SessionQuery {
+            actor: Some(actor.id.clone()),
+            ..Default::default()
+        };
+        assert!(sessions.list(&query).await.unwrap().is_empty());
+        assert!(!sessions.is_revoked(&session.id));
+
+        let revoked = sessions
+            .revoke(&session.id, &AuditContext::default())
+            .await
+            .unwrap();
+        assert_eq!(revoked, session);
+        assert!(sessions.is_revoked(&session.id));
+        assert!(sessions
+            .list(&SessionQuery::default())
+            .await
+            .unwrap()
+            .is_empty());
+        assert!(matches!(
+            sessions.revoke(&session.id, &AuditContext::default()).await,
+            Err(AppError::NotFound(_))
+        ));
+        assert_eq!(sessions.sync().await.unwrap(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_unavailable_redis_is_reported() {
+        let sessions = SessionStore::new(AuditLog::new(100, "secret"))
+            .with_redis("redis://127.0.0.1:1")
+            .unwrap();
+        let actor = AuditActor::anonymous("H001:U123", "H001".to_string());
+
+        // Sessions that cannot be stored could not be revoked either
+        assert!(matches!(
+            sessions.create(&actor, Duration::from_secs(60)).await,
+            Err(AppError::ServiceUnavailable(_))
+        ));
+        assert!(matches!(
+            sessions.revoke("missing", &AuditContext::default()).await,
+            Err(AppError::ServiceUnavailable(_))
+        ));
+    }
+}
@@ -0,0 +1,123 @@
+use std::io;
+use std::path::{Component, Path, PathBuf};
+
+/// Key of the object written to check that the storage is writable
+const PROBE_KEY: &str = ".probe";
+
+/// Object storage backed by a local directory
+///
+/// Objects are addressed by `/`-separated keys, like in a storage bucket,
+/// and kept as files below the root directory. Objects are written to a
+/// temporary file first and renamed into place, so readers never see a
+/// partially written object.
+#[derive(Debug, Clone)]
+pub struct ObjectStorage {
+    root: PathBuf,
+}
+
+impl ObjectStorage {
+    /// Create a storage keeping its objects below `root`
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    /// Store `data` under `key`, replacing any object stored before
+    pub async fn put(&self, key: &str, data: &[u8]) -> io::Result<()> {
+        let path = self.path(key)?;
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+        let partial = path.with_extension("partial");
+        tokio::fs::write(&partial, data).await?;
+        tokio::fs::rename(&partial, &path).await
+    }
+
+    /// Object stored under `key`
+    pub async fn get(&self, key: &str) -> io::Result<Vec<u8>> {
+        tokio::fs::read(self.path(key)?).await
+    }
+
+    /// Remove the object stored under `key`
+    pub async fn delete(&self, key: &str) -> io::Result<()> {
+        tokio::fs::remove_file(self.path(key)?).await
+    }
+
+    /// Keys of the objects directly below `prefix`, in lexical order
+    ///
+    /// `prefix` names a directory and ends with `/`, e.g. `backups/`.
+    pub async fn list(&self, prefix: &str) -> io::Result<Vec<String>> {
+        let directory = self.path(prefix.trim_end_matches('/'))?;
+        let mut entries = match tokio::fs::read_dir(&directory).await {
+            Ok(entries) => entries,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(err) => return Err(err),
+        };
+
+        let mut keys = Vec::new();
+        while let Some(entry) = entries.next_entry().await? {
+            let name = entry.file_name();
+            let Some(name) = name.to_str() else {
+                continue;
+            };
+            if entry.file_type().await?.is_file() && !name.ends_with(".partial") {
+                keys.push(format!("{}{}", prefix, name));
+            }
+        }
+        keys.sort();
+        Ok(keys)
+    }
+
+    /// Check that objects can be stored, by writing and removing a probe object
+    pub async fn check(&self) -> io::Result<()> {
+        self.put(PROBE_KEY, b"").await?;
+        self.delete(PROBE_KEY).await
+    }
+
+    /// File an object is kept in, rejecting keys that leave the root
+    fn path(&self, key: &str) -> io::Result<PathBuf> {
+        let relative = Path::new(key);
+        if !relative
+            .components()
+            .all(|component| matches!(component, Component::Normal(_)))
+        {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("Invalid object key: {}", key),
+            ));
+        }
+        Ok(self.root.join(relative))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_put_get_list() {
+        let root = std::env::temp_dir().join(format!("webboard-storage-{}", uuid::Uuid::new_v4()));
+        let storage = ObjectStorage::new(&root);
+
+        assert!(storage.list("backups/").await.unwrap().is_empty());
+        storage.check().await.unwrap();
+        storage.put("backups/2.json", b"two").await.unwrap();
+        storage.put("backups/1.json", b"one").await.unwrap();
+        assert_eq!(
+            storage.list("backups/").await.unwrap(),
+            vec!["backups/1.json", "backups/2.json"]
+        );
+        assert_eq!(storage.get("backups/2.json").await.unwrap(), b"two");
+
+        storage.delete("backups/1.json").await.unwrap();
+        assert_eq!(
+            storage.list("backups/").await.unwrap(),
+            vec!["backups/2.json"]
+        );
+
+        // Keys cannot address files outside the root
+        assert!(storage.get("../secrets").await.is_err());
+        assert!(storage.get("/etc/passwd").await.is_err());
+
+        tokio::fs::remove_dir_all(root).await.unwrap();
+    }
+}
@@ -0,0 +1,96 @@
+use serde::Serialize;
+
+use super::error::AppError;
+
+/// Validation failure of one field of a request
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct FieldError {
+    pub field: String,
+    pub message: String,
+}
+
+/// Field errors collected while validating a request
+///
+/// Converts into `AppError::Validation`, answered with 422 Unprocessable
+/// Entity and the errors of all fields, so clients can show every problem
+/// at once instead of one per round trip.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct ValidationErrors(Vec<FieldError>);
+
+impl ValidationErrors {
+    /// Create an empty collection
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record an error of `field`
+    pub fn add(&mut self, field: &str, message: impl Into<String>) {
+        self.0.push(FieldError {
+            field: field.to_string(),
+            message: message.into(),
+        });
+    }
+
+    /// Record an error of `field` unless `valid` holds
+    pub fn check(&mut self, valid: bool, field: &str, message: impl Into<String>) {
+        if !valid {
+            self.add(field, message);
+        }
+    }
+
+    /// Errors recorded so far
+    pub fn errors(&self) -> &[FieldError] {
+        &self.0
+    }
+
+    /// `Ok` if no error was recorded
+    pub fn into_result(self) -> Result<(), Self> {
+        if self.0.is_empty() {
+            Ok(())
+        } else {
+            Err(self)
+        }
+    }
+}
+
+impl From<ValidationErrors> for AppError {
+    fn from(errors: ValidationErrors) -> Self {
+        AppError::Validation(errors.0)
+    }
+}
+
+/// Request payload with field-level validation
+pub trait Validate {
+    /// Check every field, collecting the errors of all invalid fields
+    fn validate(&self) -> Result<(), ValidationErrors>;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{body::to_bytes, http::StatusCode, response::IntoResponse};
+
+    #[tokio::test]
+    async fn test_errors_answered_with_422() {
+        let mut errors = ValidationErrors::new();
+        errors.check(true, "username", "Username cannot be empty");
+        errors.check(false, "email", "Invalid email format");
+        errors.add("password", "Password must be at least 8 characters");
+        assert_eq!(errors.errors().len(), 2);
+
+        let response = AppError::from(errors.into_result().unwrap_err()).into_response();
+        assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+        let body: serde_json::Value =
+            serde_json::from_slice(&to_bytes(response.into_body(), usize::MAX).await.unwrap())
+                .unwrap();
+        assert_eq!(body["error"], "VALIDATION_FAILED");
+        assert_eq!(
+            body["errors"],
+            serde_json::json!([
+                {"field": "email", "message": "Invalid email format"},
+                {"field": "password", "message": "Password must be at least 8 characters"}
+            ])
+        );
+        assert!(ValidationErrors::new().into_result().is_ok());
+    }
+}
@@ -0,0 +1,139 @@
+use chrono::Utc;
+use serde_json::Value;
+use std::time::Duration;
+
+use super::dead_letter::{DeadLetterQueue, DeliveryAttempt};
+
+/// Dead-letter kind of webhook deliveries
+pub const WEBHOOK_KIND: &str = "webhook";
+
+/// Default number of delivery attempts before dead-lettering
+pub const DEFAULT_WEBHOOK_ATTEMPTS: u32 = 3;
+
+/// Delay before the second attempt, doubled for each further attempt
+const DEFAULT_BACKOFF: Duration = Duration::from_secs(1);
+
+/// Webhook JSON payloads are posted to
+///
+/// Deliveries run in the background and are retried with exponential
+/// backoff. Once all attempts failed, the payload is parked in the
+/// dead-letter queue if one is set, and dropped otherwise.
+#[derive(Clone)]
+pub struct Webhook {
+    client: reqwest::Client,
+    /// What the payloads are, e.g. `anomaly_alert`
+    source: String,
+    url: String,
+    attempts: u32,
+    backoff: Duration,
+    dead_letters: Option<DeadLetterQueue>,
+}
+
+impl Webhook {
+    /// Create a webhook posting `source` payloads to `url`
+    pub fn new(source: &str, url: String) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            source: source.to_string(),
+            url,
+            attempts: DEFAULT_WEBHOOK_ATTEMPTS,
+            backoff: DEFAULT_BACKOFF,
+            dead_letters: None,
+        }
+    }
+
+    /// Set the number of delivery attempts, at least one
+    pub fn with_attempts(mut self, attempts: u32) -> Self {
+        self.attempts = attempts.max(1);
+        self
+    }
+
+    /// Park payloads that could not be delivered in `dead_letters`
+    pub fn with_dead_letters(mut self, dead_letters: DeadLetterQueue) -> Self {
+        self.dead_letters = Some(dead_letters);
+        self
+    }
+
+    /// Deliver a payload in the background
+    pub fn deliver(&self, payload: Value) -> tokio::task::JoinHandle<()> {
+        let webhook = self.clone();
+        tokio::spawn(async move {
+            let mut attempts = Vec::new();
+            let mut backoff = webhook.backoff;
+            for attempt in 1..=webhook.attempts {
+                match post(&webhook.client, &webhook.url, &payload).await {
+                    Ok(()) => return,
+                    Err(error) => {
+                        tracing::warn!(
+                            "Failed to deliver {} (attempt {}/{}): {}",
+                            webhook.source,
+                            attempt,
+                            webhook.attempts,
+                            error
+                        );
+                        attempts.push(DeliveryAttempt {
+                            attempted_at: Utc::now(),
+                            error,
+                        });
+                    }
+                }
+                if attempt < webhook.attempts {
+                    tokio::time::sleep(backoff).await;
+                    backoff *= 2;
+                }
+            }
+
+            if let Some(dead_letters) = &webhook.dead_letters {
+                dead_letters
+                    .push(
+                        WEBHOOK_KIND,
+                        &webhook.source,
+                        &webhook.url,
+                        payload,
+                        attempts,
+                    )
+                    .await;
+            }
+        })
+    }
+}
+
+/// Post a payload to `url` once, for retrying dead-lettered deliveries
+pub async fn redeliver(url: String, payload: Value) -> Result<(), String> {
+    post(&reqwest::Client::new(), &url, &payload).await
+}
+
+async fn post(client: &reqwest::Client, url: &str, payload: &Value) -> Result<(), String> {
+    client
+        .post(url)
+        .json(payload)
+        .send()
+        .await
+        .and_then(|response| response.error_for_status())
+        .map(|_| ())
+        .map_err(|err| err.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infrastructure::AuditLog;
+
+    #[tokio::test]
+    async fn test_failed_delivery_is_dead_lettered() {
+        let dead_letters = DeadLetterQueue::new(AuditLog::default());
+        // Nothing listens on port 1
+        let mut webhook = Webhook::new("anomaly_alert", "http://127.0.0.1:1/".to_string())
+            .with_attempts(2)
+            .with_dead_letters(dead_letters.clone());
+        webhook.backoff = Duration::ZERO;
+
+        webhook.deliver(serde_json::json!({"id": 1})).await.unwrap();
+
+        let entries = dead_letters.list().await;
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].kind, WEBHOOK_KIND);
+        assert_eq!(entries[0].source, "anomaly_alert");
+        assert_eq!(entries[0].attempts.len(), 2);
+    }
+}
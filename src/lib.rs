@@ -0,0 +1,7 @@
+//! Webboard
+//!
+//! Library crate exposing the feature modules and infrastructure so the
+//! binary (`main.rs`) only has to wire configuration, routes and the server.
+
+pub mod features;
+pub mod infrastructure;
@@ -1,21 +1,49 @@
-// Module declarations
-mod features;
-mod infrastructure;
-
+use anyhow::Context;
 use axum::{
-    extract::DefaultBodyLimit,
-    http::{HeaderValue, Method},
-    routing::{get, post},
-    Router,
+    extract::{DefaultBodyLimit, FromRef},
+    http::header::AUTHORIZATION,
+    routing::{delete, get, post},
+    Extension, Router,
 };
-use infrastructure::AppConfig;
+use jsonwebtoken::Algorithm;
+use std::net::SocketAddr;
+use std::sync::Arc;
 use std::time::Duration;
 use tower::ServiceBuilder;
-use tower_http::{cors::CorsLayer, timeout::TimeoutLayer, trace::TraceLayer};
+use tower_http::{
+    request_id::{MakeRequestUuid, PropagateRequestIdLayer, SetRequestIdLayer},
+    timeout::TimeoutLayer,
+    trace::TraceLayer,
+};
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
+use webboard::{
+    features::{
+        self,
+        anomaly::AnomalyThresholds,
+        auth::{TokenBinding, UserCredentials},
+        health,
+        usage::QuotaLimits,
+        users::domain::UserIdentity,
+        HealthChecks, Role,
+    },
+    infrastructure::{
+        audit::AuditActor, cors_layer, geoip_middleware, rate_limit_middleware,
+        retention::RetentionPolicy, webhook, AppConfig, AuditLog, ClockCheck, DeadLetterQueue,
+        DeserializationMode, GeoIp, JobScheduler, LegalHolds, Lifecycle, ObjectStorage, RateLimit,
+        RateLimiter, RetentionJob, ServiceRegistration, SessionStore, Webhook,
+    },
+};
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
+    // `webboard restore [<key>]` restores a backup, the latest by default, before serving
+    let mut args = std::env::args().skip(1);
+    let restore = match args.next().as_deref() {
+        Some("restore") => Some(args.next()),
+        Some(command) => anyhow::bail!("Unknown command: {}", command),
+        None => None,
+    };
+
     // Load configuration
     let config = AppConfig::from_env()?;
 
@@ -30,87 +58,837 @@ async fn main() -> anyhow::Result<()> {
 
     tracing::info!("Starting server with config: {:?}", config);
 
+    // Warn early when the system clock has drifted, since tokens would fail validation
+    if let Some(time_source_url) = &config.time_source_url {
+        ClockCheck::new(time_source_url)
+            .warn_if_skewed(chrono::Duration::seconds(config.jwt_leeway_secs as i64))
+            .await;
+    }
+
     // Initialize services
-    let user_service = features::UserService::new();
-    let jsonrpc_service = features::JsonRpcService::new();
-    let auth_service = features::AuthService::new(config.jwt_secret.clone());
+    let audit_log = AuditLog::new(config.audit_max_entries, &config.anonymous_id_hash_secret);
+    let legal_holds = LegalHolds::new(audit_log.clone());
+    let retention_job = RetentionJob::new(
+        audit_log.clone(),
+        RetentionPolicy {
+            audit_log_max_age: chrono::Duration::days(config.audit_retention_days),
+            anonymous_identifier_max_age: chrono::Duration::days(
+                config.anonymous_id_retention_days,
+            ),
+            pseudonymization_salt: config.pseudonymization_salt.clone(),
+        },
+    )
+    .with_legal_holds(legal_holds.clone());
+    let jsonrpc_service = features::JsonRpcService::new()
+        .with_max_message_size(config.ws_max_message_size)
+        .with_shutdown_grace(Duration::from_secs(config.shutdown_grace_secs))
+        .with_metric_labels(features::jsonrpc::MetricLabels::from_names(
+            &config.rpc_metrics_labels,
+            config.rpc_metrics_max_label_values,
+        ));
+    let token_binding = config.token_binding.parse().unwrap_or_else(|err| {
+        tracing::warn!("{}; token binding disabled", err);
+        TokenBinding::Off
+    });
+    let mut anomaly_detector = features::AnomalyDetector::new(audit_log.clone())
+        .with_window(chrono::Duration::seconds(config.anomaly_window_secs))
+        .with_default_thresholds(AnomalyThresholds {
+            failed_logins: config.anomaly_failed_logins,
+            token_reuse: config.anomaly_token_reuse,
+            distinct_ips: config.anomaly_distinct_ips,
+        });
+    // Webhook payloads that could not be delivered, kept for admins to retry
+    let dead_letters = DeadLetterQueue::new(audit_log.clone())
+        .with_redelivery(webhook::WEBHOOK_KIND, webhook::redeliver);
+    if let Some(webhook_url) = &config.anomaly_webhook_url {
+        anomaly_detector = anomaly_detector.with_webhook(
+            Webhook::new("anomaly_alert", webhook_url.clone())
+                .with_attempts(config.webhook_max_attempts)
+                .with_dead_letters(dead_letters.clone()),
+        );
+    }
+    let mut token_blacklist = features::TokenBlacklist::new();
+    if let Some(redis_url) = &config.redis_url {
+        match token_blacklist.clone().with_redis(redis_url) {
+            Ok(shared) => token_blacklist = shared,
+            Err(err) => tracing::warn!("Invalid REDIS_URL, revoked tokens stay local: {}", err),
+        }
+    }
+    // Server-side sessions of anonymous tokens, listed and revoked by admins
+    let mut sessions = SessionStore::new(audit_log.clone());
+    if let Some(redis_url) = &config.redis_url {
+        match sessions.clone().with_redis(redis_url) {
+            Ok(shared) => sessions = shared,
+            Err(err) => tracing::warn!("Invalid REDIS_URL, sessions stay local: {}", err),
+        }
+    }
+    let mut auth_service = features::AuthService::new(config.jwt_secret.clone())
+        .with_jwt_keys(jwt_keys(&config)?)
+        .with_token_binding(token_binding)
+        .with_token_blacklist(token_blacklist.clone())
+        .with_anomaly_detector(anomaly_detector.clone())
+        .with_refresh_token_lifetime(chrono::Duration::days(config.refresh_token_lifetime_days))
+        .with_clock_skew_leeway(config.jwt_leeway_secs)
+        .with_password_hash_cost(config.password_hash_cost);
+    // Admins and moderators only come from the seed file, never from
+    // registration; read it up front, seed once any backup is restored
+    let seeded = match &config.user_seed_file {
+        Some(user_seed_file) => {
+            let seeded = std::fs::read(user_seed_file)
+                .with_context(|| format!("Failed to read {}", user_seed_file))?;
+            let seeded: Vec<UserCredentials> = serde_json::from_slice(&seeded)
+                .with_context(|| format!("Invalid user seed file {}", user_seed_file))?;
+            Some((user_seed_file, seeded))
+        }
+        None => None,
+    };
+    if config.anonymous_sessions {
+        auth_service = auth_service.with_sessions(sessions.clone());
+    }
+    // Registered users, managed through the users API
+    let user_service = features::UserService::new(audit_log.clone())
+        .with_auth_service(auth_service.clone())
+        .with_legal_holds(legal_holds.clone());
+    features::users::register_rpc_methods(
+        &jsonrpc_service,
+        user_service.clone(),
+        auth_service.clone(),
+    );
+    let announcement_service = features::AnnouncementService::new(audit_log.clone())
+        .with_notifications(jsonrpc_service.clone())
+        .with_reminder_interval(chrono::Duration::seconds(
+            config.acknowledgement_reminder_secs,
+        ));
+    let moderation_service = features::ModerationService::new(audit_log.clone());
+    let notification_service = features::NotificationService::new(audit_log.clone())
+        .with_live_sync(jsonrpc_service.clone());
+    let board_service = features::BoardService::new(audit_log.clone())
+        .with_legal_holds(legal_holds.clone())
+        .with_moderation(moderation_service.clone())
+        .with_notifications(jsonrpc_service.clone())
+        .with_reply_notifications(notification_service.clone())
+        .with_undo_window(chrono::Duration::seconds(config.undo_window_secs));
+    features::board::register_rpc_methods(
+        &jsonrpc_service,
+        board_service.clone(),
+        auth_service.clone(),
+    );
+    let backup_service = config.backup_dir.as_ref().map(|backup_dir| {
+        features::BackupService::new(
+            ObjectStorage::new(backup_dir),
+            auth_service.clone(),
+            board_service.clone(),
+        )
+        .with_keep(config.backup_keep)
+    });
+    let consent_service = features::ConsentService::new(audit_log.clone());
+    let maintenance_service = features::MaintenanceService::new(
+        announcement_service.clone(),
+        jsonrpc_service.clone(),
+        audit_log.clone(),
+    );
+    let usage_service = features::UsageService::new(QuotaLimits {
+        soft_daily_api_calls: (config.tenant_soft_daily_api_calls > 0)
+            .then_some(config.tenant_soft_daily_api_calls),
+        hard_daily_api_calls: (config.tenant_hard_daily_api_calls > 0)
+            .then_some(config.tenant_hard_daily_api_calls),
+    });
+
+    // Keep the GeoIP database, if configured, up to date
+    let geoip = GeoIp::new(config.geoip_database_path.clone().map(Into::into));
+    // Background jobs, listed and triggered through the Admin API
+    let jobs = JobScheduler::new();
+
+    if config.geoip_database_path.is_some() {
+        let geoip = geoip.clone();
+        // The database is loaded on startup
+        let interval = Duration::from_secs(config.geoip_reload_interval_secs);
+        jobs.schedule_after("geoip-reload", interval, interval, move || {
+            let geoip = geoip.clone();
+            async move { geoip.reload().await.map_err(|err| err.to_string()) }
+        });
+    }
+
+    // Pick up access tokens revoked on other instances
+    if config.redis_url.is_some() {
+        // The first sync runs on startup
+        let interval = Duration::from_secs(config.token_blacklist_sync_secs);
+        jobs.schedule_after("token-blacklist-sync", interval, interval, {
+            let token_blacklist = token_blacklist.clone();
+            move || {
+                let token_blacklist = token_blacklist.clone();
+                async move {
+                    token_blacklist
+                        .sync()
+                        .await
+                        .map(|_| ())
+                        .map_err(|err| err.to_string())
+                }
+            }
+        });
+    }
+
+    // Pick up sessions revoked on other instances
+    if config.anonymous_sessions && config.redis_url.is_some() {
+        // The first sync runs on startup
+        let interval = Duration::from_secs(config.token_blacklist_sync_secs);
+        jobs.schedule_after("session-revocation-sync", interval, interval, {
+            let sessions = sessions.clone();
+            move || {
+                let sessions = sessions.clone();
+                async move {
+                    sessions
+                        .sync()
+                        .await
+                        .map(|_| ())
+                        .map_err(|err| err.to_string())
+                }
+            }
+        });
+    }
+
+    // Announce, start and end maintenance windows on time
+    jobs.schedule("maintenance", Duration::from_secs(1), {
+        let maintenance_service = maintenance_service.clone();
+        move || {
+            let maintenance_service = maintenance_service.clone();
+            async move {
+                maintenance_service.advance(chrono::Utc::now()).await;
+                Ok(())
+            }
+        }
+    });
+
+    // Push announcements published ahead of their start once they start
+    jobs.schedule("announcement-starts", Duration::from_secs(1), {
+        let announcement_service = announcement_service.clone();
+        move || {
+            let announcement_service = announcement_service.clone();
+            async move {
+                let published = announcement_service
+                    .publish_started(chrono::Utc::now())
+                    .await;
+                if published > 0 {
+                    tracing::info!("Pushed {} started announcements", published);
+                }
+                Ok(())
+            }
+        }
+    });
+
+    // Remind identities that have not acknowledged announcements
+    jobs.schedule("acknowledgement-reminders", Duration::from_secs(60), {
+        let announcement_service = announcement_service.clone();
+        move || {
+            let announcement_service = announcement_service.clone();
+            async move {
+                let reminded = announcement_service
+                    .send_reminders(chrono::Utc::now())
+                    .await;
+                if reminded > 0 {
+                    tracing::info!("Sent {} acknowledgement reminders", reminded);
+                }
+                Ok(())
+            }
+        }
+    });
+
+    // Remove deleted threads and posts once their undo window has passed
+    jobs.schedule("deletion-purge", Duration::from_secs(1), {
+        let board_service = board_service.clone();
+        move || {
+            let board_service = board_service.clone();
+            async move {
+                let purged = board_service.purge_deletions(chrono::Utc::now()).await;
+                if purged > 0 {
+                    tracing::info!("Removed {} deleted threads and posts", purged);
+                }
+                Ok(())
+            }
+        }
+    });
+
+    // Apply data retention rules in the background
+    jobs.schedule(
+        "retention",
+        Duration::from_secs(config.retention_interval_secs),
+        {
+            let retention_job = retention_job.clone();
+            move || {
+                let retention_job = retention_job.clone();
+                async move {
+                    let report = retention_job.run_once(false).await;
+                    tracing::info!("Retention run completed: {:?}", report.rules);
+                    Ok(())
+                }
+            }
+        },
+    );
+
+    // Restore before serving, so requests never see a partial state
+    if let Some(key) = restore {
+        let Some(backup_service) = &backup_service else {
+            anyhow::bail!("BACKUP_DIR must be set to restore a backup");
+        };
+        let record = backup_service.restore(key.as_deref()).await?;
+        tracing::info!(
+            "Restored {} users and {} boards from {}",
+            record.users,
+            record.boards,
+            record.key
+        );
+    }
+
+    // Seed after restoring, so the seed file wins over restored users
+    if let Some((user_seed_file, seeded)) = seeded {
+        tracing::info!("Seeding {} users from {}", seeded.len(), user_seed_file);
+        auth_service.seed_credentials(seeded).await;
+    }
+
+    // Back up users and board content periodically
+    if let Some(backup_service) = &backup_service {
+        let backup_service = backup_service.clone();
+        let interval = Duration::from_secs(config.backup_interval_secs);
+        jobs.schedule_after("backup", interval, interval, move || {
+            let backup_service = backup_service.clone();
+            async move {
+                let record = backup_service
+                    .backup()
+                    .await
+                    .map_err(|err| err.to_string())?;
+                tracing::info!("Stored backup {}", record.key);
+                Ok(())
+            }
+        });
+    }
+
+    // Shed load while memory, connections or queues are above their watermarks
+    let watermarks = features::WatermarkMonitor::new(health::Watermarks {
+        rss_bytes: (config.watermark_rss_mb > 0).then(|| config.watermark_rss_mb * 1024 * 1024),
+        connections: (config.watermark_connections > 0).then_some(config.watermark_connections),
+        queued_messages: (config.watermark_queued_messages > 0)
+            .then_some(config.watermark_queued_messages),
+    });
+    jobs.schedule(
+        "watermarks",
+        Duration::from_secs(config.watermark_check_secs),
+        {
+            let watermarks = watermarks.clone();
+            let connections = jsonrpc_service.connections().clone();
+            move || {
+                let watermarks = watermarks.clone();
+                let connections = connections.clone();
+                async move {
+                    watermarks.update(&health::ResourceUsage {
+                        rss_bytes: health::process_rss_bytes(),
+                        connections: connections.len().await,
+                        queued_messages: connections.queued_messages().await,
+                    });
+                    Ok(())
+                }
+            }
+        },
+    );
+
+    // Services started in order once the listener is bound and shut down in
+    // reverse order once the server stopped; discovery registration comes
+    // last, so the instance only receives traffic when everything is up
+    let mut lifecycle = Lifecycle::new()
+        .with_service(geoip.clone())
+        .with_service(token_blacklist.clone());
+    if config.anonymous_sessions {
+        lifecycle = lifecycle.with_service(sessions.clone());
+    }
+    lifecycle = lifecycle
+        .with_service(jobs.clone())
+        .with_service(jsonrpc_service.clone());
+    if let Some(consul_url) = &config.consul_url {
+        lifecycle = lifecycle.with_service(ServiceRegistration::new(
+            consul_url,
+            &config.service_name,
+            &config.service_address,
+            config.port,
+        ));
+    }
 
-    // Give time for JSON-RPC builtin methods to register
-    tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+    // Components the readiness probe checks
+    let mut health_checks = HealthChecks::new()
+        .with_check(lifecycle.clone())
+        .with_check(jsonrpc_service.clone())
+        .with_check(watermarks.clone());
+    if config.redis_url.is_some() {
+        health_checks = health_checks.with_check(token_blacklist.clone());
+    }
+    if let Some(backup_service) = &backup_service {
+        health_checks = health_checks.with_check(backup_service.clone());
+    }
 
     // Build application with routes and middleware
-    let app = build_app(config.clone(), user_service, jsonrpc_service, auth_service);
+    let app = build_app(AppState {
+        config: Arc::new(config.clone()),
+        user_service,
+        jsonrpc_service,
+        auth_service,
+        announcement_service,
+        board_service,
+        backup_service,
+        consent_service,
+        maintenance_service,
+        moderation_service,
+        notification_service,
+        usage_service,
+        anomaly_detector,
+        geoip,
+        audit_log,
+        legal_holds,
+        retention_job,
+        jobs,
+        dead_letters,
+        sessions,
+        health_checks,
+        watermarks,
+    });
 
     // Create TCP listener
     let listener = tokio::net::TcpListener::bind(&config.address()).await?;
     tracing::info!("Server listening on {}", config.address());
 
+    lifecycle.start().await.map_err(anyhow::Error::msg)?;
+
     // Run server with graceful shutdown
-    axum::serve(listener, app)
-        .with_graceful_shutdown(shutdown_signal())
-        .await?;
+    // Client addresses are needed to bind tokens to clients
+    axum::serve(
+        listener,
+        app.into_make_service_with_connect_info::<SocketAddr>(),
+    )
+    .with_graceful_shutdown(shutdown_signal(lifecycle.clone()))
+    .await?;
 
+    lifecycle.shutdown().await;
     tracing::info!("Server shutdown complete");
     Ok(())
 }
 
+/// State shared by the application routes
+///
+/// Handlers extract the part they need, e.g. `State<AuthService>`, through
+/// the `FromRef` implementations below, so a new feature adds a field and
+/// an entry to `from_ref!` instead of another parameter to `build_app`.
+#[derive(Clone)]
+struct AppState {
+    config: Arc<AppConfig>,
+    user_service: features::UserService,
+    jsonrpc_service: features::JsonRpcService,
+    auth_service: features::AuthService,
+    announcement_service: features::AnnouncementService,
+    board_service: features::BoardService,
+    /// Set when backups are configured
+    backup_service: Option<features::BackupService>,
+    consent_service: features::ConsentService,
+    maintenance_service: features::MaintenanceService,
+    moderation_service: features::ModerationService,
+    notification_service: features::NotificationService,
+    usage_service: features::UsageService,
+    anomaly_detector: features::AnomalyDetector,
+    geoip: GeoIp,
+    audit_log: AuditLog,
+    legal_holds: LegalHolds,
+    retention_job: RetentionJob,
+    jobs: JobScheduler,
+    dead_letters: DeadLetterQueue,
+    sessions: SessionStore,
+    health_checks: HealthChecks,
+    watermarks: features::WatermarkMonitor,
+}
+
+/// Implement `FromRef<AppState>` for the types of the given fields
+macro_rules! from_ref {
+    ($($field:ident: $type:ty),* $(,)?) => {
+        $(
+            impl FromRef<AppState> for $type {
+                fn from_ref(state: &AppState) -> Self {
+                    state.$field.clone()
+                }
+            }
+        )*
+    };
+}
+
+from_ref! {
+    config: Arc<AppConfig>,
+    user_service: features::UserService,
+    jsonrpc_service: features::JsonRpcService,
+    auth_service: features::AuthService,
+    announcement_service: features::AnnouncementService,
+    board_service: features::BoardService,
+    consent_service: features::ConsentService,
+    maintenance_service: features::MaintenanceService,
+    moderation_service: features::ModerationService,
+    notification_service: features::NotificationService,
+    usage_service: features::UsageService,
+    anomaly_detector: features::AnomalyDetector,
+    geoip: GeoIp,
+    audit_log: AuditLog,
+    legal_holds: LegalHolds,
+    retention_job: RetentionJob,
+    jobs: JobScheduler,
+    dead_letters: DeadLetterQueue,
+    sessions: SessionStore,
+    health_checks: HealthChecks,
+    watermarks: features::WatermarkMonitor,
+}
+
 /// Build the application router with all routes and middleware
 ///
 /// Organizes routes by feature with clear separation:
-/// - Health check at /health
+/// - Health check at /health, liveness probe at /health/live
+/// - Readiness probe at /health/ready and /ready
 /// - WebSocket JSON-RPC at /live
 /// - Auth API at /api/v1/auth
 /// - Users API at /api/v1/users
-fn build_app(
-    config: AppConfig,
-    user_service: features::UserService,
-    jsonrpc_service: features::JsonRpcService,
-    auth_service: features::AuthService,
-) -> Router {
+/// - Announcements API at /api/v1/announcements
+/// - Boards API at /api/v1/boards
+/// - Moderation API at /api/v1/moderation
+/// - Notifications API at /api/v1/notifications
+/// - Consent API at /api/v1/consent
+/// - Maintenance status at /api/v1/maintenance
+/// - Admin API at /api/v1/admin
+///
+/// API calls are rate limited and metered per tenant before reaching the
+/// feature routes.
+/// During maintenance only the auth, admin and maintenance routes are served.
+fn build_app(state: AppState) -> Router {
+    // Services wired into middleware rather than extracted by handlers
+    let AppState {
+        config,
+        auth_service,
+        backup_service,
+        consent_service,
+        maintenance_service,
+        usage_service,
+        geoip,
+        audit_log,
+        watermarks,
+        ..
+    } = state.clone();
+
+    // Limit requests per client, keyed on the verified user when the token is
+    // valid; anonymous tokens are free to mint, so they share the IP bucket
+    let rate_limiter = RateLimiter::new((config.rate_limit_per_minute > 0).then_some(RateLimit {
+        per_minute: config.rate_limit_per_minute,
+        burst: config.rate_limit_burst.max(1),
+    }))
+    .with_routes(&config.rate_limit_routes)
+    .with_identity({
+        let auth_service = auth_service.clone();
+        let audit_log = audit_log.clone();
+        move |headers| {
+            let header = headers.get(AUTHORIZATION)?.to_str().ok()?;
+            let identity = auth_service
+                .extract_user_from_header(header)
+                .ok()
+                .filter(UserIdentity::is_verified)?;
+            Some(audit_log.stored_actor_id(&AuditActor::from(&identity)))
+        }
+    });
+
     // Build Auth API routes
     let auth_routes = Router::new()
         .route("/register", post(features::register))
         .route("/login", post(features::login))
+        .route("/refresh", post(features::refresh))
+        .route("/logout", post(features::logout))
         .route("/anonymous", post(features::anonymous_token))
-        .route("/me", get(features::me).layer(axum::middleware::from_fn_with_state(
+        .route("/device-login/start", post(features::device_login_start))
+        .route("/device-login/poll", post(features::device_login_poll))
+        .route(
+            "/device-login/approve",
+            post(features::device_login_approve).layer(axum::middleware::from_fn_with_state(
+                auth_service.clone(),
+                features::auth_middleware,
+            )),
+        )
+        .route(
+            "/action-tokens",
+            post(features::create_action_token).layer(axum::middleware::from_fn_with_state(
+                auth_service.clone(),
+                features::auth_middleware,
+            )),
+        )
+        .route(
+            "/me",
+            get(features::me).layer(axum::middleware::from_fn_with_state(
+                auth_service.clone(),
+                features::auth_middleware,
+            )),
+        );
+
+    // Build Announcements API routes
+    let announcement_routes = Router::new()
+        .route("/", post(features::create_announcement))
+        .route("/:id", delete(features::delete_announcement))
+        .route("/:id/acknowledge", post(features::acknowledge_announcement))
+        .route(
+            "/:id/acknowledgements",
+            get(features::get_acknowledgement_stats),
+        )
+        .route_layer(axum::middleware::from_fn_with_state(
+            auth_service.clone(),
+            features::auth_middleware,
+        ))
+        .route(
+            "/active",
+            get(features::list_active_announcements).layer(axum::middleware::from_fn_with_state(
+                auth_service.clone(),
+                features::optional_auth_middleware,
+            )),
+        );
+
+    // Build Boards API routes
+    let board_routes = Router::new()
+        .route("/", get(features::list_boards).post(features::create_board))
+        .route("/:board_id", delete(features::delete_board))
+        .route("/undo", post(features::undo_deletion))
+        .route(
+            "/:board_id/threads",
+            get(features::list_threads).post(features::create_thread),
+        )
+        .route(
+            "/:board_id/threads/:thread_id",
+            get(features::get_thread).delete(features::delete_thread),
+        )
+        .route(
+            "/:board_id/threads/:thread_id/posts",
+            post(features::reply_to_thread),
+        )
+        .route(
+            "/:board_id/threads/:thread_id/posts/:post_id",
+            delete(features::delete_post),
+        )
+        .route_layer(axum::middleware::from_fn_with_state(
+            auth_service.clone(),
+            features::auth_middleware,
+        ));
+
+    // Build Moderation API routes, reserved to moderators and admins
+    let moderation_routes = Router::new()
+        .route(
+            "/shadow-bans",
+            get(features::list_shadow_bans).post(features::shadow_ban),
+        )
+        .route("/shadow-bans/:actor_id", delete(features::lift_shadow_ban))
+        .route_layer(axum::middleware::from_fn_with_state(
+            Role::Moderator,
+            features::require_role,
+        ))
+        .route_layer(axum::middleware::from_fn_with_state(
+            auth_service.clone(),
+            features::auth_middleware,
+        ));
+
+    // Backup status is only served when backups are configured
+    let backup_routes = match backup_service {
+        Some(backup_service) => Router::new()
+            .route("/backups", get(features::backup_status))
+            .with_state(backup_service),
+        None => Router::new(),
+    };
+
+    // Build Notifications API routes
+    let notification_routes = Router::new()
+        .route("/", get(features::list_notifications))
+        .route("/read", post(features::mark_notifications_read))
+        .route_layer(axum::middleware::from_fn_with_state(
+            auth_service.clone(),
+            features::auth_middleware,
+        ));
+
+    // Build Admin API routes, all reserved to admins
+    let admin_routes = Router::new()
+        .route("/audit", get(features::search_audit_log))
+        .route(
+            "/maintenance",
+            get(features::list_maintenance_windows).post(features::schedule_maintenance),
+        )
+        .route("/maintenance/:id", delete(features::cancel_maintenance))
+        .route("/policies", post(features::publish_policy))
+        .route("/consent", get(features::consent_report))
+        .route(
+            "/legal-holds",
+            get(features::list_legal_holds).post(features::place_legal_hold),
+        )
+        .route(
+            "/legal-holds/:kind/:id",
+            delete(features::release_legal_hold),
+        )
+        .merge(backup_routes)
+        .route("/jobs", get(features::list_jobs))
+        .route("/jobs/:name/run", post(features::run_job))
+        .route("/dead-letters", get(features::list_dead_letters))
+        .route("/dead-letters/:id", delete(features::discard_dead_letter))
+        .route("/dead-letters/:id/retry", post(features::retry_dead_letter))
+        .route("/sessions", get(features::list_sessions))
+        .route("/sessions/:id", delete(features::revoke_session))
+        .route(
+            "/automation-tokens",
+            get(features::list_automation_tokens).post(features::create_automation_token),
+        )
+        .route(
+            "/automation-tokens/:id",
+            delete(features::revoke_automation_token),
+        )
+        .route(
+            "/automation-tokens/:id/rotate",
+            post(features::rotate_automation_token),
+        )
+        .route("/retention/report", get(features::retention_report))
+        .route("/usage", get(features::list_tenant_usage))
+        .route("/usage/:hospital_code", get(features::get_tenant_usage))
+        .route("/anomalies", get(features::list_anomalies))
+        .route(
+            "/anomalies/thresholds/:hospital_code",
+            get(features::get_anomaly_thresholds).put(features::set_anomaly_thresholds),
+        )
+        .route_layer(axum::middleware::from_fn_with_state(
+            Role::Admin,
+            features::require_role,
+        ))
+        .route_layer(axum::middleware::from_fn_with_state(
+            auth_service.clone(),
+            features::auth_middleware,
+        ));
+
+    // Users hold real accounts, so managing them is reserved to admins
+    let admin_only = ServiceBuilder::new()
+        .layer(axum::middleware::from_fn_with_state(
             auth_service.clone(),
             features::auth_middleware,
-        )))
-        .with_state(auth_service.clone());
+        ))
+        .layer(axum::middleware::from_fn_with_state(
+            Role::Admin,
+            features::require_role,
+        ));
 
     // Build Users API routes
     let api_routes = Router::new()
         .route(
             "/users",
-            get(features::list_users).post(features::create_user),
+            get(features::list_users)
+                .post(features::create_user)
+                .layer(admin_only.clone()),
+        )
+        .route(
+            "/users/:id",
+            get(features::get_user)
+                .delete(features::delete_user)
+                .layer(admin_only),
+        )
+        .merge(
+            Router::new()
+                .route("/users/me/usage", get(features::get_my_usage))
+                .route("/presence", get(features::list_presence))
+                .route_layer(axum::middleware::from_fn_with_state(
+                    auth_service.clone(),
+                    features::auth_middleware,
+                )),
         )
-        .route("/users/:id", get(features::get_user))
-        .with_state(user_service)
-        .merge(Router::new().nest("/auth", auth_routes));
+        .merge(Router::new().nest("/announcements", announcement_routes))
+        .merge(Router::new().nest("/boards", board_routes))
+        .merge(Router::new().nest("/moderation", moderation_routes))
+        .merge(Router::new().nest("/notifications", notification_routes))
+        // Block callers until they accept the current terms and privacy policy
+        .layer(axum::middleware::from_fn_with_state(
+            (auth_service.clone(), consent_service.clone()),
+            features::consent_middleware,
+        ))
+        .merge(
+            Router::new()
+                .route("/consent", get(features::consent_status))
+                .route("/consent/accept", post(features::accept_policy))
+                .route_layer(axum::middleware::from_fn_with_state(
+                    auth_service.clone(),
+                    features::auth_middleware,
+                )),
+        )
+        // Reject requests while maintenance mode is on
+        .layer(axum::middleware::from_fn_with_state(
+            maintenance_service.clone(),
+            features::maintenance_middleware,
+        ))
+        // Shed requests while resource watermarks are exceeded
+        .layer(axum::middleware::from_fn_with_state(
+            watermarks.clone(),
+            features::load_shedding_middleware,
+        ))
+        .merge(Router::new().nest("/auth", auth_routes))
+        .merge(Router::new().nest("/admin", admin_routes))
+        .route("/maintenance", get(features::maintenance_status))
+        .route("/events/schemas", get(features::list_event_schemas))
+        // Meter API calls per tenant and enforce quotas
+        .layer(axum::middleware::from_fn_with_state(
+            (auth_service.clone(), usage_service),
+            features::usage_middleware,
+        ))
+        // Reject clients exceeding their rate limit with 429 Too Many Requests;
+        // health probes and the WebSocket upgrade are not limited
+        .layer(axum::middleware::from_fn_with_state(
+            rate_limiter,
+            rate_limit_middleware,
+        ));
 
     // Build main router
     Router::new()
-        // Health check endpoint
+        // Health check and liveness endpoints
         .route("/health", get(features::health_check))
+        .route("/health/live", get(features::health_check))
+        // Readiness probe, checking the components the service depends on
+        .route("/ready", get(features::readiness_check))
+        .route("/health/ready", get(features::readiness_check))
         // WebSocket JSON-RPC endpoint
-        .route("/live", get(features::websocket_handler))
-        .with_state(jsonrpc_service.clone())
+        .route(
+            "/live",
+            get(features::websocket_handler)
+                .layer(axum::middleware::from_fn_with_state(
+                    auth_service.clone(),
+                    features::websocket_auth_middleware,
+                ))
+                .layer(axum::middleware::from_fn_with_state(
+                    watermarks,
+                    features::load_shedding_middleware,
+                )),
+        )
         // Nest API routes under /api/v1
         .nest("/api/v1", api_routes)
+        .with_state(state)
         // Set a request body size limit
         .layer(DefaultBodyLimit::max(config.max_body_size))
+        // Choose how JSON bodies with unknown fields are handled
+        .layer(Extension(if config.strict_deserialization {
+            DeserializationMode::Strict
+        } else {
+            DeserializationMode::Lenient
+        }))
+        // Attach the client location for audit and anomaly detection
+        .layer(axum::middleware::from_fn_with_state(
+            geoip,
+            geoip_middleware,
+        ))
         // Add middleware stack
         .layer(
             ServiceBuilder::new()
+                // Assign a request id (or keep the client's) for tracing and audit
+                .layer(SetRequestIdLayer::x_request_id(MakeRequestUuid))
                 // Add tracing for request/response logging
                 .layer(TraceLayer::new_for_http())
+                // Echo the request id back in the response
+                .layer(PropagateRequestIdLayer::x_request_id())
                 // Add CORS support
-                .layer(
-                    CorsLayer::new()
-                        .allow_origin("http://localhost:3000".parse::<HeaderValue>().unwrap())
-                        .allow_methods([Method::GET, Method::POST, Method::PUT, Method::DELETE])
-                        .allow_headers(tower_http::cors::Any),
-                )
+                .layer(cors_layer(
+                    &config.cors_allowed_origins,
+                    &config.cors_allowed_methods,
+                    &config.cors_allowed_headers,
+                    config.cors_allow_credentials,
+                ))
                 // Add request timeout
                 .layer(TimeoutLayer::new(Duration::from_secs(
                     config.request_timeout_secs,
@@ -118,8 +896,63 @@ fn build_app(
         )
 }
 
+/// Keys tokens are signed and verified with
+///
+/// HMAC algorithms sign with `JWT_SECRET`, the others with the key pair in
+/// `JWT_PRIVATE_KEY_FILE` and `JWT_PUBLIC_KEY_FILE`.
+fn jwt_keys(config: &AppConfig) -> anyhow::Result<features::JwtKeys> {
+    let algorithm: Algorithm = config
+        .jwt_algorithm
+        .parse()
+        .map_err(|_| anyhow::anyhow!("Unknown JWT_ALGORITHM: {}", config.jwt_algorithm))?;
+    let hmac = matches!(
+        algorithm,
+        Algorithm::HS256 | Algorithm::HS384 | Algorithm::HS512
+    );
+
+    let mut keys = if hmac {
+        let secret = config.jwt_secret.as_bytes();
+        features::JwtKeys::new(algorithm, secret, secret)?
+    } else {
+        let (Some(private_key_file), Some(public_key_file)) =
+            (&config.jwt_private_key_file, &config.jwt_public_key_file)
+        else {
+            anyhow::bail!(
+                "JWT_PRIVATE_KEY_FILE and JWT_PUBLIC_KEY_FILE must be set for {:?}",
+                algorithm
+            );
+        };
+        let private_key = std::fs::read(private_key_file)
+            .with_context(|| format!("Failed to read {}", private_key_file))?;
+        let public_key = std::fs::read(public_key_file)
+            .with_context(|| format!("Failed to read {}", public_key_file))?;
+        features::JwtKeys::new(algorithm, &private_key, &public_key)
+            .context("Invalid JWT key pair")?
+    };
+    if let Some(key_id) = &config.jwt_key_id {
+        keys = keys.with_key_id(key_id);
+    }
+    for previous_key in &config.jwt_previous_keys {
+        let Some((key_id, key)) = previous_key.split_once('=') else {
+            anyhow::bail!("JWT_PREVIOUS_KEYS entries must be kid=key");
+        };
+        let key = if hmac {
+            key.as_bytes().to_vec()
+        } else {
+            std::fs::read(key).with_context(|| format!("Failed to read {}", key))?
+        };
+        keys = keys
+            .with_previous_key(key_id, &key)
+            .with_context(|| format!("Invalid previous JWT key {}", key_id))?;
+    }
+    Ok(keys)
+}
+
 /// Graceful shutdown signal handler
-async fn shutdown_signal() {
+///
+/// Drains the services, e.g. deregisters from Consul, before the server
+/// stops accepting requests and waits for the in-flight ones.
+async fn shutdown_signal(lifecycle: Lifecycle) {
     let ctrl_c = async {
         tokio::signal::ctrl_c()
             .await
@@ -145,4 +978,6 @@ async fn shutdown_signal() {
             tracing::info!("Received terminate signal, shutting down gracefully...");
         },
     }
+
+    lifecycle.drain().await;
 }